@@ -0,0 +1,442 @@
+//! # Analysis
+//!
+//! Generation goes from patterns to words; analysis goes the other way. Given a list of words, from a natural language
+//! or an older draft of a conlang, [`Analysis`] counts what is in them -- phonemes, syllable shapes, and consonant
+//! clusters -- and suggests patterns which would generate words like them. [`Comparison`] does the same for two
+//! inventories, like those of related languages.
+
+use crate::{gen, phone};
+use std::collections::HashMap;
+
+/// What a list of words has in it, found by [`Analysis::new`]. Every list is sorted from most to least common.
+#[derive(Clone, Debug, Default)]
+pub struct Analysis {
+    words: usize,
+    phonemes: Vec<(phone::Phoneme, usize)>,
+    /// Pairs of phonemes which follow each other in a word, including across syllable boundaries.
+    bigrams: Vec<((phone::Phoneme, phone::Phoneme), usize)>,
+    /// Shapes of syllables like `CVC`.
+    syllable_shapes: Vec<(String, usize)>,
+    /// Shapes of whole words like `CV CVC`, which are patterns that would generate the word.
+    word_shapes: Vec<(String, usize)>,
+    /// Pairs of the number of syllables in a word and how many words have that many, sorted by the number of
+    /// syllables.
+    syllable_counts: Vec<(usize, usize)>,
+    initial_clusters: Vec<(Vec<phone::Phoneme>, usize)>,
+    medial_clusters: Vec<(Vec<phone::Phoneme>, usize)>,
+    final_clusters: Vec<(Vec<phone::Phoneme>, usize)>,
+}
+
+fn is_vowel(phoneme: &phone::Phoneme) -> bool {
+    matches!(phoneme, phone::Phoneme::Vowel(_))
+}
+
+/// The shape of a syllable like `CVC`, where non-pulmonic consonants count as consonants.
+fn shape(phonemes: &[phone::Phoneme]) -> String {
+    phonemes
+        .iter()
+        .map(|ph| if is_vowel(ph) { 'V' } else { 'C' })
+        .collect()
+}
+
+/// Split the phonemes of a syllable with more than one run of vowels into syllables. Each run of vowels is a nucleus,
+/// and of the consonants between two nuclei, the last one starts the next syllable: /tanka/ splits as /tan.ka/.
+fn syllabify(phonemes: &[phone::Phoneme]) -> Vec<&[phone::Phoneme]> {
+    let mut out = Vec::new();
+    let mut start = 0;
+    let mut idx = 0;
+    // Skip the onset and nucleus of the first syllable
+    while idx < phonemes.len() && !is_vowel(&phonemes[idx]) {
+        idx += 1;
+    }
+    while idx < phonemes.len() {
+        while idx < phonemes.len() && is_vowel(&phonemes[idx]) {
+            idx += 1;
+        }
+        let consonants = idx;
+        while idx < phonemes.len() && !is_vowel(&phonemes[idx]) {
+            idx += 1;
+        }
+        if idx == phonemes.len() {
+            break;
+        }
+        let next = consonants.max(idx - 1);
+        out.push(&phonemes[start..next]);
+        start = next;
+    }
+    out.push(&phonemes[start..]);
+    out
+}
+
+fn average(total: usize, words: usize) -> f64 {
+    if words == 0 {
+        0.0
+    } else {
+        total as f64 / words as f64
+    }
+}
+
+/// Split a word written without syllable breaks, like /tanka/, into syllables the way [`Analysis::new`] does: /tan.ka/.
+/// The stress of the word goes on its first syllable. Words which already have more than one syllable are returned as
+/// they are.
+pub fn syllabify_word(word: &phone::Word) -> phone::Word {
+    let [syl] = word.syllables() else {
+        return word.clone();
+    };
+    let mut out = phone::Word::new();
+    for (idx, part) in syllabify(syl.parts()).into_iter().enumerate() {
+        let part = phone::Syllable::new(part);
+        out.push(if idx == 0 {
+            part.with_stress(syl.stress())
+        } else {
+            part
+        });
+    }
+    out
+}
+
+/// Sort counts from most to least common, breaking ties by the key so the order is stable.
+fn sorted<K: Ord>(counts: HashMap<K, usize>) -> Vec<(K, usize)> {
+    let mut out: Vec<_> = counts.into_iter().collect();
+    out.sort_by(|(a, x), (b, y)| y.cmp(x).then_with(|| a.cmp(b)));
+    out
+}
+
+impl Analysis {
+    /// Analyze the `words`. Words written without syllable breaks, which parse as a single syllable with more than one
+    /// nucleus, are split into syllables first.
+    pub fn new<'a>(words: impl IntoIterator<Item = &'a phone::Word>) -> Self {
+        let mut out = Self::default();
+        let mut phonemes = HashMap::new();
+        let mut bigrams = HashMap::new();
+        let mut syllable_shapes = HashMap::new();
+        let mut word_shapes = HashMap::new();
+        let mut syllable_counts = HashMap::new();
+        let mut clusters = [HashMap::new(), HashMap::new(), HashMap::new()];
+
+        for word in words {
+            out.words += 1;
+            let syllables: Vec<&[phone::Phoneme]> = match word.syllables() {
+                [syl] => syllabify(syl.parts()),
+                syllables => syllables.iter().map(phone::Syllable::parts).collect(),
+            };
+            for ph in syllables.iter().flat_map(|syl| syl.iter()) {
+                *phonemes.entry(*ph).or_insert(0) += 1;
+            }
+            for syl in syllables.iter() {
+                *syllable_shapes.entry(shape(syl)).or_insert(0) += 1;
+            }
+            let word_shape = syllables.iter().map(|syl| shape(syl)).collect::<Vec<_>>();
+            *word_shapes.entry(word_shape.join(" ")).or_insert(0) += 1;
+            *syllable_counts.entry(syllables.len()).or_insert(0) += 1;
+
+            let flat: Vec<_> = syllables.concat();
+            for pair in flat.windows(2) {
+                *bigrams.entry((pair[0], pair[1])).or_insert(0) += 1;
+            }
+            let mut idx = 0;
+            while idx < flat.len() {
+                let len = flat[idx..].iter().take_while(|ph| !is_vowel(ph)).count();
+                if len >= 2 {
+                    let position = match (idx, idx + len) {
+                        (0, _) => 0,
+                        (_, end) if end == flat.len() => 2,
+                        _ => 1,
+                    };
+                    *clusters[position]
+                        .entry(flat[idx..idx + len].to_vec())
+                        .or_insert(0) += 1;
+                }
+                idx += len.max(1);
+            }
+        }
+
+        let [initial, medial, terminal] = clusters;
+        out.phonemes = sorted(phonemes);
+        out.bigrams = sorted(bigrams);
+        out.syllable_shapes = sorted(syllable_shapes);
+        out.word_shapes = sorted(word_shapes);
+        out.syllable_counts = syllable_counts.into_iter().collect();
+        out.syllable_counts.sort();
+        out.initial_clusters = sorted(initial);
+        out.medial_clusters = sorted(medial);
+        out.final_clusters = sorted(terminal);
+        out
+    }
+
+    /// The number of words analyzed.
+    pub fn words(&self) -> usize {
+        self.words
+    }
+
+    /// How often each phoneme appears.
+    pub fn phonemes(&self) -> &[(phone::Phoneme, usize)] {
+        &self.phonemes
+    }
+
+    /// How often each pair of phonemes appears, with the first phoneme of the pair directly before the second.
+    pub fn bigrams(&self) -> &[((phone::Phoneme, phone::Phoneme), usize)] {
+        &self.bigrams
+    }
+
+    /// The average number of phonemes in a word, or 0 if no words were analyzed.
+    pub fn average_phonemes(&self) -> f64 {
+        let total: usize = self.phonemes.iter().map(|(_, count)| count).sum();
+        average(total, self.words)
+    }
+
+    /// The average number of syllables in a word, or 0 if no words were analyzed.
+    pub fn average_syllables(&self) -> f64 {
+        let total: usize = self
+            .syllable_counts
+            .iter()
+            .map(|(count, words)| count * words)
+            .sum();
+        average(total, self.words)
+    }
+
+    /// How often each shape of syllable like `CVC` appears.
+    pub fn syllable_shapes(&self) -> &[(String, usize)] {
+        &self.syllable_shapes
+    }
+
+    /// How often each shape of word like `CV CVC` appears. Each shape is a pattern which generates words like the ones
+    /// it was found in.
+    pub fn word_shapes(&self) -> &[(String, usize)] {
+        &self.word_shapes
+    }
+
+    /// Consonant clusters at the start of words.
+    pub fn initial_clusters(&self) -> &[(Vec<phone::Phoneme>, usize)] {
+        &self.initial_clusters
+    }
+
+    /// Consonant clusters between vowels, including across syllable boundaries.
+    pub fn medial_clusters(&self) -> &[(Vec<phone::Phoneme>, usize)] {
+        &self.medial_clusters
+    }
+
+    /// Consonant clusters at the end of words.
+    pub fn final_clusters(&self) -> &[(Vec<phone::Phoneme>, usize)] {
+        &self.final_clusters
+    }
+
+    /// How many syllables the words have, as a distribution for generating words of similar lengths. Returns `None` if
+    /// no words were analyzed.
+    pub fn syllable_counts(&self) -> Option<gen::SyllableCounts> {
+        gen::SyllableCounts::new(
+            self.syllable_counts
+                .iter()
+                .map(|(count, words)| (*count, u32::try_from(*words).unwrap_or(u32::MAX))),
+        )
+    }
+
+    /// A syllable pattern covering every syllable shape, like `(C)V(C)` for words made of `V`, `CV`, and `CVC`
+    /// syllables. This can generate shapes which were never seen, like `VC` here, but is a starting point for
+    /// `generate-words`. Returns `None` if no words were analyzed.
+    pub fn syllable_pattern(&self) -> Option<String> {
+        if self.syllable_shapes.is_empty() {
+            return None;
+        }
+        let parts = self.syllable_shapes.iter().map(|(shape, _)| {
+            let onset = shape.find('V').unwrap_or(shape.len());
+            let coda = shape.len() - shape.rfind('V').map_or(shape.len(), |idx| idx + 1);
+            (onset, shape.len() - onset - coda, coda)
+        });
+        let (mut min, mut max) = ([usize::MAX; 3], [0; 3]);
+        for (onset, nucleus, coda) in parts {
+            for (idx, len) in [onset, nucleus, coda].into_iter().enumerate() {
+                min[idx] = min[idx].min(len);
+                max[idx] = max[idx].max(len);
+            }
+        }
+
+        let mut out = String::new();
+        for (idx, class) in ["C", "V", "C"].into_iter().enumerate() {
+            out.push_str(&class.repeat(min[idx]));
+            for _ in min[idx]..max[idx] {
+                out.push_str(&format!("({class})"));
+            }
+        }
+        Some(out)
+    }
+}
+
+/// How two inventories differ, found by [`Comparison::new`]: the phonemes they share, and the phonemes only one of them
+/// has, each with the nearest phoneme of the other inventory.
+#[derive(Clone, Debug, Default)]
+pub struct Comparison {
+    shared: Vec<phone::Phoneme>,
+    only_first: Vec<(phone::Phoneme, Option<phone::Phoneme>)>,
+    only_second: Vec<(phone::Phoneme, Option<phone::Phoneme>)>,
+    distance: f64,
+}
+
+/// All the phonemes of an inventory, consonants first.
+fn all_phonemes(inventory: &phone::Inventory) -> Vec<phone::Phoneme> {
+    let consonants = inventory
+        .consonants()
+        .iter()
+        .map(|c| phone::Phoneme::from(*c));
+    let non_pulmonic = inventory
+        .non_pulmonic_consonants()
+        .iter()
+        .map(|c| phone::Phoneme::from(*c));
+    let vowels = inventory.vowels().iter().map(|v| phone::Phoneme::from(*v));
+    consonants.chain(non_pulmonic).chain(vowels).collect()
+}
+
+/// Pair each phoneme of `from` which `to` does not have with the nearest phoneme of `to`, and add up how far apart they
+/// are.
+fn unmatched(
+    from: &[phone::Phoneme],
+    to: &[phone::Phoneme],
+) -> (Vec<(phone::Phoneme, Option<phone::Phoneme>)>, f64) {
+    let mut total = 0.0;
+    let out = from
+        .iter()
+        .filter(|ph| !to.contains(ph))
+        .map(|ph| {
+            let nearest = to
+                .iter()
+                .min_by(|a, b| ph.distance(a).total_cmp(&ph.distance(b)));
+            total += nearest.map_or(1.0, |x| ph.distance(x));
+            (*ph, nearest.copied())
+        })
+        .collect();
+    (out, total)
+}
+
+impl Comparison {
+    /// Compare the `first` and `second` inventories.
+    pub fn new(first: &phone::Inventory, second: &phone::Inventory) -> Self {
+        let (first, second) = (all_phonemes(first), all_phonemes(second));
+        let shared: Vec<_> = first
+            .iter()
+            .filter(|ph| second.contains(ph))
+            .copied()
+            .collect();
+        let (only_first, first_total) = unmatched(&first, &second);
+        let (only_second, second_total) = unmatched(&second, &first);
+        let union = shared.len() + only_first.len() + only_second.len();
+        let distance = if union == 0 {
+            0.0
+        } else {
+            (first_total + second_total) / union as f64
+        };
+        Self {
+            shared,
+            only_first,
+            only_second,
+            distance,
+        }
+    }
+
+    /// The phonemes both inventories have.
+    pub fn shared(&self) -> &[phone::Phoneme] {
+        &self.shared
+    }
+
+    /// The phonemes only the first inventory has, each with the nearest phoneme of the second inventory if it has any.
+    pub fn only_first(&self) -> &[(phone::Phoneme, Option<phone::Phoneme>)] {
+        &self.only_first
+    }
+
+    /// The phonemes only the second inventory has, each with the nearest phoneme of the first inventory if it has any.
+    pub fn only_second(&self) -> &[(phone::Phoneme, Option<phone::Phoneme>)] {
+        &self.only_second
+    }
+
+    /// How different the inventories sound, from 0 for the same phonemes to 1 for nothing in common. Each phoneme only
+    /// one of them has counts by its [distance](phone::Phoneme::distance) to the nearest phoneme of the other, so
+    /// trading /θ/ for /s/ counts for less than trading it for /q/.
+    pub fn distance(&self) -> f64 {
+        self.distance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn analysis() {
+        let words: Vec<phone::Word> = ["ka.ta", "ˈstan", "a.mi", "tanka"]
+            .iter()
+            .map(|w| w.parse().unwrap())
+            .collect();
+        let analysis = Analysis::new(&words);
+        assert_eq!(4, analysis.words());
+        assert_eq!(
+            (phone::Phoneme::try_from('a').unwrap(), 6),
+            analysis.phonemes()[0]
+        );
+        assert_eq!(("CV".to_string(), 4), analysis.syllable_shapes()[0]);
+        assert_eq!(("CCVC".to_string(), 1), analysis.word_shapes()[0]);
+        assert!(analysis.word_shapes().contains(&("CVC CV".to_string(), 1)));
+        assert_eq!(
+            vec![("st".parse::<phone::Word>().unwrap().phonemes().collect(), 1)],
+            analysis.initial_clusters()
+        );
+        assert_eq!(1, analysis.medial_clusters().len());
+        assert!(analysis.final_clusters().is_empty());
+        assert_eq!("(C)(C)V(C)", analysis.syllable_pattern().unwrap());
+        assert_eq!("1:1,2:3", analysis.syllable_counts().unwrap().to_string());
+        // Pairs are counted across syllable breaks, like /at/ in /ka.ta/, but not across words, like /as/
+        let pair = |pair: &str| {
+            let mut phonemes = pair.chars().map(|c| phone::Phoneme::try_from(c).unwrap());
+            (phonemes.next().unwrap(), phonemes.next().unwrap())
+        };
+        assert!(analysis.bigrams().contains(&(pair("an"), 2)));
+        assert!(analysis.bigrams().contains(&(pair("at"), 1)));
+        assert!(!analysis.bigrams().iter().any(|(p, _)| *p == pair("as")));
+        assert_eq!(16.0 / 4.0, analysis.average_phonemes());
+        assert_eq!(7.0 / 4.0, analysis.average_syllables());
+
+        let empty = Analysis::new(&[] as &[phone::Word]);
+        assert!(empty.syllable_pattern().is_none());
+        assert_eq!(0.0, empty.average_phonemes());
+    }
+    #[test]
+    fn syllabify_words() {
+        let syllabify = |word: &str| syllabify_word(&word.parse().unwrap()).to_string();
+        assert_eq!("tan.ka", syllabify("tanka"));
+        assert_eq!("ˈkan.ta", syllabify("ˈkanta"));
+        assert_eq!("ka.ta", syllabify("ka.ta"));
+        assert_eq!("stan", syllabify("stan"));
+    }
+
+    #[test]
+    fn comparison() {
+        let inventory = |consonants: &str, vowels: &str| {
+            phone::Inventory::new(
+                consonants
+                    .chars()
+                    .map(|c| phone::Consonant::try_from(c).unwrap())
+                    .collect::<Vec<_>>(),
+                vowels
+                    .chars()
+                    .map(|c| phone::Vowel::try_from(c).unwrap())
+                    .collect::<Vec<_>>(),
+                [],
+            )
+        };
+        let ph = |c| phone::Phoneme::try_from(c).unwrap();
+
+        let first = inventory("ptkθ", "aiu");
+        let comparison = Comparison::new(&first, &inventory("ptks", "aiuo"));
+        assert_eq!(6, comparison.shared().len());
+        assert_eq!(&[(ph('θ'), Some(ph('s')))], comparison.only_first());
+        assert_eq!(
+            &[(ph('s'), Some(ph('θ'))), (ph('o'), Some(ph('u')))],
+            comparison.only_second()
+        );
+        assert!(comparison.distance() > 0.0);
+        assert!(comparison.distance() < Comparison::new(&first, &inventory("qχ", "ɛɔ")).distance());
+        assert_eq!(0.0, Comparison::new(&first, &first).distance());
+        assert_eq!(
+            1.0,
+            Comparison::new(&inventory("ptk", ""), &inventory("", "a")).distance()
+        );
+    }
+}
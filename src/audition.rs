@@ -0,0 +1,141 @@
+//! # Audition
+//!
+//! Reading a list of generated words only goes so far; whether a word is a keeper usually comes down to how it sounds.
+//! This is a terminal interface for going through a batch of words, listening to them one at a time, and starring the
+//! ones worth keeping. The interface is drawn on stderr, so the starred words can be redirected from stdout.
+
+use crate::SpeakerBox;
+use conlang::phone;
+use ratatui::{
+    backend::CrosstermBackend,
+    crossterm::{
+        event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+        execute, terminal,
+    },
+    layout::{Constraint, Layout},
+    style::{Modifier, Style},
+    widgets::{Block, List, ListItem, ListState, Paragraph},
+    Terminal,
+};
+
+const HELP: &str = "↑/↓ move  enter: speak  space: star  q: quit and print starred words  \
+    z: pause  r: replay  ⌫: stop  +/-: volume";
+
+/// A word to audition.
+pub struct Entry {
+    pub word: phone::Word,
+    /// How the word is shown in the list and printed if it is starred, like the IPA and romanization separated by a
+    /// tab.
+    pub text: String,
+}
+
+/// Show the `entries` in an interactive list until the user quits, returning the starred entries in their original
+/// order. Words are spoken with the `speaker`; if it could not be created, the error is shown when speaking is tried.
+pub async fn run(
+    entries: Vec<Entry>,
+    speaker: Result<SpeakerBox, anyhow::Error>,
+) -> Result<Vec<Entry>, anyhow::Error> {
+    terminal::enable_raw_mode()?;
+    execute!(std::io::stderr(), terminal::EnterAlternateScreen)?;
+    let result = match Terminal::new(CrosstermBackend::new(std::io::stderr())) {
+        Ok(mut term) => audition(&mut term, &entries, speaker.as_ref()).await,
+        Err(e) => Err(e.into()),
+    };
+    // Put the terminal back the way it was before reporting any error, so the message is readable
+    terminal::disable_raw_mode()?;
+    execute!(std::io::stderr(), terminal::LeaveAlternateScreen)?;
+
+    let starred = result?;
+    Ok(entries
+        .into_iter()
+        .zip(starred)
+        .filter_map(|(entry, starred)| starred.then_some(entry))
+        .collect())
+}
+
+/// Run the event loop, returning which entries are starred.
+async fn audition(
+    term: &mut Terminal<CrosstermBackend<std::io::Stderr>>,
+    entries: &[Entry],
+    speaker: Result<&SpeakerBox, &anyhow::Error>,
+) -> Result<Vec<bool>, anyhow::Error> {
+    let mut starred = vec![false; entries.len()];
+    let mut state = ListState::default().with_selected((!entries.is_empty()).then_some(0));
+    let mut status = String::from(HELP);
+    let mut speaking = None;
+    loop {
+        term.draw(|frame| {
+            let [list_area, status_area] =
+                Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+            let items = entries.iter().zip(starred.iter()).map(|(entry, starred)| {
+                let mark = if *starred { '★' } else { ' ' };
+                ListItem::new(format!("{mark} {}", entry.text.replace('\t', "   ")))
+            });
+            let title = format!(
+                " {} of {} starred ",
+                starred.iter().filter(|x| **x).count(),
+                entries.len()
+            );
+            let list = List::new(items)
+                .block(Block::bordered().title(title))
+                .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+            frame.render_stateful_widget(list, list_area, &mut state);
+            frame.render_widget(Paragraph::new(status.as_str()), status_area);
+        })?;
+
+        // While a word plays, wake up now and then to notice it has finished
+        if let Some(ipa) = speaking.take_if(|_| speaker.is_ok_and(|speaker| !speaker.is_playing()))
+        {
+            // Unless something else has been said since
+            if status == format!("speaking /{ipa}/") {
+                status = format!("spoke /{ipa}/");
+            }
+            continue;
+        }
+        if speaking.is_some() && !event::poll(std::time::Duration::from_millis(50))? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        status = String::from(HELP);
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => state.select_previous(),
+            KeyCode::Down | KeyCode::Char('j') => state.select_next(),
+            KeyCode::Home => state.select_first(),
+            KeyCode::End => state.select_last(),
+            KeyCode::Char(' ') | KeyCode::Char('s') => {
+                if let Some(idx) = state.selected() {
+                    starred[idx] = !starred[idx];
+                }
+            }
+            KeyCode::Enter | KeyCode::Char('p') => {
+                let Some(entry) = state.selected().and_then(|idx| entries.get(idx)) else {
+                    continue;
+                };
+                status = match speaker {
+                    Ok(speaker) => match speaker.start_speaking(&entry.word.to_string()).await {
+                        Ok(()) => {
+                            speaking = Some(entry.word.to_string());
+                            format!("speaking /{}/", entry.word)
+                        }
+                        Err(e) => format!("could not speak /{}/: {e}", entry.word),
+                    },
+                    Err(e) => format!("speech is not available: {e}"),
+                };
+            }
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Ok(starred)
+            }
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(starred),
+            code => {
+                if let Some(done) = speaker.ok().and_then(|speaker| speaker.control(code)) {
+                    status = done;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,416 @@
+//! # Sound Change
+//!
+//! Languages change over time, and they change regularly: Latin /k/ became /tʃ/ before front vowels in Italian, so
+//! *centum* became *cento*, and the same happened in every other word with /k/ before /e/ or /i/. A [`SoundChange`]
+//! describes one of these shifts, and [`SoundChanges`] applies a list of them in order to derive the later form of a
+//! word from an earlier one.
+
+use crate::phone;
+use std::{fmt, str::FromStr};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ParseError {
+    #[error("no input")]
+    NoInput,
+    #[error("invalid sound change \"{0}\": expected `from > to` or `from > to / environment`")]
+    InvalidRule(String),
+    #[error("invalid environment \"{0}\": expected one `_` for where the change happens")]
+    InvalidEnvironment(String),
+    #[error("invalid replacement \"{0}\": a set like `[bdg]` must replace a set of the same size")]
+    InvalidReplacement(String),
+    #[error("unknown phoneme '{0}'")]
+    UnknownPhoneme(char),
+}
+
+/// One position of a sound change, which matches one phoneme.
+#[derive(Clone, Debug, PartialEq)]
+enum Segment {
+    Phoneme(phone::Phoneme),
+    /// Any consonant, written `C`.
+    Consonant,
+    /// Any vowel, written `V`.
+    Vowel,
+    /// Any of the phonemes, written like `[ptk]`.
+    Set(Vec<phone::Phoneme>),
+}
+
+impl Segment {
+    fn matches(&self, phoneme: phone::Phoneme) -> bool {
+        match self {
+            Self::Phoneme(x) => *x == phoneme,
+            Self::Consonant => !matches!(phoneme, phone::Phoneme::Vowel(_)),
+            Self::Vowel => matches!(phoneme, phone::Phoneme::Vowel(_)),
+            Self::Set(xs) => xs.contains(&phoneme),
+        }
+    }
+
+    /// Parse a sequence of segments like `V[ptk]a`. Whitespace is ignored, and `∅` is the empty sequence.
+    fn parse_all(src: &str) -> Result<Vec<Self>, ParseError> {
+        let mut out = Vec::new();
+        let mut chars = src.chars().filter(|c| !c.is_whitespace());
+        while let Some(c) = chars.next() {
+            let segment = match c {
+                '∅' => continue,
+                'C' => Self::Consonant,
+                'V' => Self::Vowel,
+                '[' => {
+                    let mut set = Vec::new();
+                    loop {
+                        match chars.next() {
+                            Some(']') => break,
+                            Some(c) => set.push(phoneme(c)?),
+                            None => return Err(ParseError::InvalidRule(src.into())),
+                        }
+                    }
+                    Self::Set(set)
+                }
+                c => Self::Phoneme(phoneme(c)?),
+            };
+            out.push(segment);
+        }
+        Ok(out)
+    }
+}
+
+fn phoneme(c: char) -> Result<phone::Phoneme, ParseError> {
+    phone::Phoneme::try_from(c).map_err(|_| ParseError::UnknownPhoneme(c))
+}
+
+impl fmt::Display for Segment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Phoneme(x) => write!(f, "{x}"),
+            Self::Consonant => f.write_str("C"),
+            Self::Vowel => f.write_str("V"),
+            Self::Set(xs) => {
+                f.write_str("[")?;
+                for x in xs.iter() {
+                    write!(f, "{x}")?;
+                }
+                f.write_str("]")
+            }
+        }
+    }
+}
+
+/// A rule for changing sounds, written `from > to / environment` like the rules of historical linguistics:
+///
+/// | Rule              | Change                                                        |
+/// |:------------------|:--------------------------------------------------------------|
+/// | `s > h`           | /s/ becomes /h/ everywhere                                    |
+/// | `k > tʃ / _i`     | /k/ becomes /tʃ/ before /i/                                   |
+/// | `[ptk] > [bdg] / V_V` | voiceless plosives become voiced between vowels           |
+/// | `n > ∅ / _#`      | /n/ is lost at the end of a word                              |
+/// | `∅ > e / #_s`     | /e/ is inserted before /s/ at the start of a word             |
+///
+/// `C` and `V` match any consonant or vowel, `[...]` matches any phoneme in the brackets, and `#` in the environment
+/// is the edge of the word. A set in the replacement maps each phoneme of the set it replaces to the phoneme in the
+/// same place, so `[ptk] > [bdg]` turns /t/ into /d/. Rules see a word as one string of phonemes, so environments
+/// match across syllable boundaries.
+#[derive(Clone, PartialEq)]
+pub struct SoundChange {
+    from: Vec<Segment>,
+    to: Vec<Segment>,
+    before: Vec<Segment>,
+    after: Vec<Segment>,
+    /// The environment starts with `#`, so the change only happens at the start of a word.
+    initial: bool,
+    /// The environment ends with `#`, so the change only happens at the end of a word.
+    terminal: bool,
+}
+
+impl SoundChange {
+    /// Apply this change to the `word`. Every place the rule matches is changed at once, based on the word as it was
+    /// before the change, so `a > b / a_` turns /aaa/ into /abb/. Syllables keep their stress and tone; a syllable
+    /// which loses all its phonemes is dropped.
+    pub fn apply(&self, word: &phone::Word) -> phone::Word {
+        // Each phoneme with the index of the syllable it is in
+        let input: Vec<(phone::Phoneme, usize)> = word
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, syl)| syl.parts().iter().map(move |ph| (*ph, idx)))
+            .collect();
+        let phonemes: Vec<_> = input.iter().map(|(ph, _)| *ph).collect();
+        if phonemes.is_empty() {
+            return word.clone();
+        }
+
+        let mut out = Vec::with_capacity(input.len());
+        let mut idx = 0;
+        while idx <= input.len() {
+            if !self.matches_at(&phonemes, idx) {
+                out.extend(input.get(idx));
+                idx += 1;
+                continue;
+            }
+
+            for (pos, segment) in self.to.iter().enumerate() {
+                let replacement = match (segment, self.from.get(pos)) {
+                    (Segment::Set(to), Some(Segment::Set(from))) => {
+                        let choice = from.iter().position(|x| *x == phonemes[idx + pos]).unwrap();
+                        to[choice]
+                    }
+                    (Segment::Phoneme(x), _) => *x,
+                    _ => unreachable!("replacements are checked when parsing"),
+                };
+                // New phonemes join the syllable of the phoneme they replace or are inserted before
+                let at = (idx + pos.min(self.from.len().saturating_sub(1))).min(input.len() - 1);
+                out.push((replacement, input[at].1));
+            }
+            if self.from.is_empty() {
+                out.extend(input.get(idx));
+                idx += 1;
+            } else {
+                idx += self.from.len();
+            }
+        }
+
+        word.iter()
+            .enumerate()
+            .filter_map(|(syl_idx, syl)| {
+                let parts: Vec<_> = out
+                    .iter()
+                    .filter(|(_, x)| *x == syl_idx)
+                    .map(|(ph, _)| *ph)
+                    .collect();
+                if parts.is_empty() {
+                    return None;
+                }
+                let mut changed = phone::Syllable::new(&parts).with_stress(syl.stress());
+                changed.set_tone(syl.tone());
+                Some(changed)
+            })
+            .collect()
+    }
+
+    /// Check if the rule matches the `phonemes` at `idx`, environment included. An insertion matches between
+    /// phonemes, so it can match at `phonemes.len()`.
+    fn matches_at(&self, phonemes: &[phone::Phoneme], idx: usize) -> bool {
+        let end = idx + self.from.len();
+        if end > phonemes.len() {
+            return false;
+        }
+        if !self
+            .from
+            .iter()
+            .zip(&phonemes[idx..end])
+            .all(|(seg, ph)| seg.matches(*ph))
+        {
+            return false;
+        }
+
+        let Some(start) = idx.checked_sub(self.before.len()) else {
+            return false;
+        };
+        if self.initial && start != 0 {
+            return false;
+        }
+        let after_end = end + self.after.len();
+        if after_end > phonemes.len() || (self.terminal && after_end != phonemes.len()) {
+            return false;
+        }
+        self.before
+            .iter()
+            .zip(&phonemes[start..idx])
+            .chain(self.after.iter().zip(&phonemes[end..after_end]))
+            .all(|(seg, ph)| seg.matches(*ph))
+    }
+}
+
+impl FromStr for SoundChange {
+    type Err = ParseError;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        let src = src.trim();
+        if src.is_empty() {
+            return Err(ParseError::NoInput);
+        }
+        let invalid = || ParseError::InvalidRule(src.into());
+        let (change, environment) = match src.split_once('/') {
+            Some((change, environment)) => (change, Some(environment.trim())),
+            None => (src, None),
+        };
+        let (from, to) = change.split_once('>').ok_or_else(invalid)?;
+        let from = Segment::parse_all(from)?;
+        let to = Segment::parse_all(to)?;
+
+        // A set can only be replaced by a set of the same size, and nothing else can be replaced by a set or a class
+        let sets_match = to
+            .iter()
+            .enumerate()
+            .all(|(pos, seg)| match (seg, from.get(pos)) {
+                (Segment::Phoneme(_), _) => true,
+                (Segment::Set(to), Some(Segment::Set(from))) => to.len() == from.len(),
+                _ => false,
+            });
+        if !sets_match {
+            return Err(ParseError::InvalidReplacement(change.trim().into()));
+        }
+
+        let mut out = Self {
+            from,
+            to,
+            before: Vec::new(),
+            after: Vec::new(),
+            initial: false,
+            terminal: false,
+        };
+        if let Some(environment) = environment {
+            let invalid = || ParseError::InvalidEnvironment(environment.into());
+            let (before, after) = environment.split_once('_').ok_or_else(invalid)?;
+            let (before, after) = (before.trim(), after.trim());
+            out.initial = before.starts_with('#');
+            out.terminal = after.ends_with('#');
+            let before = before.strip_prefix('#').unwrap_or(before);
+            let after = after.strip_suffix('#').unwrap_or(after);
+            if before.contains(['#', '_']) || after.contains(['#', '_']) {
+                return Err(invalid());
+            }
+            out.before = Segment::parse_all(before)?;
+            out.after = Segment::parse_all(after)?;
+        }
+        if out.from.is_empty() && out.to.is_empty() {
+            return Err(invalid());
+        }
+        Ok(out)
+    }
+}
+
+impl fmt::Display for SoundChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let write_all = |f: &mut fmt::Formatter, segments: &[Segment]| -> fmt::Result {
+            if segments.is_empty() {
+                return f.write_str("∅");
+            }
+            for seg in segments.iter() {
+                write!(f, "{seg}")?;
+            }
+            Ok(())
+        };
+        write_all(f, &self.from)?;
+        f.write_str(" > ")?;
+        write_all(f, &self.to)?;
+        if self.before.is_empty() && self.after.is_empty() && !self.initial && !self.terminal {
+            return Ok(());
+        }
+
+        f.write_str(" / ")?;
+        if self.initial {
+            f.write_str("#")?;
+        }
+        for seg in self.before.iter() {
+            write!(f, "{seg}")?;
+        }
+        f.write_str("_")?;
+        for seg in self.after.iter() {
+            write!(f, "{seg}")?;
+        }
+        if self.terminal {
+            f.write_str("#")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for SoundChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SoundChange({self})")
+    }
+}
+
+/// An ordered list of [sound changes](SoundChange). Order matters: with `k > tʃ / _i` before `e > i`, /ke/ becomes
+/// /ki/, but the other way around it becomes /tʃi/.
+///
+/// A list is parsed from one rule per line, where blank lines and lines starting with `#` are skipped.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SoundChanges {
+    rules: Vec<SoundChange>,
+}
+
+impl SoundChanges {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_rule(mut self, rule: SoundChange) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    pub fn rules(&self) -> &[SoundChange] {
+        &self.rules
+    }
+
+    /// Apply each rule to the `word` in order.
+    pub fn apply(&self, word: &phone::Word) -> phone::Word {
+        self.rules
+            .iter()
+            .fold(word.clone(), |word, rule| rule.apply(&word))
+    }
+}
+
+impl FromStr for SoundChanges {
+    type Err = ParseError;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        let mut out = Self::new();
+        for line in src.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            out = out.with_rule(line.parse()?);
+        }
+        if out.rules.is_empty() {
+            Err(ParseError::NoInput)
+        } else {
+            Ok(out)
+        }
+    }
+}
+
+impl fmt::Display for SoundChanges {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for rule in self.rules.iter() {
+            writeln!(f, "{rule}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(rules: &str, word: &str) -> String {
+        let rules: SoundChanges = rules.parse().unwrap();
+        rules.apply(&word.parse().unwrap()).to_string()
+    }
+
+    #[test]
+    fn sound_changes() {
+        assert_eq!("ha.ta", apply("s > h", "sa.ta"));
+        assert_eq!("ka.tʃi", apply("k > tʃ / _i", "ka.ki"));
+        assert_eq!("a.ba.di", apply("[ptk] > [bdg] / V_V", "a.pa.ti"));
+        assert_eq!("ta.ma", apply("n > ∅ / _#", "ta.man"));
+        assert_eq!("ta.ma", apply("n > ∅ / _#", "ta.ma"));
+        assert_eq!("esta", apply("∅ > e / #_s", "sta"));
+        // Stress stays on its syllable, and emptied syllables are dropped
+        assert_eq!("ˈpa", apply("a > ∅ / #_", "a.ˈpa"));
+
+        // Matches are found in the word as it was before the change
+        assert_eq!("a.b.b", apply("a > b / a_", "a.a.a"));
+
+        // Rules apply in order
+        assert_eq!("ki", apply("k > tʃ / _i\ne > i", "ke"));
+        assert_eq!("tʃi", apply("e > i\nk > tʃ / _i", "ke"));
+
+        let rule: SoundChange = "[ptk] > [bdg] / #V_#".parse().unwrap();
+        assert_eq!("[ptk] > [bdg] / #V_#", rule.to_string());
+        assert!("[ptk] > [bd]".parse::<SoundChange>().is_err());
+        assert!("p > C".parse::<SoundChange>().is_err());
+        assert!("p > b / V".parse::<SoundChange>().is_err());
+        assert!("p > b / V_#V".parse::<SoundChange>().is_err());
+        assert!("p b".parse::<SoundChange>().is_err());
+    }
+}
@@ -0,0 +1,210 @@
+//! # Configuration
+//!
+//! Settings like the voice to speak with or the language being worked on are the same for most runs, so they can be
+//! set once instead of on every command line. Each setting is taken from the first of these which has it:
+//!
+//! 1. the flag on the command line, like `--voice Matthew`
+//! 2. an environment variable named after the flag, like `CONLANG_VOICE=Matthew`
+//! 3. the config file, like `voice = "Matthew"`
+//! 4. the built-in default
+//!
+//! The config file is `$CONLANG_CONFIG`, or `conlang/config.toml` in `$XDG_CONFIG_HOME` or `~/.config`. A relative
+//! `language` or `cache_dir` in the file is relative to the file, so the file works from any directory.
+//!
+//! Settings work by becoming the defaults of their flags, so they are checked like flags and show up as the defaults in
+//! `--help`.
+
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// What `--help` says about configuration.
+pub const HELP: &str = "Configuration:
+  Flags like --voice can be set once in $CONLANG_CONFIG or ~/.config/conlang/config.toml, like `voice = \"Matthew\"`,
+  or in an environment variable like CONLANG_VOICE. A flag on the command line comes first, then the environment, then
+  the file. The settings are bracket, tts, voice, engine, audio_format, sample_rate, language_code, mbrola_voice,
+  mbrola_symbols, base_pitch, rate, pitch, volume, normalize, loudness, cache_dir, and language, the default
+  --language of the commands which use one to generate words.";
+
+/// Settings from the config file and the environment. Each but the numbers is kept as text, to be parsed like the flag
+/// it sets.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub bracket: Option<String>,
+    pub tts: Option<String>,
+    pub voice: Option<String>,
+    pub engine: Option<String>,
+    pub audio_format: Option<String>,
+    pub sample_rate: Option<u32>,
+    pub language_code: Option<String>,
+    pub mbrola_voice: Option<String>,
+    pub mbrola_symbols: Option<String>,
+    pub base_pitch: Option<f64>,
+    pub rate: Option<String>,
+    pub pitch: Option<String>,
+    pub volume: Option<String>,
+    pub normalize: Option<String>,
+    pub loudness: Option<f64>,
+    pub cache_dir: Option<PathBuf>,
+    /// The `--language` of every command which has an optional one.
+    pub language: Option<PathBuf>,
+}
+
+impl Config {
+    /// Load the config file, if there is one, with the environment variables over it. A missing file is only an error
+    /// if `CONLANG_CONFIG` names it.
+    pub fn load() -> Result<Self, anyhow::Error> {
+        let mut config = match var("CONLANG_CONFIG") {
+            Some(path) => Self::read(Path::new(&path))?,
+            None => match default_path() {
+                Some(path) if path.exists() => Self::read(&path)?,
+                _ => Self::default(),
+            },
+        };
+        let strings = [
+            ("CONLANG_BRACKET", &mut config.bracket),
+            ("CONLANG_TTS", &mut config.tts),
+            ("CONLANG_VOICE", &mut config.voice),
+            ("CONLANG_ENGINE", &mut config.engine),
+            ("CONLANG_AUDIO_FORMAT", &mut config.audio_format),
+            ("CONLANG_LANGUAGE_CODE", &mut config.language_code),
+            ("CONLANG_MBROLA_VOICE", &mut config.mbrola_voice),
+            ("CONLANG_MBROLA_SYMBOLS", &mut config.mbrola_symbols),
+            ("CONLANG_RATE", &mut config.rate),
+            ("CONLANG_PITCH", &mut config.pitch),
+            ("CONLANG_VOLUME", &mut config.volume),
+            ("CONLANG_NORMALIZE", &mut config.normalize),
+        ];
+        for (name, setting) in strings {
+            if let Some(value) = var(name) {
+                *setting = Some(value);
+            }
+        }
+        if let Some(value) = var("CONLANG_SAMPLE_RATE") {
+            let rate = value
+                .parse()
+                .map_err(|e| anyhow::anyhow!("CONLANG_SAMPLE_RATE: {e}"))?;
+            config.sample_rate = Some(rate);
+        }
+        if let Some(value) = var("CONLANG_BASE_PITCH") {
+            let pitch = value
+                .parse()
+                .map_err(|e| anyhow::anyhow!("CONLANG_BASE_PITCH: {e}"))?;
+            config.base_pitch = Some(pitch);
+        }
+        if let Some(value) = var("CONLANG_LOUDNESS") {
+            let loudness = value
+                .parse()
+                .map_err(|e| anyhow::anyhow!("CONLANG_LOUDNESS: {e}"))?;
+            config.loudness = Some(loudness);
+        }
+        let paths = [
+            ("CONLANG_CACHE_DIR", &mut config.cache_dir),
+            ("CONLANG_LANGUAGE", &mut config.language),
+        ];
+        for (name, setting) in paths {
+            if let Some(value) = var(name) {
+                *setting = Some(value.into());
+            }
+        }
+        Ok(config)
+    }
+
+    /// Read the config file at `path`.
+    fn read(path: &Path) -> Result<Self, anyhow::Error> {
+        let src = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("could not read {}: {e}", path.display()))?;
+        let mut config: Self =
+            toml::from_str(&src).map_err(|e| anyhow::anyhow!("{}: {e}", path.display()))?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+        for setting in [&mut config.cache_dir, &mut config.language] {
+            if let Some(relative) = setting.take() {
+                *setting = Some(dir.join(relative));
+            }
+        }
+        Ok(config)
+    }
+
+    /// Make the settings the defaults of their flags in `cli`, so flags on the command line still come first. The
+    /// `language` only becomes the default where `--language` is optional and nothing conflicts with it: a command
+    /// which requires one is asking for a particular file, and one like `frequency-chart` reads other input without.
+    pub fn apply(&self, mut cli: clap::Command) -> clap::Command {
+        let globals = [
+            ("bracket", self.bracket.clone()),
+            ("tts", self.tts.clone()),
+            ("voice", self.voice.clone()),
+            ("engine", self.engine.clone()),
+            ("audio_format", self.audio_format.clone()),
+            ("sample_rate", self.sample_rate.map(|rate| rate.to_string())),
+            ("language_code", self.language_code.clone()),
+            ("mbrola_voice", self.mbrola_voice.clone()),
+            ("mbrola_symbols", self.mbrola_symbols.clone()),
+            ("base_pitch", self.base_pitch.map(|pitch| pitch.to_string())),
+            ("rate", self.rate.clone()),
+            ("pitch", self.pitch.clone()),
+            ("volume", self.volume.clone()),
+            ("normalize", self.normalize.clone()),
+            (
+                "loudness",
+                self.loudness.map(|loudness| loudness.to_string()),
+            ),
+            (
+                "cache_dir",
+                self.cache_dir.as_ref().map(|p| p.display().to_string()),
+            ),
+        ];
+        for (id, value) in globals {
+            if let Some(value) = value {
+                cli = cli.mut_arg(id, |arg| arg.default_value(value));
+            }
+        }
+
+        let Some(language) = &self.language else {
+            return cli;
+        };
+        let optional: Vec<String> = cli
+            .get_subcommands()
+            .filter(|sub| {
+                let optional = sub
+                    .get_arguments()
+                    .any(|arg| arg.get_id() == "language" && !arg.is_required_set());
+                let conflicting = sub.get_arguments().any(|arg| {
+                    sub.get_arg_conflicts_with(arg)
+                        .iter()
+                        .any(|other| other.get_id() == "language")
+                });
+                optional && !conflicting
+            })
+            .map(|sub| sub.get_name().to_owned())
+            .collect();
+        for name in optional {
+            cli = cli.mut_subcommand(name, |sub| {
+                sub.mut_arg("language", |arg| {
+                    arg.default_value(language.display().to_string())
+                })
+            });
+        }
+        cli
+    }
+}
+
+/// An environment variable, if it is set to something.
+fn var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+/// Where the config file is when `CONLANG_CONFIG` is not set.
+fn default_path() -> Option<PathBuf> {
+    let dir = var("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| var("HOME").map(|home| Path::new(&home).join(".config")))?;
+    Some(dir.join("conlang").join("config.toml"))
+}
+
+/// Where synthesized speech is kept when no `cache_dir` is set.
+pub fn cache_dir() -> Option<PathBuf> {
+    let dir = var("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| var("HOME").map(|home| Path::new(&home).join(".cache")))?;
+    Some(dir.join("conlang").join("speech"))
+}
@@ -0,0 +1,566 @@
+//! # Export
+//!
+//! A language rarely lives only in this tool: the lexicon ends up in a spreadsheet or a dictionary app, sound changes
+//! get worked out in Lexurgy, and the grammar gets typeset. This writes a language definition and lexicon in the
+//! formats those tools read, and typesets tables like the inventory chart for a grammar.
+
+use conlang::{ortho, phone, speech};
+use itertools::Itertools;
+use std::{
+    io::{self, Write},
+    path::Path,
+    sync::Arc,
+};
+
+/// A format to export to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Target {
+    /// The lexicon as comma-separated values, with a header.
+    Csv,
+    /// The lexicon as comma-separated values with the columns PolyGlot's lexicon import expects: the word, its gloss,
+    /// its part of speech, its pronunciation, and its definition.
+    Polyglot,
+    /// A Lexurgy sound-change file declaring the inventory as classes, with a romanizer and deromanizer from the
+    /// romanization. Lexurgy reads words from a separate file, so the lexicon is left out.
+    Lexurgy,
+    /// A LaTeX fragment describing the phonology and listing the lexicon, for `\input` into a document compiled with
+    /// XeLaTeX or LuaLaTeX.
+    Latex,
+    /// Flash cards for Anki, as notes to import with `File > Import`: the word spelled with the romanization and its
+    /// sound on the front, and its IPA and gloss on the back. With `--media`, the sound of each word is synthesized
+    /// with the speech backend into a folder to copy into Anki's `collection.media` folder.
+    Anki,
+}
+
+/// A word of the lexicon.
+pub struct Entry {
+    pub word: phone::Word,
+    pub gloss: String,
+}
+
+/// Parse a lexicon with one word per line in IPA, optionally followed by a tab and its gloss. Blank lines are skipped,
+/// and errors are reported as being in the file called `name`.
+pub fn parse_lexicon(name: &str, src: &str) -> Result<Vec<Entry>, anyhow::Error> {
+    src.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(idx, line)| {
+            let (word, gloss) = line.split_once('\t').unwrap_or((line, ""));
+            Ok(Entry {
+                word: word
+                    .trim()
+                    .parse()
+                    .map_err(|e| anyhow::anyhow!("{name}:{}: {e}", idx + 1))?,
+                gloss: gloss.trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Everything known about a language, ready to be written out.
+pub struct Export<'a> {
+    pub inventory: &'a phone::Inventory,
+    pub romanization: &'a ortho::Romanization,
+    pub patterns: &'a [String],
+    pub lexicon: &'a [Entry],
+    /// The format of the sounds Anki cards play, written by [`write_sounds`](Self::write_sounds), or nothing for cards
+    /// without sounds.
+    pub sounds: Option<speech::Format>,
+}
+
+impl Export<'_> {
+    pub fn write(&self, target: Target, out: &mut impl Write) -> io::Result<()> {
+        match target {
+            Target::Csv => self.write_csv(out),
+            Target::Polyglot => self.write_polyglot(out),
+            Target::Lexurgy => self.write_lexurgy(out),
+            Target::Latex => self.write_latex(out),
+            Target::Anki => self.write_anki(out),
+        }
+    }
+
+    /// Synthesize the sound of every word of the lexicon into `dir`, like [`synthesize_words`].
+    pub async fn write_sounds(
+        &self,
+        dir: &Path,
+        speech: &Arc<dyn speech::TtsBackend>,
+    ) -> Result<usize, anyhow::Error> {
+        let words: Vec<_> = self
+            .lexicon
+            .iter()
+            .map(|entry| entry.word.clone())
+            .collect();
+        synthesize_words(&words, dir, speech, CONCURRENCY).await
+    }
+
+    fn romanize(&self, word: &phone::Word) -> String {
+        self.romanization
+            .romanize(&word.phonemes().collect::<Vec<_>>())
+    }
+
+    fn consonants(&self) -> Vec<phone::Phoneme> {
+        self.inventory
+            .consonants()
+            .iter()
+            .map(|c| phone::Phoneme::from(*c))
+            .chain(
+                self.inventory
+                    .non_pulmonic_consonants()
+                    .iter()
+                    .map(|c| phone::Phoneme::from(*c)),
+            )
+            .collect()
+    }
+
+    fn vowels(&self) -> Vec<phone::Phoneme> {
+        self.inventory
+            .vowels()
+            .iter()
+            .map(|v| phone::Phoneme::from(*v))
+            .collect()
+    }
+
+    fn write_csv(&self, out: &mut impl Write) -> io::Result<()> {
+        let mut table = csv::Writer::from_writer(out);
+        table.write_record(["romanization", "ipa", "syllables", "gloss"])?;
+        for entry in self.lexicon {
+            table.write_record([
+                &self.romanize(&entry.word),
+                &entry.word.to_string(),
+                &entry.word.iter().join(" "),
+                &entry.gloss,
+            ])?;
+        }
+        table.flush()
+    }
+
+    fn write_polyglot(&self, out: &mut impl Write) -> io::Result<()> {
+        let mut table = csv::Writer::from_writer(out);
+        table.write_record([
+            "Conword",
+            "Local Word",
+            "Part of Speech",
+            "Pronunciation",
+            "Definition",
+        ])?;
+        for entry in self.lexicon {
+            table.write_record([
+                &self.romanize(&entry.word),
+                &entry.gloss,
+                "",
+                &entry.word.to_string(),
+                "",
+            ])?;
+        }
+        table.flush()
+    }
+
+    fn write_anki(&self, out: &mut impl Write) -> io::Result<()> {
+        // Anki reads these headers to import without asking how the file is laid out
+        writeln!(out, "#separator:tab")?;
+        writeln!(out, "#html:true")?;
+        writeln!(out, "#notetype:Basic")?;
+        for entry in self.lexicon {
+            let mut front = html_escape(&self.romanize(&entry.word));
+            if let Some(format) = self.sounds {
+                front += &format!("[sound:{}]", sound_file(&entry.word, format));
+            }
+            let mut back = format!("/{}/", html_escape(&entry.word.to_string()));
+            if !entry.gloss.is_empty() {
+                back += &format!("<br>{}", html_escape(&entry.gloss));
+            }
+            writeln!(out, "{front}\t{back}")?;
+        }
+        Ok(())
+    }
+
+    fn write_lexurgy(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "# Exported from conlang")?;
+        for (name, phonemes) in [("consonant", self.consonants()), ("vowel", self.vowels())] {
+            if !phonemes.is_empty() {
+                writeln!(out, "Class {name} {{{}}}", phonemes.iter().join(", "))?;
+            }
+        }
+
+        let entries = self.romanization.entries();
+        if !entries.is_empty() {
+            // Lexurgy tries the expressions of a rule in order, so longer graphemes have to come before their prefixes
+            writeln!(out, "\nDeromanizer:")?;
+            for (grapheme, phoneme) in entries
+                .iter()
+                .sorted_by_key(|(g, _)| std::cmp::Reverse(g.chars().count()))
+            {
+                writeln!(out, "    {grapheme} => {phoneme}")?;
+            }
+        }
+        writeln!(out, "\n# Sound changes go here")?;
+        if !entries.is_empty() {
+            writeln!(out, "\nRomanizer:")?;
+            for (grapheme, phoneme) in entries {
+                writeln!(out, "    {phoneme} => {grapheme}")?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_latex(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(
+            out,
+            "% Exported from conlang. Compile with XeLaTeX or LuaLaTeX and a font with IPA, like Charis SIL."
+        )?;
+        writeln!(out, "\\section*{{Phonology}}")?;
+        for (name, phonemes) in [("Consonants", self.consonants()), ("Vowels", self.vowels())] {
+            if !phonemes.is_empty() {
+                writeln!(out, "\\paragraph{{{name}}} /{}/", phonemes.iter().join(" "))?;
+            }
+        }
+        if !self.patterns.is_empty() {
+            let patterns = self
+                .patterns
+                .iter()
+                .map(|p| format!("\\texttt{{{}}}", latex_escape(p)))
+                .join(", ");
+            writeln!(out, "\\paragraph{{Word shapes}} {patterns}")?;
+        }
+
+        let entries = self.romanization.entries();
+        if !entries.is_empty() {
+            writeln!(out, "\\paragraph{{Orthography}}")?;
+            writeln!(out, "\\begin{{tabular}}{{ll}}")?;
+            for (grapheme, phoneme) in entries {
+                writeln!(out, "  {} & /{phoneme}/ \\\\", latex_escape(grapheme))?;
+            }
+            writeln!(out, "\\end{{tabular}}")?;
+        }
+
+        if !self.lexicon.is_empty() {
+            writeln!(out, "\n\\section*{{Lexicon}}")?;
+            writeln!(out, "\\begin{{description}}")?;
+            for entry in self.lexicon {
+                write!(
+                    out,
+                    "  \\item[{}] /{}/",
+                    latex_escape(&self.romanize(&entry.word)),
+                    entry.word
+                )?;
+                if !entry.gloss.is_empty() {
+                    write!(out, " --- {}", latex_escape(&entry.gloss))?;
+                }
+                writeln!(out)?;
+            }
+            writeln!(out, "\\end{{description}}")?;
+        }
+        Ok(())
+    }
+}
+
+/// How many words are synthesized at once by default. Polly allows more requests at once than this, but not many more.
+pub const CONCURRENCY: usize = 4;
+
+/// The name of the sound file of a word. Anki keeps the media of every deck in one folder, so the name starts with
+/// `conlang-`, and it is made from the IPA rather than a spelling, so words which sound the same share a file.
+pub fn sound_file(word: &phone::Word, format: speech::Format) -> String {
+    let name: String = word
+        .phonemes()
+        .map(|ph| ph.to_string())
+        .collect::<String>()
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect();
+    format!("conlang-{name}.{}", format.extension())
+}
+
+/// Synthesize the sound of each of the `words` into `dir` with the speech backend, named with [`sound_file`], with up to
+/// `concurrency` requests to the speech backend at once. Files which are already there are kept, so synthesizing a
+/// list again only synthesizes the new words. Returns the number of files written.
+pub async fn synthesize_words(
+    words: &[phone::Word],
+    dir: &Path,
+    speech: &Arc<dyn speech::TtsBackend>,
+    concurrency: usize,
+) -> Result<usize, anyhow::Error> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| anyhow::anyhow!("could not create {}: {e}", dir.display()))?;
+    let mut tasks = tokio::task::JoinSet::new();
+    let mut started = std::collections::HashSet::new();
+    let mut count = 0;
+    for word in words {
+        let path = dir.join(sound_file(word, speech.format()));
+        // Words which sound the same share a file, which only needs to be written once
+        if path.exists() || !started.insert(path.clone()) {
+            continue;
+        }
+        if tasks.len() >= concurrency.max(1) {
+            if let Some(done) = tasks.join_next().await {
+                done??;
+                count += 1;
+            }
+        }
+        let speech = speech.clone();
+        let ipa = word.to_string();
+        tasks.spawn(async move {
+            let audio = speech
+                .synthesize(&ipa)
+                .await
+                .map_err(|e| anyhow::anyhow!("could not synthesize /{ipa}/: {e}"))?;
+            tokio::fs::write(&path, audio)
+                .await
+                .map_err(|e| anyhow::anyhow!("could not write {}: {e}", path.display()))
+        });
+    }
+    while let Some(done) = tasks.join_next().await {
+        done??;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// A format for the manifest of synthesized sounds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Manifest {
+    /// `manifest.csv`, comma-separated values with a header.
+    #[default]
+    Csv,
+    /// `manifest.json`, an array with an object for each word.
+    Json,
+}
+
+/// What the manifest says about the sound of a word.
+#[derive(serde::Serialize)]
+struct Sound<'a> {
+    ipa: String,
+    gloss: &'a str,
+    file: String,
+    /// The spelling of the word in the romanization, or nothing without one.
+    romanization: String,
+    voice: &'a str,
+    /// In seconds, to the millisecond.
+    duration: f64,
+    /// The SHA-256 of the file, in hexadecimal.
+    sha256: String,
+}
+
+/// Write a manifest of the sound files in `dir` of the words of the `lexicon`, synthesized by [`synthesize_words`] in
+/// the `format` with the `voice`, returning where it was written. Each word has its IPA, gloss, file, spelling in the
+/// `romanization` if there is one, voice, duration, and checksum, which are read from the file, so words synthesized
+/// before are listed as they are. Words which sound the same are listed once for each entry.
+pub fn write_manifest(
+    dir: &Path,
+    lexicon: &[Entry],
+    romanization: Option<&ortho::Romanization>,
+    voice: &str,
+    format: speech::Format,
+    manifest: Manifest,
+) -> Result<std::path::PathBuf, anyhow::Error> {
+    use sha2::{Digest, Sha256};
+
+    let mut sounds = Vec::with_capacity(lexicon.len());
+    for entry in lexicon {
+        let file = sound_file(&entry.word, format);
+        let path = dir.join(&file);
+        let audio = std::fs::read(&path)
+            .map_err(|e| anyhow::anyhow!("could not read {}: {e}", path.display()))?;
+        let duration = speech::duration(&audio, format)
+            .map_err(|e| anyhow::anyhow!("{}: {e}", path.display()))?;
+        sounds.push(Sound {
+            ipa: entry.word.to_string(),
+            gloss: &entry.gloss,
+            file,
+            romanization: romanization
+                .map(|r| r.romanize(&entry.word.phonemes().collect::<Vec<_>>()))
+                .unwrap_or_default(),
+            voice,
+            duration: (duration.as_secs_f64() * 1000.0).round() / 1000.0,
+            sha256: format!("{:x}", Sha256::digest(&audio)),
+        });
+    }
+
+    let path = dir.join(match manifest {
+        Manifest::Csv => "manifest.csv",
+        Manifest::Json => "manifest.json",
+    });
+    let written = match manifest {
+        Manifest::Csv => (|| {
+            let mut table = csv::Writer::from_path(&path)?;
+            for sound in sounds.iter() {
+                table.serialize(sound)?;
+            }
+            table.flush()?;
+            Ok::<_, anyhow::Error>(())
+        })(),
+        Manifest::Json => (|| {
+            let mut out = io::BufWriter::new(std::fs::File::create(&path)?);
+            serde_json::to_writer_pretty(&mut out, &sounds)?;
+            writeln!(out)?;
+            out.flush()?;
+            Ok::<_, anyhow::Error>(())
+        })(),
+    };
+    written.map_err(|e| anyhow::anyhow!("{}: {e}", path.display()))?;
+    Ok(path)
+}
+
+/// Write a table as a LaTeX `tabular`, with a rule under the first row if there is more than one. Cells for which
+/// `is_ipa` returns true, given their row and column, are IPA: written with the macros of the TIPA package if `tipa` is
+/// set, or left as they are for a Unicode font. Other cells are escaped.
+pub fn latex_table(
+    rows: &[Vec<String>],
+    is_ipa: impl Fn(usize, usize) -> bool,
+    tipa: bool,
+) -> String {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let mut out = format!("\\begin{{tabular}}{{{}}}\n", "l".repeat(columns));
+    for (row, cells) in rows.iter().enumerate() {
+        let cells = cells
+            .iter()
+            .enumerate()
+            .map(|(col, cell)| match is_ipa(row, col) {
+                _ if cell.is_empty() => String::new(),
+                true if tipa => format!("\\textipa{{{}}}", to_tipa(cell)),
+                true => cell.clone(),
+                false => latex_escape(cell),
+            })
+            .join(" & ");
+        out.push_str(&format!("  {cells} \\\\\n"));
+        if row == 0 && rows.len() > 1 {
+            out.push_str("  \\hline\n");
+        }
+    }
+    out.push_str("\\end{tabular}");
+    out
+}
+
+/// Write `ipa` in the input of TIPA's `\textipa`, where ASCII letters and digits stand for IPA symbols and the rest
+/// have macros. Symbols TIPA has no way of writing, like /ⱱ/, are left as they are.
+pub fn to_tipa(ipa: &str) -> String {
+    let mut out = String::with_capacity(ipa.len());
+    for c in ipa.chars() {
+        let code = match c {
+            'ʈ' => "\\:t",
+            'ɖ' => "\\:d",
+            'ɟ' => "\\textbardotlessj",
+            'ɢ' => "\\;G",
+            'ʔ' => "P",
+            'ɱ' => "M",
+            'ɳ' => "\\:n",
+            'ɲ' => "\\textltailn",
+            'ŋ' => "N",
+            'ɴ' => "\\;N",
+            'ʙ' => "\\;B",
+            'ʀ' => "\\;R",
+            'ɾ' => "R",
+            'ɽ' => "\\:r",
+            'ɸ' => "F",
+            'β' => "B",
+            'θ' => "T",
+            'ð' => "D",
+            'ʃ' => "S",
+            'ʒ' => "Z",
+            'ʂ' => "\\:s",
+            'ʐ' => "\\:z",
+            'ç' => "C",
+            'ʝ' => "J",
+            'ɣ' => "G",
+            'χ' => "X",
+            'ʁ' => "K",
+            'ħ' => "\\textcrh",
+            'ʕ' => "Q",
+            'ɦ' => "H",
+            'ɬ' => "\\textbeltl",
+            'ɮ' => "\\textlyoghlig",
+            'ʋ' => "V",
+            'ɹ' => "\\*r",
+            'ɻ' => "\\:R",
+            'ɰ' => "\\textturnmrleg",
+            'ɭ' => "\\:l",
+            'ʎ' => "L",
+            'ʟ' => "\\;L",
+            'ɨ' => "1",
+            'ʉ' => "0",
+            'ɯ' => "W",
+            'ɪ' => "I",
+            'ʏ' => "Y",
+            'ʊ' => "U",
+            'ø' => "\\o",
+            'ɘ' => "9",
+            'ɵ' => "8",
+            'ɤ' => "7",
+            'ə' => "@",
+            'ɛ' => "E",
+            'œ' => "\\oe",
+            'ɜ' => "3",
+            'ɞ' => "\\textcloserevepsilon",
+            'ʌ' => "2",
+            'ɔ' => "O",
+            'æ' => "\\ae",
+            'ɐ' => "5",
+            'ɶ' => "\\OE",
+            'ɑ' => "A",
+            'ɒ' => "6",
+            'ʘ' => "\\textbullseye",
+            'ǀ' => "\\textpipe",
+            'ǃ' => "!",
+            'ǂ' => "\\textdoublebarpipe",
+            'ǁ' => "\\textdoublepipe",
+            'ɓ' => "\\!b",
+            'ɗ' => "\\!d",
+            'ʄ' => "\\!j",
+            'ɠ' => "\\!g",
+            'ʛ' => "\\!G",
+            'ˈ' => "\"",
+            'ˌ' => "\"\"",
+            c => {
+                out.push(c);
+                continue;
+            }
+        };
+        // Braces keep a macro like `\ae` from running into a letter after it
+        if code.starts_with('\\') {
+            out.push('{');
+            out.push_str(code);
+            out.push('}');
+        } else {
+            out.push_str(code);
+        }
+    }
+    out
+}
+
+/// Escape the characters LaTeX treats specially, so `src` is typeset as written.
+/// Escape text for a field of an Anki note, which is HTML on one line of a tab-separated file.
+fn html_escape(src: &str) -> String {
+    let mut out = String::with_capacity(src.len());
+    for c in src.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '\t' | '\n' | '\r' => out.push(' '),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn latex_escape(src: &str) -> String {
+    let mut out = String::with_capacity(src.len());
+    for c in src.chars() {
+        match c {
+            '\\' => out.push_str("\\textbackslash{}"),
+            '~' => out.push_str("\\textasciitilde{}"),
+            '^' => out.push_str("\\textasciicircum{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                out.push('\\');
+                out.push(c);
+            }
+            // Brackets would end the label of a description item early
+            '[' | ']' => {
+                out.push('{');
+                out.push(c);
+                out.push('}');
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
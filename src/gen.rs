@@ -2,10 +2,69 @@
 //!
 //! This module concerns generation of words from rule sets.
 
+/// Implement `Serialize` and `Deserialize` for a type through its `Display` and `FromStr` implementations, so the
+/// serialized form is the same syntax used on the command line.
+macro_rules! serde_via_str {
+    ($t:ty) => {
+        impl serde::Serialize for $t {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $t {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let src = <std::borrow::Cow<str>>::deserialize(deserializer)?;
+                src.parse().map_err(serde::de::Error::custom)
+            }
+        }
+    };
+}
+
+mod builder;
+mod counts;
+mod family;
+mod filter;
+mod harmony;
+mod inventory;
+mod lexicon;
+mod lint;
+mod markov;
+mod pairs;
+mod position;
+mod redup;
+mod ruleset;
+mod stress;
+mod style;
+mod tone;
+
+pub use builder::{Class, SyllableBuilder, WordGeneratorBuilder};
+pub use counts::SyllableCounts;
+pub use family::{derive_family, Process};
+pub use filter::Filter;
+pub use harmony::Harmony;
+pub use inventory::{frequency, random_inventory};
+pub(crate) use lexicon::within_distance;
+pub use lexicon::Lexicon;
+pub use lint::Lint;
+pub use markov::MarkovGenerator;
+pub use position::{Position, Positions};
+pub use redup::Reduplication;
+pub use ruleset::{Ruleset, WeightedPattern};
+pub use stress::StressRule;
+pub use style::{Feature, Style};
+pub use tone::Tones;
+
 use crate::phone;
-use rand::Rng;
+use itertools::Itertools;
+use rand::{
+    distributions::{Distribution, Uniform, WeightedIndex},
+    seq::{IteratorRandom, SliceRandom},
+    Rng, SeedableRng,
+};
+use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
-use std::fmt;
+use std::{collections::HashSet, fmt, ops::Range, str::FromStr, sync::Arc};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -14,202 +73,3372 @@ pub enum ParseError {
     NoInput,
     #[error("unrecognized character: '{0}'")]
     UnknownCharacter(char),
+    #[error("'{0}' does not match any phonemes in the inventory")]
+    EmptyClass(String),
+    #[error("invalid weights for '{0}'")]
+    InvalidWeights(String),
+    #[error("invalid filter: \"{0}\"")]
+    InvalidFilter(String),
+    #[error("invalid syllable counts: \"{0}\"")]
+    InvalidSyllableCounts(String),
+    #[error("invalid stress rule: \"{0}\"")]
+    InvalidStressRule(String),
+    #[error("invalid tones: \"{0}\"")]
+    InvalidTones(String),
+    #[error("invalid process \"{0}\": expected a suffix like `-ta`, a prefix like `ka-`, or vowel changes like `a>u`")]
+    InvalidProcess(String),
+    #[error("unknown stratum \"{0}\"")]
+    UnknownStratum(String),
+    #[error("invalid reduplication \"{0}\": expected `full`, `syllable`, or `cv`")]
+    InvalidReduplication(String),
+    #[error("invalid position set \"{0}\": expected `initial=...`, `medial=...`, or `coda=...`")]
+    InvalidPositions(String),
+    #[error("unclosed '{0}'")]
+    Unclosed(char),
+    #[error("invalid probability \"{0}\": expected a number from 0 to 1")]
+    InvalidProbability(String),
+    #[error("invalid style \"{0}\": expected `elvish`, `harsh`, or pairs like `sonorant:2`")]
+    InvalidStyle(String),
+    #[error("invalid rule \"{0}\": expected `name = pattern`")]
+    InvalidRule(String),
+    #[error("unknown rule \"{0}\"")]
+    UnknownRule(String),
+    #[error("rule \"{0}\" refers to itself")]
+    RecursiveRule(String),
+    #[error(
+        "invalid weighted pattern \"{0}\": expected `pattern = weight` with a whole number above 0"
+    )]
+    InvalidPatternWeight(String),
+    /// An error in a pattern, with where it happened. `position` counts characters, not bytes, so it lines up with the
+    /// pattern when printed.
+    #[error("{source} at position {position} of \"{pattern}\"")]
+    Pattern {
+        pattern: String,
+        position: usize,
+        source: Box<ParseError>,
+        suggestion: Option<String>,
+    },
+}
+
+impl ParseError {
+    /// Get the underlying error, without the pattern it happened in.
+    pub fn kind(&self) -> &ParseError {
+        match self {
+            Self::Pattern { source, .. } => source.kind(),
+            _ => self,
+        }
+    }
+
+    /// Attach the `pattern` and the byte offset `at` where this error happened in it.
+    fn in_pattern(self, pattern: &str, at: usize) -> Self {
+        let source = match self {
+            Self::Pattern {
+                position, source, ..
+            } => {
+                let at = at
+                    + pattern[at..]
+                        .char_indices()
+                        .nth(position)
+                        .map_or(pattern.len() - at, |(x, _)| x);
+                return (*source).in_pattern(pattern, at);
+            }
+            x => x,
+        };
+        // Point at the bad character itself, which might be inside a group like `[pqt]`
+        let at = match source {
+            Self::UnknownCharacter(c) => pattern[at..].find(c).map_or(at, |x| at + x),
+            _ => at,
+        };
+        Self::Pattern {
+            pattern: pattern.into(),
+            position: pattern[..at].chars().count(),
+            suggestion: source.suggestion(),
+            source: Box::new(source),
+        }
+    }
+
+    fn suggestion(&self) -> Option<String> {
+        match self {
+            Self::UnknownCharacter(c) => {
+                let upper = c.to_uppercase().next().filter(|x| x != c);
+                if let Some(name) = upper.and_then(class_name) {
+                    Some(format!("did you mean class '{}' ({name})?", upper.unwrap()))
+                } else if phone::Phoneme::try_from(*c).is_ok() {
+                    Some(format!("write \\{c} or [{c}] for the phoneme /{c}/"))
+                } else {
+                    None
+                }
+            }
+            Self::EmptyClass(_) => Some("add phonemes of this class to the inventory".into()),
+            Self::Unclosed('[') => Some("add a closing ']'".into()),
+            Self::Unclosed('(') => Some("add a closing ')'".into()),
+            Self::Unclosed('{') => Some("add a closing '}'".into()),
+            _ => None,
+        }
+    }
+}
+
+/// Split a pattern into the patterns of its syllables. Syllables are separated by whitespace, except inside `[..]` and
+/// `{..}`, where whitespace can separate phonemes.
+fn split_syllables(src: &str) -> impl Iterator<Item = &str> {
+    let mut depth = 0usize;
+    src.split(move |c: char| {
+        match c {
+            '[' | '{' => depth += 1,
+            ']' | '}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        depth == 0 && c.is_ascii_whitespace()
+    })
+    .filter(|syl| !syl.is_empty())
+}
+
+/// Get the vowels picked by a subclass letter following `V`, like `Vf` for front vowels. Front, central, and back are
+/// the columns of [`phone::Frontness::backness`].
+fn vowel_subclass(c: char) -> Option<fn(&phone::Vowel) -> bool> {
+    match c {
+        'f' => Some(|v| v.frontness().backness() == phone::Backness::Front),
+        'c' => Some(|v| v.frontness().backness() == phone::Backness::Central),
+        'b' => Some(|v| v.frontness().backness() == phone::Backness::Back),
+        'h' => Some(|v| v.height().value() >= 8),
+        'm' => Some(|v| matches!(v.height().value(), 3..=7)),
+        'l' => Some(|v| v.height().value() <= 2),
+        'r' => Some(|v| v.is_rounded()),
+        'u' => Some(|v| !v.is_rounded()),
+        _ => None,
+    }
+}
+
+/// Get the name of a class letter in a pattern, like "nasal" for `N`.
+fn class_name(c: char) -> Option<&'static str> {
+    match c {
+        'C' => Some("consonant"),
+        'V' => Some("vowel"),
+        'K' => Some("non-pulmonic consonant"),
+        'M' => Some("bilabial"),
+        'L' => Some("labiodental"),
+        'D' => Some("dental or alveolar"),
+        'Ḍ' => Some("retroflex"),
+        'J' => Some("palatal"),
+        'G' => Some("velar"),
+        'Q' => Some("uvular"),
+        'H' => Some("pharyngeal or glottal"),
+        'P' => Some("plosive"),
+        'N' => Some("nasal"),
+        'T' => Some("trill"),
+        'X' => Some("fricative"),
+        'R' => Some("approximant"),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum GenerateError {
+    #[error("requested {requested} unique words, but the patterns can only produce {possible}")]
+    NotEnoughWords { requested: usize, possible: u128 },
+    #[error("gave up looking for unique words after generating {generated}")]
+    Exhausted { generated: usize },
+    #[error("no word passed the filters in {attempts} attempts{}", most_rejections(.rejections))]
+    Unsatisfiable {
+        attempts: usize,
+        /// Each filter, harmony, or positions which rejected candidates and how many it rejected, most first. A
+        /// candidate rejected by more than one of them is counted for each.
+        rejections: Vec<(String, usize)>,
+    },
+}
+
+fn most_rejections(rejections: &[(String, usize)]) -> String {
+    match rejections.first() {
+        Some((name, count)) => format!(" ({name} rejected {count})"),
+        None => String::new(),
+    }
+}
+
+/// [`WordGenerator::generate_batch`] generates words in chunks of this size, each with its own random number generator.
+const BATCH_CHUNK: usize = 1024;
+
+/// When the patterns can produce at most this many words, [`generate_unique`] enumerates the entire space instead of
+/// randomly generating words.
+const ENUMERATION_LIMIT: u128 = 1 << 16;
+
+/// When randomly generating unique words, give up after this many duplicates are generated in a row.
+const MAX_CONSECUTIVE_DUPLICATES: usize = 1000;
+
+/// Unless a generator sets its own limit, give up on finding a word which passes the filters after this many
+/// candidates.
+const MAX_ATTEMPTS: usize = 100_000;
+
+/// Generate `count` distinct words, picking a random generator from `generators` for each word.
+///
+/// For small word spaces, this enumerates every possible word and shuffles them. For larger spaces, words are generated
+/// randomly and duplicates are discarded. If `count` is more than the `generators` can produce, this returns
+/// [`GenerateError::NotEnoughWords`].
+pub fn generate_unique(
+    generators: &[WordGenerator],
+    rng: &mut impl Rng,
+    count: usize,
+) -> Result<Vec<phone::Word>, GenerateError> {
+    let possible = generators
+        .iter()
+        .map(WordGenerator::count)
+        .fold(0, u128::saturating_add);
+    if count as u128 > possible {
+        return Err(GenerateError::NotEnoughWords {
+            requested: count,
+            possible,
+        });
+    }
+
+    if possible <= ENUMERATION_LIMIT {
+        // Patterns can overlap (`CV` and `PV`), so the true space might be smaller than `possible`
+        let mut seen = HashSet::new();
+        let all: Vec<_> = generators
+            .iter()
+            .flat_map(|gen| gen.enumerate().map(move |word| (gen, word)))
+            .filter(|(_, word)| seen.insert(word.clone()))
+            .collect();
+        if count > all.len() {
+            return Err(GenerateError::NotEnoughWords {
+                requested: count,
+                possible: all.len() as u128,
+            });
+        }
+
+        let mut out = all.into_iter().choose_multiple(rng, count);
+        out.shuffle(rng);
+        // Tones are random rather than enumerated, so they are assigned after the words are picked
+        return Ok(out
+            .into_iter()
+            .map(|(gen, mut word)| {
+                if let Some(tones) = &gen.tones {
+                    tones.assign(rng, word.syllables_mut());
+                }
+                word
+            })
+            .collect());
+    }
+
+    let mut seen = HashSet::with_capacity(count);
+    let mut out = Vec::with_capacity(count);
+    let mut duplicates = 0;
+    while out.len() < count {
+        let word = generators[rng.gen_range(0..generators.len())].try_generate(rng)?;
+        if seen.insert(word.clone()) {
+            out.push(word);
+            duplicates = 0;
+        } else {
+            duplicates += 1;
+            if duplicates >= MAX_CONSECUTIVE_DUPLICATES {
+                return Err(GenerateError::Exhausted {
+                    generated: out.len(),
+                });
+            }
+        }
+    }
+    Ok(out)
 }
 
-#[derive(Clone, PartialEq)]
+/// Generates words from a pattern like `CV CVC`.
+///
+/// Generators can be serialized with serde, so a compiled generator can be saved and loaded without the inventory it
+/// was parsed with. Filters made with [`Filter::custom`] can not be serialized.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct WordGenerator {
     syllables: SmallVec<[SyllableGenerator; 4]>,
+    /// If set, the number of syllables in a word is random and `syllables` is the pool each syllable is picked from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    syllable_counts: Option<SyllableCounts>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    filters: Vec<Filter>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    harmony: Option<Harmony>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    positions: Option<Positions>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reduplication: Option<Reduplication>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    stress: Option<StressRule>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tones: Option<Tones>,
+    /// The chance that optional phonemes without their own probability are included.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    optional_probability: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_attempts: Option<usize>,
+    /// Runs of syllables which are included or left out as a whole, from optional references in a [`Ruleset`]. A
+    /// group inside another comes before it. These are ignored with syllable counts, which treat `syllables` as a pool.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    optional_syllables: Vec<Range<usize>>,
+}
+
+/// State carried through the generation of a single word, so that choices can depend on what was generated before.
+#[derive(Default)]
+struct Context<'a> {
+    harmony: Option<&'a Harmony>,
+    /// The harmony class of the first harmonizing vowel in the word.
+    harmony_class: Option<usize>,
+    positions: Option<&'a Positions>,
+    /// The index of the syllable being generated.
+    syllable: usize,
+    /// The position of the phoneme being generated, if it is outside the nucleus.
+    position: Option<Position>,
+    /// The value of each backreference label seen so far, or `None` if it was an optional phoneme which was left out.
+    bindings: Bindings,
+    optional_probability: Option<f64>,
+}
+
+type Bindings = SmallVec<[(u8, Option<phone::Phoneme>); 4]>;
+
+/// Get the value bound to `label`, if it has been bound.
+fn bound(bindings: &Bindings, label: u8) -> Option<Option<phone::Phoneme>> {
+    bindings
+        .iter()
+        .find(|(x, _)| *x == label)
+        .map(|(_, ph)| *ph)
 }
 
 impl WordGenerator {
-    pub fn generate(&self, rng: &mut impl Rng) -> SmallVec<[phone::Syllable; 4]> {
-        let mut out = SmallVec::new();
-        for syl in self.syllables.iter() {
-            out.push(syl.generate(rng));
+    /// Generate a word. If this generator has filters, words are generated until one is not rejected by any filter.
+    ///
+    /// Panics if no word passes the filters within [`max_attempts`](Self::max_attempts). Use
+    /// [`try_generate`](Self::try_generate) for generators which might be over-constrained.
+    pub fn generate(&self, rng: &mut impl Rng) -> phone::Word {
+        match self.try_generate(rng) {
+            Ok(word) => word,
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    /// Generate a word, returning [`GenerateError::Unsatisfiable`] if none of the first
+    /// [`max_attempts`](Self::max_attempts) candidates pass the filters. The error counts the candidates each filter
+    /// rejected, which points at the one that is too strict.
+    pub fn try_generate(&self, rng: &mut impl Rng) -> Result<phone::Word, GenerateError> {
+        self.try_generate_counted(rng).map(|(word, _)| word)
+    }
+
+    /// Like [`try_generate`](Self::try_generate), but also return how many candidates were generated to find the word,
+    /// including the word itself. Over many words, this shows how much of what the pattern produces the filters accept.
+    pub fn try_generate_counted(
+        &self,
+        rng: &mut impl Rng,
+    ) -> Result<(phone::Word, usize), GenerateError> {
+        let attempts = self.max_attempts();
+        let mut rejections: Vec<(String, usize)> = Vec::new();
+        for attempt in 1..=attempts {
+            let word = self.generate_unfiltered(rng);
+            if !self.rejects(&word) {
+                return Ok((word, attempt));
+            }
+            for name in self.rejected_by(&word) {
+                match rejections.iter_mut().find(|(x, _)| *x == name) {
+                    Some((_, count)) => *count += 1,
+                    None => rejections.push((name, 1)),
+                }
+            }
+        }
+        rejections.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        Err(GenerateError::Unsatisfiable {
+            attempts,
+            rejections,
+        })
+    }
+
+    /// Generate words forever. This is handy for composing with iterator adapters, like
+    /// `gen.iter(rng).filter(|w| w.len() > 1).take(10)`.
+    pub fn iter<R: Rng>(&self, rng: R) -> Iter<'_, R> {
+        Iter { source: self, rng }
+    }
+
+    fn generate_unfiltered(&self, rng: &mut impl Rng) -> phone::Word {
+        let mut ctx = Context {
+            harmony: self.harmony.as_ref(),
+            positions: self.positions.as_ref(),
+            optional_probability: self.optional_probability,
+            ..Default::default()
+        };
+        let mut out = phone::Word::new();
+        match &self.syllable_counts {
+            None => {
+                let included: SmallVec<[bool; 4]> = self
+                    .optional_syllables
+                    .iter()
+                    .map(|_| rng.gen_bool(self.optional_probability()))
+                    .collect();
+                for (idx, syl) in self.syllables.iter().enumerate() {
+                    let left_out = self
+                        .optional_syllables
+                        .iter()
+                        .zip(included.iter())
+                        .any(|(group, included)| !included && group.contains(&idx));
+                    if !left_out {
+                        out.push_generated(syl.generate_in(rng, &mut ctx), syl.pattern.clone());
+                    }
+                }
+            }
+            Some(counts) => {
+                for _ in 0..counts.sample(rng) {
+                    let syl = &self.syllables[rng.gen_range(0..self.syllables.len())];
+                    out.push_generated(syl.generate_in(rng, &mut ctx), syl.pattern.clone());
+                }
+            }
+        }
+        if let Some(reduplication) = &self.reduplication {
+            reduplication.apply(&mut out);
+        }
+        if let Some(stress) = &self.stress {
+            stress.apply(out.syllables_mut());
+        }
+        if let Some(tones) = &self.tones {
+            tones.assign(rng, out.syllables_mut());
         }
         out
     }
 
+    /// Reduplicate generated words. Filters see the reduplicated word, and stress and tones are applied after it.
+    pub fn with_reduplication(mut self, reduplication: Reduplication) -> Self {
+        self.reduplication = Some(reduplication);
+        self
+    }
+
+    pub fn reduplication(&self) -> Option<Reduplication> {
+        self.reduplication
+    }
+
+    /// Mark stress on generated words of more than one syllable.
+    pub fn with_stress(mut self, stress: StressRule) -> Self {
+        self.stress = Some(stress);
+        self
+    }
+
+    pub fn stress(&self) -> Option<StressRule> {
+        self.stress
+    }
+
+    /// Assign each generated syllable a tone from the inventory.
+    pub fn with_tones(mut self, tones: Tones) -> Self {
+        self.tones = Some(tones);
+        self
+    }
+
+    pub fn tones(&self) -> Option<&Tones> {
+        self.tones.as_ref()
+    }
+
+    /// Pick the number of syllables in each word from `counts`. Instead of producing the syllables of the pattern in
+    /// order, each syllable of the word is picked at random from the pattern's syllables, so `CV CVC` with counts
+    /// `1:50,2:50` produces words like `CV`, `CVC`, `CVC CV`, and `CV CV`.
+    pub fn with_syllable_counts(mut self, counts: SyllableCounts) -> Self {
+        self.syllable_counts = Some(counts);
+        self
+    }
+
+    pub fn syllable_counts(&self) -> Option<&SyllableCounts> {
+        self.syllable_counts.as_ref()
+    }
+
+    /// Include optional phonemes like `(C)` with this `probability` instead of half the time. Phonemes with their own
+    /// probability like `(C)?0.3` keep it.
+    ///
+    /// Panics if `probability` is not between 0 and 1.
+    pub fn with_optional_probability(mut self, probability: f64) -> Self {
+        assert!((0.0..=1.0).contains(&probability));
+        self.optional_probability = Some(probability);
+        self
+    }
+
+    /// The chance that optional phonemes without their own probability are included.
+    pub fn optional_probability(&self) -> f64 {
+        self.optional_probability.unwrap_or(OPTIONAL_PROBABILITY)
+    }
+
+    /// Give up on finding a word which passes the filters after this many candidates instead of 100,000.
+    ///
+    /// Panics if `attempts` is 0.
+    pub fn with_max_attempts(mut self, attempts: usize) -> Self {
+        assert!(attempts > 0);
+        self.max_attempts = Some(attempts);
+        self
+    }
+
+    /// The number of candidates to generate before giving up on finding a word which passes the filters.
+    pub fn max_attempts(&self) -> usize {
+        self.max_attempts.unwrap_or(MAX_ATTEMPTS)
+    }
+
+    /// Add a filter to reject words this generator would otherwise produce.
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    pub fn filters(&self) -> &[Filter] {
+        &self.filters
+    }
+
+    /// Restrict the vowels of generated words to a single harmony class.
+    pub fn with_harmony(mut self, harmony: Harmony) -> Self {
+        self.harmony = Some(harmony);
+        self
+    }
+
+    pub fn harmony(&self) -> Option<&Harmony> {
+        self.harmony.as_ref()
+    }
+
+    /// Restrict which phonemes can appear at the start of the word, at the start of later syllables, and in codas.
+    pub fn with_positions(mut self, positions: Positions) -> Self {
+        self.positions = Some(positions);
+        self
+    }
+
+    pub fn positions(&self) -> Option<&Positions> {
+        self.positions.as_ref()
+    }
+
+    /// Check if this generator would reject the `word`, either because a filter rejects it, its vowels are not in
+    /// harmony, or a phoneme is not allowed in its position.
+    pub fn rejects(&self, word: &[phone::Syllable]) -> bool {
+        self.filters.iter().any(|f| f.rejects(word))
+            || self.harmony.as_ref().is_some_and(|h| !h.accepts(word))
+            || self.positions.as_ref().is_some_and(|p| !p.accepts(word))
+    }
+
+    /// Describe each of the filters, harmony, and positions which reject the `word`.
+    fn rejected_by<'a>(&'a self, word: &'a [phone::Syllable]) -> impl Iterator<Item = String> + 'a {
+        let filters = self
+            .filters
+            .iter()
+            .filter(|f| f.rejects(word))
+            .map(|f| format!("filter {f}"));
+        let harmony = self
+            .harmony
+            .as_ref()
+            .filter(|h| !h.accepts(word))
+            .map(|h| format!("harmony {h}"));
+        let positions = self
+            .positions
+            .as_ref()
+            .filter(|p| !p.accepts(word))
+            .map(|p| format!("positions {p}"));
+        filters.chain(harmony).chain(positions)
+    }
+
+    /// Parse a pattern, drawing classes like `C` from the `inventory`. To draw from every phoneme, parse with
+    /// [`FromStr`] instead.
     pub fn parse(src: &str, inventory: &phone::Inventory) -> Result<Self, ParseError> {
         let mut syllables = SmallVec::new();
 
-        for syl_src in src.split_ascii_whitespace() {
-            syllables.push(SyllableGenerator::parse(syl_src, inventory)?);
+        for syl_src in split_syllables(src) {
+            let at = syl_src.as_ptr() as usize - src.as_ptr() as usize;
+            syllables.push(
+                SyllableGenerator::parse(syl_src, inventory).map_err(|e| e.in_pattern(src, at))?,
+            );
         }
 
         if syllables.is_empty() {
             Err(ParseError::NoInput)
         } else {
-            Ok(Self { syllables })
+            Ok(Self::from_syllables(syllables))
         }
     }
-}
 
-impl fmt::Display for WordGenerator {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut first = true;
-        for syl in self.syllables.iter() {
-            if first {
-                first = false;
-            } else {
-                f.write_str(" ")?;
-            }
-            write!(f, "{syl}")?;
-        }
-        Ok(())
+    /// The same as [`parse`](Self::parse), for symmetry with [`FromStr`].
+    pub fn parse_with(src: &str, inventory: &phone::Inventory) -> Result<Self, ParseError> {
+        Self::parse(src, inventory)
     }
-}
 
-impl fmt::Debug for WordGenerator {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "WordGenerator({self})")
+    /// Assemble a generator in code instead of parsing a pattern. See [`WordGeneratorBuilder`].
+    pub fn builder() -> WordGeneratorBuilder {
+        WordGeneratorBuilder::new()
     }
-}
-
-#[derive(Clone, PartialEq)]
-pub struct SyllableGenerator {
-    phonemes: SmallVec<[PhonemeGenerator; 4]>,
-}
 
-impl SyllableGenerator {
-    pub fn generate(&self, rng: &mut impl Rng) -> phone::Syllable {
-        let mut out = SmallVec::<[phone::Phoneme; 4]>::with_capacity(self.phonemes.len());
-        for ph in self.phonemes.iter() {
-            out.push(ph.generate(rng));
+    fn from_syllables(syllables: SmallVec<[SyllableGenerator; 4]>) -> Self {
+        Self {
+            syllables,
+            syllable_counts: None,
+            filters: Vec::new(),
+            harmony: None,
+            positions: None,
+            reduplication: None,
+            stress: None,
+            tones: None,
+            optional_probability: None,
+            max_attempts: None,
+            optional_syllables: Vec::new(),
         }
+    }
 
-        phone::Syllable::new(out.as_slice())
+    /// The syllables of the pattern, or the pool each syllable is picked from if the generator has syllable counts.
+    pub fn syllables(&self) -> &[SyllableGenerator] {
+        &self.syllables
     }
 
-    pub(super) fn parse(src: &str, inventory: &phone::Inventory) -> Result<Self, ParseError> {
-        let mut phonemes = SmallVec::new();
-        let mut rem = src;
-        while !rem.is_empty() {
-            let (phoneme, leftover) = PhonemeGenerator::parse(rem, inventory)?;
-            phonemes.push(phoneme);
-            rem = leftover;
-        }
+    /// Iterate over every word this generator can produce. Words are produced in a deterministic order: shorter words
+    /// come first and the last phoneme of the pattern varies fastest, following the order of phonemes in the inventory.
+    /// Words rejected by a filter or out of harmony are skipped.
+    pub fn enumerate(&self) -> Enumerate<'_> {
+        let mut out = Enumerate {
+            source: self,
+            templates: self.templates(),
+            template: None,
+            sizes: Vec::new(),
+            indices: None,
+        };
+        out.start_template();
+        out
+    }
 
-        if phonemes.is_empty() {
-            Err(ParseError::NoInput)
-        } else {
-            Ok(Self { phonemes })
+    /// Get the word at `index` in the order of [`enumerate`](Self::enumerate), without producing the words before it.
+    /// Indices run up to [`count`](Self::count) and the word at an index never changes for the same generator, so they
+    /// work as stable IDs for words and let a big word space be split into ranges and generated in pieces.
+    ///
+    /// Unlike `enumerate`, rejected words keep their place: this returns `None` for an index whose word is rejected by
+    /// a filter or out of harmony, as well as for an index past the end. Without filters, harmony, or positions,
+    /// `gen.nth(i)` is the same as `gen.enumerate().nth(i)`.
+    pub fn nth(&self, index: u128) -> Option<phone::Word> {
+        let mut index = index;
+        for template in self.templates() {
+            let sizes = slot_sizes(&template);
+            let total = sizes
+                .iter()
+                .map(|size| *size as u128)
+                .fold(1, u128::saturating_mul);
+            if index >= total {
+                index -= total;
+                continue;
+            }
+
+            // Count like the odometer of `enumerate`, with the last phoneme varying fastest
+            let mut indices = vec![0; sizes.len()];
+            for (idx, size) in indices.iter_mut().zip(sizes.iter()).rev() {
+                *idx = (index % *size as u128) as usize;
+                index /= *size as u128;
+            }
+            let word = self.build(&template, &indices);
+            return (!self.rejects(&word)).then_some(word);
         }
+        None
     }
-}
 
-impl fmt::Display for SyllableGenerator {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        for phoneme in self.phonemes.iter() {
-            write!(f, "{phoneme}")?;
+    /// The number of words the pattern can produce, computed without producing them. Saturates at `u128::MAX` for
+    /// absurdly large patterns. This does not account for filters or harmony, so [`enumerate`](Self::enumerate) can
+    /// produce fewer words than this.
+    pub fn count(&self) -> u128 {
+        if let Some(counts) = self.independent_pool() {
+            let syllables = self
+                .syllables
+                .iter()
+                .map(|syl| {
+                    syl.phonemes
+                        .iter()
+                        .map(|ph| ph.outcomes() as u128)
+                        .fold(1, u128::saturating_mul)
+                })
+                .fold(0, u128::saturating_add);
+            return counts
+                .counts()
+                .map(|n| syllables.saturating_pow(n.try_into().unwrap_or(u32::MAX)))
+                .fold(0, u128::saturating_add);
         }
-        Ok(())
+        self.templates()
+            .map(|template| {
+                independent_slots(&template)
+                    .map(|ph| ph.outcomes() as u128)
+                    .fold(1, u128::saturating_mul)
+            })
+            .fold(0, u128::saturating_add)
     }
-}
 
-#[derive(Clone)]
-pub struct PhonemeGenerator {
-    display: String,
-    choices: SmallVec<[phone::Phoneme; 8]>,
-    weights: SmallVec<[u8; 8]>,
-}
+    /// The number of distinct words this generator can produce, which is useful for knowing how big the word space is
+    /// before generating lots of words. Like [`count`](Self::count), this does not account for filters or harmony.
+    pub fn possible_words(&self) -> u128 {
+        self.count()
+    }
 
-impl PhonemeGenerator {
-    pub(super) fn parse<'a>(
-        src: &'a str,
-        inventory: &phone::Inventory,
-    ) -> Result<(Self, &'a str), ParseError> {
-        let Some(first) = src.chars().nth(0) else {
-            return Err(ParseError::NoInput)
+    /// The [entropy](https://en.wikipedia.org/wiki/Entropy_(information_theory)) of generated words, in bits. A
+    /// generator with `n` bits of entropy is as unpredictable as picking uniformly from `2ⁿ` words, so this is lower
+    /// than `log₂` of [`possible_words`](Self::possible_words) when weights make some words more likely than others.
+    ///
+    /// This assumes different choices never produce the same word, which is not true for overlapping syllables like
+    /// `CV` and `NV` in a pool with syllable counts. Filters and harmony are not accounted for.
+    pub fn entropy_bits(&self) -> f64 {
+        if let Some(counts) = self.independent_pool() {
+            let pool = self.syllables.len() as f64;
+            // The entropy of a syllable picked from the pool, on average, plus that of picking it
+            let syllable = self
+                .syllables
+                .iter()
+                .map(|syl| {
+                    syl.phonemes
+                        .iter()
+                        .map(|ph| ph.entropy_bits(self.optional_probability()))
+                        .sum::<f64>()
+                })
+                .sum::<f64>()
+                / pool
+                + pool.log2();
+            return counts
+                .counts()
+                .map(|n| {
+                    let p = counts.probability(n);
+                    p * (n as f64 * syllable - p.log2())
+                })
+                .sum();
+        }
+        self.template_probabilities()
+            .map(|(template, p)| {
+                let slots: f64 = independent_slots(&template)
+                    .map(|ph| ph.entropy_bits(self.optional_probability()))
+                    .sum();
+                p * (slots - p.log2())
+            })
+            .sum()
+    }
+
+    /// The chance that generating `n` words produces at least one duplicate. Under the same assumptions as
+    /// [`entropy_bits`](Self::entropy_bits).
+    pub fn collision_probability(&self, n: usize) -> f64 {
+        // The chance that the same choice is made twice for a slot
+        let same = |ph: &PhonemeGenerator| {
+            ph.probabilities(self.optional_probability())
+                .map(|x| x * x)
+                .sum::<f64>()
+        };
+        // The chance that two generated words are the same
+        let pair: f64 = match self.independent_pool() {
+            Some(counts) => {
+                let pool = self.syllables.len() as f64;
+                // The chance that two syllables picked from the pool are the same
+                let syllable = self
+                    .syllables
+                    .iter()
+                    .map(|syl| syl.phonemes.iter().map(same).product::<f64>())
+                    .sum::<f64>()
+                    / (pool * pool);
+                counts
+                    .counts()
+                    .map(|n| counts.probability(n).powi(2) * syllable.powi(n as i32))
+                    .sum()
+            }
+            None => self
+                .template_probabilities()
+                .map(|(template, p)| {
+                    let slots: f64 = independent_slots(&template).map(same).product();
+                    p * p * slots
+                })
+                .sum(),
         };
 
-        match first {
-            'C' => Ok(Self::from_character_class(src, inventory.consonants())),
-            'V' => Ok(Self::from_character_class(src, inventory.vowels())),
-            '[' => todo!(),
-            '(' => todo!(),
-            _ => {
-                if let Ok(places) = phone::Place::try_from(first) {
-                    Ok(Self::from_character_class_filtered(
-                        src,
-                        inventory.consonants(),
-                        |x| places.contains(&x.place()),
-                    ))
-                } else if let Ok(manners) = phone::Manner::try_from(first) {
-                    Ok(Self::from_character_class_filtered(
-                        src,
-                        inventory.consonants(),
-                        |x| manners.contains(&x.manner()),
-                    ))
-                } else {
-                    todo!()
-                }
+        // The birthday approximation: with n(n-1)/2 pairs, the chance none of them match
+        let pairs = n as f64 * n.saturating_sub(1) as f64 / 2.0;
+        1.0 - (-pairs * pair).exp()
+    }
+
+    /// Every template from [`templates`](Self::templates) with the chance of it being used for a word.
+    fn template_probabilities(
+        &self,
+    ) -> impl Iterator<Item = (SmallVec<[&SyllableGenerator; 4]>, f64)> + '_ {
+        match &self.syllable_counts {
+            None => itertools::Either::Left(self.arrangements().into_iter()),
+            Some(counts) => {
+                let pool = self.syllables.len() as f64;
+                itertools::Either::Right(self.templates().map(move |t| {
+                    let p = counts.probability(t.len()) / pool.powi(t.len() as i32);
+                    (t, p)
+                }))
             }
         }
     }
 
-    fn from_character_class<'a, T: Into<phone::Phoneme> + Copy>(
-        src: &'a str,
-        options: &[T],
-    ) -> (Self, &'a str) {
-        let out = Self {
-            display: src[..1].into(),
-            choices: options.iter().map(|x| (*x).into()).collect(),
-            weights: SmallVec::new(),
-        };
-        (out, &src[1..])
+    /// The syllable counts, if this generator has them and its pool has no backreference labels. The syllables of a
+    /// word are then picked independently of each other, so statistics over the `pool^n` arrangements of `n` syllables
+    /// can be worked out a syllable at a time instead of going through them.
+    fn independent_pool(&self) -> Option<&SyllableCounts> {
+        self.syllable_counts.as_ref().filter(|_| {
+            self.syllables
+                .iter()
+                .flat_map(|syl| syl.phonemes.iter())
+                .all(|ph| ph.label.is_none())
+        })
     }
 
-    fn from_character_class_filtered<'a, T: Into<phone::Phoneme> + Copy>(
-        src: &'a str,
-        options: &[T],
-        filter: impl Fn(&T) -> bool,
-    ) -> (Self, &'a str) {
-        let out = Self {
-            display: src[..1].into(),
-            choices: options
+    /// Every way of including or leaving out the optional syllables, shortest first, with the chance of each. A group
+    /// inside a group which is left out has nothing left to decide, so each arrangement only appears once.
+    fn arrangements(&self) -> Vec<(SmallVec<[&SyllableGenerator; 4]>, f64)> {
+        let p = self.optional_probability();
+        let groups = &self.optional_syllables;
+        let mut out = Vec::new();
+        for mask in 0..1usize << groups.len() {
+            let included = |g: usize| mask & (1 << g) != 0;
+            // Enclosing groups always come after the groups inside them
+            let hidden = |g: usize| {
+                (g + 1..groups.len()).any(|outer| {
+                    !included(outer)
+                        && groups[outer].start <= groups[g].start
+                        && groups[g].end <= groups[outer].end
+                })
+            };
+            if (0..groups.len()).any(|g| included(g) && hidden(g)) {
+                continue;
+            }
+            let chance = (0..groups.len())
+                .filter(|g| !hidden(*g))
+                .map(|g| if included(g) { p } else { 1.0 - p })
+                .product();
+            let template: SmallVec<[&SyllableGenerator; 4]> = self
+                .syllables
                 .iter()
-                .filter_map(|x| if filter(x) { Some((*x).into()) } else { None })
-                .collect(),
-            weights: SmallVec::new(),
+                .enumerate()
+                .filter(|(idx, _)| {
+                    (0..groups.len()).all(|g| included(g) || !groups[g].contains(idx))
+                })
+                .map(|(_, syl)| syl)
+                .collect();
+            out.push((template, chance));
+        }
+        out.sort_by_key(|(template, _)| template.len());
+        out
+    }
+
+    /// Every sequence of syllable generators this generator can use to make a word. Without syllable counts, this is
+    /// the pattern itself, with and without each group of optional syllables. With them, this is every arrangement of
+    /// the syllable pool for every count, made as they are needed.
+    fn templates(&self) -> Templates<'_> {
+        match &self.syllable_counts {
+            None => Templates::Arrangements(
+                self.arrangements()
+                    .into_iter()
+                    .map(|(t, _)| t)
+                    .collect::<Vec<_>>()
+                    .into_iter(),
+            ),
+            Some(counts) => Templates::Pool {
+                pool: &self.syllables,
+                counts: counts.counts().collect::<Vec<_>>().into_iter(),
+                indices: None,
+            },
+        }
+    }
+
+    /// Generate `count` words at once. With the `parallel` feature, the words are generated on multiple threads, which
+    /// is much faster for large batches with filters that reject most candidates. Like
+    /// [`try_generate`](Self::try_generate), this fails if any word can not be found within
+    /// [`max_attempts`](Self::max_attempts) candidates.
+    ///
+    /// The words only depend on the state of `rng`, not on the number of threads, so seeded batches are reproducible.
+    pub fn generate_batch(
+        &self,
+        rng: &mut impl Rng,
+        count: usize,
+    ) -> Result<Vec<phone::Word>, GenerateError> {
+        self.generate_batch_with_progress(rng, count, |_, _| {})
+    }
+
+    /// Like [`generate_batch`](Self::generate_batch), but call `progress` with the number of words and the number of
+    /// candidates generated to find them each time a chunk of words is done, for showing how far along a big batch
+    /// is. With the `parallel` feature, it is called from whichever thread finished the chunk.
+    pub fn generate_batch_with_progress(
+        &self,
+        rng: &mut impl Rng,
+        count: usize,
+        progress: impl Fn(usize, usize) + Sync,
+    ) -> Result<Vec<phone::Word>, GenerateError> {
+        let seeds: Vec<u64> = (0..count.div_ceil(BATCH_CHUNK))
+            .map(|_| rng.gen())
+            .collect();
+        let chunk = |(idx, seed): (usize, &u64)| {
+            let mut rng = rand::rngs::StdRng::seed_from_u64(*seed);
+            let len = BATCH_CHUNK.min(count - idx * BATCH_CHUNK);
+            let mut candidates = 0;
+            let words = (0..len)
+                .map(|_| {
+                    let (word, tried) = self.try_generate_counted(&mut rng)?;
+                    candidates += tried;
+                    Ok(word)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            progress(len, candidates);
+            Ok::<_, GenerateError>(words)
+        };
+
+        #[cfg(feature = "parallel")]
+        let chunks: Vec<Vec<_>> = {
+            use rayon::prelude::*;
+            seeds
+                .par_iter()
+                .enumerate()
+                .map(chunk)
+                .collect::<Result<_, _>>()?
         };
-        (out, &src[1..])
+        #[cfg(not(feature = "parallel"))]
+        let chunks: Vec<Vec<_>> = seeds
+            .iter()
+            .enumerate()
+            .map(chunk)
+            .collect::<Result<_, _>>()?;
+        Ok(chunks.into_iter().flatten().collect())
     }
 
-    pub fn generate(&self, rng: &mut impl Rng) -> phone::Phoneme {
-        self.choices[rng.next_u64() as usize % self.choices.len()]
+    /// Generate `count` distinct words from this pattern. See [`generate_unique`].
+    pub fn generate_unique(
+        &self,
+        rng: &mut impl Rng,
+        count: usize,
+    ) -> Result<Vec<phone::Word>, GenerateError> {
+        generate_unique(std::slice::from_ref(self), rng, count)
     }
 }
 
-impl PartialEq for PhonemeGenerator {
-    fn eq(&self, other: &Self) -> bool {
-        self.choices == other.choices && self.weights == other.weights
-    }
+/// An endless iterator of generated words. Created by [`WordGenerator::iter`].
+#[derive(Clone, Debug)]
+pub struct Iter<'a, R> {
+    source: &'a WordGenerator,
+    rng: R,
 }
 
-impl fmt::Display for PhonemeGenerator {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.write_str(&self.display)
+impl<'a, R: Rng> Iterator for Iter<'a, R> {
+    type Item = phone::Word;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.source.generate(&mut self.rng))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
     }
 }
 
-impl fmt::Debug for PhonemeGenerator {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Phoneme({self})")
+impl<'a, R: Rng> std::iter::FusedIterator for Iter<'a, R> {}
+
+/// Sampling a generator is the same as [`WordGenerator::generate`], so `rng.sample_iter(&gen)` is another endless
+/// stream of words.
+impl Distribution<phone::Word> for WordGenerator {
+    fn sample<R: Rng + ?Sized>(&self, mut rng: &mut R) -> phone::Word {
+        self.generate(&mut rng)
     }
 }
 
-#[cfg(test)]
-mod gen_tests {
-    use super::*;
-    use crate::phone;
+/// Every phoneme slot of a template, paired with whether it repeats an earlier slot through a backreference label.
+fn slots<'a, 'b>(
+    template: &'b [&'a SyllableGenerator],
+) -> impl Iterator<Item = (&'a PhonemeGenerator, bool)> + 'b {
+    let mut seen = SmallVec::<[u8; 4]>::new();
+    template
+        .iter()
+        .flat_map(|syl| syl.phonemes.iter())
+        .map(move |ph| match ph.label {
+            Some(label) if seen.contains(&label) => (ph, true),
+            Some(label) => {
+                seen.push(label);
+                (ph, false)
+            }
+            None => (ph, false),
+        })
+}
 
-    #[test]
-    fn parsing() {
-        let inventory = phone::Inventory::with_everything();
-        let inputs = &["C", "V", "CV", "VVC"];
-        for input in inputs.iter() {
-            WordGenerator::parse(input, &inventory).unwrap();
+/// The number of choices for each phoneme slot of a template.
+fn slot_sizes(template: &[&SyllableGenerator]) -> Vec<usize> {
+    // A backreference only has the one outcome of repeating its label
+    slots(template)
+        .map(|(ph, repeat)| if repeat { 1 } else { ph.outcomes() })
+        .collect()
+}
+
+/// The phoneme slots of a template which are chosen independently, skipping backreferences to earlier slots.
+fn independent_slots<'a, 'b>(
+    template: &'b [&'a SyllableGenerator],
+) -> impl Iterator<Item = &'a PhonemeGenerator> + 'b {
+    slots(template)
+        .filter(|(_, repeat)| !repeat)
+        .map(|(ph, _)| ph)
+}
+
+/// The templates of a [`WordGenerator`], one at a time, so the arrangements of a big pool of syllables are never all
+/// held at once.
+#[derive(Clone, Debug)]
+enum Templates<'a> {
+    /// The pattern with and without each group of optional syllables.
+    Arrangements(std::vec::IntoIter<SmallVec<[&'a SyllableGenerator; 4]>>),
+    /// Every arrangement of the pool for each count, counted through like an odometer.
+    Pool {
+        pool: &'a [SyllableGenerator],
+        /// The counts after the current one.
+        counts: std::vec::IntoIter<usize>,
+        /// The index into the pool of each syllable of the next template, or `None` once the current count is done.
+        indices: Option<Vec<usize>>,
+    },
+}
+
+impl<'a> Iterator for Templates<'a> {
+    type Item = SmallVec<[&'a SyllableGenerator; 4]>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (pool, counts, indices) = match self {
+            Self::Arrangements(templates) => return templates.next(),
+            Self::Pool {
+                pool,
+                counts,
+                indices,
+            } => (*pool, counts, indices),
+        };
+        if pool.is_empty() {
+            return None;
+        }
+        let current = match indices {
+            Some(current) => current,
+            None => indices.insert(vec![0; counts.next()?]),
+        };
+        let out = current.iter().map(|idx| &pool[*idx]).collect();
+
+        // Advance like an odometer, with the last syllable rolling over first
+        let mut exhausted = true;
+        for idx in current.iter_mut().rev() {
+            *idx += 1;
+            if *idx < pool.len() {
+                exhausted = false;
+                break;
+            }
+            *idx = 0;
+        }
+        if exhausted {
+            *indices = None;
+        }
+        Some(out)
+    }
+}
+
+/// An iterator over all the words a [`WordGenerator`] can produce. Created by [`WordGenerator::enumerate`].
+#[derive(Clone, Debug)]
+pub struct Enumerate<'a> {
+    source: &'a WordGenerator,
+    /// The sequences of syllables to enumerate after the current one.
+    templates: Templates<'a>,
+    /// The sequence of syllables currently being enumerated, or `None` once they all have been.
+    template: Option<SmallVec<[&'a SyllableGenerator; 4]>>,
+    /// The number of choices for each phoneme in the current template.
+    sizes: Vec<usize>,
+    /// The index into each phoneme's choices for the next word to produce or `None` if the template is exhausted.
+    indices: Option<Vec<usize>>,
+}
+
+impl<'a> Iterator for Enumerate<'a> {
+    type Item = phone::Word;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let word = self.next_unfiltered()?;
+            if !self.source.rejects(&word) {
+                return Some(word);
+            }
+        }
+    }
+}
+
+impl<'a> Enumerate<'a> {
+    /// Move on to the next template and reset the odometer for it.
+    fn start_template(&mut self) {
+        self.template = self.templates.next();
+        let Some(template) = &self.template else {
+            self.indices = None;
+            return;
+        };
+        self.sizes = slot_sizes(template);
+        self.indices = Some(vec![0; self.sizes.len()]);
+    }
+
+    fn next_unfiltered(&mut self) -> Option<phone::Word> {
+        while self.indices.is_none() {
+            self.template.as_ref()?;
+            self.start_template();
+        }
+        let indices = self.indices.as_mut()?;
+        let out = self.source.build(self.template.as_ref()?, indices);
+
+        // Advance like an odometer, with the last phoneme rolling over first
+        let mut exhausted = true;
+        for (idx, size) in indices.iter_mut().zip(self.sizes.iter()).rev() {
+            *idx += 1;
+            if *idx < *size {
+                exhausted = false;
+                break;
+            }
+            *idx = 0;
+        }
+        if exhausted {
+            self.indices = None;
+        }
+
+        Some(out)
+    }
+}
+
+impl WordGenerator {
+    /// Build the word a template makes from the index of the choice for each of its phoneme slots. Tones are random
+    /// rather than chosen, so they are not assigned.
+    fn build(&self, template: &[&SyllableGenerator], indices: &[usize]) -> phone::Word {
+        let mut out = phone::Word::new();
+        let mut pos = 0;
+        let mut bindings = Bindings::new();
+        for syl in template.iter() {
+            let mut parts = SmallVec::<[phone::Phoneme; 8]>::new();
+            for (ph, idx) in syl.phonemes.iter().zip(indices[pos..].iter()) {
+                let chosen = match ph.label.and_then(|label| bound(&bindings, label)) {
+                    Some(bound) => bound,
+                    None => {
+                        // An index past the last choice is an optional phoneme which was left out
+                        let chosen = ph.choices.get(*idx).copied();
+                        if let Some(label) = ph.label {
+                            bindings.push((label, chosen));
+                        }
+                        chosen
+                    }
+                };
+                ph.emit(&mut parts, chosen);
+            }
+            pos += syl.phonemes.len();
+            out.push_generated(phone::Syllable::new(&parts), syl.pattern.clone());
+        }
+        if let Some(reduplication) = &self.reduplication {
+            reduplication.apply(&mut out);
+        }
+        if let Some(stress) = &self.stress {
+            stress.apply(out.syllables_mut());
+        }
+        out
+    }
+
+    /// Get the pattern string for this generator. Parsing it with the same inventory produces the same syllables, but
+    /// settings like filters, harmony, and syllable counts are not part of the pattern.
+    ///
+    /// Optional syllables from a [`Ruleset`] are written in parentheses like `(CV)? CVC`. Patterns can not contain
+    /// these, so a generator compiled from a ruleset can not be parsed back from its pattern string.
+    pub fn to_pattern_string(&self) -> String {
+        let mut out = String::new();
+        for (idx, syl) in self.syllables.iter().enumerate() {
+            if idx > 0 {
+                out.push(' ');
+            }
+            // Enclosing groups come last, so open them in reverse and close them in order
+            for group in self.optional_syllables.iter().rev() {
+                if group.start == idx {
+                    out.push('(');
+                }
+            }
+            out.push_str(&syl.to_string());
+            for group in self.optional_syllables.iter() {
+                if group.end == idx + 1 {
+                    out.push_str(")?");
+                }
+            }
+        }
+        out
+    }
+}
+
+impl fmt::Display for WordGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_pattern_string())?;
+        if let Some(counts) = &self.syllable_counts {
+            write!(f, " ×{counts}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a pattern with classes drawn from [`phone::Inventory::with_everything`].
+impl FromStr for WordGenerator {
+    type Err = ParseError;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        Self::parse(src, &phone::Inventory::with_everything())
+    }
+}
+
+impl fmt::Debug for WordGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "WordGenerator({self})")
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "PhonemeGenerators", into = "PhonemeGenerators")]
+pub struct SyllableGenerator {
+    phonemes: PhonemeGenerators,
+    /// The pattern string, shared with every syllable generated from it.
+    pattern: Arc<str>,
+}
+
+type PhonemeGenerators = SmallVec<[PhonemeGenerator; 4]>;
+
+impl From<PhonemeGenerators> for SyllableGenerator {
+    fn from(phonemes: PhonemeGenerators) -> Self {
+        let pattern = phonemes.iter().join("").into();
+        Self { phonemes, pattern }
+    }
+}
+
+impl From<SyllableGenerator> for PhonemeGenerators {
+    fn from(syl: SyllableGenerator) -> Self {
+        syl.phonemes
+    }
+}
+
+impl SyllableGenerator {
+    /// The pattern this generates syllables from, like `CVC`.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// The slots of the pattern, one for each phoneme of a generated syllable.
+    pub fn phonemes(&self) -> &[PhonemeGenerator] {
+        &self.phonemes
+    }
+
+    pub fn generate(&self, rng: &mut impl Rng) -> phone::Syllable {
+        self.generate_in(rng, &mut Context::default())
+    }
+
+    /// The indices of the first and last slots of the nucleus, which is the span of slots which can only produce
+    /// vowels. Anything before it is the onset and anything after it is the coda.
+    fn nucleus(&self) -> Option<(usize, usize)> {
+        let is_nucleus = |ph: &PhonemeGenerator| {
+            ph.choices
+                .iter()
+                .all(|x| matches!(x, phone::Phoneme::Vowel(_)))
+        };
+        self.phonemes
+            .iter()
+            .position(is_nucleus)
+            .zip(self.phonemes.iter().rposition(is_nucleus))
+    }
+
+    fn generate_in(&self, rng: &mut impl Rng, ctx: &mut Context) -> phone::Syllable {
+        let nucleus = self.nucleus();
+        let onset = if ctx.syllable == 0 {
+            Position::Initial
+        } else {
+            Position::Medial
+        };
+
+        let mut out = SmallVec::<[phone::Phoneme; 4]>::with_capacity(self.phonemes.len());
+        for (idx, ph) in self.phonemes.iter().enumerate() {
+            if let Some(bound) = ph.label.and_then(|label| bound(&ctx.bindings, label)) {
+                ph.emit(&mut out, bound);
+                continue;
+            }
+
+            let default = ctx.optional_probability.unwrap_or(OPTIONAL_PROBABILITY);
+            let generated = if ph.optional && !rng.gen_bool(ph.inclusion(default)) {
+                None
+            } else {
+                ctx.position = match nucleus {
+                    Some((first, _)) if idx < first => Some(onset),
+                    Some((_, last)) if idx > last => Some(Position::Coda),
+                    _ => None,
+                };
+                Some(ph.generate_in(rng, ctx))
+            };
+            if let Some(label) = ph.label {
+                ctx.bindings.push((label, generated));
+            }
+            ph.emit(&mut out, generated);
+        }
+        ctx.position = None;
+        ctx.syllable += 1;
+
+        phone::Syllable::new(out.as_slice())
+    }
+
+    pub(super) fn parse(src: &str, inventory: &phone::Inventory) -> Result<Self, ParseError> {
+        let mut phonemes = SmallVec::new();
+        let mut rem = src;
+        while !rem.is_empty() {
+            let (phoneme, leftover) = PhonemeGenerator::parse(rem, inventory)
+                .map_err(|e| e.in_pattern(src, src.len() - rem.len()))?;
+            phonemes.push(phoneme);
+            rem = leftover;
+        }
+
+        if phonemes.is_empty() {
+            Err(ParseError::NoInput)
+        } else {
+            Ok(Self::from(phonemes))
+        }
+    }
+}
+
+impl Distribution<phone::Syllable> for SyllableGenerator {
+    fn sample<R: Rng + ?Sized>(&self, mut rng: &mut R) -> phone::Syllable {
+        self.generate(&mut rng)
+    }
+}
+
+impl fmt::Display for SyllableGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.pattern)
+    }
+}
+
+impl fmt::Debug for SyllableGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SyllableGenerator({self})")
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(try_from = "PhonemeGeneratorRepr", into = "PhonemeGeneratorRepr")]
+pub struct PhonemeGenerator {
+    display: String,
+    choices: SmallVec<[phone::Phoneme; 8]>,
+    weights: SmallVec<[u8; 8]>,
+    sampler: Sampler,
+    /// If the phoneme can be left out of the syllable, written like `(C)`.
+    optional: bool,
+    /// The chance an optional phoneme is included, written like `(C)?0.3`. If `None`, the generator's default is used.
+    probability: Option<f64>,
+    /// The backreference label, written as a subscript like `C₁`. The first phoneme with a label in a word is generated
+    /// normally; every later phoneme with the same label repeats it.
+    label: Option<u8>,
+    /// If the phoneme is doubled, written with a length mark like `Cː`.
+    geminate: bool,
+}
+
+/// The chance that an optional phoneme like `(C)` is included in a syllable, unless the pattern or generator says
+/// otherwise.
+const OPTIONAL_PROBABILITY: f64 = 0.5;
+
+/// The serialized form of a [`PhonemeGenerator`]. The sampler is rebuilt when deserializing.
+#[derive(Serialize, Deserialize)]
+struct PhonemeGeneratorRepr {
+    pattern: String,
+    /// The IPA of each choice, one character per phoneme.
+    choices: String,
+    #[serde(default, skip_serializing_if = "SmallVec::is_empty")]
+    weights: SmallVec<[u8; 8]>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    optional: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    probability: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    label: Option<u8>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    geminate: bool,
+}
+
+impl From<PhonemeGenerator> for PhonemeGeneratorRepr {
+    fn from(value: PhonemeGenerator) -> Self {
+        Self {
+            pattern: value.display,
+            choices: value.choices.iter().map(phone::Phoneme::code).collect(),
+            weights: value.weights,
+            optional: value.optional,
+            probability: value.probability,
+            label: value.label,
+            geminate: value.geminate,
+        }
+    }
+}
+
+impl TryFrom<PhonemeGeneratorRepr> for PhonemeGenerator {
+    type Error = ParseError;
+
+    fn try_from(value: PhonemeGeneratorRepr) -> Result<Self, Self::Error> {
+        let mut choices = SmallVec::new();
+        for c in value.choices.chars() {
+            choices.push(phone::Phoneme::try_from(c).map_err(|_| ParseError::UnknownCharacter(c))?);
+        }
+        let mut out = Self::new(value.pattern, choices, value.weights)?;
+        out.optional = value.optional;
+        out.probability = value.probability;
+        out.label = value.label;
+        out.geminate = value.geminate;
+        Ok(out)
+    }
+}
+
+/// The distribution used to pick an index into [`PhonemeGenerator::choices`]. This is computed once when the generator
+/// is built so that generation does not need to rebuild it for every phoneme.
+#[derive(Clone, Debug)]
+enum Sampler {
+    Uniform(Uniform<usize>),
+    Weighted(WeightedIndex<u32>),
+}
+
+impl PhonemeGenerator {
+    /// Create a generator which picks from `choices`. If `weights` is empty, all choices are equally likely; otherwise,
+    /// `weights` must be the same length as `choices` and the likelihood of picking a choice is proportional to its
+    /// weight.
+    pub(super) fn new(
+        display: impl Into<String>,
+        choices: SmallVec<[phone::Phoneme; 8]>,
+        weights: SmallVec<[u8; 8]>,
+    ) -> Result<Self, ParseError> {
+        let display = display.into();
+        if choices.is_empty() {
+            return Err(ParseError::EmptyClass(display));
+        }
+
+        let sampler = if weights.is_empty() {
+            Sampler::Uniform(Uniform::new(0, choices.len()))
+        } else if weights.len() != choices.len() {
+            return Err(ParseError::InvalidWeights(display));
+        } else {
+            match WeightedIndex::new(weights.iter().map(|w| u32::from(*w))) {
+                Ok(x) => Sampler::Weighted(x),
+                Err(_) => return Err(ParseError::InvalidWeights(display)),
+            }
+        };
+
+        Ok(Self {
+            display,
+            choices,
+            weights,
+            sampler,
+            optional: false,
+            probability: None,
+            label: None,
+            geminate: false,
+        })
+    }
+
+    /// Make this phoneme optional, so that it is left out of some syllables.
+    pub(super) fn into_optional(mut self) -> Self {
+        self.optional = true;
+        self
+    }
+
+    /// The same generator with new `weights` for its choices, which must not all be 0.
+    fn reweight(&self, weights: SmallVec<[u8; 8]>) -> Self {
+        let mut out = Self::new(self.display.clone(), self.choices.clone(), weights)
+            .expect("weights should match the choices");
+        out.optional = self.optional;
+        out.probability = self.probability;
+        out.label = self.label;
+        out.geminate = self.geminate;
+        out
+    }
+
+    /// The number of different outcomes of this generator: each of the choices, plus leaving it out if it is optional.
+    fn outcomes(&self) -> usize {
+        self.choices.len() + usize::from(self.optional)
+    }
+
+    /// Add the generated `phoneme` to a syllable, twice if it is geminated.
+    fn emit(&self, out: &mut impl Extend<phone::Phoneme>, phoneme: Option<phone::Phoneme>) {
+        out.extend(phoneme);
+        if self.geminate {
+            out.extend(phoneme);
+        }
+    }
+
+    /// Parse one element of a pattern, followed by its backreference label and length mark if it has them.
+    pub(super) fn parse<'a>(
+        src: &'a str,
+        inventory: &phone::Inventory,
+    ) -> Result<(Self, &'a str), ParseError> {
+        let (mut out, mut rem) = Self::parse_element(src, inventory)?;
+        let mut chars = rem.chars();
+        if let Some(label) = chars.next().and_then(subscript_value) {
+            if out.label.is_some() {
+                return Err(ParseError::UnknownCharacter(subscript(label)));
+            }
+            out.label = Some(label);
+            rem = chars.as_str();
+        }
+        // Accept a plain colon too, since the length mark is hard to type
+        if let Some(leftover) = rem.strip_prefix([phone::LENGTH_MARK, ':']) {
+            if out.geminate {
+                return Err(ParseError::UnknownCharacter(phone::LENGTH_MARK));
+            }
+            out.geminate = true;
+            rem = leftover;
+        }
+        Ok((out, rem))
+    }
+
+    /// Parse one element of a pattern, drawing from a stratum of the `inventory` if it is followed by one like
+    /// `C@loan`.
+    fn parse_element<'a>(
+        src: &'a str,
+        inventory: &phone::Inventory,
+    ) -> Result<(Self, &'a str), ParseError> {
+        let first = match Self::parse_unstratified(src, inventory) {
+            Ok((out, rem)) if !rem.starts_with('@') => return Ok((out, rem)),
+            first => first,
+        };
+
+        // Strata can be referenced anywhere in the element, like `{V+V@ono}@ono`, so they always come from the inventory
+        // the pattern is parsed with
+        let with_strata = |base: &phone::Inventory| {
+            inventory
+                .strata()
+                .fold(base.clone(), |out, (name, stratum)| {
+                    out.with_stratum(name, stratum.clone())
+                })
+        };
+        // The main inventory might not have the phonemes of the stratum, so find where the element ends with an
+        // inventory which has everything
+        let everything = with_strata(&phone::Inventory::with_everything());
+        let Ok((_, rem)) = Self::parse_unstratified(src, &everything) else {
+            return first;
+        };
+        let Some(after) = rem.strip_prefix('@') else {
+            return first;
+        };
+        // Names are lowercase, so a class letter can follow them directly like `C@loanV`
+        let len = after
+            .find(|c: char| !c.is_ascii_lowercase() && !c.is_ascii_digit() && c != '-' && c != '_')
+            .unwrap_or(after.len());
+        let name = &after[..len];
+        let stratum = inventory
+            .stratum(name)
+            .ok_or_else(|| ParseError::UnknownStratum(name.into()))?;
+        let (mut out, _) =
+            Self::parse_unstratified(&src[..src.len() - rem.len()], &with_strata(stratum))?;
+        out.display.push('@');
+        out.display.push_str(name);
+        Ok((out, &after[len..]))
+    }
+
+    fn parse_unstratified<'a>(
+        src: &'a str,
+        inventory: &phone::Inventory,
+    ) -> Result<(Self, &'a str), ParseError> {
+        let Some(first) = src.chars().nth(0) else {
+            return Err(ParseError::NoInput);
+        };
+
+        match first {
+            'C' => Self::from_character_class(src, inventory, inventory.consonants()),
+            'V' => match src[1..].chars().next().and_then(vowel_subclass) {
+                Some(subclass) => {
+                    let choices = inventory
+                        .vowels()
+                        .iter()
+                        .filter(|x| subclass(x))
+                        .map(|x| (*x).into())
+                        .collect();
+                    Ok((Self::from_class(&src[..2], choices, inventory)?, &src[2..]))
+                }
+                None => Self::from_character_class(src, inventory, inventory.vowels()),
+            },
+            'K' => Self::from_character_class(src, inventory, inventory.non_pulmonic_consonants()),
+            '[' => {
+                let Some((inner, rem)) = src[1..].split_once(']') else {
+                    return Err(ParseError::Unclosed('['));
+                };
+                let mut choices = SmallVec::new();
+                for c in inner.chars().filter(|c| !c.is_whitespace()) {
+                    choices.push(
+                        phone::Phoneme::try_from(c).map_err(|_| ParseError::UnknownCharacter(c))?,
+                    );
+                }
+                let out = Self::new(&src[..inner.len() + 2], choices, SmallVec::new())?;
+                Ok((out, rem))
+            }
+            '{' => {
+                let operand = |src: &'a str| {
+                    if src.is_empty() {
+                        Err(ParseError::Unclosed('{'))
+                    } else {
+                        Self::parse_element(src, inventory)
+                    }
+                };
+                let (base, rem) = operand(src[1..].trim_start())?;
+                let mut choices = base.choices;
+                let mut rem = rem.trim_start();
+                loop {
+                    let mut chars = rem.chars();
+                    match chars.next() {
+                        None => return Err(ParseError::Unclosed('{')),
+                        Some('}') => {
+                            rem = chars.as_str();
+                            break;
+                        }
+                        Some(op @ ('+' | '-')) => {
+                            let (other, leftover) = operand(chars.as_str().trim_start())?;
+                            if op == '+' {
+                                for ph in other.choices {
+                                    if !choices.contains(&ph) {
+                                        choices.push(ph);
+                                    }
+                                }
+                            } else {
+                                choices.retain(|ph| !other.choices.contains(ph));
+                            }
+                            rem = leftover.trim_start();
+                        }
+                        Some(c) => return Err(ParseError::UnknownCharacter(c)),
+                    }
+                }
+                let out = Self::from_class(&src[..src.len() - rem.len()], choices, inventory)?;
+                Ok((out, rem))
+            }
+            // An escaped character is always a literal phoneme, even if it would otherwise be read as a class
+            '\\' => {
+                let mut chars = src[1..].chars();
+                let Some(c) = chars.next() else {
+                    return Err(ParseError::UnknownCharacter('\\'));
+                };
+                let phoneme =
+                    phone::Phoneme::try_from(c).map_err(|_| ParseError::UnknownCharacter(c))?;
+                let out = Self::new(
+                    &src[..1 + c.len_utf8()],
+                    smallvec::smallvec![phoneme],
+                    SmallVec::new(),
+                )?;
+                Ok((out, chars.as_str()))
+            }
+            '(' => {
+                let (inner, rem) = Self::parse(&src[1..], inventory)?;
+                let Some(rem) = rem.strip_prefix(')') else {
+                    return Err(ParseError::Unclosed('('));
+                };
+                let mut out = inner.into_optional();
+                let Some(rem) = rem.strip_prefix('?') else {
+                    return Ok((out, rem));
+                };
+                let len = rem
+                    .find(|c: char| !c.is_ascii_digit() && c != '.')
+                    .unwrap_or(rem.len());
+                let probability = rem[..len]
+                    .parse()
+                    .ok()
+                    .filter(|x| (0.0..=1.0).contains(x))
+                    .ok_or_else(|| ParseError::InvalidProbability(rem[..len].into()))?;
+                out.probability = Some(probability);
+                Ok((out, &rem[len..]))
+            }
+            _ => {
+                if let Ok(places) = phone::Place::try_from(first) {
+                    Self::from_character_class_filtered(
+                        src,
+                        inventory,
+                        inventory.consonants(),
+                        |x| places.contains(&x.place()),
+                    )
+                } else if let Ok(manners) = phone::Manner::try_from(first) {
+                    Self::from_character_class_filtered(
+                        src,
+                        inventory,
+                        inventory.consonants(),
+                        |x| manners.contains(&x.manner()),
+                    )
+                } else {
+                    Err(ParseError::UnknownCharacter(first))
+                }
+            }
+        }
+    }
+
+    /// Create a generator for a class of phonemes drawn from the `inventory`, weighted by the inventory's weights if it
+    /// has any.
+    pub(super) fn from_class(
+        display: impl Into<String>,
+        choices: SmallVec<[phone::Phoneme; 8]>,
+        inventory: &phone::Inventory,
+    ) -> Result<Self, ParseError> {
+        let weights = if inventory.is_weighted() {
+            choices.iter().map(|x| inventory.weight(*x)).collect()
+        } else {
+            SmallVec::new()
+        };
+        Self::new(display, choices, weights)
+    }
+
+    fn from_character_class<'a, T: Into<phone::Phoneme> + Copy>(
+        src: &'a str,
+        inventory: &phone::Inventory,
+        options: &[T],
+    ) -> Result<(Self, &'a str), ParseError> {
+        Self::from_character_class_filtered(src, inventory, options, |_| true)
+    }
+
+    fn from_character_class_filtered<'a, T: Into<phone::Phoneme> + Copy>(
+        src: &'a str,
+        inventory: &phone::Inventory,
+        options: &[T],
+        filter: impl Fn(&T) -> bool,
+    ) -> Result<(Self, &'a str), ParseError> {
+        // Class letters like `Ḍ` can be more than one byte
+        let len = src.chars().next().map_or(0, char::len_utf8);
+        let out = Self::from_class(
+            &src[..len],
+            options
+                .iter()
+                .filter_map(|x| if filter(x) { Some((*x).into()) } else { None })
+                .collect(),
+            inventory,
+        )?;
+        Ok((out, &src[len..]))
+    }
+
+    /// The phonemes this can produce, in the order of the inventory.
+    pub fn choices(&self) -> &[phone::Phoneme] {
+        &self.choices
+    }
+
+    /// The weight of each of the [choices](Self::choices), or an empty list if they are all equally likely.
+    pub fn weights(&self) -> &[u8] {
+        &self.weights
+    }
+
+    /// Check if the phoneme can be left out, like `(C)`.
+    pub fn is_optional(&self) -> bool {
+        self.optional
+    }
+
+    /// The chance that this phoneme is included in a syllable, where `default` is the chance for optional phonemes
+    /// without their own probability.
+    pub fn inclusion(&self, default: f64) -> f64 {
+        if self.optional {
+            self.probability.unwrap_or(default)
+        } else {
+            1.0
+        }
+    }
+
+    /// The chance of each of the [outcomes](Self::outcomes), in order: each of the choices, then leaving the phoneme
+    /// out if it is optional.
+    fn probabilities(&self, default: f64) -> impl Iterator<Item = f64> + '_ {
+        let total: u32 = self.weights.iter().map(|w| u32::from(*w)).sum();
+        let included = self.inclusion(default);
+        let uniform = 1.0 / self.choices.len() as f64;
+        (0..self.choices.len())
+            .map(move |idx| match self.weights.get(idx) {
+                Some(w) => included * f64::from(*w) / f64::from(total),
+                None => included * uniform,
+            })
+            .chain(self.optional.then_some(1.0 - included))
+    }
+
+    fn entropy_bits(&self, default: f64) -> f64 {
+        self.probabilities(default)
+            .filter(|p| *p > 0.0)
+            .map(|p| -p * p.log2())
+            .sum()
+    }
+
+    pub fn generate(&self, rng: &mut impl Rng) -> phone::Phoneme {
+        let idx = match &self.sampler {
+            Sampler::Uniform(dist) => dist.sample(rng),
+            Sampler::Weighted(dist) => dist.sample(rng),
+        };
+        self.choices[idx]
+    }
+
+    fn generate_in(&self, rng: &mut impl Rng, ctx: &mut Context) -> phone::Phoneme {
+        let harmony = ctx.harmony.zip(ctx.harmony_class);
+        let positions = ctx.positions.zip(ctx.position);
+        let out = if harmony.is_none() && positions.is_none() {
+            self.generate(rng)
+        } else {
+            // If nothing is allowed, generate anyway and let the word be rejected as a whole
+            self.generate_from(rng, |ph| {
+                harmony.is_none_or(|(harmony, class)| harmony.allows(class, ph))
+                    && positions.is_none_or(|(positions, pos)| positions.allows(pos, ph))
+            })
+            .unwrap_or_else(|| self.generate(rng))
+        };
+        if let (Some(harmony), None) = (ctx.harmony, ctx.harmony_class) {
+            ctx.harmony_class = harmony.class_of(out);
+        }
+        out
+    }
+
+    /// Generate a phoneme from the subset of choices where `allowed` is `true`, respecting weights. Returns `None` if
+    /// nothing is allowed.
+    fn generate_from(
+        &self,
+        rng: &mut impl Rng,
+        allowed: impl Fn(phone::Phoneme) -> bool,
+    ) -> Option<phone::Phoneme> {
+        let indices: SmallVec<[usize; 8]> = (0..self.choices.len())
+            .filter(|idx| allowed(self.choices[*idx]))
+            .collect();
+        if indices.is_empty() {
+            return None;
+        }
+
+        let idx = if self.weights.is_empty() {
+            *indices.choose(rng)?
+        } else {
+            *indices
+                .choose_weighted(rng, |idx| u32::from(self.weights[*idx]))
+                .ok()?
+        };
+        Some(self.choices[idx])
+    }
+}
+
+impl Distribution<phone::Phoneme> for PhonemeGenerator {
+    fn sample<R: Rng + ?Sized>(&self, mut rng: &mut R) -> phone::Phoneme {
+        self.generate(&mut rng)
+    }
+}
+
+impl PartialEq for PhonemeGenerator {
+    fn eq(&self, other: &Self) -> bool {
+        self.choices == other.choices
+            && self.weights == other.weights
+            && self.optional == other.optional
+            && self.probability == other.probability
+            && self.label == other.label
+            && self.geminate == other.geminate
+    }
+}
+
+impl fmt::Display for PhonemeGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.optional {
+            f.write_str("(")?;
+        }
+        f.write_str(&self.display)?;
+        if let Some(label) = self.label {
+            write!(f, "{}", subscript(label))?;
+        }
+        if self.geminate {
+            write!(f, "{}", phone::LENGTH_MARK)?;
+        }
+        if self.optional {
+            f.write_str(")")?;
+        }
+        if let Some(probability) = self.probability {
+            write!(f, "?{probability}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Get the value of a subscript digit like `₁`.
+fn subscript_value(c: char) -> Option<u8> {
+    ('₀'..='₉')
+        .contains(&c)
+        .then(|| (u32::from(c) - u32::from('₀')) as u8)
+}
+
+fn subscript(value: u8) -> char {
+    char::from_u32(u32::from('₀') + u32::from(value)).unwrap()
+}
+
+impl fmt::Debug for PhonemeGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Phoneme({self})")
+    }
+}
+
+#[cfg(test)]
+mod gen_tests {
+    use super::*;
+    use crate::phone;
+
+    #[test]
+    fn parsing() {
+        let inventory = phone::Inventory::with_everything();
+        let inputs = &["C", "V", "CV", "VVC"];
+        for input in inputs.iter() {
+            WordGenerator::parse(input, &inventory).unwrap();
+        }
+    }
+
+    #[test]
+    fn optional_and_literal() {
+        use rand::SeedableRng;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x5eed);
+        let inventory = phone::Inventory::new(
+            &[phone::Consonant::P, phone::Consonant::T][..],
+            &[phone::Vowel::A][..],
+            &[][..],
+        );
+        let gen = WordGenerator::parse("[mn]V(C)", &inventory).unwrap();
+        assert_eq!("[mn]V(C)", gen.to_pattern_string());
+        // 2 onsets × 1 vowel × (2 codas or none)
+        assert_eq!(6, gen.count());
+        let words: Vec<_> = gen.enumerate().map(|w| w.iter().join(" ")).collect();
+        assert_eq!(vec!["map", "mat", "ma", "nap", "nat", "na"], words);
+        assert_eq!(1.0 + 1.5, gen.entropy_bits());
+
+        let mut lengths = [0usize; 4];
+        for _ in 0..10_000 {
+            lengths[gen.generate(&mut rng)[0].parts().len()] += 1;
+        }
+        assert!(lengths[2].abs_diff(5000) < 300, "{lengths:?}");
+
+        assert!(matches!(
+            WordGenerator::parse("C[pa", &inventory).unwrap_err().kind(),
+            ParseError::Unclosed('[')
+        ));
+        assert!(matches!(
+            WordGenerator::parse("CV(C", &inventory).unwrap_err().kind(),
+            ParseError::Unclosed('(')
+        ));
+
+        let gen = WordGenerator::parse("\\cV\\v", &inventory).unwrap();
+        assert_eq!("\\cV\\v", gen.to_pattern_string());
+        let words: Vec<_> = gen.enumerate().map(|w| w.iter().join(" ")).collect();
+        assert_eq!(vec!["cav"], words);
+        assert!(matches!(
+            WordGenerator::parse("CV\\", &inventory).unwrap_err().kind(),
+            ParseError::UnknownCharacter('\\')
+        ));
+    }
+
+    #[test]
+    fn non_pulmonic() {
+        let inventory = phone::Inventory::new(
+            &[phone::Consonant::P][..],
+            &[phone::Vowel::A][..],
+            &[
+                phone::NonPulmonicConsonant::BilabialClick,
+                phone::NonPulmonicConsonant::BilabialImplosive,
+            ][..],
+        );
+        let gen = WordGenerator::parse("KV CV", &inventory).unwrap();
+        assert_eq!("KV CV", gen.to_pattern_string());
+        let words: Vec<_> = gen.enumerate().map(|w| w.iter().join(".")).collect();
+        assert_eq!(vec!["ʘa.pa", "ɓa.pa"], words);
+
+        let gen = WordGenerator::builder()
+            .inventory(inventory)
+            .syllable(|s| s.class(Class::NonPulmonic).class(Class::Vowels))
+            .build()
+            .unwrap();
+        assert_eq!("KV", gen.to_pattern_string());
+
+        let inventory =
+            phone::Inventory::new(&[phone::Consonant::P][..], &[phone::Vowel::A][..], &[][..]);
+        assert!(matches!(
+            WordGenerator::parse("KV", &inventory).unwrap_err().kind(),
+            ParseError::EmptyClass(_)
+        ));
+    }
+
+    #[test]
+    fn vowel_subclasses() {
+        let inventory = phone::Inventory::new(
+            &[phone::Consonant::P][..],
+            &[
+                phone::Vowel::I,
+                phone::Vowel::Y,
+                phone::Vowel::U,
+                phone::Vowel::E,
+                phone::Vowel::Schwa,
+                phone::Vowel::O,
+                phone::Vowel::A,
+            ][..],
+            &[][..],
+        );
+        let vowels = |pattern| -> String {
+            WordGenerator::parse(pattern, &inventory)
+                .unwrap()
+                .enumerate()
+                .map(|w| w.iter().join(""))
+                .collect()
+        };
+        assert_eq!("iyea", vowels("Vf"));
+        assert_eq!("ə", vowels("Vc"));
+        assert_eq!("uo", vowels("Vb"));
+        assert_eq!("iyu", vowels("Vh"));
+        assert_eq!("eəo", vowels("Vm"));
+        assert_eq!("a", vowels("Vl"));
+        assert_eq!("yuo", vowels("Vr"));
+        assert_eq!("ieəa", vowels("Vu"));
+        assert_eq!("iy", vowels("{Vf-Vm-Vl}"));
+
+        let gen = WordGenerator::parse("CVf₁ CVf₁", &inventory).unwrap();
+        assert_eq!("CVf₁ CVf₁", gen.to_pattern_string());
+        assert_eq!(4, gen.count());
+        let inventory =
+            phone::Inventory::new(&[phone::Consonant::P][..], &[phone::Vowel::A][..], &[][..]);
+        assert!(matches!(
+            WordGenerator::parse("CVc", &inventory).unwrap_err().kind(),
+            ParseError::EmptyClass(c) if c == "Vc"
+        ));
+    }
+
+    #[test]
+    fn gemination() {
+        let inventory = phone::Inventory::new(
+            &[phone::Consonant::P, phone::Consonant::T][..],
+            &[phone::Vowel::A][..],
+            &[][..],
+        );
+        let gen = WordGenerator::parse("CVCːV", &inventory).unwrap();
+        assert_eq!(gen, WordGenerator::parse("CVC:V", &inventory).unwrap());
+        assert_eq!("CVCːV", gen.to_pattern_string());
+        assert_eq!(4, gen.count());
+        let words: Vec<_> = gen.enumerate().map(|w| w.iter().join(".")).collect();
+        assert_eq!(vec!["papːa", "patːa", "tapːa", "tatːa"], words);
+        assert_eq!(5, gen.enumerate().next().unwrap().phonemes().count());
+
+        // Gemination applies to the repeat of a backreference
+        let gen = WordGenerator::parse("C₁V C₁ːV", &inventory).unwrap();
+        assert_eq!("C₁V C₁ːV", gen.to_pattern_string());
+        let words: Vec<_> = gen.enumerate().map(|w| w.iter().join(".")).collect();
+        assert_eq!(vec!["pa.pːa", "ta.tːa"], words);
+
+        assert!(matches!(
+            WordGenerator::parse("CV(Cː)ː", &inventory)
+                .unwrap_err()
+                .kind(),
+            ParseError::UnknownCharacter('ː')
+        ));
+    }
+
+    #[test]
+    fn class_arithmetic() {
+        let inventory = phone::Inventory::new(
+            &[
+                phone::Consonant::P,
+                phone::Consonant::T,
+                phone::Consonant::NVelar,
+            ][..],
+            &[phone::Vowel::A, phone::Vowel::I][..],
+            &[][..],
+        );
+        let words = |pattern| -> Vec<String> {
+            WordGenerator::parse(pattern, &inventory)
+                .unwrap()
+                .enumerate()
+                .map(|w| w.iter().join(" "))
+                .collect()
+        };
+        assert_eq!(vec!["pa", "ta"], words("{C-[ŋ]}[a]"));
+        assert_eq!(vec!["a", "i", "l", "j"], words("{V + [l j]}"));
+        assert_eq!(vec!["t"], words("{C-[ŋ]-[p]}"));
+        assert_eq!(vec!["pa ti"], words("[p]{V-[i]} [t]{V - [a]}"));
+
+        let gen = WordGenerator::parse("{V + [l j]}C", &inventory).unwrap();
+        assert_eq!("{V + [l j]}C", gen.to_pattern_string());
+        assert_eq!(4 * 3, gen.count());
+
+        let error = |pattern| {
+            WordGenerator::parse(pattern, &inventory)
+                .unwrap_err()
+                .kind()
+                .to_string()
+        };
+        assert_eq!(error("C{V-"), ParseError::Unclosed('{').to_string());
+        assert_eq!(
+            error("C{V-V}"),
+            ParseError::EmptyClass("{V-V}".into()).to_string()
+        );
+        assert_eq!(
+            error("C{V*[l]}"),
+            ParseError::UnknownCharacter('*').to_string()
+        );
+    }
+
+    #[test]
+    fn optional_probability() {
+        use rand::SeedableRng;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x5eed);
+        let inventory = phone::Inventory::new(
+            &[phone::Consonant::P, phone::Consonant::T][..],
+            &[phone::Vowel::A][..],
+            &[][..],
+        );
+        let codas = |gen: &WordGenerator, rng: &mut rand::rngs::StdRng| {
+            (0..10_000)
+                .filter(|_| gen.generate(rng)[0].parts().len() == 3)
+                .count()
+        };
+
+        let gen = WordGenerator::parse("CV(C)?0.3", &inventory).unwrap();
+        assert_eq!("CV(C)?0.3", gen.to_pattern_string());
+        assert_eq!(6, gen.count());
+        assert!(codas(&gen, &mut rng).abs_diff(3000) < 300);
+
+        // The generator-wide default does not override the pattern
+        let gen = WordGenerator::parse("(C)V(C)?0.3", &inventory)
+            .unwrap()
+            .with_optional_probability(0.9);
+        assert_eq!(0.9, gen.optional_probability());
+        let onsets = (0..10_000)
+            .filter(|_| gen.generate(&mut rng)[0].parts()[0] != phone::Vowel::A.into())
+            .count();
+        assert!(onsets.abs_diff(9000) < 300);
+        let gen = WordGenerator::parse("CV(C)", &inventory)
+            .unwrap()
+            .with_optional_probability(0.1);
+        assert!(codas(&gen, &mut rng).abs_diff(1000) < 300);
+
+        // Entropy: 1 bit for C, then the coda is present 30% of the time with 1 bit of choice
+        let gen = WordGenerator::parse("CV(C)?0.3", &inventory).unwrap();
+        let coda = -(0.15f64 * 0.15f64.log2()) * 2.0 - 0.7 * 0.7f64.log2();
+        assert!((gen.entropy_bits() - (1.0 + coda)).abs() < 1e-9);
+
+        for bad in ["CV(C)?", "CV(C)?1.5", "CV(C)?0.3.1"] {
+            assert!(matches!(
+                WordGenerator::parse(bad, &inventory).unwrap_err().kind(),
+                ParseError::InvalidProbability(_)
+            ));
+        }
+    }
+
+    #[test]
+    fn backreferences() {
+        use rand::SeedableRng;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x5eed);
+        let inventory = phone::Inventory::new(
+            &[
+                phone::Consonant::P,
+                phone::Consonant::T,
+                phone::Consonant::K,
+            ][..],
+            &[phone::Vowel::A, phone::Vowel::I][..],
+            &[][..],
+        );
+        let gen = WordGenerator::parse("C₁VC₁ C₂V₃ C₂V₃", &inventory).unwrap();
+        assert_eq!("C₁VC₁ C₂V₃ C₂V₃", gen.to_pattern_string());
+        assert_eq!(3 * 2 * 3 * 2, gen.count());
+        assert_eq!((36f64).log2(), gen.entropy_bits());
+
+        for _ in 0..100 {
+            let word = gen.generate(&mut rng);
+            let first = word[0].parts();
+            assert_eq!(first[0], first[2]);
+            assert_eq!(word[1], word[2]);
+        }
+
+        let words: Vec<_> = gen.enumerate().collect();
+        assert_eq!(36, words.len());
+        assert!(words
+            .iter()
+            .all(|w| w[0].parts()[0] == w[0].parts()[2] && w[1] == w[2]));
+
+        // A label on an optional phoneme which was left out leaves out every repeat of it
+        let gen = WordGenerator::parse("(C₁)V(C₁)", &inventory).unwrap();
+        let words: Vec<_> = gen.enumerate().map(|w| w.iter().join(" ")).collect();
+        assert_eq!(
+            vec!["pap", "pip", "tat", "tit", "kak", "kik", "a", "i"],
+            words
+        );
+
+        assert!(WordGenerator::parse("C₁₂V", &inventory).is_err());
+    }
+
+    #[test]
+    fn builder() {
+        let inventory = phone::Inventory::new(
+            &[phone::Consonant::P, phone::Consonant::M][..],
+            &[phone::Vowel::A][..],
+            &[][..],
+        );
+        let gen = WordGenerator::builder()
+            .inventory(inventory.clone())
+            .syllable(|s| {
+                s.class(Class::Manner(phone::Manner::Nasal))
+                    .class(Class::Vowels)
+            })
+            .syllable(|s| {
+                s.phoneme(phone::Consonant::T)
+                    .class(Class::Vowels)
+                    .optional(Class::Consonants)
+            })
+            .build()
+            .unwrap();
+        assert_eq!("[m]V [t]V(C)", gen.to_pattern_string());
+        assert_eq!(
+            gen,
+            WordGenerator::parse("[m]V [t]V(C)", &inventory).unwrap()
+        );
+        assert_eq!(3, gen.count());
+
+        assert!(WordGenerator::builder().build().is_err());
+        assert!(matches!(
+            WordGenerator::builder()
+                .inventory(inventory)
+                .syllable(|s| s.class(Class::Place(phone::Place::Velar)))
+                .build(),
+            Err(ParseError::EmptyClass(_))
+        ));
+    }
+
+    #[test]
+    fn enumeration() {
+        let inventory = phone::Inventory::new(
+            &[phone::Consonant::P, phone::Consonant::T][..],
+            &[phone::Vowel::A, phone::Vowel::E, phone::Vowel::I][..],
+            &[][..],
+        );
+        let gen = WordGenerator::parse("CV C", &inventory).unwrap();
+        assert_eq!(12, gen.count());
+
+        let words: Vec<_> = gen.enumerate().map(|w| w.iter().join(" ")).collect();
+        assert_eq!(12, words.len());
+        assert_eq!("pa p", words[0]);
+        assert_eq!("pa t", words[1]);
+        assert_eq!("pe p", words[2]);
+        assert_eq!("ti t", words[11]);
+
+        let unique: std::collections::BTreeSet<_> = words.iter().collect();
+        assert_eq!(words.len(), unique.len());
+    }
+
+    #[test]
+    fn unique_generation() {
+        use rand::SeedableRng;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x5eed);
+        let inventory = phone::Inventory::new(
+            &[
+                phone::Consonant::P,
+                phone::Consonant::T,
+                phone::Consonant::N,
+            ][..],
+            &[phone::Vowel::A, phone::Vowel::I][..],
+            &[][..],
+        );
+
+        // Overlapping patterns: "CV" includes everything "NV" does
+        let gens = [
+            WordGenerator::parse("CV", &inventory).unwrap(),
+            WordGenerator::parse("NV", &inventory).unwrap(),
+        ];
+        let words = generate_unique(&gens, &mut rng, 6).unwrap();
+        let unique: std::collections::HashSet<_> = words.iter().collect();
+        assert_eq!(6, unique.len());
+
+        assert!(matches!(
+            generate_unique(&gens, &mut rng, 7),
+            Err(GenerateError::NotEnoughWords { possible: 6, .. })
+        ));
+
+        // A large space uses random generation
+        let gen = WordGenerator::parse("CVC CVC CVC CVC CVC", &phone::Inventory::with_everything())
+            .unwrap();
+        let words = gen.generate_unique(&mut rng, 1000).unwrap();
+        let unique: std::collections::HashSet<_> = words.iter().collect();
+        assert_eq!(1000, unique.len());
+    }
+
+    #[test]
+    fn filters() {
+        use rand::SeedableRng;
+        use std::str::FromStr;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x5eed);
+        let inventory = phone::Inventory::new(
+            &[phone::Consonant::P, phone::Consonant::H][..],
+            &[phone::Vowel::A, phone::Vowel::I][..],
+            &[][..],
+        );
+        let gen = WordGenerator::parse("CV CVC", &inventory)
+            .unwrap()
+            .with_filter(Filter::from_str("no-final h").unwrap())
+            .with_filter(Filter::from_str("regex pi").unwrap())
+            .with_filter(Filter::custom(|word| word[0] == word[1]));
+
+        for _ in 0..1000 {
+            let word = gen.generate(&mut rng);
+            let ipa = word.iter().join("");
+            assert!(!ipa.ends_with('h'), "{ipa}");
+            assert!(!ipa.contains("pi"), "{ipa}");
+            assert_ne!(word[0], word[1]);
+        }
+
+        // 8 words are "...h" and 4 left over contain "pi" in the second syllable
+        let words: Vec<_> = gen.enumerate().collect();
+        assert!(words.iter().all(|w| !gen.rejects(w)));
+        assert_eq!(32, gen.count());
+        assert_eq!(9, words.len());
+        // With so few words passing, it takes several candidates on average to find one
+        let candidates: usize = (0..1000)
+            .map(|_| gen.try_generate_counted(&mut rng).unwrap().1)
+            .sum();
+        assert!(candidates > 2000, "{candidates}");
+
+        let repeats = Filter::from_str("no-adjacent-repeats").unwrap();
+        assert!(repeats.rejects(&[
+            phone::Syllable::from_str("pa").unwrap(),
+            phone::Syllable::from_str("ap").unwrap()
+        ]));
+        assert!(!repeats.rejects(&[
+            phone::Syllable::from_str("pa").unwrap(),
+            phone::Syllable::from_str("pa").unwrap()
+        ]));
+
+        assert!(Filter::from_str("no-final hh").is_err());
+        assert!(Filter::from_str("regex (").is_err());
+        assert!(Filter::from_str("bogus").is_err());
+
+        let forbid = Filter::from_str("forbid hi, ip").unwrap();
+        assert_eq!("forbid hi ip", forbid.to_string());
+        assert!(forbid.rejects(&[
+            phone::Syllable::from_str("pa").unwrap(),
+            phone::Syllable::from_str("hi").unwrap()
+        ]));
+        assert!(forbid.rejects(&[
+            phone::Syllable::from_str("pi").unwrap(),
+            phone::Syllable::from_str("pa").unwrap()
+        ]));
+        assert!(!forbid.rejects(&[
+            phone::Syllable::from_str("ha").unwrap(),
+            phone::Syllable::from_str("pi").unwrap()
+        ]));
+        assert!(Filter::from_str("forbid").is_err());
+        assert!(Filter::from_str("forbid j!").is_err());
+
+        let word = |src: &str| -> Vec<_> {
+            src.split(' ')
+                .map(|syl| phone::Syllable::from_str(syl).unwrap())
+                .collect()
+        };
+        let phonemes = Filter::from_str("phonemes 3-4").unwrap();
+        assert_eq!(
+            Filter::Phonemes {
+                min: Some(3),
+                max: Some(4)
+            },
+            phonemes
+        );
+        assert!(phonemes.rejects(&word("pa")));
+        assert!(!phonemes.rejects(&word("pa ih")));
+        assert!(phonemes.rejects(&word("pa pa pa")));
+        let syllables = Filter::from_str("syllables 2-").unwrap();
+        assert_eq!("syllables 2-", syllables.to_string());
+        assert!(syllables.rejects(&word("pah")));
+        assert!(!syllables.rejects(&word("pa pa pa")));
+        assert_eq!(
+            "phonemes 5",
+            Filter::from_str("phonemes 5").unwrap().to_string()
+        );
+        assert!(Filter::from_str("phonemes -").is_err());
+        assert!(Filter::from_str("phonemes 4-3").is_err());
+    }
+
+    #[test]
+    fn lexicon() {
+        use rand::SeedableRng;
+        use std::str::FromStr;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x5eed);
+        let inventory = phone::Inventory::new(
+            &[phone::Consonant::P, phone::Consonant::T][..],
+            &[phone::Vowel::A, phone::Vowel::I][..],
+            &[][..],
+        );
+        let word = |src: &str| phone::Word::from_str(src).unwrap();
+        let ipa = |src: &str| word(src).phonemes().collect::<Vec<_>>();
+
+        let mut lexicon = Lexicon::new(1);
+        lexicon.insert(&ipa("pata"));
+        assert!(lexicon.conflicts(&word("pa.ta")));
+        assert!(lexicon.conflicts(&word("pa.ti")));
+        assert!(lexicon.conflicts(&word("a.ta")));
+        assert!(lexicon.conflicts(&word("pa.tap")));
+        assert!(!lexicon.conflicts(&word("ti.ta")));
+        assert!(!Lexicon::new(0).conflicts(&word("pa.ta")));
+
+        let gen = WordGenerator::parse("CV CV", &inventory)
+            .unwrap()
+            .with_filter(Filter::Lexicon(Arc::new(lexicon.clone())));
+        // Of the 16 words, /pata/ and the 4 one substitution away conflict
+        let words: Vec<_> = gen.enumerate().collect();
+        assert_eq!(11, words.len());
+        for _ in 0..100 {
+            assert!(!lexicon.conflicts(&gen.generate(&mut rng)));
+        }
+    }
+
+    #[test]
+    fn harmony() {
+        use rand::SeedableRng;
+        use std::str::FromStr;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x5eed);
+        let inventory = phone::Inventory::new(
+            &[phone::Consonant::K, phone::Consonant::T][..],
+            &[
+                phone::Vowel::I,
+                phone::Vowel::E,
+                phone::Vowel::U,
+                phone::Vowel::O,
+                phone::Vowel::Schwa,
+            ][..],
+            &[][..],
+        );
+        let harmony = Harmony::from_str("ie/uo").unwrap();
+        let gen = WordGenerator::parse("CV CV CVC", &inventory)
+            .unwrap()
+            .with_harmony(harmony.clone());
+
+        let mut saw_neutral = false;
+        for _ in 0..1000 {
+            let word = gen.generate(&mut rng);
+            assert!(harmony.accepts(&word), "{}", word.iter().join(" "));
+            saw_neutral |= word
+                .iter()
+                .any(|syl| syl.parts().contains(&phone::Vowel::Schwa.into()));
+        }
+        assert!(saw_neutral);
+
+        // The count does not account for harmony, but enumeration does
+        assert_eq!(2000, gen.count());
+        assert!(gen.enumerate().all(|w| harmony.accepts(&w)));
+
+        let front_back = Harmony::front_back();
+        assert_eq!(Some(0), front_back.class_of(phone::Vowel::I.into()));
+        assert_eq!(Some(1), front_back.class_of(phone::Vowel::U.into()));
+        assert_eq!(None, front_back.class_of(phone::Vowel::Schwa.into()));
+        assert_eq!(None, front_back.class_of(phone::Consonant::K.into()));
+        assert_eq!(
+            "front-back",
+            Harmony::from_str("front-back").unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn sonority() {
+        use std::str::FromStr;
+
+        let filter = Filter::from_str("sonority 2").unwrap();
+        let rejects = |src: &str| {
+            let word: Vec<_> = src
+                .split(' ')
+                .map(|syl| phone::Syllable::from_str(syl).unwrap())
+                .collect();
+            filter.rejects(&word)
+        };
+        assert!(!rejects("pla"));
+        assert!(!rejects("pa alp"));
+        assert!(!rejects("pa"));
+        assert!(!rejects("pn"));
+        assert!(rejects("pfa"));
+        assert!(rejects("tsa"));
+        assert!(rejects("mla"));
+        assert!(rejects("pa apl"));
+        assert!(rejects("lpa"));
+    }
+
+    #[test]
+    fn markov() {
+        use rand::SeedableRng;
+        use std::str::FromStr;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x5eed);
+        let corpus = ["pata", "kapa", "tapak", "pakat", "katapa"];
+        let mut gen = MarkovGenerator::new(1);
+        for word in corpus {
+            gen.train(phone::Syllable::from_str(word).unwrap().parts());
+        }
+
+        for _ in 0..100 {
+            let word = gen.generate(&mut rng);
+            let ipa = word.iter().join("");
+            assert!(!ipa.is_empty());
+            // Every bigram in the output was seen in the corpus
+            for pair in ipa.chars().collect::<Vec<_>>().windows(2) {
+                let bigram: String = pair.iter().collect();
+                assert!(
+                    corpus.iter().any(|w| w.contains(&bigram)),
+                    "{bigram} in {ipa}"
+                );
+            }
+        }
+
+        let novel = gen.generate_novel(&mut rng).unwrap().iter().join("");
+        assert!(!corpus.contains(&novel.as_str()));
+
+        assert!(MarkovGenerator::new(2).generate(&mut rng).is_empty());
+    }
+
+    #[test]
+    fn batch() {
+        use rand::SeedableRng;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let inventory = phone::Inventory::with_everything();
+        let gen = WordGenerator::parse("CV CVC", &inventory)
+            .unwrap()
+            .with_filter(Filter::NoAdjacentRepeats);
+        let words = gen
+            .generate_batch(&mut rand::rngs::StdRng::seed_from_u64(0x5eed), 2500)
+            .unwrap();
+        assert_eq!(2500, words.len());
+        assert!(words.iter().all(|w| !gen.rejects(w)));
+        assert_eq!(
+            words,
+            gen.generate_batch(&mut rand::rngs::StdRng::seed_from_u64(0x5eed), 2500)
+                .unwrap()
+        );
+        assert!(gen
+            .generate_batch(&mut rand::thread_rng(), 0)
+            .unwrap()
+            .is_empty());
+
+        // Every word is reported, with at least as many candidates
+        let (words, candidates) = (AtomicUsize::new(0), AtomicUsize::new(0));
+        gen.generate_batch_with_progress(&mut rand::thread_rng(), 2500, |w, c| {
+            words.fetch_add(w, Ordering::Relaxed);
+            candidates.fetch_add(c, Ordering::Relaxed);
+        })
+        .unwrap();
+        assert_eq!(2500, words.into_inner());
+        assert!(candidates.into_inner() >= 2500);
+
+        // An over-filtered pattern is an error, not a panic on a worker thread
+        let impossible = WordGenerator::parse("CV", &inventory)
+            .unwrap()
+            .with_filter(Filter::Phonemes {
+                min: Some(3),
+                max: None,
+            })
+            .with_max_attempts(50);
+        assert!(matches!(
+            impossible.generate_batch(&mut rand::thread_rng(), 3000),
+            Err(GenerateError::Unsatisfiable { .. })
+        ));
+    }
+
+    #[test]
+    fn iter() {
+        use rand::SeedableRng;
+
+        let inventory = phone::Inventory::with_everything();
+        let gen = WordGenerator::parse("CV CV", &inventory).unwrap();
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x5eed);
+        let words: Vec<_> = gen.iter(&mut rng).take(10).collect();
+        assert_eq!(10, words.len());
+
+        // Seeded iterators produce the same words as generating one at a time
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x5eed);
+        for word in words.iter() {
+            assert_eq!(*word, gen.generate(&mut rng));
+        }
+
+        // So does sampling the generator as a distribution
+        let rng = rand::rngs::StdRng::seed_from_u64(0x5eed);
+        let sampled: Vec<phone::Word> = rng.sample_iter(&gen).take(10).collect();
+        assert_eq!(words, sampled);
+
+        let unique: Vec<_> = gen.iter(rand::thread_rng()).unique().take(50).collect();
+        assert_eq!(50, unique.iter().unique().count());
+    }
+
+    #[test]
+    fn syllable_counts() {
+        use rand::SeedableRng;
+        use std::str::FromStr;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x5eed);
+        let inventory = phone::Inventory::new(
+            &[phone::Consonant::P, phone::Consonant::T][..],
+            &[phone::Vowel::A][..],
+            &[][..],
+        );
+        let counts = SyllableCounts::from_str("1:20, 2:50%, 3:30").unwrap();
+        assert_eq!("1:20,2:50,3:30", counts.to_string());
+        let gen = WordGenerator::parse("CV CVC", &inventory)
+            .unwrap()
+            .with_syllable_counts(counts);
+
+        let mut lengths = [0usize; 4];
+        for _ in 0..10_000 {
+            let word = gen.generate(&mut rng);
+            for (idx, syl) in word.iter().enumerate() {
+                let expected = if syl.parts().len() == 2 { "CV" } else { "CVC" };
+                assert_eq!(Some(expected), word.pattern(idx));
+            }
+            lengths[word.len()] += 1;
+        }
+        assert_eq!(0, lengths[0]);
+        for (actual, expected) in lengths[1..].iter().zip([2000, 5000, 3000]) {
+            assert!(
+                actual.abs_diff(expected) < 300,
+                "{actual} vs expected {expected}"
+            );
+        }
+
+        // The pool has 2 + 4 = 6 one-syllable words, so 6 + 6² + 6³
+        assert_eq!(258, gen.count());
+        let words: Vec<_> = gen.enumerate().collect();
+        assert_eq!(258, words.len());
+        assert_eq!(1, words[0].len());
+        assert_eq!(3, words[257].len());
+
+        // Worked out a syllable at a time, the statistics are the same as going through every template
+        let (entropy, pair) = gen
+            .template_probabilities()
+            .map(|(template, p)| {
+                let slots = independent_slots(&template);
+                let (bits, same) = slots.fold((0.0, 1.0), |(bits, same), ph| {
+                    let probabilities = ph.probabilities(gen.optional_probability());
+                    let same_choice: f64 = probabilities.map(|x| x * x).sum();
+                    (
+                        bits + ph.entropy_bits(gen.optional_probability()),
+                        same * same_choice,
+                    )
+                });
+                (p * (bits - p.log2()), p * p * same)
+            })
+            .fold((0.0, 0.0), |(a, b), (x, y)| (a + x, b + y));
+        assert!((entropy - gen.entropy_bits()).abs() < 1e-9);
+        let collision = 1.0 - (-45.0 * pair).exp();
+        assert!((collision - gen.collision_probability(10)).abs() < 1e-9);
+
+        // A big pool is not arranged just to count it
+        let big = WordGenerator::parse(&["CV"; 10].join(" "), &inventory)
+            .unwrap()
+            .with_syllable_counts(SyllableCounts::from_str("1:1,8:1").unwrap());
+        assert_eq!(20 + 20u128.pow(8), big.count());
+        // Half the words have one syllable and half eight, each with a bit for the consonant and log₂ 10 for the pick
+        let syllable = 1.0 + 10f64.log2();
+        assert!(
+            (0.5 * (syllable + 1.0) + 0.5 * (8.0 * syllable + 1.0) - big.entropy_bits()).abs()
+                < 1e-9
+        );
+        assert_eq!(2, big.enumerate().take(2).count());
+
+        assert!(SyllableCounts::from_str("0:1").is_err());
+        assert!(SyllableCounts::from_str("1:0").is_err());
+        assert!(SyllableCounts::from_str("1").is_err());
+
+        let counts = gen.syllable_counts().unwrap();
+        assert_eq!(
+            "2:50,3:30",
+            counts.within(Some(2), None).unwrap().to_string()
+        );
+        assert!(counts.within(Some(4), Some(5)).is_none());
+    }
+
+    #[test]
+    fn serialization() {
+        use std::str::FromStr;
+
+        let inventory = phone::Inventory::with_everything();
+        let gen = WordGenerator::parse("CV NVC", &inventory)
+            .unwrap()
+            .with_filter(Filter::from_str("no-final h").unwrap())
+            .with_harmony(Harmony::front_back())
+            .with_syllable_counts(SyllableCounts::from_str("1:1,2:3").unwrap());
+
+        let json = serde_json::to_string(&gen).unwrap();
+        let loaded: WordGenerator = serde_json::from_str(&json).unwrap();
+        assert_eq!(gen, loaded);
+        assert_eq!(gen.count(), loaded.count());
+
+        let reparsed = WordGenerator::parse(&gen.to_pattern_string(), &inventory).unwrap();
+        assert_eq!("CV NVC", reparsed.to_pattern_string());
+        assert_eq!(gen.syllables, reparsed.syllables);
+
+        let custom = WordGenerator::parse("CV", &inventory)
+            .unwrap()
+            .with_filter(Filter::custom(|_| false));
+        assert!(serde_json::to_string(&custom).is_err());
+
+        assert!(serde_json::from_str::<WordGenerator>(
+            r#"{"syllables":[[{"pattern":"C","choices":""}]]}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn statistics() {
+        use std::str::FromStr;
+
+        let inventory = phone::Inventory::new(
+            &[phone::Consonant::P, phone::Consonant::T][..],
+            &[
+                phone::Vowel::A,
+                phone::Vowel::E,
+                phone::Vowel::I,
+                phone::Vowel::O,
+            ][..],
+            &[][..],
+        );
+        let gen = WordGenerator::parse("CV CV", &inventory).unwrap();
+        assert_eq!(64, gen.possible_words());
+        assert!((gen.entropy_bits() - 6.0).abs() < 1e-9);
+        assert_eq!(0.0, gen.collision_probability(1));
+        // Two words: 1 in 64 chance they match
+        assert!((gen.collision_probability(2) - (1.0 - (-1.0f64 / 64.0).exp())).abs() < 1e-9);
+        assert!(gen.collision_probability(100) > 0.99);
+
+        // One or two syllables of "CV": 1 bit for the count plus 3 or 6 bits for the syllables
+        let gen = WordGenerator::parse("CV", &inventory)
+            .unwrap()
+            .with_syllable_counts(SyllableCounts::from_str("1:1,2:1").unwrap());
+        assert_eq!(8 + 64, gen.possible_words());
+        assert!((gen.entropy_bits() - 5.5).abs() < 1e-9);
+
+        // Weights lower the entropy
+        let choices = [phone::Vowel::A, phone::Vowel::E]
+            .iter()
+            .map(|x| (*x).into())
+            .collect();
+        let weighted = PhonemeGenerator::new("V", choices, smallvec::smallvec![3, 1]).unwrap();
+        assert!(weighted.entropy_bits(OPTIONAL_PROBABILITY) < 1.0);
+    }
+
+    #[test]
+    fn stress() {
+        use rand::SeedableRng;
+        use std::str::FromStr;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x5eed);
+        let inventory = phone::Inventory::with_everything();
+        let mut stressed = |rule: &str, pattern: &str| {
+            let gen = WordGenerator::parse(pattern, &inventory)
+                .unwrap()
+                .with_stress(StressRule::from_str(rule).unwrap());
+            let word = gen.generate(&mut rng);
+            word.iter()
+                .map(|syl| syl.stress() == phone::Stress::Primary)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(vec![false], stressed("initial", "CV"));
+        assert_eq!(vec![true, false, false], stressed("initial", "CV CV CV"));
+        assert_eq!(vec![false, false, true], stressed("final", "CV CV CV"));
+        assert_eq!(
+            vec![false, true, false],
+            stressed("penultimate", "CV CV CV")
+        );
+        assert_eq!(
+            vec![false, true, false, false],
+            stressed("antepenultimate", "CV CV CV CV")
+        );
+        assert_eq!(
+            vec![false, true, false],
+            stressed("weight-sensitive", "CV CVC CV")
+        );
+        assert_eq!(
+            vec![true, false, false],
+            stressed("weight-sensitive", "CV CV CV")
+        );
+        assert_eq!(vec![true, false], stressed("weight-sensitive", "CV CV"));
+
+        let gen = WordGenerator::parse("CV CV", &inventory)
+            .unwrap()
+            .with_stress(StressRule::Initial);
+        assert!(gen.generate(&mut rng).iter().join(" ").starts_with('ˈ'));
+        assert!(gen
+            .enumerate()
+            .all(|w| w[0].stress() == phone::Stress::Primary));
+    }
+
+    #[test]
+    fn tones() {
+        use rand::SeedableRng;
+        use std::str::FromStr;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x5eed);
+        let inventory = phone::Inventory::with_everything();
+        let high_falling = phone::Tone::from_str("51").unwrap();
+        let tones = Tones::from_str("55:3, 35:2, ˥˩:1:1").unwrap();
+        assert_eq!(Some(1), tones.limit(high_falling));
+        assert_eq!("˥˥:3,˧˥:2,˥˩:1:1", tones.to_string());
+        assert!(Tones::from_str("55:x").is_err());
+        assert!(Tones::from_str("55:0").is_err());
+
+        let gen = WordGenerator::parse("CV CV CV CV", &inventory)
+            .unwrap()
+            .with_tones(tones);
+        for _ in 0..100 {
+            let word = gen.generate(&mut rng);
+            assert!(word.iter().all(|syl| syl.tone().is_some()));
+            assert!(
+                word.iter()
+                    .filter(|syl| syl.tone() == Some(high_falling))
+                    .count()
+                    <= 1
+            );
+        }
+
+        // Once the only tone is used up, the rest of the word has no tone
+        let gen = gen.with_tones(Tones::from_str("51::1").unwrap());
+        let word = gen.generate(&mut rng);
+        assert_eq!(Some(high_falling), word[0].tone());
+        assert!(word[1..].iter().all(|syl| syl.tone().is_none()));
+    }
+
+    #[test]
+    fn positions() {
+        use rand::SeedableRng;
+        use std::str::FromStr;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x5eed);
+        let inventory = phone::Inventory::new(
+            &[
+                phone::Consonant::P,
+                phone::Consonant::H,
+                phone::Consonant::NVelar,
+            ][..],
+            &[phone::Vowel::A][..],
+            &[][..],
+        );
+        let positions = Positions::from_str("initial=ph medial=pŋ coda=pŋ").unwrap();
+        assert_eq!("initial=ph medial=pŋ coda=pŋ", positions.to_string());
+        assert!(!positions.allows(Position::Initial, phone::Consonant::NVelar.into()));
+        assert!(Positions::from_str("nucleus=a").is_err());
+        assert!(Positions::from_str("").is_err());
+
+        let gen = WordGenerator::parse("CVC CVC", &inventory)
+            .unwrap()
+            .with_positions(positions.clone());
+        for _ in 0..1000 {
+            let word = gen.generate(&mut rng);
+            assert!(positions.accepts(&word), "{}", word.iter().join(" "));
+        }
+
+        // 2 initials × 2 codas × 2 medials × 2 codas
+        let words: Vec<_> = gen.enumerate().collect();
+        assert_eq!(16, words.len());
+        assert!(words.iter().all(|w| positions.accepts(w)));
+    }
+
+    #[test]
+    fn reduplication() {
+        use std::str::FromStr;
+
+        let word = |src: &str, reduplication: &str| {
+            let mut word = phone::Word::from_str(src).unwrap();
+            Reduplication::from_str(reduplication)
+                .unwrap()
+                .apply(&mut word);
+            word.to_string()
+        };
+        assert_eq!("ta.kan.ta.kan", word("ta.kan", "σσ"));
+        assert_eq!("ta.ta.kan", word("ta.kan", "syllable"));
+        assert_eq!("su.su.lat", word("su.lat", "CV-"));
+        assert_eq!("ta.tra.ko", word("tra.ko", "cv"));
+        assert_eq!("a.an", word("an", "cv"));
+        assert_eq!("pst", word("pst", "cv"));
+        assert!(Reduplication::from_str("σσσ").is_err());
+
+        let inventory = phone::Inventory::new(
+            &[phone::Consonant::P, phone::Consonant::T][..],
+            &[phone::Vowel::A][..],
+            &[][..],
+        );
+        let gen = WordGenerator::parse("CVC", &inventory)
+            .unwrap()
+            .with_reduplication(Reduplication::Full)
+            .with_stress(StressRule::Initial)
+            .with_filter(Filter::Regex(regex::Regex::new("tat").unwrap()));
+        let words: Vec<_> = gen.enumerate().map(|w| w.to_string()).collect();
+        assert_eq!(vec!["ˈpap.pap", "ˈpat.pat", "ˈtap.tap"], words);
+        let word = gen.generate(&mut rand::thread_rng());
+        assert_eq!(2, word.len());
+        assert_eq!(Some("CVC"), word.pattern(0));
+        assert_eq!(None, word.pattern(1));
+    }
+
+    #[test]
+    fn families() {
+        let root = phone::Word::from_str("ˈta.kan").unwrap();
+        let processes: Vec<Process> = ["-ip", "ma-", "a>u, i>e"]
+            .iter()
+            .map(|x| x.parse().unwrap())
+            .collect();
+        assert_eq!("a>u,i>e", processes[2].to_string());
+        let family: Vec<_> = derive_family(&root, &processes)
+            .iter()
+            .map(|w| w.to_string())
+            .collect();
+        assert_eq!(vec!["ˈta.kan.ip", "maˈta.kan", "ˈtu.kun"], family);
+
+        for bad in ["-", "!-", "a>", "a>p", "ta"] {
+            assert!(Process::from_str(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn style() {
+        let inventory = phone::Inventory::new(
+            &[phone::Consonant::P, phone::Consonant::N][..],
+            &[phone::Vowel::A][..],
+            &[][..],
+        );
+        let style = Style::from_str("sonorant:3, coda:0.5").unwrap();
+        assert_eq!("sonorant:3,coda:0.5", style.to_string());
+        assert_eq!(3.0, style.multiplier(phone::Consonant::N.into()));
+        assert_eq!(1.0, style.multiplier(phone::Vowel::A.into()));
+        assert_eq!("elvish", Style::from_str("elvish").unwrap().to_string());
+        for bad in ["", "sonorant", "sonorant:-1", "nasal:2", "harsh:2"] {
+            assert!(Style::from_str(bad).is_err(), "{bad}");
+        }
+
+        let gen = WordGenerator::parse("CV(C)", &inventory)
+            .unwrap()
+            .with_style(&style);
+        let onset = &gen.syllables[0].phonemes[0];
+        let weights: Vec<_> = onset
+            .choices
+            .iter()
+            .zip(onset.weights.iter())
+            .map(|(x, w)| (x.to_string(), *w))
+            .collect();
+        assert_eq!(vec![("p".to_string(), 85), ("n".to_string(), 255)], weights);
+        // The vowel has no features in the style, so it stays uniform
+        assert!(gen.syllables[0].phonemes[1].weights.is_empty());
+        // Halving the odds of an even chance leaves a third
+        let coda = gen.syllables[0].phonemes[2].inclusion(gen.optional_probability());
+        assert!((coda - 1.0 / 3.0).abs() < 1e-9, "{coda}");
+
+        // A slot with nothing left is not changed
+        let gen = WordGenerator::parse("N", &inventory)
+            .unwrap()
+            .with_style(&Style::new([(Feature::Sonorant, 0.0)]));
+        assert!(gen.syllables[0].phonemes[0].weights.is_empty());
+    }
+
+    #[test]
+    fn nth() {
+        let inventory = phone::Inventory::new(
+            &[phone::Consonant::P, phone::Consonant::T][..],
+            &[phone::Vowel::A][..],
+            &[][..],
+        );
+        let gen = WordGenerator::parse("CV(C) C₁V C₁V", &inventory)
+            .unwrap()
+            .with_stress(StressRule::Initial);
+        let count = gen.count();
+        assert_eq!(12, count);
+        let indexed: Vec<_> = (0..count).map(|i| gen.nth(i).unwrap()).collect();
+        assert_eq!(gen.enumerate().collect::<Vec<_>>(), indexed);
+        assert_eq!(None, gen.nth(count));
+
+        // Rejected words keep their index
+        let gen = gen.with_filter(Filter::NoInitial(phone::Consonant::T.into()));
+        assert_eq!(None, gen.nth(6));
+        assert_eq!(Some(&indexed[5]), gen.nth(5).as_ref());
+        let indexed: Vec<_> = (0..count).filter_map(|i| gen.nth(i)).collect();
+        assert_eq!(gen.enumerate().collect::<Vec<_>>(), indexed);
+    }
+
+    #[test]
+    fn rulesets() {
+        let inventory =
+            phone::Inventory::new(&[phone::Consonant::P][..], &[phone::Vowel::A][..], &[][..]);
+        let rules = Ruleset::from_str(
+            "# a comment
+            prefix = CV
+            stem = CVC
+            outer = prefix? CV
+            word = prefix? stem
+            nested = outer? stem",
+        )
+        .unwrap();
+        assert_eq!(Ruleset::from_str(&rules.to_string()).unwrap(), rules);
+
+        let gen = rules.compile("word", &inventory).unwrap();
+        assert_eq!("(CV)? CVC", gen.to_pattern_string());
+        let words: Vec<_> = gen.enumerate().map(|w| w.to_string()).collect();
+        assert_eq!(vec!["pap", "pa.pap"], words);
+
+        // Leaving out `outer` leaves nothing to decide for the `prefix` inside it
+        let gen = rules.compile("nested", &inventory).unwrap();
+        assert_eq!("((CV)? CV)? CVC", gen.to_pattern_string());
+        assert_eq!(3, gen.count());
+        let total: f64 = gen.template_probabilities().map(|(_, p)| p).sum();
+        assert!((total - 1.0).abs() < 1e-9, "{total}");
+
+        assert!(matches!(
+            rules.compile("suffix", &inventory),
+            Err(ParseError::UnknownRule(_))
+        ));
+        let looped = Ruleset::new().with_rule("a", "CV b?").with_rule("b", "a");
+        assert!(matches!(
+            looped.compile("a", &inventory),
+            Err(ParseError::RecursiveRule(_))
+        ));
+        for bad in ["word", "Word = CV", "word =", "a = CV\na = V"] {
+            assert!(Ruleset::from_str(bad).is_err(), "{bad}");
+        }
+        let err = Ruleset::new()
+            .with_rule("stem", "CV C!")
+            .compile("stem", &inventory)
+            .unwrap_err();
+        assert!(
+            matches!(err, ParseError::Pattern { position: 4, .. }),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn weighted_patterns() {
+        let patterns = WeightedPattern::parse_list(
+            "# common shapes first
+            CV = 5
+            CVC=2
+
+            word",
+        )
+        .unwrap();
+        assert_eq!(
+            vec![
+                WeightedPattern::new("CV", 5),
+                WeightedPattern::new("CVC", 2),
+                WeightedPattern::new("word", 1),
+            ],
+            patterns
+        );
+        assert_eq!("CV = 5", patterns[0].to_string());
+        assert_eq!("word", patterns[2].to_string());
+        for bad in ["CV = 0", "CV = x", "CV =", "= 3"] {
+            assert!(bad.parse::<WeightedPattern>().is_err(), "{bad}");
+        }
+        assert!(matches!(
+            WeightedPattern::parse_list("# nothing\n"),
+            Err(ParseError::NoInput)
+        ));
+    }
+
+    #[test]
+    fn unsatisfiable() {
+        let inventory = phone::Inventory::new(
+            &[phone::Consonant::P, phone::Consonant::T][..],
+            &[phone::Vowel::A][..],
+            &[][..],
+        );
+        let gen = WordGenerator::parse("CV", &inventory)
+            .unwrap()
+            .with_filter(Filter::NoInitial(phone::Consonant::P.into()))
+            .with_filter(Filter::NoFinal(phone::Vowel::A.into()))
+            .with_max_attempts(50);
+        assert_eq!(50, gen.max_attempts());
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x5eed);
+        let err = gen.try_generate(&mut rng).unwrap_err();
+        let GenerateError::Unsatisfiable {
+            attempts,
+            rejections,
+        } = &err
+        else {
+            panic!("{err:?}");
+        };
+        assert_eq!(50, *attempts);
+        assert_eq!(2, rejections.len());
+        assert_eq!(("filter no-final a".to_string(), 50), rejections[0]);
+        assert_eq!(
+            "no word passed the filters in 50 attempts (filter no-final a rejected 50)",
+            err.to_string()
+        );
+
+        let gen = WordGenerator::parse("CV", &inventory).unwrap();
+        assert_eq!(MAX_ATTEMPTS, gen.max_attempts());
+        assert!(gen.try_generate(&mut rng).is_ok());
+    }
+
+    #[test]
+    fn minimal_pairs() {
+        let inventory = phone::Inventory::new(
+            &[phone::Consonant::P, phone::Consonant::T][..],
+            &[phone::Vowel::A, phone::Vowel::I][..],
+            &[][..],
+        );
+        let gen = WordGenerator::parse("CV", &inventory).unwrap();
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x5eed);
+        // pa, pi, ta, and ti make four pairs, but no more
+        let pairs = gen.minimal_pairs(&mut rng, 10);
+        assert_eq!(4, pairs.len());
+        for (a, b) in pairs.iter() {
+            let differences = a.phonemes().zip(b.phonemes()).filter(|(x, y)| x != y);
+            assert_eq!(1, differences.count(), "{a} {b}");
+        }
+        assert_eq!(2, gen.minimal_pairs(&mut rng, 2).len());
+
+        let gen = gen.with_filter(Filter::NoInitial(phone::Consonant::T.into()));
+        let pairs = gen.minimal_pairs(&mut rng, 10);
+        assert_eq!(1, pairs.len());
+        assert!(pairs[0]
+            .0
+            .phonemes()
+            .all(|ph| ph != phone::Consonant::T.into()));
+    }
+
+    #[test]
+    fn from_str() {
+        let gen = WordGenerator::from_str("CV(N)").unwrap();
+        let everything = phone::Inventory::with_everything();
+        assert_eq!(gen, WordGenerator::parse("CV(N)", &everything).unwrap());
+        assert_eq!(
+            gen,
+            WordGenerator::parse_with("CV(N)", &everything).unwrap()
+        );
+        assert_eq!(
+            (phone::Consonant::all().len() * phone::Vowel::all().len()) as u128,
+            "CV".parse::<WordGenerator>().unwrap().count()
+        );
+        assert!(matches!(
+            WordGenerator::from_str("").unwrap_err(),
+            ParseError::NoInput
+        ));
+    }
+
+    #[test]
+    fn lint() {
+        use std::str::FromStr;
+
+        let inventory = phone::Inventory::new(
+            &[
+                phone::Consonant::P,
+                phone::Consonant::H,
+                phone::Consonant::NVelar,
+            ][..],
+            &[phone::Vowel::A][..],
+            &[][..],
+        );
+        let gen = WordGenerator::parse("CV NV(C)?0", &inventory)
+            .unwrap()
+            .with_positions(Positions::from_str("initial=p").unwrap());
+        let lints = gen.lint(&inventory);
+        assert_eq!(2, lints.len(), "{lints:?}");
+        assert_eq!("C in CV never produces /h ŋ/", lints[0].to_string());
+        assert_eq!(
+            Lint::Unreachable {
+                syllable: "NV(C)?0".into(),
+                slot: "(C)?0".into(),
+                phonemes: gen.syllables[1].phonemes[2].choices.to_vec(),
+            },
+            lints[1]
+        );
+
+        let without_nasals =
+            phone::Inventory::new(&[phone::Consonant::P][..], &[phone::Vowel::A][..], &[][..]);
+        assert!(gen.lint(&without_nasals).contains(&Lint::EmptyClass {
+            syllable: "NV(C)?0".into(),
+            slot: "N".into(),
+        }));
+
+        let gen = gen.with_filter(Filter::NoInitial(phone::Consonant::P.into()));
+        assert_eq!(Some(&Lint::RejectsEverything), gen.lint(&inventory).last());
+
+        let gen = WordGenerator::parse("CV", &inventory).unwrap();
+        assert!(gen.lint(&inventory).is_empty());
+        assert_eq!(None, gen.lint_count(3));
+        assert_eq!(
+            Some(Lint::TooFewWords {
+                possible: 3,
+                requested: 100
+            }),
+            gen.lint_count(100)
+        );
+    }
+
+    /// Sample `gen` many times, returning how often each of its choices was picked.
+    fn frequencies(gen: &PhonemeGenerator) -> Vec<f64> {
+        use rand::SeedableRng;
+
+        const SAMPLES: usize = 100_000;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x5eed);
+        let mut counts = vec![0usize; gen.choices.len()];
+        for _ in 0..SAMPLES {
+            let ph = gen.generate(&mut rng);
+            let idx = gen.choices.iter().position(|x| *x == ph).unwrap();
+            counts[idx] += 1;
+        }
+        counts
+            .into_iter()
+            .map(|c| c as f64 / SAMPLES as f64)
+            .collect()
+    }
+
+    #[test]
+    fn uniform_distribution() {
+        let inventory = phone::Inventory::with_everything();
+        let (gen, _) = PhonemeGenerator::parse("V", &inventory).unwrap();
+        let expected = 1.0 / gen.choices.len() as f64;
+        for freq in frequencies(&gen) {
+            assert!(
+                (freq - expected).abs() < 0.005,
+                "{freq} vs expected {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn weighted_distribution() {
+        let choices = [phone::Vowel::A, phone::Vowel::E, phone::Vowel::I]
+            .iter()
+            .map(|x| (*x).into())
+            .collect();
+        let gen = PhonemeGenerator::new("V", choices, smallvec::smallvec![50, 30, 20]).unwrap();
+        for (freq, expected) in frequencies(&gen).into_iter().zip([0.5, 0.3, 0.2]) {
+            assert!(
+                (freq - expected).abs() < 0.01,
+                "{freq} vs expected {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn inventory_weights() {
+        let inventory = phone::Inventory::new(
+            &[
+                phone::Consonant::P,
+                phone::Consonant::T,
+                phone::Consonant::TRetroflex,
+            ][..],
+            &[phone::Vowel::A][..],
+            &[][..],
+        )
+        .with_weight(phone::Consonant::P, 6)
+        .with_weight(phone::Consonant::TRetroflex, 3);
+        let (gen, _) = PhonemeGenerator::parse("C", &inventory).unwrap();
+        for (freq, expected) in frequencies(&gen).into_iter().zip([0.6, 0.1, 0.3]) {
+            assert!(
+                (freq - expected).abs() < 0.01,
+                "{freq} vs expected {expected}"
+            );
+        }
+
+        // Literal phonemes are not classes, so they are not weighted
+        let (gen, _) = PhonemeGenerator::parse("[pt]", &inventory).unwrap();
+        assert!(gen.weights.is_empty());
+        let (gen, _) = PhonemeGenerator::parse("Ḍ", &inventory).unwrap();
+        assert_eq!("Ḍ", gen.to_string());
+        assert_eq!(&[3][..], &gen.weights[..]);
+        let (gen, _) = PhonemeGenerator::parse("{C-[t]}", &inventory).unwrap();
+        assert_eq!(&[6, 3][..], &gen.weights[..]);
+
+        let (gen, _) = PhonemeGenerator::parse("C", &inventory.without_weights()).unwrap();
+        assert!(gen.weights.is_empty());
+    }
+
+    #[test]
+    fn strata() {
+        let inventory = phone::Inventory::new(
+            &[phone::Consonant::P, phone::Consonant::T][..],
+            &[phone::Vowel::A][..],
+            &[][..],
+        )
+        .with_stratum(
+            "ono",
+            phone::Inventory::new(
+                &[phone::Consonant::F][..],
+                &[phone::Vowel::U][..],
+                &[phone::NonPulmonicConsonant::BilabialClick][..],
+            ),
+        );
+        let gen = WordGenerator::parse("[p]V K@ono{V+V@ono}(C@ono)", &inventory).unwrap();
+        assert_eq!("[p]V K@ono{V+V@ono}(C@ono)", gen.to_pattern_string());
+        assert_eq!(
+            1,
+            WordGenerator::parse("K@onoV@ono", &inventory)
+                .unwrap()
+                .count()
+        );
+        let words: Vec<_> = gen.enumerate().map(|w| w.iter().join(".")).collect();
+        assert_eq!(vec!["pa.ʘaf", "pa.ʘa", "pa.ʘuf", "pa.ʘu"], words);
+        let gen = WordGenerator::parse("{V+V@ono}@ono", &inventory).unwrap();
+        assert_eq!(
+            vec!["u"],
+            gen.enumerate()
+                .map(|w| w.iter().join("."))
+                .collect::<Vec<_>>()
+        );
+        assert!(gen.lint(&inventory).is_empty());
+
+        assert!(matches!(
+            WordGenerator::parse("C@loan", &inventory).unwrap_err().kind(),
+            ParseError::UnknownStratum(name) if name == "loan"
+        ));
+        assert!(matches!(
+            WordGenerator::parse("K", &inventory).unwrap_err().kind(),
+            ParseError::EmptyClass(_)
+        ));
+    }
+
+    #[test]
+    fn diagnostics() {
+        let inventory = phone::Inventory::with_everything();
+        let error = |src: &str| match WordGenerator::parse(src, &inventory).unwrap_err() {
+            ParseError::Pattern {
+                pattern,
+                position,
+                suggestion,
+                ..
+            } => (pattern, position, suggestion),
+            e => panic!("{e} has no position"),
+        };
+
+        let (pattern, position, suggestion) = error("CV CnV");
+        assert_eq!("CV CnV", pattern);
+        assert_eq!(4, position);
+        assert_eq!(
+            Some("did you mean class 'N' (nasal)?"),
+            suggestion.as_deref()
+        );
+
+        // Positions count characters, so they line up under IPA
+        assert_eq!(
+            (9, Some("write \\ʃ or [ʃ] for the phoneme /ʃ/".into())),
+            (error("[ŋ]V [ʃ]Vʃ").1, error("[ŋ]V [ʃ]Vʃ").2)
+        );
+        assert_eq!(6, error("CV [pq!]").1);
+        assert_eq!(2, error("CV(C").1);
+        assert_eq!(
+            "unrecognized character: 'n' at position 4 of \"CV CnV\"",
+            WordGenerator::parse("CV CnV", &inventory)
+                .unwrap_err()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn empty_class() {
+        let inventory =
+            phone::Inventory::new(&[phone::Consonant::P][..], phone::Vowel::all(), &[][..]);
+        assert!(matches!(
+            WordGenerator::parse("QV", &inventory).unwrap_err().kind(),
+            ParseError::EmptyClass(_)
+        ));
+    }
+
+    #[test]
+    fn random_inventories() {
+        use rand::SeedableRng;
+
+        let inventory = |seed| {
+            let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+            random_inventory(&mut rng, 18, 5)
+        };
+        for seed in 0..20 {
+            let inventory = inventory(seed);
+            assert_eq!(18, inventory.consonants().len());
+            assert_eq!(5, inventory.vowels().len());
+            assert!(inventory.non_pulmonic_consonants().is_empty());
+            // Marked phonemes only come with the unmarked ones they depend on
+            let has = |code| {
+                let ph = phone::Phoneme::try_from(code).unwrap();
+                inventory
+                    .consonants()
+                    .iter()
+                    .any(|c| phone::Phoneme::from(*c) == ph)
+                    || inventory
+                        .vowels()
+                        .iter()
+                        .any(|v| phone::Phoneme::from(*v) == ph)
+            };
+            assert!(!has('b') || has('p'));
+            assert!(!has('ɖ') || has('ʈ'));
+            assert!(!has('ø') || has('y'));
+        }
+        assert_eq!(
+            inventory(0x5eed).consonants(),
+            inventory(0x5eed).consonants()
+        );
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x5eed);
+        let everything = random_inventory(&mut rng, 1000, 1000);
+        assert_eq!(phone::Consonant::all(), everything.consonants());
+        assert_eq!(phone::Vowel::all(), everything.vowels());
+    }
+
+    #[test]
+    fn phoneme_frequency() {
+        let ph = |code| phone::Phoneme::try_from(code).unwrap();
+        assert!(frequency(ph('m')) > frequency(ph('ʃ')));
+        assert!(frequency(ph('i')) > frequency(ph('ø')));
+        assert_eq!(None, frequency(ph('ɓ')));
+        for c in phone::Consonant::all() {
+            assert!(frequency((*c).into()).is_some(), "{c} has no frequency");
         }
     }
 }
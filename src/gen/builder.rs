@@ -0,0 +1,162 @@
+//! # Builder
+//!
+//! Patterns are convenient to type, but awkward to assemble in code. A [`WordGeneratorBuilder`] puts together the same
+//! generators one syllable at a time, like `WordGenerator::builder().syllable(|s| s.class(Class::Consonants))`.
+
+use super::{ParseError, PhonemeGenerator, SyllableGenerator, WordGenerator};
+use crate::phone;
+use itertools::Itertools;
+use smallvec::SmallVec;
+
+/// A set of phonemes for one slot of a syllable. Classes are resolved against the builder's inventory when the
+/// generator is built.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Class {
+    /// Any consonant in the inventory, like `C` in a pattern.
+    Consonants,
+    /// Any vowel in the inventory, like `V` in a pattern.
+    Vowels,
+    /// Any click or implosive in the inventory, like `K` in a pattern.
+    NonPulmonic,
+    /// The consonants in the inventory with the place of articulation.
+    Place(phone::Place),
+    /// The consonants in the inventory with the manner of articulation.
+    Manner(phone::Manner),
+    /// Exactly these phonemes, whether or not they are in the inventory, like `[ptk]` in a pattern.
+    Phonemes(Vec<phone::Phoneme>),
+}
+
+impl Class {
+    fn resolve(&self, inventory: &phone::Inventory) -> Result<PhonemeGenerator, ParseError> {
+        let consonants = |f: &dyn Fn(&phone::Consonant) -> bool| -> SmallVec<[phone::Phoneme; 8]> {
+            inventory
+                .consonants()
+                .iter()
+                .filter(|x| f(x))
+                .map(|x| (*x).into())
+                .collect()
+        };
+        let (display, choices) = match self {
+            Self::Consonants => ("C".to_string(), consonants(&|_| true)),
+            Self::Vowels => (
+                "V".to_string(),
+                inventory.vowels().iter().map(|x| (*x).into()).collect(),
+            ),
+            Self::NonPulmonic => (
+                "K".to_string(),
+                inventory
+                    .non_pulmonic_consonants()
+                    .iter()
+                    .map(|x| (*x).into())
+                    .collect(),
+            ),
+            Self::Place(place) => {
+                let choices = consonants(&|x| x.place() == *place);
+                (format!("[{}]", choices.iter().join("")), choices)
+            }
+            Self::Manner(manner) => {
+                let choices = consonants(&|x| x.manner() == *manner);
+                (format!("[{}]", choices.iter().join("")), choices)
+            }
+            Self::Phonemes(phonemes) => (
+                format!("[{}]", phonemes.iter().join("")),
+                phonemes.iter().copied().collect(),
+            ),
+        };
+        // Report place and manner classes by name, since the display of an empty set is just `[]`
+        let display = match (self, choices.is_empty()) {
+            (Self::Place(place), true) => format!("{place:?}"),
+            (Self::Manner(manner), true) => format!("{manner:?}"),
+            _ => display,
+        };
+        match self {
+            Self::Phonemes(_) => PhonemeGenerator::new(display, choices, SmallVec::new()),
+            _ => PhonemeGenerator::from_class(display, choices, inventory),
+        }
+    }
+}
+
+/// Builds a [`WordGenerator`] one syllable at a time. Created by [`WordGenerator::builder`].
+///
+/// Classes are drawn from [`phone::Inventory::with_everything`] unless another inventory is given.
+#[derive(Clone, Debug)]
+pub struct WordGeneratorBuilder {
+    inventory: phone::Inventory,
+    syllables: Vec<SyllableBuilder>,
+}
+
+impl WordGeneratorBuilder {
+    pub fn new() -> Self {
+        Self {
+            inventory: phone::Inventory::with_everything(),
+            syllables: Vec::new(),
+        }
+    }
+
+    /// Draw classes from the `inventory`.
+    pub fn inventory(mut self, inventory: phone::Inventory) -> Self {
+        self.inventory = inventory;
+        self
+    }
+
+    /// Add a syllable to the end of the word, built by `f`.
+    pub fn syllable(mut self, f: impl FnOnce(SyllableBuilder) -> SyllableBuilder) -> Self {
+        self.syllables.push(f(SyllableBuilder::default()));
+        self
+    }
+
+    /// Build the generator. This fails if there are no syllables, a syllable has no slots, or a class has no phonemes in
+    /// the inventory.
+    pub fn build(self) -> Result<WordGenerator, ParseError> {
+        let mut syllables = SmallVec::new();
+        for syl in self.syllables.iter() {
+            let mut phonemes = SmallVec::new();
+            for (class, optional) in syl.slots.iter() {
+                let ph = class.resolve(&self.inventory)?;
+                phonemes.push(if *optional { ph.into_optional() } else { ph });
+            }
+            if phonemes.is_empty() {
+                return Err(ParseError::NoInput);
+            }
+            syllables.push(SyllableGenerator::from(phonemes));
+        }
+
+        if syllables.is_empty() {
+            Err(ParseError::NoInput)
+        } else {
+            Ok(WordGenerator::from_syllables(syllables))
+        }
+    }
+}
+
+impl Default for WordGeneratorBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Builds one syllable of a [`WordGeneratorBuilder`], slot by slot.
+#[derive(Clone, Debug, Default)]
+pub struct SyllableBuilder {
+    /// Each slot's class and if it is optional.
+    slots: Vec<(Class, bool)>,
+}
+
+impl SyllableBuilder {
+    /// Add a slot which picks from the `class`.
+    pub fn class(mut self, class: Class) -> Self {
+        self.slots.push((class, false));
+        self
+    }
+
+    /// Add a slot which is always the `phoneme`.
+    pub fn phoneme(self, phoneme: impl Into<phone::Phoneme>) -> Self {
+        self.class(Class::Phonemes(vec![phoneme.into()]))
+    }
+
+    /// Add a slot which picks from the `class` or is left out, like `(C)` in a pattern.
+    pub fn optional(mut self, class: Class) -> Self {
+        self.slots.push((class, true));
+        self
+    }
+}
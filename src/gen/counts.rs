@@ -0,0 +1,124 @@
+//! # Syllable Counts
+//!
+//! Real lexicons have words of varying lengths: lots of one- and two-syllable words, fewer long ones. A
+//! [`SyllableCounts`] describes how likely each word length is.
+
+use super::ParseError;
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    Rng,
+};
+use std::{fmt, str::FromStr};
+
+/// A distribution over the number of syllables in a word.
+///
+/// This can be parsed from a list of `count:weight` pairs separated by commas, like `1:20,2:50,3:25,4:5`. Weights are
+/// relative, so they do not need to add up to 100, and a trailing `%` is allowed.
+#[derive(Clone)]
+pub struct SyllableCounts {
+    /// Pairs of syllable count and weight, sorted by count.
+    counts: Vec<(usize, u32)>,
+    dist: WeightedIndex<u32>,
+}
+
+impl SyllableCounts {
+    /// Create a distribution from pairs of syllable count and weight. Returns `None` if there are no counts, a count is
+    /// 0, or all the weights are 0.
+    pub fn new(counts: impl IntoIterator<Item = (usize, u32)>) -> Option<Self> {
+        let mut counts: Vec<_> = counts
+            .into_iter()
+            .filter(|(_, weight)| *weight > 0)
+            .collect();
+        counts.sort();
+        if counts.iter().any(|(count, _)| *count == 0) {
+            return None;
+        }
+        counts.dedup_by(|b, a| {
+            if a.0 == b.0 {
+                a.1 += b.1;
+                true
+            } else {
+                false
+            }
+        });
+
+        let dist = WeightedIndex::new(counts.iter().map(|(_, weight)| *weight)).ok()?;
+        Some(Self { counts, dist })
+    }
+
+    /// The syllable counts with a non-zero chance of being picked, from shortest to longest.
+    pub fn counts(&self) -> impl Iterator<Item = usize> + '_ {
+        self.counts.iter().map(|(count, _)| *count)
+    }
+
+    /// The chance that a word has `count` syllables.
+    pub fn probability(&self, count: usize) -> f64 {
+        let total: u32 = self.counts.iter().map(|(_, weight)| *weight).sum();
+        self.counts
+            .iter()
+            .find(|(c, _)| *c == count)
+            .map_or(0.0, |(_, weight)| f64::from(*weight) / f64::from(total))
+    }
+
+    /// Keep only the syllable counts from `min` to `max`, where a missing bound does not limit them. Returns `None` if
+    /// no count is in the range.
+    pub fn within(&self, min: Option<usize>, max: Option<usize>) -> Option<Self> {
+        Self::new(self.counts.iter().copied().filter(|(count, _)| {
+            min.is_none_or(|min| *count >= min) && max.is_none_or(|max| *count <= max)
+        }))
+    }
+
+    pub fn sample(&self, rng: &mut impl Rng) -> usize {
+        self.counts[self.dist.sample(rng)].0
+    }
+}
+
+impl PartialEq for SyllableCounts {
+    fn eq(&self, other: &Self) -> bool {
+        self.counts == other.counts
+    }
+}
+
+impl FromStr for SyllableCounts {
+    type Err = ParseError;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseError::InvalidSyllableCounts(src.into());
+        if src.trim().is_empty() {
+            return Err(ParseError::NoInput);
+        }
+
+        let mut counts = Vec::new();
+        for pair in src.split(',') {
+            let (count, weight) = pair.split_once(':').ok_or_else(invalid)?;
+            let count = count.trim().parse().map_err(|_| invalid())?;
+            let weight = weight
+                .trim()
+                .trim_end_matches('%')
+                .parse()
+                .map_err(|_| invalid())?;
+            counts.push((count, weight));
+        }
+        Self::new(counts).ok_or_else(invalid)
+    }
+}
+
+impl fmt::Display for SyllableCounts {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (idx, (count, weight)) in self.counts.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(",")?;
+            }
+            write!(f, "{count}:{weight}")?;
+        }
+        Ok(())
+    }
+}
+
+serde_via_str!(SyllableCounts);
+
+impl fmt::Debug for SyllableCounts {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SyllableCounts({self})")
+    }
+}
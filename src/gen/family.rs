@@ -0,0 +1,119 @@
+//! # Families
+//!
+//! Words in a language are not all unrelated: a verb, the noun for someone who does it, and the adjective for something
+//! which has been done are often built from the same root. [`derive_family`] applies morphological [`Process`]es to a
+//! generated root so the forms share their sounds the way real word families do.
+
+use super::ParseError;
+use crate::phone;
+use std::{fmt, str::FromStr};
+
+/// A way of deriving one word from another.
+///
+/// Processes can be parsed from a short syntax, where affixes are written with a hyphen on the side that attaches to
+/// the root:
+///
+/// | Syntax      | Process                                      |
+/// |:------------|:---------------------------------------------|
+/// | `-ta`       | [`Process::Suffix`] /ta/                     |
+/// | `ka.ma-`    | [`Process::Prefix`] /ka.ma/                  |
+/// | `a>u,i>e`   | [`Process::Ablaut`] from /a/ to /u/ and /i/ to /e/ |
+#[derive(Clone, Debug, PartialEq)]
+pub enum Process {
+    /// Add syllables after the root.
+    Suffix(phone::Word),
+    /// Add syllables before the root.
+    Prefix(phone::Word),
+    /// Change the vowels of the root, like English *sing* and *sang*. Vowels without a replacement are left alone.
+    Ablaut(Vec<(phone::Vowel, phone::Vowel)>),
+}
+
+impl Process {
+    /// Derive a word from the `root`.
+    pub fn apply(&self, root: &phone::Word) -> phone::Word {
+        match self {
+            Self::Suffix(suffix) => root.iter().chain(suffix.iter()).cloned().collect(),
+            Self::Prefix(prefix) => prefix.iter().chain(root.iter()).cloned().collect(),
+            Self::Ablaut(changes) => {
+                let mut out = root.clone();
+                for syl in out.syllables_mut() {
+                    let parts: Vec<_> = syl
+                        .parts()
+                        .iter()
+                        .map(|ph| match ph {
+                            phone::Phoneme::Vowel(v) => changes
+                                .iter()
+                                .find(|(from, _)| from == v)
+                                .map_or(*ph, |(_, to)| (*to).into()),
+                            _ => *ph,
+                        })
+                        .collect();
+                    let mut changed = phone::Syllable::new(&parts).with_stress(syl.stress());
+                    changed.set_tone(syl.tone());
+                    *syl = changed;
+                }
+                out
+            }
+        }
+    }
+}
+
+/// Derive a word from the `root` with each of the `processes`, in order. Each process is applied to the root on its
+/// own; to stack processes, apply them to a derived word.
+pub fn derive_family(root: &phone::Word, processes: &[Process]) -> Vec<phone::Word> {
+    processes
+        .iter()
+        .map(|process| process.apply(root))
+        .collect()
+}
+
+impl FromStr for Process {
+    type Err = ParseError;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseError::InvalidProcess(src.into());
+        let src = src.trim();
+        if src.is_empty() {
+            return Err(ParseError::NoInput);
+        }
+
+        let affix = |syllables: &str| match syllables.parse::<phone::Word>() {
+            Ok(word) if !word.is_empty() => Ok(word),
+            _ => Err(invalid()),
+        };
+        if let Some(suffix) = src.strip_prefix('-') {
+            Ok(Self::Suffix(affix(suffix)?))
+        } else if let Some(prefix) = src.strip_suffix('-') {
+            Ok(Self::Prefix(affix(prefix)?))
+        } else {
+            let mut changes = Vec::new();
+            for change in src.split(',') {
+                let (from, to) = change.split_once('>').ok_or_else(invalid)?;
+                let from = from.trim().parse().map_err(|_| invalid())?;
+                let to = to.trim().parse().map_err(|_| invalid())?;
+                changes.push((from, to));
+            }
+            Ok(Self::Ablaut(changes))
+        }
+    }
+}
+
+impl fmt::Display for Process {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Suffix(suffix) => write!(f, "-{suffix}"),
+            Self::Prefix(prefix) => write!(f, "{prefix}-"),
+            Self::Ablaut(changes) => {
+                for (idx, (from, to)) in changes.iter().enumerate() {
+                    if idx > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{from}>{to}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+serde_via_str!(Process);
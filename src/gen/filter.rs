@@ -0,0 +1,314 @@
+//! # Filters
+//!
+//! Filters reject generated words after the fact. Some constraints are awkward or impossible to express in a pattern
+//! -- "never the same phoneme twice in a row" would require knowing what was generated before -- so the generator
+//! produces a candidate, checks it against its filters, and tries again if any of them reject it.
+
+use super::{Lexicon, ParseError};
+use crate::phone;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{borrow::Cow, fmt, str::FromStr, sync::Arc};
+
+/// A function which returns `true` for words which should be rejected.
+pub type RejectFn = dyn Fn(&[phone::Syllable]) -> bool + Send + Sync;
+
+/// A rule for rejecting generated words. See [`WordGenerator::with_filter`](super::WordGenerator::with_filter).
+///
+/// Filters can be parsed from the directive syntax used in pattern files (the part after `filter:`):
+///
+/// | Directive              | Filter                           |
+/// |:-----------------------|:---------------------------------|
+/// | `no-adjacent-repeats`  | [`Filter::NoAdjacentRepeats`]    |
+/// | `no-initial h`         | [`Filter::NoInitial`]            |
+/// | `no-final h`           | [`Filter::NoFinal`]              |
+/// | `regex ^ŋ`             | [`Filter::Regex`]                |
+/// | `sonority 2`           | [`Filter::Sonority`]             |
+/// | `forbid ji wu tl`      | [`Filter::Forbid`]               |
+/// | `phonemes 3-8`         | [`Filter::Phonemes`]             |
+/// | `syllables 2-`         | [`Filter::Syllables`]            |
+#[derive(Clone)]
+pub enum Filter {
+    /// Reject words where the same phoneme appears twice in a row, including across syllable boundaries.
+    NoAdjacentRepeats,
+    /// Reject words starting with the phoneme.
+    NoInitial(phone::Phoneme),
+    /// Reject words ending with the phoneme.
+    NoFinal(phone::Phoneme),
+    /// Reject words where the regular expression matches the IPA of the word. The IPA does not contain syllable
+    /// boundaries, so `ji` matches both /ji/ and /j.i/.
+    Regex(regex::Regex),
+    /// Reject words with consonant clusters that do not rise in [sonority](phone::Phoneme::sonority) by at least this
+    /// much going into the vowel of a syllable or fall by at least this much coming out of it. With a distance of 2,
+    /// /pl/ is an acceptable onset, but /pf/ and /ml/ are not. Syllables without clusters are always accepted.
+    Sonority(u8),
+    /// Reject words containing any of the phoneme sequences. Like [`Filter::Regex`], sequences are matched across
+    /// syllable boundaries.
+    Forbid(Vec<Vec<phone::Phoneme>>),
+    /// Reject words with fewer than `min` or more than `max` phonemes. In a directive, the range is written like `3-8`,
+    /// `3-` or `-8` for only one bound, or `5` for an exact length.
+    Phonemes {
+        min: Option<usize>,
+        max: Option<usize>,
+    },
+    /// Reject words with fewer than `min` or more than `max` syllables, written like [`Filter::Phonemes`].
+    Syllables {
+        min: Option<usize>,
+        max: Option<usize>,
+    },
+    /// Reject words which are too close to a word in the lexicon. Lexicons are usually loaded from a file, so this can
+    /// not be parsed from a directive.
+    Lexicon(Arc<Lexicon>),
+    /// Reject words where the function returns `true`.
+    Custom(Arc<RejectFn>),
+}
+
+impl Filter {
+    /// Create a filter from a function which returns `true` for words that should be rejected.
+    pub fn custom(reject: impl Fn(&[phone::Syllable]) -> bool + Send + Sync + 'static) -> Self {
+        Self::Custom(Arc::new(reject))
+    }
+
+    /// Check if this filter rejects the `word`.
+    pub fn rejects(&self, word: &[phone::Syllable]) -> bool {
+        let mut phonemes = word.iter().flat_map(|syl| syl.parts().iter());
+        match self {
+            Self::NoAdjacentRepeats => {
+                let mut prev = None;
+                phonemes.any(|ph| prev.replace(ph) == Some(ph))
+            }
+            Self::NoInitial(x) => phonemes.next() == Some(x),
+            Self::NoFinal(x) => phonemes.last() == Some(x),
+            Self::Regex(re) => re.is_match(&phonemes.map(phone::Phoneme::code).collect::<String>()),
+            Self::Sonority(distance) => !word.iter().all(|syl| sonority_accepts(syl, *distance)),
+            Self::Forbid(seqs) => {
+                let phonemes: Vec<_> = phonemes.copied().collect();
+                seqs.iter()
+                    .filter(|seq| !seq.is_empty())
+                    .any(|seq| phonemes.windows(seq.len()).any(|w| w == seq.as_slice()))
+            }
+            Self::Phonemes { min, max } => !within(phonemes.count(), *min, *max),
+            Self::Syllables { min, max } => !within(word.len(), *min, *max),
+            Self::Lexicon(lexicon) => lexicon.conflicts(word),
+            Self::Custom(f) => f(word),
+        }
+    }
+}
+
+/// Check that `len` is at least `min` and at most `max`, where a missing bound does not limit it.
+fn within(len: usize, min: Option<usize>, max: Option<usize>) -> bool {
+    min.is_none_or(|min| len >= min) && max.is_none_or(|max| len <= max)
+}
+
+/// Parse a range of lengths like `3-8`, `3-`, `-8`, or `5`.
+fn parse_range(src: &str) -> Option<(Option<usize>, Option<usize>)> {
+    let bound = |x: &str| -> Option<Option<usize>> {
+        match x.trim() {
+            "" => Some(None),
+            x => x.parse().ok().map(Some),
+        }
+    };
+    let (min, max) = match src.split_once('-') {
+        Some((min, max)) => (bound(min)?, bound(max)?),
+        None => {
+            let exact = bound(src)?;
+            (exact, exact)
+        }
+    };
+    match (min, max) {
+        (None, None) => None,
+        (Some(min), Some(max)) if min > max => None,
+        range => Some(range),
+    }
+}
+
+/// Write a range of lengths the way [`parse_range`] reads it.
+fn write_range(f: &mut fmt::Formatter, min: Option<usize>, max: Option<usize>) -> fmt::Result {
+    match (min, max) {
+        (Some(min), Some(max)) if min == max => write!(f, "{min}"),
+        (min, max) => {
+            if let Some(min) = min {
+                write!(f, "{min}")?;
+            }
+            f.write_str("-")?;
+            if let Some(max) = max {
+                write!(f, "{max}")?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Check that the onset of the syllable rises in sonority by at least `distance` at each step and the coda falls by at
+/// least `distance` at each step. Syllables without a vowel are not checked, since they have no clear nucleus.
+fn sonority_accepts(syl: &phone::Syllable, distance: u8) -> bool {
+    let parts = syl.parts();
+    let is_vowel = |ph: &phone::Phoneme| matches!(ph, phone::Phoneme::Vowel(_));
+    let (Some(first), Some(last)) = (
+        parts.iter().position(is_vowel),
+        parts.iter().rposition(is_vowel),
+    ) else {
+        return true;
+    };
+
+    let rises = |pair: &[phone::Phoneme]| pair[1].sonority() >= pair[0].sonority() + distance;
+    let falls = |pair: &[phone::Phoneme]| pair[0].sonority() >= pair[1].sonority() + distance;
+    parts[..first].windows(2).all(rises) && parts[last + 1..].windows(2).all(falls)
+}
+
+impl FromStr for Filter {
+    type Err = ParseError;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        let src = src.trim();
+        let (name, arg) = match src.split_once(char::is_whitespace) {
+            Some((name, arg)) => (name, arg.trim()),
+            None => (src, ""),
+        };
+
+        let phoneme_arg = || {
+            let mut chars = arg.chars();
+            match (chars.next(), chars.next()) {
+                (None, _) => Err(ParseError::NoInput),
+                (Some(c), None) => {
+                    phone::Phoneme::try_from(c).map_err(|_| ParseError::UnknownCharacter(c))
+                }
+                (Some(_), Some(_)) => Err(ParseError::InvalidFilter(src.into())),
+            }
+        };
+
+        match name {
+            "" => Err(ParseError::NoInput),
+            "no-adjacent-repeats" if arg.is_empty() => Ok(Self::NoAdjacentRepeats),
+            "no-initial" => Ok(Self::NoInitial(phoneme_arg()?)),
+            "no-final" => Ok(Self::NoFinal(phoneme_arg()?)),
+            "regex" => regex::Regex::new(arg)
+                .map(Self::Regex)
+                .map_err(|_| ParseError::InvalidFilter(src.into())),
+            "sonority" => arg
+                .parse()
+                .map(Self::Sonority)
+                .map_err(|_| ParseError::InvalidFilter(src.into())),
+            "forbid" => {
+                let mut seqs = Vec::new();
+                for seq in arg.split(|c: char| c.is_whitespace() || c == ',') {
+                    if seq.is_empty() {
+                        continue;
+                    }
+                    let seq = seq
+                        .chars()
+                        .map(|c| {
+                            phone::Phoneme::try_from(c).map_err(|_| ParseError::UnknownCharacter(c))
+                        })
+                        .collect::<Result<_, _>>()?;
+                    seqs.push(seq);
+                }
+                if seqs.is_empty() {
+                    Err(ParseError::NoInput)
+                } else {
+                    Ok(Self::Forbid(seqs))
+                }
+            }
+            "phonemes" => parse_range(arg)
+                .map(|(min, max)| Self::Phonemes { min, max })
+                .ok_or_else(|| ParseError::InvalidFilter(src.into())),
+            "syllables" => parse_range(arg)
+                .map(|(min, max)| Self::Syllables { min, max })
+                .ok_or_else(|| ParseError::InvalidFilter(src.into())),
+            _ => Err(ParseError::InvalidFilter(src.into())),
+        }
+    }
+}
+
+impl PartialEq for Filter {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::NoAdjacentRepeats, Self::NoAdjacentRepeats) => true,
+            (Self::NoInitial(a), Self::NoInitial(b)) => a == b,
+            (Self::NoFinal(a), Self::NoFinal(b)) => a == b,
+            (Self::Regex(a), Self::Regex(b)) => a.as_str() == b.as_str(),
+            (Self::Sonority(a), Self::Sonority(b)) => a == b,
+            (Self::Forbid(a), Self::Forbid(b)) => a == b,
+            (
+                Self::Phonemes { min, max },
+                Self::Phonemes {
+                    min: min_b,
+                    max: max_b,
+                },
+            )
+            | (
+                Self::Syllables { min, max },
+                Self::Syllables {
+                    min: min_b,
+                    max: max_b,
+                },
+            ) => (min, max) == (min_b, max_b),
+            (Self::Lexicon(a), Self::Lexicon(b)) => a == b,
+            (Self::Custom(a), Self::Custom(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NoAdjacentRepeats => write!(f, "no-adjacent-repeats"),
+            Self::NoInitial(x) => write!(f, "no-initial {x}"),
+            Self::NoFinal(x) => write!(f, "no-final {x}"),
+            Self::Regex(re) => write!(f, "regex {re}"),
+            Self::Sonority(distance) => write!(f, "sonority {distance}"),
+            Self::Forbid(seqs) => {
+                f.write_str("forbid")?;
+                for seq in seqs.iter() {
+                    f.write_str(" ")?;
+                    for ph in seq.iter() {
+                        write!(f, "{ph}")?;
+                    }
+                }
+                Ok(())
+            }
+            Self::Phonemes { min, max } => {
+                f.write_str("phonemes ")?;
+                write_range(f, *min, *max)
+            }
+            Self::Syllables { min, max } => {
+                f.write_str("syllables ")?;
+                write_range(f, *min, *max)
+            }
+            Self::Lexicon(lexicon) => write!(
+                f,
+                "lexicon of {} words at distance {}",
+                lexicon.len(),
+                lexicon.distance()
+            ),
+            Self::Custom(_) => write!(f, "custom"),
+        }
+    }
+}
+
+impl Serialize for Filter {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Custom(_) => Err(serde::ser::Error::custom(
+                "custom filters can not be serialized",
+            )),
+            Self::Lexicon(_) => Err(serde::ser::Error::custom(
+                "lexicon filters can not be serialized",
+            )),
+            _ => serializer.collect_str(self),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Filter {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let src = Cow::<str>::deserialize(deserializer)?;
+        src.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl fmt::Debug for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Filter({self})")
+    }
+}
@@ -0,0 +1,145 @@
+//! # Harmony
+//!
+//! [Vowel harmony](https://en.wikipedia.org/wiki/Vowel_harmony) restricts which vowels can appear together in a word.
+//! In Turkish, a word with front vowels only has front vowels; a word with back vowels only has back vowels. This can
+//! not be expressed in a pattern, since the choice for each `V` depends on the choices made before it.
+
+use super::ParseError;
+use crate::phone;
+use std::{fmt, str::FromStr};
+
+/// A set of vowel classes which do not mix within a word. Once a vowel from a class is generated, the rest of the word
+/// can only use vowels from that class. Vowels which are not in any class are neutral and can appear anywhere.
+///
+/// Harmony can be parsed from a name (`front-back` or `atr`) or an explicit list of classes separated by `/` like
+/// `iyeø/ɯuɤo`.
+#[derive(Clone, PartialEq)]
+pub struct Harmony {
+    name: Option<&'static str>,
+    classes: Vec<Vec<phone::Vowel>>,
+}
+
+impl Harmony {
+    pub fn new(classes: impl Into<Vec<Vec<phone::Vowel>>>) -> Self {
+        Self {
+            name: None,
+            classes: classes.into(),
+        }
+    }
+
+    /// Front vowels do not mix with back vowels. Central vowels are neutral.
+    pub fn front_back() -> Self {
+        Self::by_vowel("front-back", |v| match v.frontness().backness() {
+            phone::Backness::Front => Some(0),
+            phone::Backness::Back => Some(1),
+            phone::Backness::Central => None,
+        })
+    }
+
+    /// [Advanced tongue root](https://en.wikipedia.org/wiki/Advanced_and_retracted_tongue_root) vowels do not mix with
+    /// retracted vowels. Close, close-mid, and mid vowels like /i e ə/ are treated as +ATR; near-close and open-mid or
+    /// lower vowels like /ɪ ɛ a/ are treated as -ATR.
+    pub fn atr() -> Self {
+        Self::by_vowel("atr", |v| match v.height().value() {
+            9 | 7 | 5 => Some(0),
+            _ => Some(1),
+        })
+    }
+
+    fn by_vowel(name: &'static str, class: impl Fn(&phone::Vowel) -> Option<usize>) -> Self {
+        let mut classes = vec![Vec::new(), Vec::new()];
+        for v in phone::Vowel::all() {
+            if let Some(idx) = class(v) {
+                classes[idx].push(*v);
+            }
+        }
+        Self {
+            name: Some(name),
+            classes,
+        }
+    }
+
+    pub fn classes(&self) -> &[Vec<phone::Vowel>] {
+        &self.classes
+    }
+
+    /// Get the index of the class the phoneme belongs to or `None` if it is neutral.
+    pub fn class_of(&self, phoneme: phone::Phoneme) -> Option<usize> {
+        let phone::Phoneme::Vowel(v) = phoneme else {
+            return None;
+        };
+        self.classes.iter().position(|class| class.contains(&v))
+    }
+
+    /// Check if the `phoneme` can appear in a word whose harmonizing vowels are from `class`.
+    pub fn allows(&self, class: usize, phoneme: phone::Phoneme) -> bool {
+        match self.class_of(phoneme) {
+            None => true,
+            Some(x) => x == class,
+        }
+    }
+
+    /// Check if all the vowels of the `word` are in harmony.
+    pub fn accepts(&self, word: &[phone::Syllable]) -> bool {
+        let mut classes = word
+            .iter()
+            .flat_map(|syl| syl.parts().iter())
+            .filter_map(|ph| self.class_of(*ph));
+        match classes.next() {
+            None => true,
+            Some(first) => classes.all(|x| x == first),
+        }
+    }
+}
+
+impl FromStr for Harmony {
+    type Err = ParseError;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        match src.trim() {
+            "" => Err(ParseError::NoInput),
+            "front-back" => Ok(Self::front_back()),
+            "atr" => Ok(Self::atr()),
+            src => {
+                let mut classes = Vec::new();
+                for class_src in src.split('/') {
+                    let mut class = Vec::new();
+                    for c in class_src.trim().chars() {
+                        class.push(
+                            phone::Vowel::try_from(c)
+                                .map_err(|_| ParseError::UnknownCharacter(c))?,
+                        );
+                    }
+                    classes.push(class);
+                }
+                Ok(Self::new(classes))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Harmony {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(name) = self.name {
+            return f.write_str(name);
+        }
+
+        for (idx, class) in self.classes.iter().enumerate() {
+            if idx > 0 {
+                f.write_str("/")?;
+            }
+            for v in class.iter() {
+                write!(f, "{v}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+serde_via_str!(Harmony);
+
+impl fmt::Debug for Harmony {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Harmony({self})")
+    }
+}
@@ -0,0 +1,159 @@
+//! # Random Inventories
+//!
+//! Picking phonemes by hand is where most conlangs start, but it is easy to end up with something no natural language
+//! would have, like /b/ without /p/ or five front rounded vowels and no /i/. [`random_inventory`] favors phonemes which
+//! are common in the world's languages, and only picks a phoneme once the simpler one it is usually found with has been
+//! picked.
+
+use crate::phone;
+use rand::{seq::SliceRandom, Rng};
+
+/// Consonants with roughly how many languages in a hundred have them, and the consonant they are rarely found without.
+const CONSONANTS: &[(char, u32, Option<char>)] = &[
+    ('m', 96, None),
+    ('k', 90, None),
+    ('j', 90, None),
+    ('p', 86, None),
+    ('n', 78, None),
+    ('t', 68, None),
+    ('l', 68, None),
+    ('s', 67, None),
+    ('h', 63, None),
+    ('ŋ', 63, None),
+    ('b', 63, Some('p')),
+    ('g', 56, Some('k')),
+    ('ʔ', 48, None),
+    ('d', 45, Some('t')),
+    ('r', 44, None),
+    ('f', 43, Some('p')),
+    ('ɲ', 42, Some('n')),
+    ('ʃ', 36, Some('s')),
+    ('z', 30, Some('s')),
+    ('ɾ', 30, None),
+    ('v', 28, Some('f')),
+    ('x', 22, Some('k')),
+    ('ʒ', 16, Some('ʃ')),
+    ('q', 15, Some('k')),
+    ('c', 14, Some('k')),
+    ('ɣ', 14, Some('x')),
+    ('ɟ', 12, Some('c')),
+    ('ʎ', 12, Some('l')),
+    ('β', 10, None),
+    ('ɦ', 10, Some('h')),
+    ('ɸ', 9, Some('p')),
+    ('χ', 9, Some('x')),
+    ('ʈ', 8, Some('t')),
+    ('ɖ', 8, Some('ʈ')),
+    ('ɳ', 8, Some('ʈ')),
+    ('ɬ', 8, Some('l')),
+    ('ʋ', 8, None),
+    ('ʁ', 6, Some('χ')),
+    ('ɭ', 6, Some('ɳ')),
+    ('ʂ', 6, Some('ʃ')),
+    ('ç', 6, None),
+    ('ħ', 5, Some('h')),
+    ('ɹ', 5, None),
+    ('ɻ', 5, Some('ʈ')),
+    ('ɽ', 5, Some('ʈ')),
+    ('ð', 5, Some('θ')),
+    ('θ', 4, Some('s')),
+    ('ʕ', 4, Some('ħ')),
+    ('ʐ', 3, Some('ʂ')),
+    ('ɰ', 3, None),
+    ('ʝ', 2, Some('ç')),
+    ('ɢ', 2, Some('q')),
+    ('ɴ', 2, Some('q')),
+    ('ʀ', 2, None),
+    ('ɮ', 1, Some('ɬ')),
+    ('ɱ', 1, Some('m')),
+    ('ʙ', 1, Some('b')),
+    ('ⱱ', 1, Some('v')),
+    ('ʟ', 1, Some('ʎ')),
+];
+
+/// Vowels with roughly how many languages in a hundred have them, and the vowel they are rarely found without.
+const VOWELS: &[(char, u32, Option<char>)] = &[
+    ('i', 92, None),
+    ('a', 88, None),
+    ('u', 88, None),
+    ('e', 62, Some('i')),
+    ('o', 60, Some('u')),
+    ('ɛ', 37, Some('e')),
+    ('ɔ', 35, Some('o')),
+    ('ə', 22, None),
+    ('ɨ', 15, Some('i')),
+    ('ɪ', 8, Some('i')),
+    ('ʊ', 7, Some('u')),
+    ('y', 6, Some('i')),
+    ('æ', 6, Some('ɛ')),
+    ('ɯ', 6, Some('u')),
+    ('ɑ', 5, Some('a')),
+    ('ø', 4, Some('y')),
+    ('ʌ', 4, None),
+    ('ɤ', 3, Some('o')),
+    ('ʉ', 3, Some('u')),
+    ('ɐ', 3, Some('a')),
+    ('œ', 2, Some('ø')),
+    ('ɒ', 2, Some('ɔ')),
+    ('ʏ', 1, Some('y')),
+    ('ɘ', 1, Some('ə')),
+    ('ɵ', 1, Some('ø')),
+    ('ɜ', 1, Some('ə')),
+    ('ɞ', 1, Some('ɜ')),
+    ('ɶ', 1, Some('œ')),
+];
+
+/// Roughly how many languages in a hundred have the `phoneme`, or nothing for non-pulmonic consonants, which are too
+/// rare to count this way.
+pub fn frequency(phoneme: phone::Phoneme) -> Option<u32> {
+    let table = match phoneme {
+        phone::Phoneme::Consonant(_) => CONSONANTS,
+        phone::Phoneme::Vowel(_) => VOWELS,
+        phone::Phoneme::NonPulmonicConsonant(_) => return None,
+    };
+    table
+        .iter()
+        .find(|(code, _, _)| *code == phoneme.code())
+        .map(|(_, frequency, _)| *frequency)
+}
+
+/// Pick `count` phonemes from the `table`, favoring the more common ones and never picking a phoneme before the one it
+/// depends on.
+fn pick(rng: &mut impl Rng, table: &[(char, u32, Option<char>)], count: usize) -> Vec<char> {
+    let mut out = Vec::with_capacity(count);
+    while out.len() < count {
+        let candidates: Vec<_> = table
+            .iter()
+            .filter(|(code, _, requires)| {
+                !out.contains(code) && requires.is_none_or(|x| out.contains(&x))
+            })
+            .collect();
+        // Squaring how common each phoneme is keeps rare ones rare: otherwise the dozens of phonemes only a few
+        // languages have would together crowd out common ones like /u/
+        let Ok((code, _, _)) = candidates.choose_weighted(rng, |(_, weight, _)| weight * weight)
+        else {
+            // Every phoneme depends on a chain ending in one which depends on nothing, so there are no candidates left
+            // only once the whole table has been picked
+            break;
+        };
+        out.push(*code);
+    }
+    out
+}
+
+/// Create an inventory of `consonants` pulmonic consonants and `vowels` vowels which looks like one a natural language
+/// could have. Asking for more phonemes than the IPA chart has gives all of them.
+pub fn random_inventory(rng: &mut impl Rng, consonants: usize, vowels: usize) -> phone::Inventory {
+    let mut consonants: Vec<_> = pick(rng, CONSONANTS, consonants)
+        .into_iter()
+        .map(|code| phone::Consonant::try_from(code).unwrap())
+        .collect();
+    let mut vowels: Vec<_> = pick(rng, VOWELS, vowels)
+        .into_iter()
+        .map(|code| phone::Vowel::try_from(code).unwrap())
+        .collect();
+    // List the phonemes in the order of the IPA chart instead of the order they were picked in
+    consonants.sort();
+    vowels.sort();
+    phone::Inventory::new(consonants, vowels, [])
+}
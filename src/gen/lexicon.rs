@@ -0,0 +1,87 @@
+//! # Lexicon
+//!
+//! New words should not sound too much like words the language already has. A [`Lexicon`] holds the existing words so
+//! that a generator can reject candidates which would be homophones or too easily confused with them.
+
+use crate::phone;
+
+/// A list of existing words and how far from all of them a new word has to be.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Lexicon {
+    words: Vec<Vec<phone::Phoneme>>,
+    distance: usize,
+}
+
+impl Lexicon {
+    /// Create an empty lexicon which conflicts with words `distance` or fewer edits away from an existing word. With a
+    /// distance of 0, only exact homophones conflict; with 1, /pata/ also conflicts with /bata/, /ata/, and /patai/.
+    pub fn new(distance: usize) -> Self {
+        Self {
+            words: Vec::new(),
+            distance,
+        }
+    }
+
+    pub fn distance(&self) -> usize {
+        self.distance
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    pub fn insert(&mut self, word: &[phone::Phoneme]) {
+        self.words.push(word.to_vec());
+    }
+
+    /// Find an existing word which conflicts with the `word`, if there is one.
+    pub fn conflict(&self, word: &[phone::Syllable]) -> Option<&[phone::Phoneme]> {
+        let phonemes: Vec<_> = word
+            .iter()
+            .flat_map(|syl| syl.parts().iter().copied())
+            .collect();
+        self.words
+            .iter()
+            .find(|existing| within_distance(existing, &phonemes, self.distance))
+            .map(Vec::as_slice)
+    }
+
+    /// Check if the `word` is too close to an existing word.
+    pub fn conflicts(&self, word: &[phone::Syllable]) -> bool {
+        self.conflict(word).is_some()
+    }
+}
+
+impl Extend<Vec<phone::Phoneme>> for Lexicon {
+    fn extend<T: IntoIterator<Item = Vec<phone::Phoneme>>>(&mut self, iter: T) {
+        self.words.extend(iter);
+    }
+}
+
+/// Check if the [edit distance](https://en.wikipedia.org/wiki/Levenshtein_distance) between `a` and `b` is at most
+/// `max`, counting each inserted, deleted, or substituted phoneme (or any other item) as one edit.
+pub(crate) fn within_distance<T: PartialEq>(a: &[T], b: &[T], max: usize) -> bool {
+    if a.len().abs_diff(b.len()) > max {
+        return false;
+    }
+
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut cur = vec![0; b.len() + 1];
+    for (i, x) in a.iter().enumerate() {
+        cur[0] = i + 1;
+        for (j, y) in b.iter().enumerate() {
+            let substitute = prev[j] + usize::from(x != y);
+            cur[j + 1] = substitute.min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        // Every later row is at least the minimum of this one
+        if cur.iter().min().is_some_and(|x| *x > max) {
+            return false;
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    prev[b.len()] <= max
+}
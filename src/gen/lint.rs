@@ -0,0 +1,145 @@
+//! # Lint
+//!
+//! A pattern can parse and still not do what was meant: a class with nothing in it, choices which can never be picked,
+//! or filters so strict that nothing gets through. [`WordGenerator::lint`] looks for these before any words are
+//! generated, since they are hard to spot by reading the output.
+
+use super::{Position, WordGenerator, ENUMERATION_LIMIT};
+use crate::phone;
+use itertools::Itertools;
+use rand::SeedableRng;
+use std::fmt;
+
+/// When a generator can produce too many words to enumerate, check this many random words to see if they are all
+/// rejected.
+const SAMPLES: usize = 1000;
+
+/// A likely mistake in a [`WordGenerator`], found by [`WordGenerator::lint`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum Lint {
+    /// A slot has no phonemes in the inventory, like `N` when the inventory has no nasals.
+    EmptyClass { syllable: String, slot: String },
+    /// Some phonemes of a slot are never generated, because they have no weight, the slot is optional and never
+    /// included, or the [positions](super::Positions) do not allow them where the slot is.
+    Unreachable {
+        syllable: String,
+        slot: String,
+        phonemes: Vec<phone::Phoneme>,
+    },
+    /// The filters, harmony, or positions reject every word the pattern produces.
+    RejectsEverything,
+    /// The pattern can not produce as many different words as were asked for.
+    TooFewWords { possible: u128, requested: usize },
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::EmptyClass { syllable, slot } => {
+                write!(f, "{slot} in {syllable} has no phonemes in the inventory")
+            }
+            Self::Unreachable {
+                syllable,
+                slot,
+                phonemes,
+            } => write!(
+                f,
+                "{slot} in {syllable} never produces /{}/",
+                phonemes.iter().join(" ")
+            ),
+            Self::RejectsEverything => write!(f, "every word is rejected"),
+            Self::TooFewWords {
+                possible,
+                requested,
+            } => write!(
+                f,
+                "only {possible} different words are possible, but {requested} were requested"
+            ),
+        }
+    }
+}
+
+impl WordGenerator {
+    /// Look for likely mistakes in this generator, given the `inventory` it is meant to draw from, including its
+    /// strata. An empty list does not mean the generator is good, only that nothing obviously wrong was found.
+    pub fn lint(&self, inventory: &phone::Inventory) -> Vec<Lint> {
+        let mut out = Vec::new();
+        for (syl_idx, syl) in self.syllables.iter().enumerate() {
+            let nucleus = syl.nucleus();
+            // With syllable counts, any syllable of the pattern can start a word. Without them, a syllable can start a
+            // word if everything before it is optional.
+            let after_optional =
+                (0..syl_idx).all(|idx| self.optional_syllables.iter().any(|g| g.contains(&idx)));
+            let onset: &[Position] = match (&self.syllable_counts, syl_idx) {
+                (Some(_), _) => &[Position::Initial, Position::Medial],
+                (None, 0) => &[Position::Initial],
+                (None, _) if after_optional => &[Position::Initial, Position::Medial],
+                (None, _) => &[Position::Medial],
+            };
+
+            for (idx, ph) in syl.phonemes.iter().enumerate() {
+                let in_inventory = |x: &phone::Phoneme| {
+                    inventory.contains(*x) || inventory.strata().any(|(_, s)| s.contains(*x))
+                };
+                if !ph.choices.iter().any(in_inventory) {
+                    out.push(Lint::EmptyClass {
+                        syllable: syl.pattern.to_string(),
+                        slot: ph.to_string(),
+                    });
+                    continue;
+                }
+
+                let positions: &[Position] = match nucleus {
+                    Some((first, _)) if idx < first => onset,
+                    Some((_, last)) if idx > last => &[Position::Coda],
+                    _ => &[],
+                };
+                let never_included = ph.inclusion(self.optional_probability()) == 0.0;
+                let phonemes: Vec<_> = ph
+                    .choices
+                    .iter()
+                    .enumerate()
+                    .filter(|(choice, x)| {
+                        never_included
+                            || ph.weights.get(*choice) == Some(&0)
+                            || self.positions.as_ref().is_some_and(|p| {
+                                !positions.is_empty()
+                                    && positions.iter().all(|pos| !p.allows(*pos, **x))
+                            })
+                    })
+                    .map(|(_, x)| *x)
+                    .collect();
+                if !phonemes.is_empty() {
+                    out.push(Lint::Unreachable {
+                        syllable: syl.pattern.to_string(),
+                        slot: ph.to_string(),
+                        phonemes,
+                    });
+                }
+            }
+        }
+
+        if self.rejects_everything() {
+            out.push(Lint::RejectsEverything);
+        }
+        out
+    }
+
+    /// Check that this generator can produce `count` different words.
+    pub fn lint_count(&self, count: usize) -> Option<Lint> {
+        let possible = self.possible_words();
+        (possible < count as u128).then_some(Lint::TooFewWords {
+            possible,
+            requested: count,
+        })
+    }
+
+    fn rejects_everything(&self) -> bool {
+        if self.count() <= ENUMERATION_LIMIT {
+            self.enumerate().next().is_none()
+        } else {
+            let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+            (0..SAMPLES).all(|_| self.rejects(&self.generate_unfiltered(&mut rng)))
+        }
+    }
+}
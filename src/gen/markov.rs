@@ -0,0 +1,96 @@
+//! # Markov
+//!
+//! Instead of describing a language with patterns, a [`MarkovGenerator`] learns what a language sounds like from a
+//! list of its words. It tracks which phoneme follows each sequence of `order` phonemes, then generates new words by
+//! walking those transitions.
+
+use crate::phone;
+use rand::{seq::SliceRandom, Rng};
+use smallvec::{smallvec, SmallVec};
+use std::collections::{HashMap, HashSet};
+
+/// The sequence of previous phonemes used to pick the next one. `None` marks the start or end of a word.
+type State = SmallVec<[Option<phone::Phoneme>; 4]>;
+
+/// Generated words are cut off after this many phonemes, in case the model loops.
+const MAX_LENGTH: usize = 32;
+
+/// When looking for a word which is not in the corpus, give up after this many attempts.
+const MAX_ATTEMPTS: usize = 1000;
+
+#[derive(Clone, Debug)]
+pub struct MarkovGenerator {
+    order: usize,
+    /// For each state, the phonemes which have followed it and how many times.
+    transitions: HashMap<State, Vec<(Option<phone::Phoneme>, u32)>>,
+    corpus: HashSet<Vec<phone::Phoneme>>,
+}
+
+impl MarkovGenerator {
+    /// Create an untrained generator which picks each phoneme based on the previous `order` phonemes. Higher orders
+    /// produce words closer to the corpus; lower orders produce more novel (and more chaotic) words.
+    pub fn new(order: usize) -> Self {
+        assert!(order > 0);
+        Self {
+            order,
+            transitions: HashMap::new(),
+            corpus: HashSet::new(),
+        }
+    }
+
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// Learn the transitions of a word.
+    pub fn train(&mut self, word: &[phone::Phoneme]) {
+        if word.is_empty() || !self.corpus.insert(word.to_vec()) {
+            return;
+        }
+
+        let mut state: State = smallvec![None; self.order];
+        for next in word.iter().copied().map(Some).chain(std::iter::once(None)) {
+            let counts = self.transitions.entry(state.clone()).or_default();
+            match counts.iter_mut().find(|(ph, _)| *ph == next) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((next, 1)),
+            }
+            state.remove(0);
+            state.push(next);
+        }
+    }
+
+    /// Generate a word by walking the learned transitions. The word might be one from the corpus. Returns an empty word
+    /// if nothing has been trained.
+    pub fn generate(&self, rng: &mut impl Rng) -> phone::Word {
+        let mut out = Vec::new();
+        let mut state: State = smallvec![None; self.order];
+        while out.len() < MAX_LENGTH {
+            let Some(counts) = self.transitions.get(&state) else {
+                break;
+            };
+            let Ok((Some(next), _)) = counts.choose_weighted(rng, |(_, count)| *count) else {
+                break;
+            };
+            out.push(*next);
+            state.remove(0);
+            state.push(Some(*next));
+        }
+
+        // There is no syllable information in the corpus, so the whole word is one syllable
+        let mut word = phone::Word::new();
+        if !out.is_empty() {
+            word.push(phone::Syllable::new(&out));
+        }
+        word
+    }
+
+    /// Generate a word which is not in the corpus. Returns `None` if a novel word could not be found, which happens
+    /// when the corpus is too small or the order is too high for words to diverge.
+    pub fn generate_novel(&self, rng: &mut impl Rng) -> Option<phone::Word> {
+        (0..MAX_ATTEMPTS).map(|_| self.generate(rng)).find(|word| {
+            let phonemes: Vec<_> = word.phonemes().collect();
+            !phonemes.is_empty() && !self.corpus.contains(&phonemes)
+        })
+    }
+}
@@ -0,0 +1,77 @@
+//! # Minimal Pairs
+//!
+//! A [minimal pair](https://en.wikipedia.org/wiki/Minimal_pair) is two words which differ in only one sound, like
+//! English "pat" and "bat". They are the usual evidence that two sounds contrast in a language, and the raw material
+//! of listening drills.
+
+use super::WordGenerator;
+use crate::phone;
+use rand::Rng;
+use std::collections::{HashMap, HashSet};
+
+/// Stop looking for pairs after this many words in a row without finding a new one.
+const MAX_ATTEMPTS: usize = 10_000;
+
+/// A word with the phoneme at `gap` taken out. Two different words with the same frame are a minimal pair. The shape of
+/// the syllables is part of the frame, so /pa.ta/ and /pat.a/ are not mistaken for the same word around a gap.
+#[derive(PartialEq, Eq, Hash)]
+struct Frame {
+    gap: usize,
+    phonemes: Vec<phone::Phoneme>,
+    syllables: Vec<(usize, phone::Stress, Option<phone::Tone>)>,
+}
+
+/// Every frame of the `word`, one for each of its phonemes.
+fn frames(word: &phone::Word) -> impl Iterator<Item = Frame> + '_ {
+    let phonemes: Vec<_> = word.phonemes().collect();
+    let syllables: Vec<_> = word
+        .iter()
+        .map(|syl| (syl.parts().len(), syl.stress(), syl.tone()))
+        .collect();
+    (0..phonemes.len()).map(move |gap| {
+        let mut phonemes = phonemes.clone();
+        phonemes.remove(gap);
+        Frame {
+            gap,
+            phonemes,
+            syllables: syllables.clone(),
+        }
+    })
+}
+
+impl WordGenerator {
+    /// Generate up to `n` minimal pairs: pairs of words which differ in exactly one phoneme and are otherwise the same,
+    /// down to their syllables, stress, and tones. Both words of a pair come from this generator and pass its filters,
+    /// so each pair contrasts two phonemes the pattern can really put in the same place.
+    ///
+    /// Pairs are found by generating words until two of them line up, so fewer than `n` pairs are returned when the
+    /// generator can not make that many or they are too rare to find. Like [`generate`](Self::generate), this panics if
+    /// no word passes the filters.
+    pub fn minimal_pairs(&self, rng: &mut impl Rng, n: usize) -> Vec<(phone::Word, phone::Word)> {
+        let mut out = Vec::new();
+        let mut words: Vec<phone::Word> = Vec::new();
+        let mut seen = HashSet::new();
+        let mut by_frame: HashMap<Frame, Vec<usize>> = HashMap::new();
+        let mut attempts = 0;
+        while out.len() < n && attempts < MAX_ATTEMPTS {
+            attempts += 1;
+            let word = self.generate(rng);
+            if !seen.insert(word.clone()) {
+                continue;
+            }
+
+            for frame in frames(&word) {
+                let matches = by_frame.entry(frame).or_default();
+                for other in matches.iter() {
+                    if out.len() < n {
+                        out.push((words[*other].clone(), word.clone()));
+                        attempts = 0;
+                    }
+                }
+                matches.push(words.len());
+            }
+            words.push(word);
+        }
+        out
+    }
+}
@@ -0,0 +1,148 @@
+//! # Positions
+//!
+//! Most languages restrict where a phoneme can appear in a syllable: English has /ŋ/ in codas ("sing") but never at the
+//! start of a word, and /h/ at the start of syllables but never in codas. A flat `C` class can not express this, so
+//! [`Positions`] gives each position in the word its own set of allowed phonemes.
+
+use super::ParseError;
+use crate::phone;
+use std::{fmt, str::FromStr};
+
+/// Where a consonant falls in a word.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Position {
+    /// Before the vowel of the first syllable.
+    Initial,
+    /// Before the vowel of any other syllable.
+    Medial,
+    /// After the vowel of any syllable.
+    Coda,
+}
+
+impl Position {
+    const ALL: [Self; 3] = [Self::Initial, Self::Medial, Self::Coda];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Initial => "initial",
+            Self::Medial => "medial",
+            Self::Coda => "coda",
+        }
+    }
+}
+
+/// The phonemes allowed in each [`Position`]. Positions without a set are unrestricted.
+///
+/// This can be parsed from a list of `position=phonemes` entries separated by whitespace, like
+/// `initial=ptkmnh medial=ptkmnŋh coda=mnŋ`.
+#[derive(Clone, Default, PartialEq)]
+pub struct Positions {
+    sets: [Option<Vec<phone::Phoneme>>; 3],
+}
+
+impl Positions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only allow the `phonemes` in the `position`, replacing any set which was already there.
+    pub fn with(mut self, position: Position, phonemes: impl Into<Vec<phone::Phoneme>>) -> Self {
+        self.sets[position.index()] = Some(phonemes.into());
+        self
+    }
+
+    /// Get the phonemes allowed in the `position`, or `None` if it is unrestricted.
+    pub fn allowed(&self, position: Position) -> Option<&[phone::Phoneme]> {
+        self.sets[position.index()].as_deref()
+    }
+
+    pub fn allows(&self, position: Position, phoneme: phone::Phoneme) -> bool {
+        match self.allowed(position) {
+            None => true,
+            Some(set) => set.contains(&phoneme),
+        }
+    }
+
+    /// Check that every phoneme of the `word` is allowed in its position. The vowels of a syllable are its nucleus, so
+    /// syllables without a vowel are not checked.
+    pub fn accepts(&self, word: &[phone::Syllable]) -> bool {
+        word.iter().enumerate().all(|(idx, syl)| {
+            let parts = syl.parts();
+            let is_vowel = |ph: &phone::Phoneme| matches!(ph, phone::Phoneme::Vowel(_));
+            let (Some(first), Some(last)) = (
+                parts.iter().position(is_vowel),
+                parts.iter().rposition(is_vowel),
+            ) else {
+                return true;
+            };
+            let onset = if idx == 0 {
+                Position::Initial
+            } else {
+                Position::Medial
+            };
+            parts[..first].iter().all(|ph| self.allows(onset, *ph))
+                && parts[last + 1..]
+                    .iter()
+                    .all(|ph| self.allows(Position::Coda, *ph))
+        })
+    }
+}
+
+impl FromStr for Positions {
+    type Err = ParseError;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        let mut out = Self::new();
+        for entry in src.split_whitespace() {
+            let invalid = || ParseError::InvalidPositions(entry.into());
+            let (name, phonemes) = entry.split_once('=').ok_or_else(invalid)?;
+            let position = Position::ALL
+                .into_iter()
+                .find(|pos| pos.name() == name)
+                .ok_or_else(invalid)?;
+            let phonemes = phonemes
+                .chars()
+                .map(|c| phone::Phoneme::try_from(c).map_err(|_| ParseError::UnknownCharacter(c)))
+                .collect::<Result<Vec<_>, _>>()?;
+            out = out.with(position, phonemes);
+        }
+
+        if out.sets.iter().all(Option::is_none) {
+            Err(ParseError::NoInput)
+        } else {
+            Ok(out)
+        }
+    }
+}
+
+impl fmt::Display for Positions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut first = true;
+        for position in Position::ALL {
+            let Some(set) = self.allowed(position) else {
+                continue;
+            };
+            if !first {
+                f.write_str(" ")?;
+            }
+            first = false;
+            write!(f, "{}=", position.name())?;
+            for ph in set.iter() {
+                write!(f, "{ph}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+serde_via_str!(Positions);
+
+impl fmt::Debug for Positions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Positions({self})")
+    }
+}
@@ -0,0 +1,80 @@
+//! # Reduplication
+//!
+//! Many languages build words by repeating part of another word: Indonesian plurals repeat the whole word (*buku*,
+//! "book", becomes *buku-buku*), and Tagalog marks the future by copying the first consonant and vowel (*sulat*,
+//! "write", becomes *susulat*). A [`Reduplication`] applies one of these processes to every generated word.
+
+use super::ParseError;
+use crate::phone;
+use std::{fmt, str::FromStr};
+
+/// Which part of a word is repeated. The copies are not generated from a pattern, so
+/// [`phone::Word::pattern`] is `None` for them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Reduplication {
+    /// Repeat every syllable of the word after it, so /ta.kan/ becomes /ta.kan.ta.kan/. Parsed from `full` or `σσ`.
+    Full,
+    /// Repeat the first syllable in front of the word, so /ta.kan/ becomes /ta.ta.kan/. Parsed from `syllable` or `σ-`.
+    Syllable,
+    /// Copy the first consonant and vowel of the word into a new syllable in front of it, so /tra.ko/ becomes
+    /// /ta.tra.ko/. Words starting with a vowel only copy the vowel. Parsed from `cv` or `CV-`.
+    Partial,
+}
+
+impl Reduplication {
+    /// Reduplicate the `word`. Words without any syllables are left alone, as are words without a vowel for
+    /// [`Partial`](Self::Partial) reduplication.
+    pub fn apply(&self, word: &mut phone::Word) {
+        let Some(first) = word.first().cloned() else {
+            return;
+        };
+        match self {
+            Self::Full => {
+                for syl in word.syllables().to_vec() {
+                    word.push(syl);
+                }
+            }
+            Self::Syllable => word.insert(0, first),
+            Self::Partial => {
+                let onset = word
+                    .phonemes()
+                    .next()
+                    .filter(|ph| !matches!(ph, phone::Phoneme::Vowel(_)));
+                let Some(vowel) = word
+                    .phonemes()
+                    .find(|ph| matches!(ph, phone::Phoneme::Vowel(_)))
+                else {
+                    return;
+                };
+                let copy: Vec<_> = onset.into_iter().chain([vowel]).collect();
+                word.insert(0, phone::Syllable::new(&copy));
+            }
+        }
+    }
+}
+
+impl FromStr for Reduplication {
+    type Err = ParseError;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        match src.trim() {
+            "" => Err(ParseError::NoInput),
+            "full" | "σσ" => Ok(Self::Full),
+            "syllable" | "σ-" => Ok(Self::Syllable),
+            "cv" | "CV-" => Ok(Self::Partial),
+            _ => Err(ParseError::InvalidReduplication(src.into())),
+        }
+    }
+}
+
+impl fmt::Display for Reduplication {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Full => "full",
+            Self::Syllable => "syllable",
+            Self::Partial => "cv",
+        })
+    }
+}
+
+serde_via_str!(Reduplication);
@@ -0,0 +1,249 @@
+//! # Rulesets
+//!
+//! A big grammar is easier to write and read in pieces. A [`Ruleset`] gives patterns names and lets patterns refer to
+//! each other by name, so a word can be described as `word = prefix? stem suffix?` with each part defined on its own.
+//! A list of [`WeightedPattern`]s is written the same way, with a weight in place of the name.
+
+use super::{split_syllables, ParseError, SyllableGenerator, WordGenerator};
+use crate::phone;
+use smallvec::SmallVec;
+use std::{fmt, ops::Range, str::FromStr};
+
+/// A set of named patterns which can be composed into a [`WordGenerator`].
+///
+/// A ruleset is parsed from lines of `name = pattern`, where blank lines and lines starting with `#` are skipped:
+///
+/// ```text
+/// prefix = CV
+/// stem = CVC CV(N)
+/// suffix = VC
+/// word = prefix? stem suffix?
+/// ```
+///
+/// Names are lowercase, so they can not be mistaken for classes. In a pattern, a name stands for all the syllables of
+/// that rule. Following it with `?` makes those syllables optional as a group: they are included or left out together,
+/// with the generator's [optional probability](WordGenerator::with_optional_probability).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Ruleset {
+    /// Pairs of name and pattern, in the order they were added.
+    rules: Vec<(String, String)>,
+}
+
+impl Ruleset {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a rule, replacing any rule with the same name.
+    ///
+    /// Panics if the name is not lowercase ASCII letters, digits, `-`, and `_`, starting with a letter.
+    pub fn with_rule(mut self, name: impl Into<String>, pattern: impl Into<String>) -> Self {
+        let name = name.into();
+        assert!(is_name(&name), "invalid rule name \"{name}\"");
+        let pattern = pattern.into();
+        match self.rules.iter_mut().find(|(x, _)| *x == name) {
+            Some((_, existing)) => *existing = pattern,
+            None => self.rules.push((name, pattern)),
+        }
+        self
+    }
+
+    /// The names of the rules, in the order they were added.
+    pub fn names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.rules.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Get the pattern of the rule called `name`.
+    pub fn rule(&self, name: &str) -> Option<&str> {
+        self.rules
+            .iter()
+            .find(|(x, _)| x == name)
+            .map(|(_, pattern)| pattern.as_str())
+    }
+
+    /// Compile the rule called `name` into a generator, replacing references to other rules with their syllables and
+    /// drawing classes like `C` from the `inventory`. Errors in a pattern point into the rule they are in.
+    pub fn compile(
+        &self,
+        name: &str,
+        inventory: &phone::Inventory,
+    ) -> Result<WordGenerator, ParseError> {
+        let mut syllables = SmallVec::new();
+        let mut groups = Vec::new();
+        self.expand(
+            name,
+            inventory,
+            &mut Vec::new(),
+            &mut syllables,
+            &mut groups,
+        )?;
+        if syllables.is_empty() {
+            return Err(ParseError::NoInput);
+        }
+        let mut out = WordGenerator::from_syllables(syllables);
+        out.optional_syllables = groups;
+        Ok(out)
+    }
+
+    /// Append the syllables of the rule called `name`. `stack` holds the rules being expanded, to catch rules which
+    /// refer to themselves.
+    fn expand(
+        &self,
+        name: &str,
+        inventory: &phone::Inventory,
+        stack: &mut Vec<String>,
+        syllables: &mut SmallVec<[SyllableGenerator; 4]>,
+        groups: &mut Vec<Range<usize>>,
+    ) -> Result<(), ParseError> {
+        let pattern = self
+            .rule(name)
+            .ok_or_else(|| ParseError::UnknownRule(name.into()))?;
+        if stack.iter().any(|x| x == name) {
+            return Err(ParseError::RecursiveRule(name.into()));
+        }
+
+        stack.push(name.into());
+        for part in split_syllables(pattern) {
+            let (reference, optional) = match part.strip_suffix('?') {
+                Some(reference) => (reference, true),
+                None => (part, false),
+            };
+            if is_name(reference) {
+                let start = syllables.len();
+                self.expand(reference, inventory, stack, syllables, groups)?;
+                // Groups inside this one were pushed while expanding it, so they come first
+                if optional {
+                    groups.push(start..syllables.len());
+                }
+            } else {
+                let at = part.as_ptr() as usize - pattern.as_ptr() as usize;
+                syllables.push(
+                    SyllableGenerator::parse(part, inventory)
+                        .map_err(|e| e.in_pattern(pattern, at))?,
+                );
+            }
+        }
+        stack.pop();
+        Ok(())
+    }
+}
+
+/// Check if `src` is a rule name, which is the same as a stratum name: lowercase ASCII letters, digits, `-`, and `_`,
+/// starting with a letter.
+fn is_name(src: &str) -> bool {
+    src.starts_with(|c: char| c.is_ascii_lowercase())
+        && src
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
+/// The lines of a ruleset or pattern list which hold something, trimmed: blank lines and lines starting with `#` are
+/// skipped.
+fn lines(src: &str) -> impl Iterator<Item = &str> {
+    src.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+}
+
+impl FromStr for Ruleset {
+    type Err = ParseError;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        let mut out = Self::new();
+        for line in lines(src) {
+            let invalid = || ParseError::InvalidRule(line.into());
+            let (name, pattern) = line.split_once('=').ok_or_else(invalid)?;
+            let (name, pattern) = (name.trim(), pattern.trim());
+            if !is_name(name) || pattern.is_empty() || out.rule(name).is_some() {
+                return Err(invalid());
+            }
+            out = out.with_rule(name, pattern);
+        }
+        if out.rules.is_empty() {
+            Err(ParseError::NoInput)
+        } else {
+            Ok(out)
+        }
+    }
+}
+
+impl fmt::Display for Ruleset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (name, pattern) in self.rules.iter() {
+            writeln!(f, "{name} = {pattern}")?;
+        }
+        Ok(())
+    }
+}
+
+serde_via_str!(Ruleset);
+
+/// A pattern and how often it is picked relative to the other patterns it is listed with.
+///
+/// A weighted pattern is parsed from `pattern = weight` like `CVC = 3`, where the weight is a whole number above 0, or
+/// just the pattern for a weight of 1. The pattern can also be the name of a rule in a [`Ruleset`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WeightedPattern {
+    pattern: String,
+    weight: u32,
+}
+
+impl WeightedPattern {
+    pub fn new(pattern: impl Into<String>, weight: u32) -> Self {
+        Self {
+            pattern: pattern.into(),
+            weight,
+        }
+    }
+
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    pub fn weight(&self) -> u32 {
+        self.weight
+    }
+
+    /// Parse a list of patterns, one per line. Like a [`Ruleset`], blank lines and lines starting with `#` are skipped.
+    pub fn parse_list(src: &str) -> Result<Vec<Self>, ParseError> {
+        let out = lines(src).map(str::parse).collect::<Result<Vec<_>, _>>()?;
+        if out.is_empty() {
+            Err(ParseError::NoInput)
+        } else {
+            Ok(out)
+        }
+    }
+}
+
+impl FromStr for WeightedPattern {
+    type Err = ParseError;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        let (pattern, weight) = match src.rsplit_once('=') {
+            Some((pattern, weight)) => (
+                pattern.trim(),
+                weight
+                    .trim()
+                    .parse()
+                    .ok()
+                    .filter(|weight| *weight > 0)
+                    .ok_or_else(|| ParseError::InvalidPatternWeight(src.into()))?,
+            ),
+            None => (src.trim(), 1),
+        };
+        if pattern.is_empty() {
+            return Err(ParseError::NoInput);
+        }
+        Ok(Self::new(pattern, weight))
+    }
+}
+
+impl fmt::Display for WeightedPattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.weight == 1 {
+            f.write_str(&self.pattern)
+        } else {
+            write!(f, "{} = {}", self.pattern, self.weight)
+        }
+    }
+}
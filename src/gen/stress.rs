@@ -0,0 +1,92 @@
+//! # Stress
+//!
+//! Multi-syllable words usually have one syllable which is emphasized over the others. Which syllable gets the stress
+//! is often predictable from simple rules, so generated words follow a [`StressRule`] instead of placing stress at
+//! random.
+
+use super::ParseError;
+use crate::phone;
+use std::{fmt, str::FromStr};
+
+/// A rule for picking which syllable of a word gets primary stress. Words of one syllable are left unmarked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StressRule {
+    /// Stress the first syllable, like Finnish and Czech.
+    Initial,
+    /// Stress the last syllable, like French.
+    Final,
+    /// Stress the second-to-last syllable, like Polish and Swahili.
+    Penultimate,
+    /// Stress the third-to-last syllable, like Macedonian.
+    Antepenultimate,
+    /// Stress the second-to-last syllable if it is [heavy](phone::Syllable::is_heavy), otherwise the third-to-last, like
+    /// Latin.
+    WeightSensitive,
+}
+
+impl StressRule {
+    /// Get the index of the syllable to stress in a word of `syllables`, or `None` if the word has fewer than two
+    /// syllables.
+    pub fn position(&self, syllables: &[phone::Syllable]) -> Option<usize> {
+        let len = syllables.len();
+        if len < 2 {
+            return None;
+        }
+
+        Some(match self {
+            Self::Initial => 0,
+            Self::Final => len - 1,
+            Self::Penultimate => len - 2,
+            Self::Antepenultimate => len.saturating_sub(3),
+            Self::WeightSensitive => {
+                if len == 2 || syllables[len - 2].is_heavy() {
+                    len - 2
+                } else {
+                    len - 3
+                }
+            }
+        })
+    }
+
+    /// Mark primary stress on the `syllables`, clearing any stress which was already there.
+    pub fn apply(&self, syllables: &mut [phone::Syllable]) {
+        let position = self.position(syllables);
+        for (idx, syl) in syllables.iter_mut().enumerate() {
+            syl.set_stress(if Some(idx) == position {
+                phone::Stress::Primary
+            } else {
+                phone::Stress::Unstressed
+            });
+        }
+    }
+}
+
+impl FromStr for StressRule {
+    type Err = ParseError;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        match src.trim() {
+            "" => Err(ParseError::NoInput),
+            "initial" => Ok(Self::Initial),
+            "final" => Ok(Self::Final),
+            "penultimate" => Ok(Self::Penultimate),
+            "antepenultimate" => Ok(Self::Antepenultimate),
+            "weight-sensitive" => Ok(Self::WeightSensitive),
+            _ => Err(ParseError::InvalidStressRule(src.into())),
+        }
+    }
+}
+
+impl fmt::Display for StressRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Initial => "initial",
+            Self::Final => "final",
+            Self::Penultimate => "penultimate",
+            Self::Antepenultimate => "antepenultimate",
+            Self::WeightSensitive => "weight-sensitive",
+        })
+    }
+}
+
+serde_via_str!(StressRule);
@@ -0,0 +1,260 @@
+//! # Style
+//!
+//! Two languages with the same phonemes can still sound nothing alike, depending on which sounds they lean on. A
+//! [`Style`] nudges a generator toward or away from broad features, like the flowing sonorants and open syllables of
+//! an "elvish" language or the velars and heavy codas of a "harsh" one, without rewriting the pattern.
+
+use super::{ParseError, WordGenerator};
+use crate::phone::{self, Manner, Place};
+use smallvec::SmallVec;
+use std::{fmt, str::FromStr};
+
+/// A broad feature a [`Style`] can favor or avoid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Feature {
+    /// Nasals, trills, taps, and approximants, like /m n r l j/.
+    Sonorant,
+    /// Plosives, fricatives, and non-pulmonic consonants, like /p s ʔ ɓ/.
+    Obstruent,
+    /// Consonants made with the lips, like /p b m f/.
+    Labial,
+    /// Consonants made with the tip or blade of the tongue, like /t s n ʃ/.
+    Coronal,
+    /// Palatal, velar, and uvular consonants, like /c k ŋ q χ/.
+    Dorsal,
+    /// Pharyngeal and glottal consonants, like /ħ ʔ h/.
+    Guttural,
+    /// Optional phonemes after the vowel of a syllable, like the `(C)` of `CV(C)`. Favoring codas makes more closed
+    /// syllables; avoiding them makes more open ones.
+    Coda,
+}
+
+impl Feature {
+    const ALL: [(Self, &'static str); 7] = [
+        (Self::Sonorant, "sonorant"),
+        (Self::Obstruent, "obstruent"),
+        (Self::Labial, "labial"),
+        (Self::Coronal, "coronal"),
+        (Self::Dorsal, "dorsal"),
+        (Self::Guttural, "guttural"),
+        (Self::Coda, "coda"),
+    ];
+
+    /// Check if the `phoneme` has this feature. Codas are a property of where a phoneme is, not what it is, so no
+    /// phoneme has [`Feature::Coda`].
+    pub fn has(&self, phoneme: phone::Phoneme) -> bool {
+        let c = match phoneme {
+            phone::Phoneme::Consonant(c) => c,
+            phone::Phoneme::NonPulmonicConsonant(_) => return *self == Self::Obstruent,
+            phone::Phoneme::Vowel(_) => return false,
+        };
+        match self {
+            Self::Sonorant => !matches!(
+                c.manner(),
+                Manner::Plosive | Manner::Fricative | Manner::LateralFricative
+            ),
+            Self::Obstruent => matches!(
+                c.manner(),
+                Manner::Plosive | Manner::Fricative | Manner::LateralFricative
+            ),
+            Self::Labial => matches!(c.place(), Place::Bilabial | Place::Labiodental),
+            Self::Coronal => matches!(
+                c.place(),
+                Place::Dental | Place::Alveolar | Place::PostAlveolar | Place::Retroflex
+            ),
+            Self::Dorsal => matches!(c.place(), Place::Palatal | Place::Velar | Place::Uvular),
+            Self::Guttural => matches!(c.place(), Place::Pharyngeal | Place::Glottal),
+            Self::Coda => false,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        Self::ALL
+            .iter()
+            .find(|(x, _)| x == self)
+            .map(|(_, name)| *name)
+            .unwrap()
+    }
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A set of multipliers which bias a generator toward or away from [features](Feature). A multiplier of 2 makes
+/// phonemes with the feature twice as likely, 0.5 makes them half as likely, and 0 rules them out.
+///
+/// A style can be parsed from a name (`elvish` or `harsh`) or a list of `feature:multiplier` pairs separated by commas,
+/// like `sonorant:3,dorsal:0.5,coda:0.5`.
+#[derive(Clone, PartialEq)]
+pub struct Style {
+    name: Option<&'static str>,
+    biases: Vec<(Feature, f64)>,
+}
+
+impl Style {
+    /// Create a style from pairs of feature and multiplier.
+    ///
+    /// Panics if a multiplier is negative or not finite.
+    pub fn new(biases: impl Into<Vec<(Feature, f64)>>) -> Self {
+        let biases = biases.into();
+        assert!(biases.iter().all(|(_, m)| m.is_finite() && *m >= 0.0));
+        Self { name: None, biases }
+    }
+
+    /// Flowing and soft: lots of sonorants, few sounds from the back of the mouth, and mostly open syllables.
+    pub fn elvish() -> Self {
+        Self {
+            name: Some("elvish"),
+            ..Self::new([
+                (Feature::Sonorant, 3.0),
+                (Feature::Dorsal, 0.5),
+                (Feature::Guttural, 0.25),
+                (Feature::Coda, 0.5),
+            ])
+        }
+    }
+
+    /// Guttural and clipped: lots of obstruents and sounds from the back of the mouth, and mostly closed syllables.
+    pub fn harsh() -> Self {
+        Self {
+            name: Some("harsh"),
+            ..Self::new([
+                (Feature::Obstruent, 2.0),
+                (Feature::Dorsal, 3.0),
+                (Feature::Guttural, 2.0),
+                (Feature::Sonorant, 0.5),
+                (Feature::Coda, 2.0),
+            ])
+        }
+    }
+
+    pub fn biases(&self) -> &[(Feature, f64)] {
+        &self.biases
+    }
+
+    /// The multiplier for a feature, which is 1 if the style does not mention it.
+    pub fn bias(&self, feature: Feature) -> f64 {
+        self.biases
+            .iter()
+            .filter(|(x, _)| *x == feature)
+            .map(|(_, m)| m)
+            .product()
+    }
+
+    /// The combined multiplier for all the features of the `phoneme`.
+    pub fn multiplier(&self, phoneme: phone::Phoneme) -> f64 {
+        self.biases
+            .iter()
+            .filter(|(feature, _)| feature.has(phoneme))
+            .map(|(_, m)| m)
+            .product()
+    }
+}
+
+impl WordGenerator {
+    /// Bias the choices of every slot toward or away from the features of the `style`, and optional codas toward being
+    /// included or left out. Codas which are not optional, like the last `C` of `CVC`, are not affected.
+    ///
+    /// The style is applied to the weights of the pattern, so applying a second style multiplies its biases with the
+    /// first. A slot where every choice would be ruled out is left as it was.
+    pub fn with_style(mut self, style: &Style) -> Self {
+        let default = self.optional_probability();
+        let coda = style.bias(Feature::Coda);
+        for syl in self.syllables.iter_mut() {
+            let nucleus = syl.nucleus();
+            for (idx, ph) in syl.phonemes.iter_mut().enumerate() {
+                let multipliers: SmallVec<[f64; 8]> =
+                    ph.choices.iter().map(|x| style.multiplier(*x)).collect();
+                // Scaling every choice by the same amount changes nothing, so uniform slots can stay uniform
+                if multipliers.iter().any(|m| *m != multipliers[0]) {
+                    let biased: SmallVec<[f64; 8]> = multipliers
+                        .iter()
+                        .enumerate()
+                        .map(|(choice, m)| {
+                            f64::from(ph.weights.get(choice).copied().unwrap_or(1)) * m
+                        })
+                        .collect();
+                    let max = biased.iter().copied().fold(0.0, f64::max);
+                    if max > 0.0 {
+                        let weights = biased
+                            .iter()
+                            .map(|w| match *w {
+                                0.0 => 0,
+                                w => (w / max * f64::from(u8::MAX)).round().max(1.0) as u8,
+                            })
+                            .collect();
+                        *ph = ph.reweight(weights);
+                    }
+                }
+
+                let after_nucleus = nucleus.is_some_and(|(_, last)| idx > last);
+                if ph.optional && after_nucleus && coda != 1.0 {
+                    // Multiply the odds of including the coda, which keeps the chance between 0 and 1
+                    let p = ph.inclusion(default);
+                    ph.probability = Some(match coda {
+                        0.0 => 0.0,
+                        coda => p * coda / (p * coda + 1.0 - p),
+                    });
+                }
+            }
+        }
+        self
+    }
+}
+
+impl FromStr for Style {
+    type Err = ParseError;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseError::InvalidStyle(src.into());
+        match src.trim() {
+            "" => Err(ParseError::NoInput),
+            "elvish" => Ok(Self::elvish()),
+            "harsh" => Ok(Self::harsh()),
+            src => {
+                let mut biases = Vec::new();
+                for pair in src.split(',') {
+                    let (name, m) = pair.split_once(':').ok_or_else(invalid)?;
+                    let feature = Feature::ALL
+                        .iter()
+                        .find(|(_, x)| *x == name.trim())
+                        .map(|(feature, _)| *feature)
+                        .ok_or_else(invalid)?;
+                    let m: f64 = m.trim().parse().map_err(|_| invalid())?;
+                    if !m.is_finite() || m < 0.0 {
+                        return Err(invalid());
+                    }
+                    biases.push((feature, m));
+                }
+                Ok(Self::new(biases))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Style {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(name) = self.name {
+            return f.write_str(name);
+        }
+
+        for (idx, (feature, m)) in self.biases.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(",")?;
+            }
+            write!(f, "{feature}:{m}")?;
+        }
+        Ok(())
+    }
+}
+
+serde_via_str!(Style);
+
+impl fmt::Debug for Style {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Style({self})")
+    }
+}
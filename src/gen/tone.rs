@@ -0,0 +1,154 @@
+//! # Tone
+//!
+//! In a tonal language, the pitch a syllable is spoken with can change its meaning: Mandarin /ma˥/ "mother" and
+//! /ma˨˩˦/ "horse" differ only in tone. [`Tones`] assigns a tone to each generated syllable from a weighted inventory.
+
+use super::ParseError;
+use crate::phone;
+use rand::{seq::SliceRandom, Rng};
+use std::{fmt, str::FromStr};
+
+#[derive(Clone, Copy, PartialEq)]
+struct Entry {
+    tone: phone::Tone,
+    weight: u32,
+    /// The most syllables in a word which can have this tone, if limited.
+    max: Option<usize>,
+}
+
+/// A weighted inventory of tones, where some tones can be limited to appear only so many times per word.
+///
+/// This can be parsed from a list of `tone:weight` or `tone:weight:max` entries separated by commas, like
+/// `55:3,35:2,51:1:1`. Tones are written in Chao numerals or tone letters and the weight defaults to 1, so
+/// `˥,˧˥,˥˩::1` gives each tone the same chance, but allows no more than one high-falling tone per word.
+#[derive(Clone, PartialEq)]
+pub struct Tones {
+    entries: Vec<Entry>,
+}
+
+impl Tones {
+    /// Create an inventory from pairs of tone and weight. Returns `None` if there are no tones or all the weights are 0.
+    pub fn new(tones: impl IntoIterator<Item = (phone::Tone, u32)>) -> Option<Self> {
+        let entries: Vec<_> = tones
+            .into_iter()
+            .filter(|(_, weight)| *weight > 0)
+            .map(|(tone, weight)| Entry {
+                tone,
+                weight,
+                max: None,
+            })
+            .collect();
+        if entries.is_empty() {
+            None
+        } else {
+            Some(Self { entries })
+        }
+    }
+
+    /// Allow the `tone` on at most `max` syllables of a word.
+    pub fn with_limit(mut self, tone: phone::Tone, max: usize) -> Self {
+        for entry in self.entries.iter_mut().filter(|e| e.tone == tone) {
+            entry.max = Some(max);
+        }
+        self
+    }
+
+    /// The tones in the inventory, in the order they were given.
+    pub fn tones(&self) -> impl Iterator<Item = phone::Tone> + '_ {
+        self.entries.iter().map(|e| e.tone)
+    }
+
+    /// The most syllables in a word which can have the `tone`, or `None` if it is not limited.
+    pub fn limit(&self, tone: phone::Tone) -> Option<usize> {
+        self.entries
+            .iter()
+            .find(|e| e.tone == tone)
+            .and_then(|e| e.max)
+    }
+
+    /// Assign a tone to each of the `syllables`. Tones which have reached their limit are not picked again; if every
+    /// tone has reached its limit, the rest of the syllables are left without a tone.
+    pub fn assign(&self, rng: &mut impl Rng, syllables: &mut [phone::Syllable]) {
+        let mut used = vec![0; self.entries.len()];
+        for syl in syllables.iter_mut() {
+            let picked = self
+                .entries
+                .iter()
+                .enumerate()
+                .filter(|(idx, e)| !matches!(e.max, Some(max) if used[*idx] >= max))
+                .collect::<Vec<_>>()
+                .choose_weighted(rng, |(_, e)| e.weight)
+                .ok()
+                .copied();
+            syl.set_tone(picked.map(|(idx, e)| {
+                used[idx] += 1;
+                e.tone
+            }));
+        }
+    }
+}
+
+impl FromStr for Tones {
+    type Err = ParseError;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseError::InvalidTones(src.into());
+        if src.trim().is_empty() {
+            return Err(ParseError::NoInput);
+        }
+
+        let mut entries = Vec::new();
+        for entry in src.split(',') {
+            let mut fields = entry.split(':').map(str::trim);
+            let tone = fields
+                .next()
+                .unwrap_or_default()
+                .parse()
+                .map_err(|_| invalid())?;
+            let weight = match fields.next() {
+                None | Some("") => 1,
+                Some(weight) => weight.parse().map_err(|_| invalid())?,
+            };
+            let max = match fields.next() {
+                None => None,
+                Some(max) => Some(max.parse().map_err(|_| invalid())?),
+            };
+            if fields.next().is_some() {
+                return Err(invalid());
+            }
+            entries.push((tone, weight, max));
+        }
+
+        let mut out = Self::new(entries.iter().map(|(tone, weight, _)| (*tone, *weight)))
+            .ok_or_else(invalid)?;
+        for (tone, _, max) in entries {
+            if let Some(max) = max {
+                out = out.with_limit(tone, max);
+            }
+        }
+        Ok(out)
+    }
+}
+
+impl fmt::Display for Tones {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (idx, entry) in self.entries.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(",")?;
+            }
+            write!(f, "{}:{}", entry.tone, entry.weight)?;
+            if let Some(max) = entry.max {
+                write!(f, ":{max}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+serde_via_str!(Tones);
+
+impl fmt::Debug for Tones {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Tones({self})")
+    }
+}
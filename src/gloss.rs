@@ -0,0 +1,255 @@
+//! # Glosses
+//!
+//! Grammars show example sentences as interlinear glosses: each word, the morphemes it is made of, and what each
+//! morpheme means, lined up underneath each other. A [`Glossary`] holds the morphemes of a language and splits words
+//! into them, so sentences can be glossed from a lexicon instead of by hand.
+
+use crate::phone;
+use std::{fmt, str::FromStr};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ParseError {
+    #[error("no input")]
+    NoInput,
+    #[error("invalid entry \"{0}\": expected a morpheme in IPA, a tab, and its gloss")]
+    InvalidEntry(String),
+    #[error("unknown phoneme '{0}'")]
+    UnknownPhoneme(char),
+}
+
+/// Where a morpheme goes in a word.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    /// A morpheme which can stand on its own, or be compounded with other roots.
+    Root,
+    /// An affix before the root, written with a hyphen after it like `ka-`.
+    Prefix,
+    /// An affix after the root, written with a hyphen before it like `-mi`.
+    Suffix,
+}
+
+/// A morpheme and what it means.
+///
+/// A morpheme can be parsed from its IPA and gloss separated by a tab, like `ka.ta` and `dog` or `-mi` and `PL`.
+/// Syllable breaks and stress marks are ignored, since morphemes are matched against words phoneme by phoneme.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Morpheme {
+    kind: Kind,
+    phonemes: Vec<phone::Phoneme>,
+    gloss: String,
+}
+
+impl Morpheme {
+    pub fn new(
+        kind: Kind,
+        phonemes: impl Into<Vec<phone::Phoneme>>,
+        gloss: impl Into<String>,
+    ) -> Self {
+        Self {
+            kind,
+            phonemes: phonemes.into(),
+            gloss: gloss.into(),
+        }
+    }
+
+    pub fn kind(&self) -> Kind {
+        self.kind
+    }
+
+    pub fn phonemes(&self) -> &[phone::Phoneme] {
+        &self.phonemes
+    }
+
+    pub fn gloss(&self) -> &str {
+        &self.gloss
+    }
+}
+
+impl FromStr for Morpheme {
+    type Err = ParseError;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        let Some((ipa, gloss)) = src.split_once('\t') else {
+            return Err(ParseError::InvalidEntry(src.into()));
+        };
+        let (ipa, gloss) = (ipa.trim(), gloss.trim());
+        let kind = match (ipa.starts_with('-'), ipa.ends_with('-')) {
+            (false, false) => Kind::Root,
+            (false, true) => Kind::Prefix,
+            (true, false) => Kind::Suffix,
+            (true, true) => return Err(ParseError::InvalidEntry(src.into())),
+        };
+        let phonemes = ipa
+            .chars()
+            .filter(|c| !matches!(c, '-' | '.' | 'ˈ' | 'ˌ'))
+            .map(|c| phone::Phoneme::try_from(c).map_err(|_| ParseError::UnknownPhoneme(c)))
+            .collect::<Result<Vec<_>, _>>()?;
+        if phonemes.is_empty() || gloss.is_empty() {
+            return Err(ParseError::InvalidEntry(src.into()));
+        }
+        Ok(Self::new(kind, phonemes, gloss))
+    }
+}
+
+impl fmt::Display for Morpheme {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.kind == Kind::Suffix {
+            f.write_str("-")?;
+        }
+        for ph in self.phonemes.iter() {
+            write!(f, "{ph}")?;
+        }
+        if self.kind == Kind::Prefix {
+            f.write_str("-")?;
+        }
+        write!(f, "\t{}", self.gloss)
+    }
+}
+
+/// The morphemes of a language, parsed from one [`Morpheme`] per line. Blank lines and lines starting with `#` are
+/// skipped.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Glossary {
+    morphemes: Vec<Morpheme>,
+}
+
+impl Glossary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_morpheme(mut self, morpheme: Morpheme) -> Self {
+        self.morphemes.push(morpheme);
+        self
+    }
+
+    pub fn morphemes(&self) -> &[Morpheme] {
+        &self.morphemes
+    }
+
+    /// Split a word into morphemes: any number of prefixes, at least one root, then any number of suffixes. If there
+    /// is more than one way to split the word, the one with the fewest morphemes wins, then the one with the fewest
+    /// roots, so /katami/ is read as a root and a suffix rather than a compound of two roots. Returns `None` if the word
+    /// can not be made from the morphemes.
+    pub fn segment(&self, word: &[phone::Phoneme]) -> Option<Vec<&Morpheme>> {
+        // The stage of the word a split has reached: 0 is still in the prefixes, 1 has a root, and 2 is in the
+        // suffixes. For every position and stage, the fewest morphemes and roots reaching it, and the position, stage,
+        // and morpheme it was reached from.
+        type Step = ((usize, usize), Option<(usize, usize, usize)>);
+        let mut best: Vec<[Option<Step>; 3]> = vec![[None; 3]; word.len() + 1];
+        best[0][0] = Some(((0, 0), None));
+        for pos in 0..word.len() {
+            for stage in 0..3 {
+                let Some(((count, roots), _)) = best[pos][stage] else {
+                    continue;
+                };
+                for (idx, morpheme) in self.morphemes.iter().enumerate() {
+                    let next = match (morpheme.kind, stage) {
+                        (Kind::Prefix, 0) => 0,
+                        (Kind::Root, 0 | 1) => 1,
+                        (Kind::Suffix, 1 | 2) => 2,
+                        _ => continue,
+                    };
+                    if !word[pos..].starts_with(&morpheme.phonemes) {
+                        continue;
+                    }
+                    let end = pos + morpheme.phonemes.len();
+                    let cost = (count + 1, roots + usize::from(morpheme.kind == Kind::Root));
+                    if best[end][next].is_none_or(|(x, _)| cost < x) {
+                        best[end][next] = Some((cost, Some((pos, stage, idx))));
+                    }
+                }
+            }
+        }
+
+        let (_, mut step) = [1, 2]
+            .into_iter()
+            .filter_map(|stage| best[word.len()][stage])
+            .min_by_key(|(cost, _)| *cost)?;
+        let mut out = Vec::new();
+        while let Some((pos, stage, idx)) = step {
+            out.push(&self.morphemes[idx]);
+            (_, step) = best[pos][stage].unwrap();
+        }
+        out.reverse();
+        Some(out)
+    }
+}
+
+impl FromStr for Glossary {
+    type Err = ParseError;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        let mut out = Self::new();
+        for line in src.lines() {
+            if line.trim().is_empty() || line.trim_start().starts_with('#') {
+                continue;
+            }
+            out = out.with_morpheme(line.parse()?);
+        }
+        if out.morphemes.is_empty() {
+            Err(ParseError::NoInput)
+        } else {
+            Ok(out)
+        }
+    }
+}
+
+impl fmt::Display for Glossary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for morpheme in self.morphemes.iter() {
+            writeln!(f, "{morpheme}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segment() {
+        let glossary: Glossary =
+            "ka.ta\tdog\nkat\tcut\n-a\tNOM\n-mi\tPL\nni-\tNEG\nmi\tsee\n# comment\n"
+                .parse()
+                .unwrap();
+        assert_eq!(6, glossary.morphemes().len());
+        assert_eq!(Kind::Prefix, glossary.morphemes()[4].kind());
+
+        let gloss = |word: &str| {
+            let phonemes: Vec<_> = word
+                .chars()
+                .map(|c| phone::Phoneme::try_from(c).unwrap())
+                .collect();
+            glossary.segment(&phonemes).map(|morphemes| {
+                morphemes
+                    .iter()
+                    .map(|m| m.gloss())
+                    .collect::<Vec<_>>()
+                    .join("-")
+            })
+        };
+        // /kata/ is one root rather than a root and a suffix, and /katami/ is a root and a suffix rather than two roots
+        assert_eq!(Some("dog".into()), gloss("kata"));
+        assert_eq!(Some("dog-PL".into()), gloss("katami"));
+        assert_eq!(Some("cut-PL".into()), gloss("katmi"));
+        assert_eq!(Some("NEG-see".into()), gloss("nimi"));
+        assert_eq!(Some("see-dog".into()), gloss("mikata"));
+        // Prefixes come before the root and suffixes after it
+        assert_eq!(None, gloss("kani"));
+        assert_eq!(None, gloss("ni"));
+        assert_eq!(None, gloss("mika"));
+
+        assert!(matches!(
+            "ka".parse::<Glossary>(),
+            Err(ParseError::InvalidEntry(_))
+        ));
+        assert!(matches!(
+            "-ka-\tX".parse::<Morpheme>(),
+            Err(ParseError::InvalidEntry(_))
+        ));
+        assert_eq!("-mi\tPL", glossary.morphemes()[3].to_string());
+    }
+}
@@ -0,0 +1,577 @@
+//! # Import
+//!
+//! Plenty of conlangs were started in other word generators. This module reads the definitions of two popular ones,
+//! [Awkwords](http://akana.conlang.org/tools/awkwords/) and Zompist's [Gen](https://www.zompist.com/gen.html), and
+//! converts their categories and patterns into an inventory and patterns for [`gen`](crate::gen).
+//!
+//! Both tools name categories of phonemes with one uppercase letter and build words from them, but this crate's
+//! classes are defined by the inventory rather than by hand. A category called `C` or `V` which has every consonant or
+//! vowel becomes the class of the same name, and any other category becomes a set like `[mn]`.
+
+use crate::{gen, phone};
+use std::{iter::Peekable, str::Chars};
+use thiserror::Error;
+
+/// Alternatives in a pattern are expanded into separate patterns, which can multiply quickly, so expanding stops past
+/// this many.
+const MAX_PATTERNS: usize = 256;
+
+#[derive(Debug, Error)]
+pub enum ParseError {
+    #[error("no input")]
+    NoInput,
+    #[error("no patterns")]
+    NoPatterns,
+    #[error("invalid line \"{0}\"")]
+    InvalidLine(String),
+    #[error("invalid category \"{0}\": categories are named with one uppercase letter")]
+    InvalidCategory(String),
+    #[error("unknown category '{0}'")]
+    UnknownCategory(char),
+    #[error("\"{0}\" is not a single IPA phoneme")]
+    UnknownPhoneme(String),
+    #[error("invalid weight \"{0}\"")]
+    InvalidWeight(String),
+    #[error("unclosed '{0}'")]
+    Unclosed(char),
+    #[error("unexpected '{0}'")]
+    UnexpectedCharacter(char),
+    #[error("the patterns expand to more than {MAX_PATTERNS} patterns")]
+    TooManyPatterns,
+}
+
+/// A language definition converted from another word generator.
+#[derive(Clone, Debug)]
+pub struct Import {
+    inventory: phone::Inventory,
+    patterns: Vec<String>,
+    filters: Vec<gen::Filter>,
+    notes: Vec<String>,
+}
+
+/// A category of phonemes like `C:p/t/k`, with the weight of each phoneme.
+struct Category {
+    name: char,
+    members: Vec<(phone::Phoneme, u32)>,
+}
+
+/// A part of a pattern in the syntax of the source.
+enum Node {
+    Phoneme(phone::Phoneme),
+    Category(char),
+    /// A sequence which may be left out, like `(CV)`.
+    Optional(Vec<Node>),
+    /// A choice between sequences, like `[CV/V]`.
+    Choice(Vec<Vec<Node>>),
+}
+
+/// One slot of a pattern in this crate's syntax, like `C`, `[mn]`, or `(\n)`.
+#[derive(Clone)]
+struct Slot {
+    text: String,
+    /// Every phoneme the slot can produce is a vowel.
+    vowel: bool,
+}
+
+fn is_vowel(phoneme: &phone::Phoneme) -> bool {
+    matches!(phoneme, phone::Phoneme::Vowel(_))
+}
+
+fn literal(c: char) -> Result<phone::Phoneme, ParseError> {
+    phone::Phoneme::try_from(c).map_err(|_| ParseError::UnknownPhoneme(c.into()))
+}
+
+fn phoneme(src: &str) -> Result<phone::Phoneme, ParseError> {
+    let mut chars = src.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => literal(c),
+        _ => Err(ParseError::UnknownPhoneme(src.into())),
+    }
+}
+
+/// Parse the name of a category, which must be a single uppercase letter.
+fn category_name(src: &str) -> Result<char, ParseError> {
+    let mut chars = src.trim().chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_uppercase() => Ok(c),
+        _ => Err(ParseError::InvalidCategory(src.trim().into())),
+    }
+}
+
+/// Parses patterns in the syntax shared by Awkwords and Gen: category letters, literal phonemes which can be quoted
+/// like `"ka"`, optional sequences like `(CV)`, and choices like `[CV/V*2]` whose weights are dropped.
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    categories: &'a [Category],
+    dropped_weights: bool,
+}
+
+impl<'a> Parser<'a> {
+    /// Characters which end a sequence, to be handled by whatever contains it.
+    const STOPS: [char; 5] = [')', ']', '/', '*', '^'];
+
+    fn new(src: &'a str, categories: &'a [Category]) -> Self {
+        Self {
+            chars: src.chars().peekable(),
+            categories,
+            dropped_weights: false,
+        }
+    }
+
+    fn sequence(&mut self) -> Result<Vec<Node>, ParseError> {
+        let mut out = Vec::new();
+        while let Some(c) = self.chars.next_if(|c| !Self::STOPS.contains(c)) {
+            match c {
+                c if c.is_whitespace() => {}
+                '(' => {
+                    let inner = self.sequence()?;
+                    if self.chars.next() != Some(')') {
+                        return Err(ParseError::Unclosed('('));
+                    }
+                    out.push(Node::Optional(inner));
+                }
+                '[' => {
+                    let mut alternatives = vec![self.sequence()?];
+                    loop {
+                        match self.chars.next() {
+                            Some('/') => alternatives.push(self.sequence()?),
+                            Some('*') => {
+                                let weight: String =
+                                    std::iter::from_fn(|| self.chars.next_if(char::is_ascii_digit))
+                                        .collect();
+                                if weight.is_empty() {
+                                    return Err(ParseError::InvalidWeight("*".into()));
+                                }
+                                self.dropped_weights = true;
+                            }
+                            Some(']') => break,
+                            _ => return Err(ParseError::Unclosed('[')),
+                        }
+                    }
+                    out.push(Node::Choice(alternatives));
+                }
+                '"' => loop {
+                    match self.chars.next() {
+                        Some('"') => break,
+                        Some(c) => out.push(Node::Phoneme(literal(c)?)),
+                        None => return Err(ParseError::Unclosed('"')),
+                    }
+                },
+                c if self.categories.iter().any(|x| x.name == c) => out.push(Node::Category(c)),
+                c if c.is_uppercase() => return Err(ParseError::UnknownCategory(c)),
+                c => out.push(Node::Phoneme(literal(c)?)),
+            }
+        }
+        Ok(out)
+    }
+
+    /// Parse a whole pattern, which must not have anything left over.
+    fn pattern(mut self) -> Result<(Vec<Node>, bool), ParseError> {
+        let out = self.sequence()?;
+        match self.chars.next() {
+            Some(c) => Err(ParseError::UnexpectedCharacter(c)),
+            None => Ok((out, self.dropped_weights)),
+        }
+    }
+}
+
+/// Combine every sequence of `prefixes` with every sequence of `suffixes`.
+fn product(prefixes: Vec<Vec<Slot>>, suffixes: &[Vec<Slot>]) -> Result<Vec<Vec<Slot>>, ParseError> {
+    if prefixes.len() * suffixes.len() > MAX_PATTERNS {
+        return Err(ParseError::TooManyPatterns);
+    }
+    Ok(prefixes
+        .iter()
+        .flat_map(|prefix| {
+            suffixes
+                .iter()
+                .map(move |suffix| prefix.iter().chain(suffix).cloned().collect())
+        })
+        .collect())
+}
+
+/// Write the `slots` as a pattern, with a syllable break before the consonant in front of each vowel after the
+/// first, so `CVCV` becomes `CV CV`.
+fn to_pattern(slots: &[Slot]) -> String {
+    let mut out = String::new();
+    let mut nucleus = false;
+    for (idx, slot) in slots.iter().enumerate() {
+        if nucleus && !slot.vowel && slots.get(idx + 1).is_some_and(|x| x.vowel) {
+            out.push(' ');
+            nucleus = false;
+        }
+        nucleus |= slot.vowel;
+        out.push_str(&slot.text);
+    }
+    out
+}
+
+/// Expand a pattern into every sequence of slots it can produce.
+fn expand(nodes: &[Node], slots: &[(char, Slot)]) -> Result<Vec<Vec<Slot>>, ParseError> {
+    let mut out = vec![Vec::new()];
+    for node in nodes.iter() {
+        let alternatives = match node {
+            Node::Phoneme(ph) => vec![vec![Slot {
+                text: format!("\\{ph}"),
+                vowel: is_vowel(ph),
+            }]],
+            Node::Category(name) => {
+                let (_, slot) = slots.iter().find(|(x, _)| x == name).unwrap();
+                vec![vec![slot.clone()]]
+            }
+            Node::Optional(inner) => {
+                let mut inner = expand(inner, slots)?;
+                // A single slot can be optional on its own; anything longer is the same as a choice between
+                // having the sequence and not
+                match &inner[..] {
+                    [seq] if seq.len() == 1 => vec![vec![Slot {
+                        text: format!("({})", seq[0].text),
+                        vowel: seq[0].vowel,
+                    }]],
+                    _ => {
+                        inner.push(Vec::new());
+                        inner
+                    }
+                }
+            }
+            Node::Choice(alternatives) => {
+                let phonemes: Option<Vec<_>> = alternatives
+                    .iter()
+                    .map(|alt| match &alt[..] {
+                        [Node::Phoneme(ph)] => Some(*ph),
+                        _ => None,
+                    })
+                    .collect();
+                match phonemes {
+                    Some(phonemes) => vec![vec![Slot {
+                        text: format!(
+                            "[{}]",
+                            phonemes.iter().map(|x| x.to_string()).collect::<String>()
+                        ),
+                        vowel: phonemes.iter().all(is_vowel),
+                    }]],
+                    None => {
+                        let mut out = Vec::new();
+                        for alt in alternatives.iter() {
+                            out.extend(expand(alt, slots)?);
+                        }
+                        out
+                    }
+                }
+            }
+        };
+        out = product(out, &alternatives)?;
+    }
+    Ok(out)
+}
+
+impl Import {
+    /// Convert the categories of a definition to an inventory, and its patterns to patterns in this crate's syntax.
+    fn new(
+        categories: &[Category],
+        patterns: &[Vec<Node>],
+        mut notes: Vec<String>,
+    ) -> Result<Self, ParseError> {
+        let mut phonemes: Vec<phone::Phoneme> = Vec::new();
+        for (ph, _) in categories.iter().flat_map(|x| x.members.iter()) {
+            if !phonemes.contains(ph) {
+                phonemes.push(*ph);
+            }
+        }
+        let mut inventory = phone::Inventory::new(
+            phonemes
+                .iter()
+                .filter_map(|ph| match ph {
+                    phone::Phoneme::Consonant(x) => Some(*x),
+                    _ => None,
+                })
+                .collect::<Vec<_>>(),
+            phonemes
+                .iter()
+                .filter_map(|ph| match ph {
+                    phone::Phoneme::Vowel(x) => Some(*x),
+                    _ => None,
+                })
+                .collect::<Vec<_>>(),
+            phonemes
+                .iter()
+                .filter_map(|ph| match ph {
+                    phone::Phoneme::NonPulmonicConsonant(x) => Some(*x),
+                    _ => None,
+                })
+                .collect::<Vec<_>>(),
+        );
+
+        let mut slots = Vec::with_capacity(categories.len());
+        for category in categories.iter() {
+            let members: Vec<_> = category.members.iter().map(|(ph, _)| *ph).collect();
+            let same = |class: Vec<phone::Phoneme>| {
+                class.len() == members.len() && class.iter().all(|x| members.contains(x))
+            };
+            let class = match category.name {
+                'C' if same(inventory.consonants().iter().map(|x| (*x).into()).collect()) => {
+                    Some("C")
+                }
+                'V' if same(inventory.vowels().iter().map(|x| (*x).into()).collect()) => Some("V"),
+                _ => None,
+            };
+            let weighted = category
+                .members
+                .iter()
+                .any(|(_, w)| *w != category.members[0].1);
+            let text = match (class, &members[..]) {
+                (Some(class), _) => {
+                    // Only classes draw from the inventory, so only their weights carry over
+                    if weighted {
+                        for (ph, weight) in category.members.iter() {
+                            let weight = u8::try_from(*weight).unwrap_or(u8::MAX);
+                            inventory = inventory.with_weight(*ph, weight);
+                        }
+                    }
+                    class.to_string()
+                }
+                (None, [ph]) => format!("\\{ph}"),
+                (None, _) => {
+                    if weighted {
+                        notes.push(format!(
+                            "the weights of category {} were dropped, since only C and V can be weighted",
+                            category.name
+                        ));
+                    }
+                    format!(
+                        "[{}]",
+                        members.iter().map(|x| x.to_string()).collect::<String>()
+                    )
+                }
+            };
+            slots.push((
+                category.name,
+                Slot {
+                    text,
+                    vowel: members.iter().all(is_vowel),
+                },
+            ));
+        }
+
+        let mut out = Self {
+            inventory,
+            patterns: Vec::new(),
+            filters: Vec::new(),
+            notes,
+        };
+        for pattern in patterns.iter() {
+            for expanded in expand(pattern, &slots)? {
+                let expanded = to_pattern(&expanded);
+                if !expanded.is_empty() && !out.patterns.contains(&expanded) {
+                    out.patterns.push(expanded);
+                }
+                if out.patterns.len() > MAX_PATTERNS {
+                    return Err(ParseError::TooManyPatterns);
+                }
+            }
+        }
+        if out.patterns.is_empty() {
+            return Err(ParseError::NoPatterns);
+        }
+        Ok(out)
+    }
+
+    /// Read an Awkwords definition. Each line is either a category like `C:p/t/k*2/s`, where a phoneme's weight can
+    /// follow a `*`, or the pattern of a word like `r:CV[CV/V](N)`. Filters follow the pattern like `^ji^wu`, and
+    /// reject words containing the sequences.
+    ///
+    /// Choices between sequences like `[CV/V]` and optional sequences like `(CV)` are expanded into separate patterns,
+    /// since this crate's patterns can only choose between phonemes. Other settings are ignored, and listed in the
+    /// [notes](Self::notes).
+    pub fn awkwords(src: &str) -> Result<Self, ParseError> {
+        let mut categories = Vec::new();
+        let mut patterns = Vec::new();
+        let mut notes = Vec::new();
+        let mut filters = Vec::new();
+        for line in src.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once(':') else {
+                return Err(ParseError::InvalidLine(line.into()));
+            };
+            match key.trim() {
+                "r" => {
+                    let (pattern, rest) = value.split_once('^').unwrap_or((value, ""));
+                    patterns.push(pattern.to_string());
+                    for filter in rest.split('^').filter(|x| !x.trim().is_empty()) {
+                        filters.push(
+                            filter
+                                .chars()
+                                .filter(|c| !c.is_whitespace() && *c != '"')
+                                .map(literal)
+                                .collect::<Result<Vec<_>, _>>()?,
+                        );
+                    }
+                }
+                key if key.chars().all(|c| c.is_lowercase()) => {
+                    notes.push(format!("the setting `{line}` was ignored"));
+                }
+                key => {
+                    let mut members = Vec::new();
+                    for member in value.split('/').map(str::trim).filter(|x| !x.is_empty()) {
+                        let (member, weight) = match member.split_once('*') {
+                            Some((member, weight)) => (
+                                member.trim(),
+                                weight
+                                    .trim()
+                                    .parse()
+                                    .map_err(|_| ParseError::InvalidWeight(weight.into()))?,
+                            ),
+                            None => (member, 1),
+                        };
+                        members.push((phoneme(member)?, weight));
+                    }
+                    categories.push(Category {
+                        name: category_name(key)?,
+                        members,
+                    });
+                }
+            }
+        }
+
+        if categories.is_empty() && patterns.is_empty() {
+            return Err(ParseError::NoInput);
+        }
+        let mut nodes = Vec::new();
+        for pattern in patterns.iter() {
+            let (pattern, dropped_weights) = Parser::new(pattern, &categories).pattern()?;
+            if dropped_weights {
+                notes.push("the weights of choices like `[CV*2/V]` were dropped".into());
+            }
+            nodes.push(pattern);
+        }
+        let mut out = Self::new(&categories, &nodes, notes)?;
+        if !filters.is_empty() {
+            out.filters.push(gen::Filter::Forbid(filters));
+        }
+        Ok(out)
+    }
+
+    /// Read a Gen definition. Each line is either a category like `C=ptkmn`, a rewrite rule like `ki>ci` or `ki|ci`,
+    /// or syllable types like `CV V CVC`. Each syllable type becomes a pattern of one syllable; Gen builds words out of
+    /// several syllables, which `generate-syllables` can do with all the syllable types in one pattern and
+    /// `--syllable-counts`.
+    ///
+    /// Rewrite rules can not be converted to a pattern, and are listed in the [notes](Self::notes) instead.
+    pub fn zompist(src: &str) -> Result<Self, ParseError> {
+        let mut categories = Vec::new();
+        let mut patterns = Vec::new();
+        let mut notes = Vec::new();
+        for line in src.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line.contains(['>', '|']) {
+                notes.push(format!("the rewrite rule `{line}` was not imported"));
+            } else if let Some((name, members)) = line.split_once('=') {
+                categories.push(Category {
+                    name: category_name(name)?,
+                    members: members
+                        .chars()
+                        .filter(|c| !c.is_whitespace())
+                        .map(|c| Ok((literal(c)?, 1)))
+                        .collect::<Result<_, ParseError>>()?,
+                });
+            } else {
+                patterns.extend(line.split_whitespace().map(str::to_string));
+            }
+        }
+
+        let nodes = patterns
+            .iter()
+            .map(|pattern| Ok(Parser::new(pattern, &categories).pattern()?.0))
+            .collect::<Result<Vec<_>, ParseError>>()?;
+        if categories.is_empty() && nodes.is_empty() {
+            return Err(ParseError::NoInput);
+        }
+        // Gen's dropoff makes the first phonemes of a category the most common, which weights can imitate
+        notes.push(
+            "Gen favors the first phonemes of each category; use weights to do the same".into(),
+        );
+        Self::new(&categories, &nodes, notes)
+    }
+
+    /// The phonemes of every category, with weights from weighted `C` and `V` categories.
+    pub fn inventory(&self) -> &phone::Inventory {
+        &self.inventory
+    }
+
+    pub fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
+    pub fn filters(&self) -> &[gen::Filter] {
+        &self.filters
+    }
+
+    /// Parts of the definition which could not be converted.
+    pub fn notes(&self) -> &[String] {
+        &self.notes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn awkwords() {
+        let import =
+            Import::awkwords("C:p/t*3/k/m/n\nV:a/i/u\nN:m/n\nr:CV[CV/V](N)^ti\nn:100\n").unwrap();
+        assert_eq!(5, import.inventory().consonants().len());
+        assert_eq!(3, import.inventory().vowels().len());
+        assert_eq!(
+            3,
+            import
+                .inventory()
+                .weight(phone::Phoneme::try_from('t').unwrap())
+        );
+        assert_eq!(vec!["CV CV([mn])", "CVV([mn])"], import.patterns());
+        assert_eq!("forbid ti", import.filters()[0].to_string());
+        assert_eq!(1, import.notes().len());
+
+        for pattern in import.patterns() {
+            gen::WordGenerator::parse(pattern, import.inventory()).unwrap();
+        }
+
+        assert!(matches!(
+            Import::awkwords("C:p/ts\nr:CV"),
+            Err(ParseError::UnknownPhoneme(_))
+        ));
+        assert!(matches!(
+            Import::awkwords("C:p/t\nV:a\nr:CVX"),
+            Err(ParseError::UnknownCategory('X'))
+        ));
+        assert!(matches!(
+            Import::awkwords("C:p/t\nr:C[V"),
+            Err(ParseError::UnknownCategory('V'))
+        ));
+        assert!(matches!(
+            Import::awkwords("V:a/i\nr:[V/\"a\""),
+            Err(ParseError::Unclosed('['))
+        ));
+    }
+
+    #[test]
+    fn zompist() {
+        let import = Import::zompist("C=ptkmn\nV=aiu\nN=n\nCV\nCVN V\nki|ci\n").unwrap();
+        assert_eq!(vec!["CV", "CV\\n", "V"], import.patterns());
+        assert!(import.filters().is_empty());
+        assert_eq!(2, import.notes().len());
+        for pattern in import.patterns() {
+            gen::WordGenerator::parse(pattern, import.inventory()).unwrap();
+        }
+
+        assert!(matches!(
+            Import::zompist("Cx=ptk\nCV"),
+            Err(ParseError::InvalidCategory(_))
+        ));
+        assert!(matches!(Import::zompist(""), Err(ParseError::NoInput)));
+    }
+}
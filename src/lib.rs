@@ -1,2 +1,11 @@
+pub mod analysis;
+pub mod change;
 pub mod gen;
+pub mod gloss;
+pub mod import;
+pub mod ortho;
 pub mod phone;
+#[cfg(feature = "speech")]
+pub mod speech;
+#[cfg(feature = "speech")]
+pub mod synth;
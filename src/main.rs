@@ -1,16 +1,67 @@
 use anyhow::anyhow;
-use bytes::Bytes;
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches, Parser};
 use itertools::Itertools;
+use rand::{distributions::Distribution, Rng, SeedableRng};
 use soloud::{AudioExt, LoadExt};
 use std::fmt::Write;
 
-use conlang::{gen, phone};
+use conlang::{analysis, change, gen, gloss, import, ortho, phone, speech, synth};
+
+mod audition;
+mod config;
+mod export;
+mod picker;
+mod preset;
+mod quiz;
+mod serve;
+mod trainer;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+
+    /// How words in IPA are set off in the output of every command and format: bare like `ka.ta`, between `slashes`
+    /// like `/ka.ta/` for phonemic transcriptions, or in `square` brackets like `[ka.ta]` for phonetic ones.
+    #[arg(long, global = true, value_enum, default_value_t)]
+    bracket: Bracket,
+
+    #[command(flatten, next_help_heading = "Speech")]
+    voice: Voice,
+}
+
+#[derive(clap::Subcommand, Debug)]
+// The subcommands are named after the variants, so they share a prefix like `generate-words`
+#[allow(clippy::enum_variant_names)]
 enum Command {
-    GenerateSyllables(GenerateSyllablesCmd),
+    GenerateSyllables(Box<GenerateSyllablesCmd>),
+    GenerateWords(GenerateWordsCmd),
+    GenerateNames(GenerateNamesCmd),
+    GenerateFromCorpus(GenerateFromCorpusCmd),
+    ShowInventory(ShowInventoryCmd),
+    SoundChange(SoundChangeCmd),
+    Analyze(AnalyzeCmd),
+    Transliterate(TransliterateCmd),
+    ValidatePattern(ValidatePatternCmd),
+    RandomInventory(RandomInventoryCmd),
+    Export(ExportCmd),
+    Import(ImportCmd),
+    Gloss(GlossCmd),
+    Pronounce(PronounceCmd),
+    CompareInventories(CompareInventoriesCmd),
+    Stats(StatsCmd),
+    Diff(DiffCmd),
+    Quiz(QuizCmd),
+    Trainer(TrainerCmd),
+    Serve(ServeCmd),
+    Preset(PresetCmd),
+    Paradigm(ParadigmCmd),
+    FrequencyChart(FrequencyChartCmd),
+    Swadesh(SwadeshCmd),
+    Synthesize(SynthesizeCmd),
+    PickInventory(PickInventoryCmd),
+    PhonemeInfo(PhonemeInfoCmd),
 }
 
 fn parse_all<T>(src: &str) -> Result<Vec<T>, anyhow::Error>
@@ -19,28 +70,209 @@ where
 {
     let mut out = Vec::with_capacity(src.len());
     let mut unknowns = Vec::new();
+    // A caret under each unknown character, to point them out in a long list
+    let mut carets = String::new();
     for c in src.chars() {
         match T::try_from(c) {
-            Ok(x) => out.push(x),
-            Err(_) => unknowns.push(c),
+            Ok(x) => {
+                out.push(x);
+                carets.push(' ');
+            }
+            Err(_) => {
+                unknowns.push(c);
+                carets.push('^');
+            }
         }
     }
 
-    match unknowns.len() {
-        0 => Ok(out),
-        1 => Err(anyhow!("unknown character: {}", unknowns[0])),
+    let mut msg = match unknowns.len() {
+        0 => return Ok(out),
+        1 => format!("unknown character: {}", unknowns[0]),
         _ => {
             let mut msg = format!("unknown characters: {}", unknowns[0]);
             for c in unknowns[1..].iter() {
                 write!(msg, ", {c}")?;
             }
-            Err(anyhow!(msg))
+            msg
+        }
+    };
+    write!(msg, "\n  | {src}\n  | {}", carets.trim_end())?;
+    Err(anyhow!(msg))
+}
+
+/// The inventory of the given phonemes, with every consonant and vowel if none are given.
+fn inventory(
+    consonants: &Option<Vec<phone::Consonant>>,
+    vowels: &Option<Vec<phone::Vowel>>,
+    non_pulmonic: &Option<Vec<phone::NonPulmonicConsonant>>,
+) -> phone::Inventory {
+    phone::Inventory::new(
+        consonants
+            .as_ref()
+            .map(|x| &x[..])
+            .unwrap_or(phone::Consonant::all()),
+        vowels
+            .as_ref()
+            .map(|x| &x[..])
+            .unwrap_or(phone::Vowel::all()),
+        non_pulmonic.as_ref().map(|x| &x[..]).unwrap_or(&[]),
+    )
+}
+
+/// The number of words to generate, which is effectively endless with `--infinite`.
+fn limit(count: usize, infinite: bool) -> usize {
+    if infinite {
+        usize::MAX
+    } else {
+        count
+    }
+}
+
+/// How words in IPA are set off in the output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+enum Bracket {
+    #[default]
+    None,
+    Slashes,
+    Square,
+}
+
+/// The `--bracket` style, set once the command line has been parsed.
+static BRACKET: std::sync::OnceLock<Bracket> = std::sync::OnceLock::new();
+
+/// Set off a word or phrase in IPA with the `--bracket` style. Empty text is left empty.
+fn bracket(ipa: impl std::fmt::Display) -> String {
+    let ipa = ipa.to_string();
+    if ipa.is_empty() {
+        return ipa;
+    }
+    match BRACKET.get().copied().unwrap_or_default() {
+        Bracket::None => ipa,
+        Bracket::Slashes => format!("/{ipa}/"),
+        Bracket::Square => format!("[{ipa}]"),
+    }
+}
+
+/// Print a line of output, stopping quietly if stdout has been closed, like when an endless stream is piped into
+/// `head`.
+fn output(line: std::fmt::Arguments) {
+    use std::io::Write;
+    if let Err(e) = Progress::suspend(|| writeln!(Output, "{line}")) {
+        output_failed(e);
+    }
+}
+
+/// The file given with `--output`, which [`Output`] writes to instead of stdout.
+static OUTPUT_FILE: std::sync::Mutex<Option<std::io::BufWriter<std::fs::File>>> =
+    std::sync::Mutex::new(None);
+
+/// Where generated words go: the `--output` file if one was opened with [`open_output`], or stdout.
+struct Output;
+
+impl std::io::Write for Output {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match OUTPUT_FILE.lock().unwrap().as_mut() {
+            Some(file) => file.write(buf),
+            None => std::io::stdout().write(buf),
+        }
+    }
+
+    /// Flush stdout, so `--infinite` streams. An `--output` file is only flushed by [`close_output`], since buffering
+    /// is the point of writing to it directly.
+    fn flush(&mut self) -> std::io::Result<()> {
+        match OUTPUT_FILE.lock().unwrap().as_ref() {
+            Some(_) => Ok(()),
+            None => std::io::stdout().flush(),
+        }
+    }
+}
+
+/// Stop after failing to write output. Stdout being closed is expected when piping into `head`, but failing to write
+/// an `--output` file is an error.
+fn output_failed(e: std::io::Error) -> ! {
+    if OUTPUT_FILE.lock().unwrap().is_some() {
+        ErrorKind::Output.exit(e);
+    }
+    std::process::exit(0);
+}
+
+/// Send [`output`] to the file at `path` instead of stdout, replacing it or, with `append`, adding to its end.
+fn open_output(path: &std::path::Path, append: bool) -> Result<(), anyhow::Error> {
+    let file = std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .append(append)
+        .truncate(!append)
+        .open(path)
+        .map_err(|e| anyhow!("{}: {e}", path.display()))?;
+    *OUTPUT_FILE.lock().unwrap() = Some(std::io::BufWriter::new(file));
+    Ok(())
+}
+
+/// Flush the `--output` file, if there is one, and print a summary of what was written to it.
+fn close_output(path: Option<&std::path::Path>, count: usize, noun: &str) {
+    use std::io::Write;
+    let Some(path) = path else {
+        return;
+    };
+    if let Some(mut file) = OUTPUT_FILE.lock().unwrap().take() {
+        if let Err(e) = file.flush() {
+            ErrorKind::Output.exit(format_args!("{}: {e}", path.display()));
+        }
+    }
+    eprintln!("wrote {count} {noun} to {}", path.display());
+}
+
+fn parse_weights(src: &str) -> Result<Vec<(phone::Phoneme, u8)>, anyhow::Error> {
+    let mut out = Vec::new();
+    for pair in src.split(',') {
+        let invalid = || anyhow!("invalid weight \"{pair}\": expected `phoneme:weight`");
+        let (phoneme, weight) = pair.split_once(':').ok_or_else(invalid)?;
+        let mut chars = phoneme.trim().chars();
+        let (Some(c), None) = (chars.next(), chars.next()) else {
+            return Err(invalid());
+        };
+        out.push((phone::Phoneme::try_from(c)?, weight.trim().parse()?));
+    }
+    Ok(out)
+}
+
+fn parse_stratum(src: &str) -> Result<(String, phone::Inventory), anyhow::Error> {
+    let Some((name, phonemes)) = src.split_once('=') else {
+        return Err(anyhow!(
+            "invalid stratum \"{src}\": expected `name=phonemes`"
+        ));
+    };
+    let (mut consonants, mut vowels, mut non_pulmonic) = (Vec::new(), Vec::new(), Vec::new());
+    for c in phonemes.chars() {
+        match phone::Phoneme::try_from(c)? {
+            phone::Phoneme::Consonant(x) => consonants.push(x),
+            phone::Phoneme::Vowel(x) => vowels.push(x),
+            phone::Phoneme::NonPulmonicConsonant(x) => non_pulmonic.push(x),
         }
     }
+    Ok((
+        name.trim().into(),
+        phone::Inventory::new(consonants, vowels, non_pulmonic),
+    ))
+}
+
+fn parse_probability(src: &str) -> Result<f64, anyhow::Error> {
+    let probability: f64 = src.parse()?;
+    if (0.0..=1.0).contains(&probability) {
+        Ok(probability)
+    } else {
+        Err(anyhow!("{probability} is not between 0 and 1"))
+    }
 }
 
 #[derive(Parser, Debug)]
 struct GenerateSyllablesCmd {
+    /// A TOML file defining the language: its inventory, weights, patterns, filters, and romanization. Flags given on
+    /// the command line are used instead of the file's values or, for weights and filters, added to them.
+    #[arg(long)]
+    pub language: Option<std::path::PathBuf>,
+
     #[arg(long, value_parser = parse_all::<phone::Consonant>)]
     pub consonants: Option<std::vec::Vec<phone::Consonant>>,
 
@@ -50,110 +282,4701 @@ struct GenerateSyllablesCmd {
     #[arg(long, value_parser = parse_all::<phone::NonPulmonicConsonant>)]
     pub non_pulmonic: Option<std::vec::Vec<phone::NonPulmonicConsonant>>,
 
-    /// A phonotological constraint pattern like `CVC` or `VV`. Specify more than once for multiple patterns.
-    #[arg(long, required(true))]
+    /// Make some phonemes more common than others with a weighted list like `t:5,k:3,a:4`. Phonemes without a weight
+    /// have a weight of 1.
+    #[arg(long, value_parser = parse_weights)]
+    pub weights: Option<std::vec::Vec<(phone::Phoneme, u8)>>,
+
+    /// An alternate inventory for a layer of the lexicon like `loan=fvzʒaeiou`, used by pattern slots like `C@loan`.
+    /// Specify more than once for multiple strata.
+    #[arg(long, value_parser = parse_stratum)]
+    pub stratum: Vec<(String, phone::Inventory)>,
+
+    /// A phonotological constraint pattern like `CVC` or `VV`. Specify more than once for multiple patterns, each
+    /// optionally followed by a weight like `CVC=3` to be picked three times as often as a pattern without one. Weights
+    /// are ignored with `--unique`, which draws from every possible word at once. With `--rules`, this can also be the
+    /// name of a rule. With `-`, patterns are read from stdin one per line, and `--count` words are generated for each.
+    #[arg(long)]
     pub pattern: Vec<String>,
 
-    /// Speak the generated phrases.
+    /// A file of patterns to use instead of `--pattern`, one per line with an optional weight like `CVC = 3`. Lines
+    /// starting with `#` are comments.
+    #[arg(long, conflicts_with = "pattern")]
+    pub pattern_file: Option<std::path::PathBuf>,
+
+    /// A file of named patterns, one `name = pattern` per line. Patterns in the file can refer to each other by name,
+    /// like `word = prefix? stem suffix?`.
+    #[arg(long)]
+    pub rules: Option<std::path::PathBuf>,
+
+    /// Speak the generated phrases. Phrases go on being generated and printed while the ones before them are spoken.
     #[arg(long)]
     pub speak: bool,
+
+    /// Go through the generated words in an interactive list instead of printing them: enter speaks a word and space
+    /// stars it. The starred words are printed on quitting.
+    #[arg(long, conflicts_with_all = ["speak", "infinite", "format", "minimal_pairs"])]
+    pub audition: bool,
+
+    /// How many words to generate.
+    #[arg(long, default_value_t = 100)]
+    pub count: usize,
+
+    /// Keep generating words until stopped, instead of stopping after `--count`.
+    #[arg(long, conflicts_with = "count")]
+    pub infinite: bool,
+
+    /// Generate again whenever the `--language`, `--rules`, or `--pattern-file` file changes, for a quick loop while
+    /// tuning a language. Stop with Ctrl-C. With `--seed`, every run uses the same seed, so runs are easy to compare.
+    #[arg(long, conflicts_with_all = ["infinite", "audition"])]
+    pub watch: bool,
+
+    /// Pick the number of syllables in each word at random, like `1:20,2:50,3:25,4:5`. Each syllable of the word is
+    /// picked from the syllables of the pattern.
+    #[arg(long)]
+    pub syllable_counts: Option<gen::SyllableCounts>,
+
+    /// Reduplicate each word: `full` repeats the whole word, `syllable` repeats the first syllable in front of it, and
+    /// `cv` copies its first consonant and vowel in front of it.
+    #[arg(long)]
+    pub reduplication: Option<gen::Reduplication>,
+
+    /// Mark stress on words of more than one syllable: `initial`, `final`, `penultimate`, `antepenultimate`, or
+    /// `weight-sensitive`.
+    #[arg(long)]
+    pub stress: Option<gen::StressRule>,
+
+    /// Restrict which consonants can appear in each position of a word, like `initial=ptkh medial=ptkŋh coda=mnŋ`.
+    #[arg(long)]
+    pub positions: Option<gen::Positions>,
+
+    /// Assign tones to syllables from a weighted list like `55:3,35:2,51:1:1`, where the optional third field limits
+    /// how many syllables of a word can have the tone.
+    #[arg(long)]
+    pub tones: Option<gen::Tones>,
+
+    /// The chance that optional phonemes like `(C)` are included, from 0 to 1. Phonemes with their own probability like
+    /// `(C)?0.3` keep it.
+    #[arg(long, value_parser = parse_probability)]
+    pub optional_probability: Option<f64>,
+
+    /// Bias generation toward a style: `elvish`, `harsh`, or multipliers for features like `sonorant:3,dorsal:0.5`.
+    /// The features are `sonorant`, `obstruent`, `labial`, `coronal`, `dorsal`, `guttural`, and `coda`.
+    #[arg(long)]
+    pub style: Option<gen::Style>,
+
+    /// Give up on a word after generating this many candidates which are all rejected by the filters, harmony, or
+    /// positions.
+    #[arg(long)]
+    pub max_attempts: Option<std::num::NonZeroUsize>,
+
+    /// Reject generated words matching a filter like `no-final h`, `no-adjacent-repeats`, `regex ^ŋ`, `sonority 2`, or
+    /// `forbid ji wu tl`. Specify more than once for multiple filters.
+    #[arg(long)]
+    pub filter: Vec<gen::Filter>,
+
+    /// Restrict the vowels in each word to a single harmony class: `front-back`, `atr`, or explicit classes like
+    /// `iyeø/ɯuɤo`.
+    #[arg(long)]
+    pub harmony: Option<gen::Harmony>,
+
+    /// A file of existing words, one per line in IPA. Generated words which are too close to one of them are rejected,
+    /// so new words do not become accidental homophones.
+    #[arg(long)]
+    pub lexicon: Option<std::path::PathBuf>,
+
+    /// With `--lexicon`, reject words this many edits (inserted, deleted, or changed phonemes) or fewer from an
+    /// existing word. 0 only rejects exact homophones.
+    #[arg(long, default_value_t = 1)]
+    pub lexicon_distance: usize,
+
+    /// A file of the words generated by earlier runs, one per line in IPA, which is created if it does not exist. Words
+    /// in it are never generated again, and every generated word is added to it, so vocabulary which has been adopted
+    /// into the language is not repeated by later runs.
+    #[arg(long, conflicts_with_all = ["audition", "minimal_pairs"])]
+    pub history: Option<std::path::PathBuf>,
+
+    /// Reject words whose romanization matches or closely resembles a real word in a file with one word per line,
+    /// like profanity. Without a file, a small bundled list of English words is used.
+    #[arg(long, num_args = 0..=1, value_name = "PATH")]
+    pub avoid_wordlist: Option<Option<std::path::PathBuf>>,
+
+    /// Also print each word spelled with a romanization like `sh=ʃ ng=ŋ y=j`, separated from the IPA by a tab.
+    #[arg(long)]
+    pub romanization: Option<ortho::Romanization>,
+
+    /// Spell words with the romanization `alongside` the IPA, which is the default when there is a romanization, or
+    /// `instead` of it. Only the `lines` format changes, since the others always have both.
+    #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "alongside")]
+    pub romanize: Option<Romanize>,
+
+    /// Derive related words from each generated word with a suffix like `-ta`, a prefix like `ka-`, or vowel changes
+    /// like `a>u,i>e`. Derived words are printed after the word they come from, separated by tabs. Specify more than
+    /// once for multiple derived words.
+    #[arg(long)]
+    pub derive: Vec<gen::Process>,
+
+    /// Print pairs of words which differ in exactly one phoneme instead of single words, separated by a tab. Up to
+    /// `--count` pairs are printed, fewer if the patterns can not make that many.
+    #[arg(long, conflicts_with = "infinite")]
+    pub minimal_pairs: bool,
+
+    /// Never output the same word twice.
+    #[arg(long, conflicts_with = "infinite")]
+    pub unique: bool,
+
+    /// Print the number of possible words, entropy, and chance of duplicates for each pattern to stderr.
+    #[arg(long)]
+    pub stats: bool,
+
+    /// Instead of generating, print each slot of the patterns with the phonemes it can produce from the inventory, like
+    /// `validate-pattern`, to find out why words come out wrong.
+    #[arg(long, conflicts_with_all = ["speak", "audition", "infinite", "minimal_pairs", "output", "format"])]
+    pub explain: bool,
+
+    /// Reject words with fewer phonemes than this.
+    #[arg(long)]
+    pub min_phonemes: Option<usize>,
+
+    /// Reject words with more phonemes than this.
+    #[arg(long)]
+    pub max_phonemes: Option<usize>,
+
+    /// Reject words with fewer syllables than this. With `--syllable-counts`, shorter and longer
+    /// words are never picked in the first place.
+    #[arg(long)]
+    pub min_syllables: Option<usize>,
+
+    /// Reject words with more syllables than this.
+    #[arg(long)]
+    pub max_syllables: Option<usize>,
+
+    /// Write the words to this file instead of stdout, and print how many were written to stderr when done.
+    #[arg(long, conflicts_with = "audition")]
+    pub output: Option<std::path::PathBuf>,
+
+    /// With `--output`, add to the end of the file instead of replacing it.
+    #[arg(long, requires = "output")]
+    pub append: bool,
+
+    /// Seed the random number generator, so the same seed and flags generate the same words. Without a seed, a random
+    /// one is used.
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// How to print words: `lines` of IPA, or records with the IPA, romanization, syllables, pattern, and seed as
+    /// `json` (one object per line), `csv`, or `tsv`.
+    #[arg(long, value_enum, default_value_t = Format::Lines, conflicts_with_all = ["derive", "minimal_pairs"])]
+    pub format: Format,
 }
 
-struct SpeakerBox {
-    polly: aws_sdk_polly::Client,
-    speaker: soloud::Soloud,
+#[derive(Parser, Debug)]
+struct GenerateWordsCmd {
+    /// A TOML file defining the language. Its inventory, weights, filters, and romanization are used, and its patterns
+    /// as the syllable shapes if no `--syllable` is given.
+    #[arg(long)]
+    pub language: Option<std::path::PathBuf>,
+
+    #[arg(long, value_parser = parse_all::<phone::Consonant>)]
+    pub consonants: Option<std::vec::Vec<phone::Consonant>>,
+
+    #[arg(long, value_parser = parse_all::<phone::Vowel>)]
+    pub vowels: Option<std::vec::Vec<phone::Vowel>>,
+
+    #[arg(long, value_parser = parse_all::<phone::NonPulmonicConsonant>)]
+    pub non_pulmonic: Option<std::vec::Vec<phone::NonPulmonicConsonant>>,
+
+    /// A syllable shape like `CV` or `CVC` to build words from. Specify more than once for more shapes; each syllable
+    /// of a word is picked from all of them.
+    #[arg(long)]
+    pub syllable: Vec<String>,
+
+    /// How likely each number of syllables in a word is, like `1:20,2:50,3:25,4:5`.
+    #[arg(long, default_value = "1:20,2:50,3:25,4:5")]
+    pub syllable_counts: gen::SyllableCounts,
+
+    /// Mark stress on words of more than one syllable: `initial`, `final`, `penultimate`, `antepenultimate`, or
+    /// `weight-sensitive`.
+    #[arg(long)]
+    pub stress: Option<gen::StressRule>,
+
+    /// Reject words whose romanization matches or closely resembles a real word in a file with one word per line,
+    /// like profanity. Without a file, a small bundled list of English words is used.
+    #[arg(long, num_args = 0..=1, value_name = "PATH")]
+    pub avoid_wordlist: Option<Option<std::path::PathBuf>>,
+
+    /// A file of the words generated by earlier runs, one per line in IPA, which is created if it does not exist. Words
+    /// in it are never generated again, and every generated word is added to it, so vocabulary which has been adopted
+    /// into the language is not repeated by later runs.
+    #[arg(long, conflicts_with_all = ["audition"])]
+    pub history: Option<std::path::PathBuf>,
+
+    /// Also print each word spelled with a romanization like `sh=ʃ ng=ŋ y=j`, separated from the IPA by a tab.
+    #[arg(long)]
+    pub romanization: Option<ortho::Romanization>,
+
+    /// Spell words with the romanization `alongside` the IPA, which is the default when there is a romanization, or
+    /// `instead` of it. Only the `lines` format changes, since the others always have both.
+    #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "alongside")]
+    pub romanize: Option<Romanize>,
+
+    /// Speak the generated words. Words go on being generated and printed while the ones before them are spoken.
+    #[arg(long)]
+    pub speak: bool,
+
+    /// Go through the generated words in an interactive list instead of printing them: enter speaks a word and space
+    /// stars it. The starred words are printed on quitting.
+    #[arg(long, conflicts_with_all = ["speak", "infinite", "format"])]
+    pub audition: bool,
+
+    /// How many words to generate.
+    #[arg(long, default_value_t = 100)]
+    pub count: usize,
+
+    /// Keep generating words until stopped, instead of stopping after `--count`.
+    #[arg(long, conflicts_with = "count")]
+    pub infinite: bool,
+
+    /// Generate again whenever the `--language` file changes, for a quick loop while tuning a language. Stop with
+    /// Ctrl-C. With `--seed`, every run uses the same seed, so runs are easy to compare.
+    #[arg(long, conflicts_with_all = ["infinite", "audition"])]
+    pub watch: bool,
+
+    /// Instead of generating, print each slot of the syllable shapes with the phonemes it can produce from the
+    /// inventory, like `validate-pattern`, to find out why words come out wrong.
+    #[arg(long, conflicts_with_all = ["speak", "audition", "infinite", "output", "format"])]
+    pub explain: bool,
+
+    /// Reject words with fewer phonemes than this.
+    #[arg(long)]
+    pub min_phonemes: Option<usize>,
+
+    /// Reject words with more phonemes than this.
+    #[arg(long)]
+    pub max_phonemes: Option<usize>,
+
+    /// Reject words with fewer syllables than this. Shorter and longer words are never picked from
+    /// `--syllable-counts` in the first place.
+    #[arg(long)]
+    pub min_syllables: Option<usize>,
+
+    /// Reject words with more syllables than this.
+    #[arg(long)]
+    pub max_syllables: Option<usize>,
+
+    /// Write the words to this file instead of stdout, and print how many were written to stderr when done.
+    #[arg(long, conflicts_with = "audition")]
+    pub output: Option<std::path::PathBuf>,
+
+    /// With `--output`, add to the end of the file instead of replacing it.
+    #[arg(long, requires = "output")]
+    pub append: bool,
+
+    /// Seed the random number generator, so the same seed and flags generate the same words. Without a seed, a random
+    /// one is used.
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// How to print words: `lines` of IPA, or records with the IPA, romanization, syllables, pattern, and seed as
+    /// `json` (one object per line), `csv`, or `tsv`.
+    #[arg(long, value_enum, default_value_t = Format::Lines)]
+    pub format: Format,
 }
 
-impl SpeakerBox {
-    pub async fn new() -> Result<Self, anyhow::Error> {
-        let aws_conf = aws_config::from_env().load().await;
-        let polly = aws_sdk_polly::Client::new(&aws_conf);
-        let speaker = soloud::Soloud::default()?;
-        Ok(Self { polly, speaker })
-    }
+/// Generate proper nouns: words of two to four syllables by default, optionally with an ending or beginning from a
+/// pool like `-ia` or `-or`, printed with a capitalized spelling.
+#[derive(Parser, Debug)]
+struct GenerateNamesCmd {
+    /// A TOML file defining the language. Its inventory, weights, filters, and romanization are used, and its patterns
+    /// as the syllable shapes if no `--syllable` is given.
+    #[arg(long)]
+    pub language: Option<std::path::PathBuf>,
 
-    pub async fn speak(&self, ipa: &str) -> Result<(), anyhow::Error> {
-        let ogg = self.text_to_speech(ipa).await?;
-        self.play_audio(&ogg).await?;
-        Ok(())
+    #[arg(long, value_parser = parse_all::<phone::Consonant>)]
+    pub consonants: Option<std::vec::Vec<phone::Consonant>>,
+
+    #[arg(long, value_parser = parse_all::<phone::Vowel>)]
+    pub vowels: Option<std::vec::Vec<phone::Vowel>>,
+
+    #[arg(long, value_parser = parse_all::<phone::NonPulmonicConsonant>)]
+    pub non_pulmonic: Option<std::vec::Vec<phone::NonPulmonicConsonant>>,
+
+    /// A syllable shape like `CV` or `CVC` to build names from. Specify more than once for more shapes; each syllable
+    /// of a name is picked from all of them.
+    #[arg(long)]
+    pub syllable: Vec<String>,
+
+    /// How likely each number of syllables in a name is, not counting a suffix or prefix, like `2:40,3:45,4:15`.
+    #[arg(long, default_value = "2:40,3:45,4:15")]
+    pub syllable_counts: gen::SyllableCounts,
+
+    /// An ending for names in IPA, like `-i.a` or `-or`. Specify more than once for a pool to pick from.
+    #[arg(long, value_parser = parse_affix)]
+    pub suffix: Vec<phone::Word>,
+
+    /// A beginning for names in IPA, like `al-`. Specify more than once for a pool to pick from.
+    #[arg(long, value_parser = parse_affix)]
+    pub prefix: Vec<phone::Word>,
+
+    /// The chance that a name gets a suffix from the pool, and separately a prefix, from 0 to 1.
+    #[arg(long, default_value_t = 0.5, value_parser = parse_probability)]
+    pub affix_probability: f64,
+
+    /// Mark stress on names: `initial`, `final`, `penultimate`, `antepenultimate`, or `weight-sensitive`.
+    #[arg(long)]
+    pub stress: Option<gen::StressRule>,
+
+    /// Reject names whose spelling matches or closely resembles a real word in a file with one word per line, like
+    /// profanity. Without a file, a small bundled list of English words is used.
+    #[arg(long, num_args = 0..=1, value_name = "PATH")]
+    pub avoid_wordlist: Option<Option<std::path::PathBuf>>,
+
+    /// Spell names with a romanization like `sh=ʃ ng=ŋ y=j`. Without one, names are spelled with their IPA symbols.
+    #[arg(long)]
+    pub romanization: Option<ortho::Romanization>,
+
+    /// How many names to generate.
+    #[arg(long, default_value_t = 100)]
+    pub count: usize,
+
+    /// Keep generating names until stopped, instead of stopping after `--count`.
+    #[arg(long, conflicts_with = "count")]
+    pub infinite: bool,
+
+    /// Instead of generating, print each slot of the syllable shapes with the phonemes it can produce from the
+    /// inventory, like `validate-pattern`, to find out why names come out wrong.
+    #[arg(long, conflicts_with_all = ["infinite", "output"])]
+    pub explain: bool,
+
+    /// Reject names with fewer phonemes than this.
+    #[arg(long)]
+    pub min_phonemes: Option<usize>,
+
+    /// Reject names with more phonemes than this.
+    #[arg(long)]
+    pub max_phonemes: Option<usize>,
+
+    /// Reject names with fewer syllables than this. Prefixes and suffixes count toward the length.
+    #[arg(long)]
+    pub min_syllables: Option<usize>,
+
+    /// Reject names with more syllables than this.
+    #[arg(long)]
+    pub max_syllables: Option<usize>,
+
+    /// Write the names to this file instead of stdout, and print how many were written to stderr when done.
+    #[arg(long)]
+    pub output: Option<std::path::PathBuf>,
+
+    /// With `--output`, add to the end of the file instead of replacing it.
+    #[arg(long, requires = "output")]
+    pub append: bool,
+
+    /// Seed the random number generator, so the same seed and flags generate the same names. Without a seed, a random
+    /// one is used.
+    #[arg(long)]
+    pub seed: Option<u64>,
+}
+
+/// Parse an affix like `-i.a` or `al-`, where the hyphen shows which side of the name it goes on.
+fn parse_affix(src: &str) -> Result<phone::Word, anyhow::Error> {
+    Ok(src.trim().trim_matches('-').parse()?)
+}
+
+/// Generate words for `generate-syllables --pattern -`: patterns are read from stdin one per line, and each gets a
+/// block of `--count` words, so scripts can try many patterns without starting the process again for each. In the
+/// `lines` format, each block starts with the pattern as a `#` comment and blocks are separated by blank lines. A
+/// pattern which can not be parsed or generated from is reported, and the rest are still generated.
+fn generate_batch(
+    cmd: &GenerateSyllablesCmd,
+    build: impl Fn(&str) -> Result<gen::WordGenerator, gen::ParseError>,
+) -> usize {
+    use std::io::BufRead;
+
+    if cmd.infinite
+        || cmd.speak
+        || cmd.audition
+        || cmd.minimal_pairs
+        || cmd.stats
+        || cmd.explain
+        || cmd.history.is_some()
+    {
+        ErrorKind::Usage.exit(
+            "--pattern - can not be used with --infinite, --speak, --audition, --minimal-pairs, --stats, --explain, or --history",
+        );
     }
+    let seed = cmd.seed.unwrap_or_else(rand::random);
+    let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+    let mut printer = Printer::new(cmd.format, seed);
+    let mut first = true;
+    let mut failed = false;
+    let mut count = 0;
+    for line in std::io::stdin().lock().lines() {
+        let line = match line {
+            Ok(x) => x,
+            Err(e) => ErrorKind::Input.exit(e),
+        };
+        let pattern = line.trim();
+        if pattern.is_empty() || pattern.starts_with('#') {
+            continue;
+        }
+        let words = match build(pattern) {
+            Ok(gen) if cmd.unique => gen.generate_unique(&mut rng, cmd.count),
+            Ok(gen) => (0..cmd.count).map(|_| gen.try_generate(&mut rng)).collect(),
+            Err(e) => {
+                report_parse_error(&e);
+                failed = true;
+                continue;
+            }
+        };
+        let words = match words {
+            Ok(x) => x,
+            Err(e) => {
+                eprintln!("error: {pattern}: {e}");
+                failed = true;
+                continue;
+            }
+        };
 
-    async fn text_to_speech(&self, src: &str) -> Result<Bytes, anyhow::Error> {
-        let resp = self
-            .polly
-            .synthesize_speech()
-            .output_format(aws_sdk_polly::types::OutputFormat::OggVorbis)
-            .text_type(aws_sdk_polly::types::TextType::Ssml)
-            .text(format!(r#"<phoneme alphabet="ipa" ph="{src}"></phoneme>"#))
-            .voice_id(aws_sdk_polly::types::VoiceId::Joanna)
-            .engine(aws_sdk_polly::types::Engine::Neural)
-            .send()
-            .await?;
-        let blob = resp.audio_stream.collect().await?;
-        Ok(blob.into_bytes())
+        count += words.len();
+        if let Some(printer) = printer.as_mut() {
+            for word in words.iter() {
+                printer.print(word, cmd.romanization.as_ref(), Some(pattern));
+            }
+            continue;
+        }
+        if !first {
+            output(format_args!(""));
+        }
+        first = false;
+        output(format_args!("# {pattern}"));
+        for word in words.iter() {
+            let mut line = spell(
+                word.iter().join(" "),
+                word,
+                cmd.romanization.as_ref(),
+                cmd.romanize.unwrap_or_default(),
+            );
+            for derived in gen::derive_family(word, &cmd.derive) {
+                write!(line, "\t{}", derived.iter().join(" ")).unwrap();
+            }
+            output(format_args!("{line}"));
+        }
+    }
+    if failed {
+        close_output(cmd.output.as_deref(), count, "words");
+        ErrorKind::Pattern.exit_quietly();
     }
+    count
+}
 
-    async fn play_audio(&self, src: &[u8]) -> Result<(), anyhow::Error> {
-        let mut wav = soloud::audio::Wav::default();
-        wav.load_mem(src)?;
-        self.speaker.play(&wav);
-        while self.speaker.voice_count() > 0 {
-            tokio::time::sleep(std::time::Duration::from_millis(10)).await
+/// Load the wordlist for `--avoid-wordlist`: the file at the path if one is given, or the bundled English list.
+fn load_wordlist(
+    path: &Option<Option<std::path::PathBuf>>,
+) -> Result<Option<ortho::Wordlist>, anyhow::Error> {
+    match path {
+        None => Ok(None),
+        Some(None) => Ok(Some(ortho::Wordlist::english())),
+        Some(Some(path)) => {
+            let src =
+                std::fs::read_to_string(path).map_err(|e| anyhow!("{}: {e}", path.display()))?;
+            Ok(Some(
+                src.parse()
+                    .map_err(|e| anyhow!("{}: {e}", path.display()))?,
+            ))
         }
-        Ok(())
     }
 }
 
-#[tokio::main]
-async fn main() {
-    let cmd = Command::parse();
-    match cmd {
-        Command::GenerateSyllables(cmd) => {
-            let inventory = phone::Inventory::new(
-                cmd.consonants
-                    .as_ref()
-                    .map(|x| &x[..])
-                    .unwrap_or(phone::Consonant::all()),
-                cmd.vowels
-                    .as_ref()
-                    .map(|x| &x[..])
-                    .unwrap_or(phone::Vowel::all()),
-                cmd.non_pulmonic.as_ref().map(|x| &x[..]).unwrap_or(&[]),
-            );
+/// The `--history` of generation: the words earlier runs generated, and the file to add this run's words to.
+struct History {
+    path: std::path::PathBuf,
+    file: std::fs::File,
+}
 
-            let speaker = if cmd.speak {
-                Some(SpeakerBox::new().await.unwrap())
-            } else {
-                None
-            };
+impl History {
+    /// Open the history at `path`, creating it if it does not exist, and read the words in it into a lexicon which
+    /// rejects them.
+    fn open(path: &std::path::Path) -> Result<(Self, gen::Lexicon), anyhow::Error> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| anyhow!("{}: {e}", path.display()))?;
+        let mut lexicon = gen::Lexicon::new(0);
+        lexicon.extend(read_words(path, &ortho::Romanization::default())?);
+        let history = Self {
+            path: path.to_path_buf(),
+            file,
+        };
+        Ok((history, lexicon))
+    }
 
-            let patterns: Result<Vec<_>, _> = cmd
-                .pattern
-                .iter()
-                .map(|p| {
-                    gen::WordGenerator::parse(p, &inventory)
-                        .map_err(|e| format!("Could not parse pattern \"{p}\": {e}"))
-                })
-                .collect();
-            let patterns = match patterns {
-                Ok(ps) => ps,
-                Err(e) => panic!("{e}"),
-            };
+    /// Add a generated word. Words are written as they are generated, so a run stopped partway through still records
+    /// the words it printed.
+    fn record(&mut self, word: &phone::Word) {
+        use std::io::Write;
+        if let Err(e) = writeln!(self.file, "{word}") {
+            ErrorKind::Output.exit(format_args!("{}: {e}", self.path.display()));
+        }
+    }
+}
 
-            let mut rng = rand::thread_rng();
-            use rand::Rng;
-            for idx in rand::thread_rng()
-                .sample_iter(rand::distributions::Uniform::new(0, patterns.len()))
-                .take(100)
-            {
-                let pattern = &patterns[idx];
-                let word = pattern.generate(&mut rng);
-                let ipa = word.iter().join(" ");
-                println!("{}", ipa);
-                if let Some(speaker) = speaker.as_ref() {
-                    speaker.speak(&ipa).await.unwrap();
-                }
+/// A filter rejecting words which, spelled with the `romanization`, resemble a word in the `wordlist`.
+fn wordlist_filter(wordlist: ortho::Wordlist, romanization: ortho::Romanization) -> gen::Filter {
+    gen::Filter::custom(move |word| {
+        let phonemes: Vec<_> = word.iter().flat_map(|syl| syl.parts()).copied().collect();
+        wordlist.resembles(&romanization.romanize(&phonemes))
+    })
+}
+
+/// How many novel words `generate-from-corpus` tries before giving up on one within the length limits.
+const CORPUS_LENGTH_ATTEMPTS: usize = 1000;
+
+/// Filters for `--min-phonemes`, `--max-phonemes`, `--min-syllables`, and `--max-syllables`.
+fn length_filters(
+    phonemes: (Option<usize>, Option<usize>),
+    syllables: (Option<usize>, Option<usize>),
+) -> Result<Vec<gen::Filter>, anyhow::Error> {
+    for (name, range) in [("phonemes", phonemes), ("syllables", syllables)] {
+        if let (Some(min), Some(max)) = range {
+            if min > max {
+                return Err(anyhow!(
+                    "--min-{name} {min} is more than --max-{name} {max}"
+                ));
             }
         }
     }
+    let mut out = Vec::new();
+    if phonemes != (None, None) {
+        out.push(gen::Filter::Phonemes {
+            min: phonemes.0,
+            max: phonemes.1,
+        });
+    }
+    if syllables != (None, None) {
+        out.push(gen::Filter::Syllables {
+            min: syllables.0,
+            max: syllables.1,
+        });
+    }
+    Ok(out)
+}
+
+/// Parse the syllable shapes of `generate-words` or `generate-names` over `inventory`, weighted by a language file's
+/// `weights` and rejecting words any of its `filters` reject.
+fn syllable_generator(
+    syllables: &str,
+    inventory: phone::Inventory,
+    weights: &[(phone::Phoneme, u8)],
+    filters: &[gen::Filter],
+) -> Result<gen::WordGenerator, gen::ParseError> {
+    let inventory = weights
+        .iter()
+        .fold(inventory, |inventory, (phoneme, weight)| {
+            inventory.with_weight(*phoneme, *weight)
+        });
+    let generator = gen::WordGenerator::parse(syllables, &inventory)?;
+    Ok(filters
+        .iter()
+        .cloned()
+        .fold(generator, gen::WordGenerator::with_filter))
+}
+
+/// The preset called `name`, or exit with the names of the presets if there is none.
+fn find_preset(name: &str) -> &'static preset::Preset {
+    let Some(preset) = preset::find(name) else {
+        ErrorKind::Usage.exit(format_args!(
+            "no preset named \"{name}\"; the presets are {}",
+            preset::PRESETS.iter().map(|preset| preset.name).join(", ")
+        ));
+    };
+    preset
+}
+
+/// Capitalize the first letter of a spelling, like a proper noun.
+fn capitalize(src: &str) -> String {
+    let mut chars = src.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[derive(Parser, Debug)]
+struct GenerateFromCorpusCmd {
+    /// A TOML file defining the language. Only its romanization is used, for reading the corpus.
+    #[arg(long)]
+    pub language: Option<std::path::PathBuf>,
+
+    /// A file with one word per line to learn from. Words are written in IPA unless a `--romanization` is given.
+    /// Syllable breaks (`.`) and stress marks (`ˈ`, `ˌ`) are ignored.
+    #[arg(long)]
+    pub corpus: std::path::PathBuf,
+
+    /// How many previous phonemes are used to pick the next one. Higher values stay closer to the corpus.
+    #[arg(long, default_value_t = 2)]
+    pub order: usize,
+
+    /// How the corpus is spelled, as a list of `grapheme=phoneme` pairs like `sh=ʃ ng=ŋ y=j`.
+    #[arg(long)]
+    pub romanization: Option<ortho::Romanization>,
+
+    /// Spell generated words with the corpus's romanization `alongside` the IPA, which is the default, or `instead` of
+    /// it. Only the `lines` format changes, since the others always have both.
+    #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "alongside")]
+    pub romanize: Option<Romanize>,
+
+    /// Speak the generated words. Words go on being generated and printed while the ones before them are spoken.
+    #[arg(long)]
+    pub speak: bool,
+
+    /// How many words to generate.
+    #[arg(long, default_value_t = 100)]
+    pub count: usize,
+
+    /// Keep generating words until stopped, instead of stopping after `--count`.
+    #[arg(long, conflicts_with = "count")]
+    pub infinite: bool,
+
+    /// Reject words with fewer phonemes than this.
+    #[arg(long)]
+    pub min_phonemes: Option<usize>,
+
+    /// Reject words with more phonemes than this.
+    #[arg(long)]
+    pub max_phonemes: Option<usize>,
+
+    /// Reject words with fewer syllables than this.
+    #[arg(long)]
+    pub min_syllables: Option<usize>,
+
+    /// Reject words with more syllables than this.
+    #[arg(long)]
+    pub max_syllables: Option<usize>,
+
+    /// Write the words to this file instead of stdout, and print how many were written to stderr when done.
+    #[arg(long)]
+    pub output: Option<std::path::PathBuf>,
+
+    /// With `--output`, add to the end of the file instead of replacing it.
+    #[arg(long, requires = "output")]
+    pub append: bool,
+
+    /// Seed the random number generator, so the same seed and flags generate the same words. Without a seed, a random
+    /// one is used.
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// How to print words: `lines` of IPA, or records with the IPA, romanization, syllables, pattern, and seed as
+    /// `json` (one object per line), `csv`, or `tsv`.
+    #[arg(long, value_enum, default_value_t = Format::Lines)]
+    pub format: Format,
+}
+
+/// Print the inventory as IPA charts, a table of features, and counts, to check it before generating words from it.
+#[derive(Parser, Debug)]
+struct ShowInventoryCmd {
+    /// A TOML file defining the language. Only its inventory and weights are used.
+    #[arg(long)]
+    pub language: Option<std::path::PathBuf>,
+
+    /// Use the inventory and weights of a bundled language instead of a file, as listed by `preset list`.
+    #[arg(long, conflicts_with = "language")]
+    pub preset: Option<String>,
+
+    #[arg(long, value_parser = parse_all::<phone::Consonant>)]
+    pub consonants: Option<std::vec::Vec<phone::Consonant>>,
+
+    #[arg(long, value_parser = parse_all::<phone::Vowel>)]
+    pub vowels: Option<std::vec::Vec<phone::Vowel>>,
+
+    #[arg(long, value_parser = parse_all::<phone::NonPulmonicConsonant>)]
+    pub non_pulmonic: Option<std::vec::Vec<phone::NonPulmonicConsonant>>,
+
+    /// Make some phonemes more common than others with a weighted list like `t:5,k:3,a:4`, as for generation.
+    #[arg(long, value_parser = parse_weights)]
+    pub weights: Option<std::vec::Vec<(phone::Phoneme, u8)>>,
+
+    /// How to print the inventory.
+    #[arg(long, value_enum, default_value_t)]
+    pub format: InventoryFormat,
+
+    /// With `--format latex`, write IPA with the macros of the TIPA package instead of Unicode, for documents compiled
+    /// with pdfLaTeX.
+    #[arg(long)]
+    pub tipa: bool,
+}
+
+/// How `show-inventory` prints the inventory.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+enum InventoryFormat {
+    /// Charts of the consonants and vowels, and a table of every phoneme.
+    #[default]
+    Table,
+    /// The same charts and table in LaTeX, for a grammar.
+    Latex,
+}
+
+/// Apply ordered sound changes to a list of words and print what they become.
+#[derive(Parser, Debug)]
+struct SoundChangeCmd {
+    /// A file of sound changes like `[ptk] > [bdg] / V_V`, one per line, applied in order.
+    #[arg(long)]
+    pub rules: std::path::PathBuf,
+
+    /// A file with one word per line in IPA, with syllables separated by `.` or stress marks. Without it, words are
+    /// read from stdin.
+    #[arg(long)]
+    pub input: Option<std::path::PathBuf>,
+}
+
+impl SoundChangeCmd {
+    fn load_rules(&self) -> Result<change::SoundChanges, anyhow::Error> {
+        let src = std::fs::read_to_string(&self.rules)
+            .map_err(|e| anyhow!("could not read {}: {e}", self.rules.display()))?;
+        src.parse()
+            .map_err(|e| anyhow!("{}: {e}", self.rules.display()))
+    }
+}
+
+/// Report the phonemes, syllable shapes, and consonant clusters of a list of words, with patterns for generating words
+/// like them.
+#[derive(Parser, Debug)]
+struct AnalyzeCmd {
+    /// A file with one word per line in IPA. Syllables can be separated by `.` or stress marks; words without them are
+    /// split into syllables before each consonant which comes before a vowel.
+    pub words: std::path::PathBuf,
+
+    /// How many of the most common word shapes to suggest as patterns.
+    #[arg(long, default_value_t = 5)]
+    pub suggestions: usize,
+}
+
+/// Show what patterns expand to without generating anything: the phonemes each slot can produce, the number of
+/// possible words, and likely mistakes.
+#[derive(Parser, Debug)]
+struct ValidatePatternCmd {
+    /// A TOML file defining the language. Its inventory and weights are used, and its patterns if no `--pattern` is
+    /// given.
+    #[arg(long)]
+    pub language: Option<std::path::PathBuf>,
+
+    #[arg(long, value_parser = parse_all::<phone::Consonant>)]
+    pub consonants: Option<std::vec::Vec<phone::Consonant>>,
+
+    #[arg(long, value_parser = parse_all::<phone::Vowel>)]
+    pub vowels: Option<std::vec::Vec<phone::Vowel>>,
+
+    #[arg(long, value_parser = parse_all::<phone::NonPulmonicConsonant>)]
+    pub non_pulmonic: Option<std::vec::Vec<phone::NonPulmonicConsonant>>,
+
+    /// Make some phonemes more common than others with a weighted list like `t:5,k:3,a:4`, as for generation.
+    #[arg(long, value_parser = parse_weights)]
+    pub weights: Option<std::vec::Vec<(phone::Phoneme, u8)>>,
+
+    /// An alternate inventory for a layer of the lexicon like `loan=fvzʒaeiou`, as for generation.
+    #[arg(long, value_parser = parse_stratum)]
+    pub stratum: Vec<(String, phone::Inventory)>,
+
+    /// A pattern to check, like `CV(C)`. Specify more than once to check several.
+    #[arg(long)]
+    pub pattern: Vec<String>,
+
+    /// The chance that optional phonemes like `(C)` are included, from 0 to 1.
+    #[arg(long, value_parser = parse_probability)]
+    pub optional_probability: Option<f64>,
+}
+
+/// Pick a random inventory like one a natural language could have, and print it as a `--language` file to edit and
+/// generate words from.
+#[derive(Parser, Debug)]
+struct RandomInventoryCmd {
+    /// How many consonants to pick.
+    #[arg(long, default_value_t = 22)]
+    pub consonant_count: usize,
+
+    /// How many vowels to pick.
+    #[arg(long, default_value_t = 5)]
+    pub vowel_count: usize,
+
+    /// The seed for the random number generator, to pick the same inventory again. A random seed is used by default.
+    #[arg(long)]
+    pub seed: Option<u64>,
+}
+
+/// Write a language definition and its lexicon in a format other tools read.
+#[derive(Parser, Debug)]
+struct ExportCmd {
+    #[arg(long, value_enum)]
+    pub to: export::Target,
+
+    /// A TOML file defining the language. Its inventory, patterns, and romanization are exported.
+    #[arg(long, required_unless_present = "lexicon")]
+    pub language: Option<std::path::PathBuf>,
+
+    /// A file of words, one per line in IPA, optionally followed by a tab and a gloss like `ka.ta<TAB>dog`.
+    #[arg(long)]
+    pub lexicon: Option<std::path::PathBuf>,
+
+    /// Spell the words with a romanization like `sh=ʃ ng=ŋ y=j` instead of the one in the `--language` file.
+    #[arg(long)]
+    pub romanization: Option<ortho::Romanization>,
+
+    /// With `--to anki`, synthesize the sound of each word into this folder and play it on the cards. Copy the files
+    /// into Anki's `collection.media` folder before importing the cards.
+    #[arg(long)]
+    pub media: Option<std::path::PathBuf>,
+}
+
+/// A word generator whose definitions can be imported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+enum ImportFormat {
+    /// Awkwords, with categories like `C:p/t/k` and a pattern like `r:CV(C)`.
+    Awkwords,
+    /// Zompist's Gen, with categories like `C=ptk` and syllable types like `CV CVC`.
+    Zompist,
+}
+
+/// Convert the categories and patterns of another word generator into a `--language` file.
+#[derive(Parser, Debug)]
+struct ImportCmd {
+    #[arg(long, value_enum)]
+    pub from: ImportFormat,
+
+    /// The file to convert. Without it, the definition is read from stdin.
+    pub input: Option<std::path::PathBuf>,
+}
+
+/// Print an interlinear gloss of a sentence: each word, the morphemes it is made of, and what they mean, lined up in
+/// columns.
+#[derive(Parser, Debug)]
+struct GlossCmd {
+    /// A file of morphemes, one per line in IPA followed by a tab and its gloss like `ka.ta<TAB>dog`. Prefixes end with
+    /// a hyphen like `ni-` and suffixes start with one like `-mi`.
+    #[arg(long)]
+    pub lexicon: std::path::PathBuf,
+
+    /// Read the sentence with a romanization like `sh=ʃ ng=ŋ y=j` instead of the one in the `--language` file.
+    #[arg(long)]
+    pub romanization: Option<ortho::Romanization>,
+
+    /// A TOML file defining the language. Only its romanization is used.
+    #[arg(long)]
+    pub language: Option<std::path::PathBuf>,
+
+    /// A free translation of the sentence, printed in quotes under the gloss.
+    #[arg(long)]
+    pub translation: Option<String>,
+
+    /// The sentence to gloss, spelled with the romanization or in IPA.
+    pub sentence: String,
+}
+
+/// Speak IPA written by hand, like `ˈka.ta ˈru.na`, to hear how words sound without generating them.
+#[derive(Parser, Debug)]
+struct PronounceCmd {
+    /// The IPA to speak. Without it, each line of stdin is printed and spoken in turn.
+    pub ipa: Option<String>,
+}
+
+/// Compare the inventories of two languages, like related languages of a family: the phonemes they share, the phonemes
+/// only one of them has, and how different they sound overall.
+#[derive(Parser, Debug)]
+struct CompareInventoriesCmd {
+    /// A TOML file defining the first language. Only its inventory is used.
+    pub first: std::path::PathBuf,
+
+    /// A TOML file defining the second language.
+    pub second: std::path::PathBuf,
+}
+
+/// Count what a list of words is made of, like the output of `generate-words` or a lexicon: how often each phoneme and
+/// pair of phonemes appears, how long the words are, and how often each syllable shape appears.
+#[derive(Parser, Debug)]
+struct StatsCmd {
+    /// A file with one word per line in IPA. If not given, words are read from stdin.
+    pub words: Option<std::path::PathBuf>,
+
+    /// How to print the statistics.
+    #[arg(long, value_enum, default_value_t)]
+    pub format: StatsFormat,
+
+    /// With `--format latex`, write IPA with the macros of the TIPA package instead of Unicode, for documents compiled
+    /// with pdfLaTeX.
+    #[arg(long)]
+    pub tipa: bool,
+}
+
+/// How `stats` prints its statistics.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+enum StatsFormat {
+    /// Tables with a histogram bar for each count.
+    #[default]
+    Table,
+    /// One JSON object.
+    Json,
+    /// LaTeX tables, for a grammar.
+    Latex,
+}
+
+/// Show what changed between two versions of a language file: phonemes added and removed, changed weights, and added
+/// and removed patterns, filters, and romanizations. Unlike a diff of the text, reordering or reformatting the file
+/// does not show up as a change.
+#[derive(Parser, Debug)]
+struct DiffCmd {
+    /// The old version of the language file.
+    pub old: std::path::PathBuf,
+
+    /// The new version of the language file.
+    pub new: std::path::PathBuf,
+}
+
+/// Practice a language: hear words from a lexicon and type their IPA, or read them and type what they mean. Answers are
+/// typed one per line, and the score is printed at the end or when input ends. When listening, an empty line speaks the
+/// word again, and `+` or `-` makes words louder or softer.
+#[derive(Parser, Debug)]
+struct QuizCmd {
+    /// A file of words, one per line in IPA, optionally followed by a tab and a gloss like `ka.ta<TAB>dog`.
+    pub lexicon: std::path::PathBuf,
+
+    /// What to be asked: `listen` speaks each word and asks for its IPA, and `meaning` shows its IPA and asks for its
+    /// gloss. Only words with a gloss are asked about for their meaning.
+    #[arg(long, value_enum, default_value_t)]
+    pub mode: quiz::Mode,
+
+    /// How many words to be asked about, at most. Each word is asked about once.
+    #[arg(long, default_value_t = 10)]
+    pub count: usize,
+
+    /// The seed for picking words.
+    #[arg(long)]
+    pub seed: Option<u64>,
+}
+
+/// Train your ear: hear phonemes of a language one at a time and type which each was. Consonants are played between
+/// vowels, like /aʃa/. Answers are typed one per line, and the score and the phonemes most often taken for one another
+/// are printed at the end or when input ends.
+#[derive(Parser, Debug)]
+struct TrainerCmd {
+    /// A TOML file defining the language. Only its inventory is used, to pick the phonemes to play.
+    #[arg(long)]
+    pub language: Option<std::path::PathBuf>,
+
+    /// The phonemes to play instead of the inventory of the language, like `sʃɕ` to practice telling those apart.
+    #[arg(long, value_parser = parse_all::<phone::Phoneme>)]
+    pub phonemes: Option<std::vec::Vec<phone::Phoneme>>,
+
+    /// How many phonemes to play.
+    #[arg(long, default_value_t = 20)]
+    pub count: usize,
+
+    /// The seed for picking phonemes.
+    #[arg(long)]
+    pub seed: Option<u64>,
+}
+
+/// Serve generation, syllabification, and speech over HTTP, for web front-ends and bots. See the `serve` module for
+/// the API.
+#[derive(Parser, Debug)]
+struct ServeCmd {
+    /// A TOML file defining the language. Its inventory, weights, filters, and romanization are used for every
+    /// request, and its patterns for requests which do not give one.
+    #[arg(long)]
+    pub language: Option<std::path::PathBuf>,
+
+    /// The address to listen on.
+    #[arg(long, default_value = "127.0.0.1:3000")]
+    pub address: std::net::SocketAddr,
+}
+
+/// List the language files bundled for natural languages like Spanish and Japanese, or print one to start a language
+/// from.
+#[derive(Parser, Debug)]
+struct PresetCmd {
+    #[command(subcommand)]
+    pub action: PresetAction,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum PresetAction {
+    /// Print the name and a description of each preset.
+    List,
+    /// Print a preset as a `--language` file.
+    Show {
+        /// The name of the preset, as printed by `preset list`.
+        name: String,
+
+        /// Write the language file here instead of printing it, to edit it. An existing file is not replaced.
+        #[arg(long)]
+        output: Option<std::path::PathBuf>,
+    },
+}
+
+/// Print the inflection table of a root, like its cases by number, by marking it with an affix or vowel change for
+/// each row and column.
+#[derive(Parser, Debug)]
+struct ParadigmCmd {
+    /// The root in IPA, like `ka.ta`.
+    pub root: phone::Word,
+
+    /// A row of the table: a label and what marks it, like `ACC=-i` for a suffix, `NEG=ni-` for a prefix, or `PST=a>u`
+    /// for a vowel change. A label alone like `NOM` leaves the form unmarked. Specify more than once for more rows.
+    #[arg(long = "row", value_parser = parse_inflection, required = true)]
+    pub rows: Vec<(String, Option<gen::Process>)>,
+
+    /// A column of the table, written like a row. The marks of columns are applied first, so their affixes are closer
+    /// to the root than those of rows. Without columns, the table has a single column of the rows' forms.
+    #[arg(long = "column", value_parser = parse_inflection)]
+    pub columns: Vec<(String, Option<gen::Process>)>,
+
+    /// How to print the table.
+    #[arg(long, value_enum, default_value_t)]
+    pub format: ParadigmFormat,
+
+    /// With `--format latex`, write IPA with the macros of the TIPA package instead of Unicode, for documents compiled
+    /// with pdfLaTeX.
+    #[arg(long)]
+    pub tipa: bool,
+}
+
+/// How `paradigm` prints its table.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+enum ParadigmFormat {
+    /// Columns aligned with spaces.
+    #[default]
+    Text,
+    /// A Markdown table, for a wiki or a README.
+    Markdown,
+    /// A LaTeX `tabular`, for a grammar.
+    Latex,
+}
+
+/// Parse a row or column of a paradigm, like `PL=-mi` or a bare label like `SG`.
+fn parse_inflection(src: &str) -> Result<(String, Option<gen::Process>), anyhow::Error> {
+    let (label, process) = match src.split_once('=') {
+        Some((label, process)) => (label.trim(), Some(process)),
+        None => (src.trim(), None),
+    };
+    if label.is_empty() {
+        return Err(anyhow!(
+            "expected a label like `SG`, or a label and a mark like `PL=-mi`"
+        ));
+    }
+    let process = process
+        .filter(|process| !process.trim().is_empty())
+        .map(str::parse)
+        .transpose()?;
+    Ok((label.into(), process))
+}
+
+/// Chart how often each phoneme appears in a list of words, or in words generated from a language, as bars from the
+/// most to the least common, to see whether weights give the distribution they were meant to.
+#[derive(Parser, Debug)]
+struct FrequencyChartCmd {
+    /// A file with one word per line in IPA. If not given, words are read from stdin.
+    #[arg(conflicts_with = "language")]
+    pub words: Option<std::path::PathBuf>,
+
+    /// Generate words from the patterns of this language file and chart them, instead of reading words. The weight of
+    /// each phoneme in the file is printed after its bar, to compare with.
+    #[arg(long)]
+    pub language: Option<std::path::PathBuf>,
+
+    /// With `--language`, how many words to generate. More words give a steadier chart.
+    #[arg(long, default_value_t = 1000, requires = "language")]
+    pub count: usize,
+
+    /// With `--language`, the seed for generating words.
+    #[arg(long, requires = "language")]
+    pub seed: Option<u64>,
+
+    /// The width of the longest bar, in characters.
+    #[arg(long, default_value_t = 40)]
+    pub width: usize,
+}
+
+/// Start the lexicon of a language with a different generated word for each of the 207 concepts of the Swadesh list,
+/// like `water`, `to eat`, and `two`, which nearly every language has a word for. The lexicon has one word per line in
+/// IPA with a tab and its gloss, ready for `export`, `quiz`, and `--lexicon`.
+#[derive(Parser, Debug)]
+struct SwadeshCmd {
+    /// A TOML file defining the language. Words are generated from its patterns.
+    #[arg(long)]
+    pub language: std::path::PathBuf,
+
+    /// Write the lexicon to this file instead of stdout.
+    #[arg(long)]
+    pub output: Option<std::path::PathBuf>,
+
+    /// The seed for generating words.
+    #[arg(long)]
+    pub seed: Option<u64>,
+}
+
+/// The concepts of the 207-word Swadesh list, one per line.
+const SWADESH: &str = include_str!("data/swadesh.txt");
+
+/// Synthesize the sound of each word of a list into its own audio file, with a manifest of which file holds which
+/// word, for games, apps, and websites. The manifest lists each word's IPA, gloss, file, spelling, voice, duration in
+/// seconds, and the SHA-256 of its file, so a site or deck can check it has the file it expects.
+#[derive(Parser, Debug)]
+struct SynthesizeCmd {
+    /// A file of words, one per line in IPA, optionally followed by a tab and a gloss like `ka.ta<TAB>dog`.
+    #[arg(long)]
+    pub input: std::path::PathBuf,
+
+    /// The folder to write the audio files and the manifest to, which is created if it does not exist. Files which
+    /// are already there are kept, so running again only synthesizes new words.
+    #[arg(long)]
+    pub out_dir: std::path::PathBuf,
+
+    /// The format of the manifest: `csv` for `manifest.csv`, or `json` for `manifest.json`.
+    #[arg(long, value_enum, default_value_t)]
+    pub manifest: export::Manifest,
+
+    /// A TOML file defining the language. Only its romanization is used, to spell the words in the manifest.
+    #[arg(long)]
+    pub language: Option<std::path::PathBuf>,
+
+    /// Spell the words in the manifest with a romanization like `sh=ʃ ng=ŋ y=j` instead of the one in the `--language`
+    /// file.
+    #[arg(long)]
+    pub romanization: Option<ortho::Romanization>,
+
+    /// How many words to synthesize at once. More is faster, until the speech backend starts turning requests away.
+    #[arg(long, default_value_t = export::CONCURRENCY)]
+    pub concurrency: usize,
+}
+
+/// Pick the phonemes of a language from the IPA charts in an interactive terminal interface, listening to each one,
+/// and write them to a language file.
+#[derive(Parser, Debug)]
+struct PickInventoryCmd {
+    /// The language file to write the inventory to. If it already exists, its phonemes are picked to start with, and
+    /// everything else in it like patterns and weights is kept, though not its comments.
+    pub language: std::path::PathBuf,
+}
+
+/// Describe a phoneme: its name on the IPA chart, its features, how common it is in the world's languages, and words of
+/// natural languages which have it.
+#[derive(Parser, Debug)]
+struct PhonemeInfoCmd {
+    /// The phoneme, like `ʃ` or `ɛ`.
+    pub phoneme: phone::Phoneme,
+
+    /// Also speak the phoneme. Consonants are spoken between vowels, like /aʃa/, since many can not be heard alone.
+    #[arg(long)]
+    pub speak: bool,
+}
+
+/// Words of natural languages with each phoneme, one phoneme per line followed by a tab and the examples.
+const EXAMPLES: &str = include_str!("data/examples.txt");
+
+/// The name of the `phoneme` on the IPA chart, like "voiceless post-alveolar fricative" or "close front unrounded
+/// vowel".
+fn phoneme_name(phoneme: phone::Phoneme) -> String {
+    match phoneme {
+        phone::Phoneme::Consonant(c) => {
+            let voicing = if c.is_voiced() { "voiced" } else { "voiceless" };
+            format!("{voicing} {} {}", c.place(), c.manner())
+        }
+        phone::Phoneme::Vowel(v) => format!(
+            "{} {} {} vowel",
+            height_name(v.height()),
+            v.frontness().backness(),
+            if v.is_rounded() {
+                "rounded"
+            } else {
+                "unrounded"
+            }
+        ),
+        phone::Phoneme::NonPulmonicConsonant(c) => match c {
+            phone::NonPulmonicConsonant::BilabialClick => "bilabial click",
+            phone::NonPulmonicConsonant::DentalClick => "dental click",
+            phone::NonPulmonicConsonant::Postalveoalar => "post-alveolar click",
+            phone::NonPulmonicConsonant::Palatoalveolar => "palato-alveolar click",
+            phone::NonPulmonicConsonant::AlveolarLateral => "alveolar lateral click",
+            phone::NonPulmonicConsonant::BilabialImplosive => "voiced bilabial implosive",
+            phone::NonPulmonicConsonant::DentalImplosive => "voiced dental implosive",
+            phone::NonPulmonicConsonant::Palatal => "voiced palatal implosive",
+            phone::NonPulmonicConsonant::Velar => "voiced velar implosive",
+            phone::NonPulmonicConsonant::Uvular => "voiced uvular implosive",
+        }
+        .into(),
+    }
+}
+
+/// Print the slots of each syllable of the `pattern`, with the phonemes they can produce.
+fn show_pattern(pattern: &gen::WordGenerator) {
+    output(format_args!(
+        "{pattern}: {} possible words, {:.1} bits of entropy",
+        pattern.possible_words(),
+        pattern.entropy_bits()
+    ));
+    for syl in pattern.syllables() {
+        output(format_args!("  {}", syl.pattern()));
+        let mut rows = Vec::new();
+        for ph in syl.phonemes() {
+            // Weights are only worth showing if they make a difference
+            let weighted = !ph.weights().iter().all_equal();
+            let choices = ph
+                .choices()
+                .iter()
+                .enumerate()
+                .map(|(idx, choice)| match ph.weights().get(idx) {
+                    Some(weight) if weighted => format!("{choice}:{weight}"),
+                    _ => choice.to_string(),
+                })
+                .join(" ");
+            let inclusion = ph.inclusion(pattern.optional_probability());
+            let note = if ph.is_optional() {
+                format!("included {:.0}% of the time", inclusion * 100.0)
+            } else {
+                String::new()
+            };
+            rows.push(vec![format!("    {ph}"), choices, note]);
+        }
+        print_table(&rows);
+    }
+}
+
+/// A way of writing words down.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+enum Notation {
+    Ipa,
+    Xsampa,
+    /// The romanization given with `--romanization` or in the `--language` file.
+    Romanization,
+}
+
+/// Convert a list of words from one notation to another, one word or line of words at a time.
+#[derive(Parser, Debug)]
+struct TransliterateCmd {
+    #[arg(long, value_enum)]
+    pub from: Notation,
+
+    #[arg(long, value_enum)]
+    pub to: Notation,
+
+    /// The romanization for `--from romanization` or `--to romanization`, like `sh=ʃ ng=ŋ y=j`.
+    #[arg(long)]
+    pub romanization: Option<ortho::Romanization>,
+
+    /// A TOML file defining the language. Only its romanization is used.
+    #[arg(long)]
+    pub language: Option<std::path::PathBuf>,
+
+    /// A file of words to convert. Without it, words are read from stdin.
+    #[arg(long)]
+    pub input: Option<std::path::PathBuf>,
+}
+
+impl TransliterateCmd {
+    /// Convert a line from `--from` to `--to`. Words are separated by whitespace, which is kept. Romanizations do not
+    /// mark syllables, stress, or tone, so those are lost when converting to one.
+    fn convert(
+        &self,
+        line: &str,
+        romanization: Option<&ortho::Romanization>,
+    ) -> Result<String, anyhow::Error> {
+        let romanization = || {
+            romanization.ok_or_else(|| anyhow!("no romanization: use --romanization or --language"))
+        };
+        let ipa = match self.from {
+            Notation::Ipa => line.to_string(),
+            Notation::Xsampa => ortho::from_xsampa(line),
+            Notation::Romanization => {
+                let romanization = romanization()?;
+                let mut out = String::with_capacity(line.len());
+                for token in split_whitespace_kept(line) {
+                    if token.trim().is_empty() {
+                        out.push_str(token);
+                    } else {
+                        out.extend(romanization.read(token)?.iter().map(phone::Phoneme::code));
+                    }
+                }
+                out
+            }
+        };
+        Ok(match self.to {
+            Notation::Ipa => bracket(ipa),
+            Notation::Xsampa => ortho::to_xsampa(&ipa),
+            Notation::Romanization => {
+                let romanization = romanization()?;
+                let mut out = String::with_capacity(ipa.len());
+                for token in split_whitespace_kept(&ipa) {
+                    if token.trim().is_empty() {
+                        out.push_str(token);
+                    } else {
+                        let word: phone::Word = token.parse()?;
+                        let phonemes: Vec<_> = word.phonemes().collect();
+                        out.push_str(&romanization.romanize(&phonemes));
+                    }
+                }
+                out
+            }
+        })
+    }
+}
+
+/// Split `src` into runs of whitespace and runs of everything else, so it can be put back together.
+fn split_whitespace_kept(src: &str) -> Vec<&str> {
+    let mut out = Vec::new();
+    let mut start = 0;
+    for (idx, c) in src.char_indices().skip(1) {
+        let prev = src[..idx].chars().next_back().unwrap();
+        if prev.is_whitespace() != c.is_whitespace() {
+            out.push(&src[start..idx]);
+            start = idx;
+        }
+    }
+    if start < src.len() {
+        out.push(&src[start..]);
+    }
+    out
+}
+
+impl GenerateSyllablesCmd {
+    fn load_pattern_file(&self) -> Result<Option<Vec<gen::WeightedPattern>>, anyhow::Error> {
+        let Some(path) = &self.pattern_file else {
+            return Ok(None);
+        };
+        let src = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("could not read {}: {e}", path.display()))?;
+        Ok(Some(
+            gen::WeightedPattern::parse_list(&src)
+                .map_err(|e| anyhow!("{}: {e}", path.display()))?,
+        ))
+    }
+
+    fn load_rules(&self) -> Result<Option<gen::Ruleset>, anyhow::Error> {
+        let Some(path) = &self.rules else {
+            return Ok(None);
+        };
+        let src = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("could not read {}: {e}", path.display()))?;
+        Ok(Some(src.parse()?))
+    }
+
+    fn load_lexicon(&self) -> Result<Option<gen::Lexicon>, anyhow::Error> {
+        let Some(path) = &self.lexicon else {
+            return Ok(None);
+        };
+        let mut out = gen::Lexicon::new(self.lexicon_distance);
+        out.extend(read_words(path, &ortho::Romanization::default())?);
+        Ok(Some(out))
+    }
+}
+
+impl GenerateFromCorpusCmd {
+    fn load(&self) -> Result<gen::MarkovGenerator, anyhow::Error> {
+        let romanization = self.romanization.clone().unwrap_or_default();
+        let mut out = gen::MarkovGenerator::new(self.order);
+        for phonemes in read_words(&self.corpus, &romanization)? {
+            out.train(&phonemes);
+        }
+        Ok(out)
+    }
+}
+
+/// The contents of a `--language` file, before the phonemes and directives in it are parsed. Everything is optional:
+///
+/// ```toml
+/// consonants = "ptkmnslj"
+/// vowels = "aeiou"
+/// patterns = ["CV", "CVC"]
+/// filters = ["no-adjacent-repeats", "no-final j"]
+/// romanization = "y=j"
+///
+/// [weights]
+/// t = 5
+/// a = 4
+/// ```
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize)]
+#[serde(default, deny_unknown_fields)]
+struct LanguageFile {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    consonants: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    vowels: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    non_pulmonic: Option<String>,
+    #[serde(skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    weights: std::collections::BTreeMap<String, u8>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    patterns: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    filters: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    romanization: Option<String>,
+}
+
+/// A language definition loaded from a `--language` file.
+#[derive(Debug, Default)]
+struct Language {
+    consonants: Option<Vec<phone::Consonant>>,
+    vowels: Option<Vec<phone::Vowel>>,
+    non_pulmonic: Option<Vec<phone::NonPulmonicConsonant>>,
+    weights: Vec<(phone::Phoneme, u8)>,
+    patterns: Vec<String>,
+    filters: Vec<gen::Filter>,
+    romanization: Option<ortho::Romanization>,
+}
+
+impl Language {
+    /// Build the weighted inventory of the language, and compile each of its patterns with its filters. Patterns which
+    /// do not parse are reported, and the program exits.
+    fn compile(&self) -> (phone::Inventory, Vec<(String, gen::WordGenerator)>) {
+        let inventory = inventory(&self.consonants, &self.vowels, &self.non_pulmonic);
+        let inventory = self
+            .weights
+            .iter()
+            .fold(inventory, |inventory, (phoneme, weight)| {
+                inventory.with_weight(*phoneme, *weight)
+            });
+        let mut patterns = Vec::new();
+        for src in self.patterns.iter() {
+            let gen = match gen::WordGenerator::parse(src, &inventory) {
+                Ok(gen) => gen,
+                Err(e) => exit_parse_error(&e),
+            };
+            let gen = self
+                .filters
+                .iter()
+                .cloned()
+                .fold(gen, gen::WordGenerator::with_filter);
+            patterns.push((src.clone(), gen));
+        }
+        (inventory, patterns)
+    }
+
+    /// Load the language at `path`, or an empty language if there is no path.
+    fn load(path: Option<&std::path::Path>) -> Result<Self, anyhow::Error> {
+        let Some(path) = path else {
+            return Ok(Self::default());
+        };
+        let src = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("could not read {}: {e}", path.display()))?;
+        Self::parse(&src, &path.display().to_string())
+    }
+
+    /// Parse the TOML source of a language file. Errors start with `name`, the file or preset the source is from.
+    fn parse(src: &str, name: &str) -> Result<Self, anyhow::Error> {
+        let file: LanguageFile = toml::from_str(src).map_err(|e| anyhow!("{name}: {e}"))?;
+        let in_file = |e: anyhow::Error| anyhow!("{name}: {e}");
+
+        let mut weights = Vec::new();
+        for (phoneme, weight) in file.weights.iter() {
+            weights.extend(parse_weights(&format!("{phoneme}:{weight}")).map_err(in_file)?);
+        }
+        Ok(Self {
+            consonants: file
+                .consonants
+                .as_deref()
+                .map(parse_all)
+                .transpose()
+                .map_err(in_file)?,
+            vowels: file
+                .vowels
+                .as_deref()
+                .map(parse_all)
+                .transpose()
+                .map_err(in_file)?,
+            non_pulmonic: file
+                .non_pulmonic
+                .as_deref()
+                .map(parse_all)
+                .transpose()
+                .map_err(in_file)?,
+            weights,
+            patterns: file.patterns,
+            filters: file
+                .filters
+                .iter()
+                .map(|f| f.parse())
+                .collect::<Result<_, _>>()
+                .map_err(|e: gen::ParseError| in_file(e.into()))?,
+            romanization: file
+                .romanization
+                .as_deref()
+                .map(str::parse)
+                .transpose()
+                .map_err(|e: ortho::ParseError| in_file(e.into()))?,
+        })
+    }
+}
+
+/// Read words in IPA from the file at `path`, or stdin if there is no path, one per line. Syllables are separated by
+/// `.` or stress marks, and blank lines are skipped.
+fn read_ipa_words(path: Option<&std::path::Path>) -> Result<Vec<phone::Word>, anyhow::Error> {
+    let (name, src) = match path {
+        Some(path) => (
+            path.display().to_string(),
+            std::fs::read_to_string(path)
+                .map_err(|e| anyhow!("could not read {}: {e}", path.display()))?,
+        ),
+        None => ("stdin".into(), std::io::read_to_string(std::io::stdin())?),
+    };
+    src.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(idx, line)| {
+            line.trim()
+                .parse()
+                .map_err(|e| anyhow!("{name}:{}: {e}", idx + 1))
+        })
+        .collect()
+}
+
+/// Read a file with one word per line, spelled with the `romanization`. Syllable breaks and stress marks are ignored.
+fn read_words(
+    path: &std::path::Path,
+    romanization: &ortho::Romanization,
+) -> Result<Vec<Vec<phone::Phoneme>>, anyhow::Error> {
+    let src = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("could not read {}: {e}", path.display()))?;
+
+    let mut out = Vec::new();
+    for (idx, line) in src.lines().enumerate() {
+        let word: String = line
+            .chars()
+            .filter(|c| !c.is_whitespace() && !matches!(c, '.' | 'ˈ' | 'ˌ'))
+            .collect();
+        if word.is_empty() {
+            continue;
+        }
+        let phonemes = romanization
+            .read(&word)
+            .map_err(|e| anyhow!("{}:{}: {e}", path.display(), idx + 1))?;
+        out.push(phonemes);
+    }
+    Ok(out)
+}
+
+/// Draw a bar of `value` out of `most` which is `width` characters long at its longest, in eighths of a character.
+fn unicode_bar(value: usize, most: usize, width: usize) -> String {
+    const EIGHTHS: [char; 8] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉', '█'];
+    let eighths = (value * width * 8).div_ceil(most.max(1));
+    let mut out = "█".repeat(eighths / 8);
+    if let Some(part) = (eighths % 8).checked_sub(1) {
+        out.push(EIGHTHS[part]);
+    }
+    out
+}
+
+/// Print rows of cells as a Markdown table, with the first row as the header.
+fn print_markdown_table(rows: &[Vec<String>]) {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let line = |cells: &mut dyn Iterator<Item = String>| {
+        output(format_args!("| {} |", cells.join(" | ")));
+    };
+    for (idx, row) in rows.iter().enumerate() {
+        line(&mut (0..columns).map(|col| {
+            row.get(col)
+                .map_or(String::new(), |cell| cell.replace('|', "\\|"))
+        }));
+        if idx == 0 {
+            line(&mut (0..columns).map(|_| "---".to_string()));
+        }
+    }
+}
+
+/// Print rows of cells in aligned columns. Widths are counted in characters, since IPA is not ASCII.
+fn print_table(rows: &[Vec<String>]) {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|col| {
+            rows.iter()
+                .filter_map(|row| row.get(col))
+                .map(|cell| cell.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    for row in rows.iter() {
+        let mut line = String::new();
+        for (cell, width) in row.iter().zip(widths.iter()) {
+            write!(
+                line,
+                "{cell}{:pad$}  ",
+                "",
+                pad = width - cell.chars().count()
+            )
+            .unwrap();
+        }
+        output(format_args!("{}", line.trim_end()));
+    }
+}
+
+/// The IPA name for a vowel height.
+fn height_name(height: phone::Height) -> String {
+    match height.value() {
+        9 => "close".into(),
+        8 => "near-close".into(),
+        7 => "close-mid".into(),
+        5 => "mid".into(),
+        3 => "open-mid".into(),
+        2 => "near-open".into(),
+        1 => "open".into(),
+        x => format!("height {x}"),
+    }
+}
+
+fn show_inventory(inventory: &phone::Inventory, tables: Tables) {
+    tables.begin();
+    let consonants = inventory.consonants();
+    if !consonants.is_empty() {
+        let places: Vec<_> = consonants
+            .iter()
+            .map(phone::Consonant::place)
+            .sorted()
+            .dedup()
+            .collect();
+        let manners: Vec<_> = consonants
+            .iter()
+            .map(phone::Consonant::manner)
+            .sorted()
+            .dedup()
+            .collect();
+        let header = std::iter::once(String::new()).chain(places.iter().map(ToString::to_string));
+        let mut rows = vec![header.collect()];
+        for manner in manners.iter() {
+            let cells = places.iter().map(|place| {
+                consonants
+                    .iter()
+                    .filter(|c| c.place() == *place && c.manner() == *manner)
+                    .join(" ")
+            });
+            rows.push(std::iter::once(manner.to_string()).chain(cells).collect());
+        }
+        // The places and manners are labels, and every other cell holds consonants
+        tables.print(&rows, |row, col| row > 0 && col > 0);
+    }
+
+    let vowels = inventory.vowels();
+    if !vowels.is_empty() {
+        let columns = [
+            phone::Backness::Front,
+            phone::Backness::Central,
+            phone::Backness::Back,
+        ];
+        let header = std::iter::once(String::new()).chain(columns.iter().map(ToString::to_string));
+        let mut rows = vec![header.collect()];
+        for height in vowels
+            .iter()
+            .map(phone::Vowel::height)
+            .sorted_by_key(|height| std::cmp::Reverse(*height))
+            .dedup()
+        {
+            // Unrounded vowels come before rounded ones, like in the IPA chart
+            let cells = columns.iter().map(|column| {
+                vowels
+                    .iter()
+                    .filter(|v| v.height() == height && v.frontness().backness() == *column)
+                    .sorted_by_key(|v| (std::cmp::Reverse(v.frontness()), v.is_rounded()))
+                    .join(" ")
+            });
+            rows.push(std::iter::once(height_name(height)).chain(cells).collect());
+        }
+        tables.print(&rows, |row, col| row > 0 && col > 0);
+    }
+
+    let non_pulmonic = inventory.non_pulmonic_consonants();
+    if !non_pulmonic.is_empty() {
+        tables.print(
+            &[vec!["non-pulmonic".into(), non_pulmonic.iter().join(" ")]],
+            |_, col| col > 0,
+        );
+    }
+
+    let mut rows = vec![["phoneme", "features", "sonority", "weight"]
+        .map(String::from)
+        .to_vec()];
+    let phonemes = consonants
+        .iter()
+        .map(|&c| phone::Phoneme::from(c))
+        .chain(vowels.iter().map(|&v| phone::Phoneme::from(v)))
+        .chain(non_pulmonic.iter().map(|&c| phone::Phoneme::from(c)));
+    for phoneme in phonemes {
+        let features = match phoneme {
+            phone::Phoneme::Consonant(c) => format!("{} {}", c.place(), c.manner()),
+            phone::Phoneme::Vowel(v) => format!(
+                "{} {} {}",
+                height_name(v.height()),
+                v.frontness().backness(),
+                if v.is_rounded() {
+                    "rounded"
+                } else {
+                    "unrounded"
+                }
+            ),
+            phone::Phoneme::NonPulmonicConsonant(_) => "non-pulmonic".into(),
+        };
+        rows.push(vec![
+            phoneme.to_string(),
+            features,
+            phoneme.sonority().to_string(),
+            inventory.weight(phoneme).to_string(),
+        ]);
+    }
+    tables.print(&rows, |row, col| row > 0 && col == 0);
+
+    output(format_args!(
+        "{} consonants, {} vowels, {} non-pulmonic consonants, {} phonemes",
+        consonants.len(),
+        vowels.len(),
+        non_pulmonic.len(),
+        consonants.len() + vowels.len() + non_pulmonic.len(),
+    ));
+}
+
+fn show_comparison(comparison: &analysis::Comparison, first: &str, second: &str) {
+    let unmatched = |phonemes: &[(phone::Phoneme, Option<phone::Phoneme>)]| {
+        let list = phonemes
+            .iter()
+            .map(|(phoneme, nearest)| match nearest {
+                Some(nearest) => format!("{phoneme} (nearest {nearest})"),
+                None => phoneme.to_string(),
+            })
+            .join(" ");
+        if list.is_empty() {
+            "none".into()
+        } else {
+            list
+        }
+    };
+    let shared = comparison.shared().iter().join(" ");
+    print_table(&[
+        vec![
+            format!("shared ({})", comparison.shared().len()),
+            if shared.is_empty() {
+                "none".into()
+            } else {
+                shared
+            },
+        ],
+        vec![
+            format!("only in {first} ({})", comparison.only_first().len()),
+            unmatched(comparison.only_first()),
+        ],
+        vec![
+            format!("only in {second} ({})", comparison.only_second().len()),
+            unmatched(comparison.only_second()),
+        ],
+        vec!["distance".into(), format!("{:.2}", comparison.distance())],
+    ]);
+}
+
+/// Print the changes from the `old` language to the `new` one, one section for each part of the language which changed.
+/// Lines starting with `+` are only in the new language and lines starting with `-` only in the old one.
+fn show_language_diff(old: &Language, new: &Language) {
+    fn changes<T: PartialEq + std::fmt::Display>(old: &[T], new: &[T]) -> Vec<String> {
+        old.iter()
+            .filter(|x| !new.contains(x))
+            .map(|x| format!("- {x}"))
+            .chain(
+                new.iter()
+                    .filter(|x| !old.contains(x))
+                    .map(|x| format!("+ {x}")),
+            )
+            .collect()
+    }
+
+    // A language without consonants or vowels generates from all of them, so that is what it is compared as
+    let phonemes = |language: &Language| -> Vec<phone::Phoneme> {
+        let inventory = inventory(
+            &language.consonants,
+            &language.vowels,
+            &language.non_pulmonic,
+        );
+        let consonants = inventory
+            .consonants()
+            .iter()
+            .map(|c| phone::Phoneme::from(*c));
+        let non_pulmonic = inventory
+            .non_pulmonic_consonants()
+            .iter()
+            .map(|c| phone::Phoneme::from(*c));
+        let vowels = inventory.vowels().iter().map(|v| phone::Phoneme::from(*v));
+        consonants.chain(non_pulmonic).chain(vowels).collect()
+    };
+    // Later weights for a phoneme replace earlier ones, and phonemes without one have a weight of 1
+    let weight = |language: &Language, phoneme: phone::Phoneme| {
+        language
+            .weights
+            .iter()
+            .rev()
+            .find(|(x, _)| *x == phoneme)
+            .map_or(1, |(_, weight)| *weight)
+    };
+    let weighted: std::collections::BTreeSet<_> = old
+        .weights
+        .iter()
+        .chain(new.weights.iter())
+        .map(|(phoneme, _)| *phoneme)
+        .collect();
+    let weights = weighted
+        .into_iter()
+        .map(|phoneme| (phoneme, weight(old, phoneme), weight(new, phoneme)))
+        .filter(|(_, old, new)| old != new)
+        .map(|(phoneme, old, new)| format!("{phoneme}: {old} -> {new}"))
+        .collect();
+    let filters = |language: &Language| -> Vec<String> {
+        language.filters.iter().map(ToString::to_string).collect()
+    };
+    let romanization = |language: &Language| -> Vec<String> {
+        language
+            .romanization
+            .iter()
+            .flat_map(|r| r.entries())
+            .map(|(grapheme, phoneme)| format!("{grapheme}={phoneme}"))
+            .collect()
+    };
+
+    let sections = [
+        ("phonemes", changes(&phonemes(old), &phonemes(new))),
+        ("weights", weights),
+        ("patterns", changes(&old.patterns, &new.patterns)),
+        ("filters", changes(&filters(old), &filters(new))),
+        (
+            "romanization",
+            changes(&romanization(old), &romanization(new)),
+        ),
+    ];
+    let mut changed = false;
+    for (name, lines) in sections {
+        if lines.is_empty() {
+            continue;
+        }
+        changed = true;
+        output(format_args!("{name}"));
+        for line in lines {
+            output(format_args!("  {line}"));
+        }
+    }
+    if !changed {
+        output(format_args!("no changes"));
+    }
+}
+
+fn show_analysis(analysis: &analysis::Analysis, suggestions: usize) {
+    let percent =
+        |count: usize, total: usize| format!("{:.1}%", count as f64 * 100.0 / total as f64);
+
+    let total: usize = analysis.phonemes().iter().map(|(_, count)| count).sum();
+    let mut rows = vec![["phoneme", "count", "share"].map(String::from).to_vec()];
+    for (phoneme, count) in analysis.phonemes() {
+        rows.push(vec![
+            phoneme.to_string(),
+            count.to_string(),
+            percent(*count, total),
+        ]);
+    }
+    print_table(&rows);
+    output(format_args!(""));
+
+    let total: usize = analysis
+        .syllable_shapes()
+        .iter()
+        .map(|(_, count)| count)
+        .sum();
+    let mut rows = vec![["syllable", "count", "share"].map(String::from).to_vec()];
+    for (shape, count) in analysis.syllable_shapes() {
+        rows.push(vec![
+            shape.clone(),
+            count.to_string(),
+            percent(*count, total),
+        ]);
+    }
+    print_table(&rows);
+    output(format_args!(""));
+
+    let clusters = [
+        ("initial", analysis.initial_clusters()),
+        ("medial", analysis.medial_clusters()),
+        ("final", analysis.final_clusters()),
+    ];
+    let mut rows = vec![["clusters", ""].map(String::from).to_vec()];
+    for (position, clusters) in clusters {
+        let list = clusters
+            .iter()
+            .map(|(cluster, count)| format!("{} ({count})", cluster.iter().join("")))
+            .join(" ");
+        rows.push(vec![
+            position.into(),
+            if list.is_empty() { "none".into() } else { list },
+        ]);
+    }
+    print_table(&rows);
+    output(format_args!(""));
+
+    output(format_args!(
+        "{} words\nsuggested: generate-syllables {}",
+        analysis.words(),
+        analysis
+            .word_shapes()
+            .iter()
+            .take(suggestions)
+            .map(|(shape, _)| format!("--pattern '{shape}'"))
+            .join(" ")
+    ));
+    if let (Some(pattern), Some(counts)) = (analysis.syllable_pattern(), analysis.syllable_counts())
+    {
+        output(format_args!(
+            "suggested: generate-words --syllable '{pattern}' --syllable-counts {counts}"
+        ));
+    }
+}
+
+/// The statistics of a list of words, as `stats` prints them in JSON.
+#[derive(serde::Serialize)]
+struct Stats {
+    words: usize,
+    average_phonemes: f64,
+    average_syllables: f64,
+    phonemes: Vec<StatsCount>,
+    bigrams: Vec<StatsCount>,
+    syllable_shapes: Vec<StatsCount>,
+}
+
+#[derive(serde::Serialize)]
+struct StatsCount {
+    /// A phoneme, a pair of phonemes, or a syllable shape.
+    value: String,
+    count: usize,
+}
+
+impl Stats {
+    fn new(analysis: &analysis::Analysis) -> Self {
+        let count = |value: String, count: usize| StatsCount { value, count };
+        Self {
+            words: analysis.words(),
+            average_phonemes: analysis.average_phonemes(),
+            average_syllables: analysis.average_syllables(),
+            phonemes: analysis
+                .phonemes()
+                .iter()
+                .map(|(ph, n)| count(ph.to_string(), *n))
+                .collect(),
+            bigrams: analysis
+                .bigrams()
+                .iter()
+                .map(|((a, b), n)| count(format!("{a}{b}"), *n))
+                .collect(),
+            syllable_shapes: analysis
+                .syllable_shapes()
+                .iter()
+                .map(|(shape, n)| count(shape.clone(), *n))
+                .collect(),
+        }
+    }
+}
+
+/// How [`show_inventory`] and [`show_stats`] lay out their tables.
+#[derive(Clone, Copy, Debug)]
+enum Tables {
+    /// Columns aligned with spaces, for reading in a terminal.
+    Text,
+    /// LaTeX `tabular`s, with IPA in TIPA's notation if `tipa` is set.
+    Latex { tipa: bool },
+    /// Markdown tables, with the first row as the header.
+    Markdown,
+}
+
+impl Tables {
+    /// Print what the tables need before the first one: for LaTeX, a comment on how to compile them.
+    fn begin(self) {
+        match self {
+            Self::Text | Self::Markdown => {}
+            Self::Latex { tipa: true } => output(format_args!(
+                "% Generated by conlang. Needs \\usepackage{{tipa}}."
+            )),
+            Self::Latex { tipa: false } => output(format_args!(
+                "% Generated by conlang. Compile with XeLaTeX or LuaLaTeX and a font with IPA, like Charis SIL."
+            )),
+        }
+    }
+
+    /// Print a table followed by a blank line. `is_ipa` tells which cells, by row and column, hold IPA rather than
+    /// labels or numbers.
+    fn print(self, rows: &[Vec<String>], is_ipa: impl Fn(usize, usize) -> bool) {
+        match self {
+            Self::Text => print_table(rows),
+            Self::Latex { tipa } => {
+                output(format_args!("{}", export::latex_table(rows, is_ipa, tipa)))
+            }
+            Self::Markdown => print_markdown_table(rows),
+        }
+        output(format_args!(""));
+    }
+}
+
+/// Print the statistics of a list of words as tables: histograms of the phonemes and syllable shapes, and a table of
+/// how often each phoneme is followed by each other phoneme.
+fn show_stats(analysis: &analysis::Analysis, tables: Tables) {
+    const BAR_WIDTH: usize = 40;
+    tables.begin();
+    // `ipa` is whether the values being counted are IPA, rather than shapes like `CVC`
+    let histogram = |header: &str, counts: Vec<(String, usize)>, ipa: bool| {
+        let total: usize = counts.iter().map(|(_, count)| count).sum();
+        let most = counts.iter().map(|(_, count)| *count).max().unwrap_or(0);
+        let mut rows = vec![vec![header.into(), "count".into(), "share".into()]];
+        for (value, count) in counts {
+            let mut row = vec![
+                value,
+                count.to_string(),
+                format!("{:.1}%", count as f64 * 100.0 / total as f64),
+            ];
+            // Every count gets at least some bar, so rare values are still visible. Typeset tables are left without
+            // bars, since they are for reading off the numbers.
+            if let Tables::Text = tables {
+                row.push("#".repeat((count * BAR_WIDTH).div_ceil(most)));
+            }
+            rows.push(row);
+        }
+        tables.print(&rows, |row, col| ipa && row > 0 && col == 0);
+    };
+
+    histogram(
+        "phoneme",
+        analysis
+            .phonemes()
+            .iter()
+            .map(|(ph, count)| (ph.to_string(), *count))
+            .collect(),
+        true,
+    );
+
+    // Rows are the first phoneme of each pair and columns the second, both from most to least common
+    let phonemes: Vec<_> = analysis.phonemes().iter().map(|(ph, _)| *ph).collect();
+    let bigrams: std::collections::HashMap<_, _> = analysis.bigrams().iter().cloned().collect();
+    let mut rows = vec![std::iter::once(String::new())
+        .chain(phonemes.iter().map(ToString::to_string))
+        .collect::<Vec<_>>()];
+    for first in phonemes.iter() {
+        let mut row = vec![first.to_string()];
+        for second in phonemes.iter() {
+            row.push(match bigrams.get(&(*first, *second)) {
+                Some(count) => count.to_string(),
+                None => ".".into(),
+            });
+        }
+        rows.push(row);
+    }
+    tables.print(&rows, |row, col| (row == 0) != (col == 0));
+
+    histogram("syllable", analysis.syllable_shapes().to_vec(), false);
+
+    output(format_args!(
+        "{} words, {:.2} phonemes and {:.2} syllables on average",
+        analysis.words(),
+        analysis.average_phonemes(),
+        analysis.average_syllables()
+    ));
+}
+
+/// Where the romanization of a generated word goes in the `lines` format.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+enum Romanize {
+    /// After the IPA, separated by a tab.
+    #[default]
+    Alongside,
+    /// In place of the IPA.
+    Instead,
+}
+
+/// Write a generated word for the `lines` format: its `ipa`, with its spelling in the `romanization` if there is one.
+fn spell(
+    ipa: String,
+    word: &phone::Word,
+    romanization: Option<&ortho::Romanization>,
+    romanize: Romanize,
+) -> String {
+    let Some(romanization) = romanization else {
+        return bracket(ipa);
+    };
+    let spelled = romanization.romanize(&word.phonemes().collect::<Vec<_>>());
+    match romanize {
+        Romanize::Alongside => format!("{}\t{spelled}", bracket(ipa)),
+        Romanize::Instead => spelled,
+    }
+}
+
+/// Check that there is a romanization for `--romanize` to use, exiting with an error if there is not.
+fn check_romanize(romanize: Option<Romanize>, romanization: Option<&ortho::Romanization>) {
+    if romanize.is_some() && romanization.is_none() {
+        ErrorKind::Usage.exit("no romanization for --romanize: use --romanization or --language");
+    }
+}
+
+/// How generated words are printed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+enum Format {
+    /// One word per line, in IPA.
+    #[default]
+    Lines,
+    /// One JSON object per line.
+    Json,
+    /// Comma-separated values, with a header.
+    Csv,
+    /// Tab-separated values, with a header.
+    Tsv,
+}
+
+/// A generated word, as it is printed by the structured formats.
+#[derive(serde::Serialize)]
+struct Record<'a> {
+    ipa: String,
+    romanization: Option<String>,
+    syllables: Vec<String>,
+    /// The pattern the word was generated from, if it is known.
+    pattern: Option<&'a str>,
+    seed: u64,
+}
+
+/// Prints generated words as records in one of the structured formats.
+struct Printer {
+    seed: u64,
+    /// The writer for CSV and TSV, or `None` for JSON.
+    table: Option<csv::Writer<Output>>,
+}
+
+impl Printer {
+    /// Create a printer for the `format`, or `None` if the format is not structured.
+    fn new(format: Format, seed: u64) -> Option<Self> {
+        let delimiter = match format {
+            Format::Lines => return None,
+            Format::Json => None,
+            Format::Csv => Some(b','),
+            Format::Tsv => Some(b'\t'),
+        };
+        let mut out = Self {
+            seed,
+            table: delimiter.map(|delimiter| {
+                csv::WriterBuilder::new()
+                    .delimiter(delimiter)
+                    .from_writer(Output)
+            }),
+        };
+        out.write_row(&["ipa", "romanization", "syllables", "pattern", "seed"]);
+        Some(out)
+    }
+
+    fn print(
+        &mut self,
+        word: &phone::Word,
+        romanization: Option<&ortho::Romanization>,
+        pattern: Option<&str>,
+    ) {
+        let record = Record {
+            ipa: bracket(word),
+            romanization: romanization.map(|r| r.romanize(&word.phonemes().collect::<Vec<_>>())),
+            syllables: word.iter().map(ToString::to_string).collect(),
+            pattern,
+            seed: self.seed,
+        };
+        if self.table.is_none() {
+            output(format_args!("{}", serde_json::to_string(&record).unwrap()));
+            return;
+        }
+        self.write_row(&[
+            &record.ipa,
+            record.romanization.as_deref().unwrap_or(""),
+            &record.syllables.join(" "),
+            record.pattern.unwrap_or(""),
+            &record.seed.to_string(),
+        ]);
+    }
+
+    /// Write a row of a table, flushing it right away so `--infinite` streams. Like [`output`], this stops quietly if
+    /// stdout has been closed.
+    fn write_row(&mut self, row: &[&str]) {
+        let Some(table) = self.table.as_mut() else {
+            return;
+        };
+        if let Err(e) = Progress::suspend(|| table.write_record(row)) {
+            output_failed(e.into());
+        }
+        if let Err(e) = Progress::suspend(|| table.flush()) {
+            output_failed(e);
+        }
+    }
+}
+
+/// The progress bar being drawn, if any, so [`output`] can move it out of the way of lines printed to the terminal.
+static PROGRESS: std::sync::Mutex<Option<indicatif::ProgressBar>> = std::sync::Mutex::new(None);
+
+/// A progress bar on stderr for long runs, with how many of the generated candidates the filters accept. It starts
+/// hidden and appears once a run looks long: a large `--count`, or filters rejecting most candidates. It never appears
+/// when stdout is not a terminal, so scripts and pipelines are left alone. Words can be counted from any thread, as
+/// batches generated on many threads are.
+struct Progress {
+    bar: indicatif::ProgressBar,
+    /// The number of candidates generated, including the words which were accepted.
+    candidates: std::sync::atomic::AtomicU64,
+    shown: std::sync::atomic::AtomicBool,
+}
+
+impl Progress {
+    /// Show the bar from the start for at least this many words.
+    const LARGE_COUNT: usize = 10_000;
+    /// Show the bar once the filters accept less than this share of the candidates, after
+    /// [`MIN_CANDIDATES`](Self::MIN_CANDIDATES) so a few unlucky words at the start do not count.
+    const LOW_ACCEPTANCE: f64 = 0.1;
+    const MIN_CANDIDATES: u64 = 1000;
+
+    /// Create a progress bar for `count` words, or for an endless run if there is no count.
+    fn new(count: Option<usize>) -> Self {
+        let (bar, template) = match count {
+            Some(count) => (
+                indicatif::ProgressBar::with_draw_target(
+                    Some(count as u64),
+                    indicatif::ProgressDrawTarget::hidden(),
+                ),
+                "{wide_bar} {human_pos}/{human_len} words, {eta} left, {msg}",
+            ),
+            None => (
+                indicatif::ProgressBar::hidden(),
+                "{spinner} {human_pos} words in {elapsed}, {msg}",
+            ),
+        };
+        bar.set_style(indicatif::ProgressStyle::with_template(template).unwrap());
+        let out = Self {
+            bar,
+            candidates: 0.into(),
+            shown: false.into(),
+        };
+        if count.is_some_and(|count| count >= Self::LARGE_COUNT) {
+            out.show();
+        }
+        out
+    }
+
+    fn show(&self) {
+        use std::{io::IsTerminal, sync::atomic::Ordering};
+        if self.shown.swap(true, Ordering::Relaxed) || !std::io::stdout().is_terminal() {
+            return;
+        }
+        self.bar
+            .set_draw_target(indicatif::ProgressDrawTarget::stderr());
+        *PROGRESS.lock().unwrap() = Some(self.bar.clone());
+    }
+
+    /// Count a word which took `candidates` candidates to find.
+    fn record(&self, candidates: usize) {
+        self.record_words(1, candidates);
+    }
+
+    /// Count `words` words which took `candidates` candidates to find between them.
+    fn record_words(&self, words: usize, candidates: usize) {
+        use std::sync::atomic::Ordering;
+
+        let candidates = self
+            .candidates
+            .fetch_add(candidates as u64, Ordering::Relaxed)
+            + candidates as u64;
+        self.bar.inc(words as u64);
+        let acceptance = self.bar.position() as f64 / candidates as f64;
+        if candidates >= Self::MIN_CANDIDATES && acceptance < Self::LOW_ACCEPTANCE {
+            self.show();
+        }
+        if self.shown.load(Ordering::Relaxed) {
+            self.bar
+                .set_message(format!("{:.1}% of candidates accepted", acceptance * 100.0));
+        }
+    }
+
+    /// Run `f` with the progress bar, if one is being drawn, cleared from the terminal, so what `f` prints to stdout
+    /// does not end up mixed into it. Output to an `--output` file does not need the bar cleared.
+    fn suspend<R>(f: impl FnOnce() -> R) -> R {
+        let bar = PROGRESS.lock().unwrap().clone();
+        match bar {
+            Some(bar) if OUTPUT_FILE.lock().unwrap().is_none() => bar.suspend(f),
+            _ => f(),
+        }
+    }
+
+    /// Stop drawing the bar, replacing it with a line of the final counts.
+    fn finish(self) {
+        if PROGRESS.lock().unwrap().take().is_none() {
+            return;
+        }
+        self.bar.finish_and_clear();
+        let words = self.bar.position();
+        let candidates = self.candidates.into_inner();
+        eprintln!(
+            "generated {words} words from {candidates} candidates, {:.1}% accepted, in {:.1}s",
+            words as f64 / candidates as f64 * 100.0,
+            self.bar.elapsed().as_secs_f64()
+        );
+    }
+}
+
+/// How words are spoken, for every command which speaks or writes sounds.
+#[derive(clap::Args, Debug)]
+// The flags for Polly are only read with the `aws` feature
+#[cfg_attr(not(feature = "aws"), allow(dead_code))]
+struct Voice {
+    /// The speech backend: `polly` for Amazon Polly, which needs AWS credentials and conlang built with the `aws`
+    /// feature, as it is by default, `espeak` for eSpeak NG, which runs offline but needs the `espeak-ng` program
+    /// installed, `mbrola` for MBROLA, which runs offline and speaks length, stress, and tone exactly, but needs the
+    /// `mbrola` program and a `--mbrola-voice` installed, or `formant` for the built-in synthesizer, which needs
+    /// nothing and gives every phoneme a sound of its own, but sounds robotic. Without the `aws` feature, this is
+    /// `formant` if not given.
+    #[arg(long, global = true, value_enum, default_value_t)]
+    tts: Tts,
+
+    /// The Polly voice to speak with, like `Joanna`, `Matthew`, or `Lupe`. Each voice reads IPA with the sounds of its
+    /// own language, so a voice of a language closer to yours can render its phonemes better.
+    #[arg(long, global = true, default_value = "Joanna")]
+    voice: String,
+
+    /// The Polly engine: `neural` sounds more natural, but not every voice has it.
+    #[arg(long, global = true, value_enum, default_value_t)]
+    engine: SpeechEngine,
+
+    /// The format of Polly's audio: `ogg` for Ogg Vorbis, or `wav`, which is larger but plays everywhere. eSpeak always
+    /// speaks WAV, and so does Polly with `--by-syllable` or `--normalize`.
+    #[arg(long, global = true, value_enum, default_value_t)]
+    audio_format: AudioFormat,
+
+    /// Samples a second of Polly's audio: 8000, 16000, 22050, or 24000 for `ogg`, and 8000 or 16000 for `wav`.
+    /// Without it, Polly picks the best for the voice.
+    #[arg(long, global = true, value_parser = parse_sample_rate)]
+    sample_rate: Option<u32>,
+
+    /// The language to speak with, like `en-IN`, for voices which speak more than one. With `--tts espeak`, this picks
+    /// the eSpeak voice, like `en` or `fi`, which is `en` if not given.
+    #[arg(long, global = true)]
+    language_code: Option<String>,
+
+    /// With `--tts mbrola`, the MBROLA voice to speak with: the name of one installed in `/usr/share/mbrola`, like
+    /// `en1`, or the path to its database.
+    #[arg(long, global = true)]
+    mbrola_voice: Option<String>,
+
+    /// With `--tts mbrola`, the voice's symbols for phonemes it does not name in X-SAMPA, like `ɹ:r,ɑ:A`.
+    #[arg(long, global = true, value_parser = parse_symbols)]
+    mbrola_symbols: Option<std::vec::Vec<(phone::Phoneme, String)>>,
+
+    /// With `--tts mbrola` or `--tts formant`, the pitch in Hz to speak at before stress and tone, which should be near
+    /// the pitch of an MBROLA voice. It is 110 if not given.
+    #[arg(long, global = true)]
+    base_pitch: Option<f64>,
+
+    /// How fast to speak: `x-slow`, `slow`, `medium`, `fast`, `x-fast`, or a percentage of the normal rate like `80%`.
+    #[arg(long, global = true, value_parser = parse_rate)]
+    rate: Option<String>,
+
+    /// How high to speak: `x-low`, `low`, `medium`, `high`, `x-high`, or a change like `+10%` or `-20%`. Neural Polly
+    /// voices can not change their pitch, so this needs `--engine standard` with Polly.
+    #[arg(long, global = true, value_parser = parse_pitch, allow_hyphen_values = true)]
+    pitch: Option<String>,
+
+    /// How loud to speak: `silent`, `x-soft`, `soft`, `medium`, `loud`, `x-loud`, or a change in decibels like `+6dB`
+    /// or `-3dB`.
+    #[arg(long, global = true, value_parser = parse_volume, allow_hyphen_values = true)]
+    volume: Option<String>,
+
+    /// A folder to keep synthesized speech in, so a word spoken again with the same voice is played from it instead of
+    /// asking the speech backend again. Without it, speech is kept in `conlang/speech` in `$XDG_CACHE_HOME` or
+    /// `~/.cache`.
+    #[arg(long, global = true)]
+    cache_dir: Option<std::path::PathBuf>,
+
+    /// Always ask the speech backend, without reading or writing the cache of synthesized speech.
+    #[arg(long, global = true)]
+    no_cache: bool,
+
+    /// Speak each syllable on its own and join them into words. Syllables are spoken once and cached, so long texts
+    /// flow evenly and need fewer calls to the speech backend.
+    #[arg(long, global = true)]
+    by_syllable: bool,
+
+    /// With `--by-syllable`, the milliseconds of silence between syllables.
+    #[arg(long, global = true, default_value_t = 0)]
+    syllable_gap: u64,
+
+    /// With `--by-syllable`, the milliseconds syllables fade into each other for, or with a `--syllable-gap`, fade in
+    /// and out for.
+    #[arg(long, global = true, default_value_t = 10)]
+    crossfade: u64,
+
+    /// The milliseconds of silence between words, so long passages are easier to follow. With `--by-syllable`, this is
+    /// 150 if not given.
+    #[arg(long, global = true)]
+    word_gap: Option<u64>,
+
+    /// Make every word as loud as the others before playing or writing it, so words from different voices or backends
+    /// sound even one after another: `peak` brings the loudest sample to `--loudness`, and `lufs` brings how loud the
+    /// word sounds there.
+    #[arg(long, global = true, value_enum)]
+    normalize: Option<Normalize>,
+
+    /// With `--normalize`, how loud to make words: decibels below full scale for `peak`, -1 if not given, or LUFS for
+    /// `lufs`, -18 if not given.
+    #[arg(long, global = true, allow_hyphen_values = true)]
+    loudness: Option<f64>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+enum Tts {
+    #[cfg_attr(feature = "aws", default)]
+    Polly,
+    Espeak,
+    Mbrola,
+    #[cfg_attr(not(feature = "aws"), default)]
+    Formant,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+enum SpeechEngine {
+    #[default]
+    Neural,
+    Standard,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+enum Normalize {
+    Peak,
+    Lufs,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+enum AudioFormat {
+    #[default]
+    Ogg,
+    Wav,
+}
+
+/// The speech flags, set once the command line has been parsed.
+static VOICE: std::sync::OnceLock<Voice> = std::sync::OnceLock::new();
+
+/// Parse a speaking rate for SSML prosody: one of its keywords, or a percentage from 20% to 200%, which is what Polly
+/// allows.
+fn parse_rate(src: &str) -> Result<String, anyhow::Error> {
+    if matches!(src, "x-slow" | "slow" | "medium" | "fast" | "x-fast") {
+        return Ok(src.into());
+    }
+    match src.strip_suffix('%').map(str::parse::<u32>) {
+        Some(Ok(percent)) if (20..=200).contains(&percent) => Ok(src.into()),
+        _ => Err(anyhow!(
+            "expected x-slow, slow, medium, fast, x-fast, or a percentage from 20% to 200%"
+        )),
+    }
+}
+
+/// Parse a pitch for SSML prosody: one of its keywords, or a percentage change.
+fn parse_pitch(src: &str) -> Result<String, anyhow::Error> {
+    if matches!(src, "x-low" | "low" | "medium" | "high" | "x-high") {
+        return Ok(src.into());
+    }
+    match src.strip_suffix('%').map(str::parse::<i32>) {
+        Some(Ok(_)) if src.starts_with(['+', '-']) => Ok(src.into()),
+        _ => Err(anyhow!(
+            "expected x-low, low, medium, high, x-high, or a change like +10% or -20%"
+        )),
+    }
+}
+
+/// Parse a volume for SSML prosody: one of its keywords, or a change in decibels.
+fn parse_volume(src: &str) -> Result<String, anyhow::Error> {
+    if matches!(
+        src,
+        "silent" | "x-soft" | "soft" | "medium" | "loud" | "x-loud"
+    ) {
+        return Ok(src.into());
+    }
+    match src.strip_suffix("dB").map(str::parse::<f64>) {
+        Some(Ok(_)) if src.starts_with(['+', '-']) => Ok(src.into()),
+        _ => Err(anyhow!(
+            "expected silent, x-soft, soft, medium, loud, x-loud, or a change like +6dB or -3dB"
+        )),
+    }
+}
+
+/// Parse a sample rate which Polly makes audio at.
+fn parse_sample_rate(src: &str) -> Result<u32, anyhow::Error> {
+    match src.parse() {
+        Ok(rate @ (8000 | 16000 | 22050 | 24000)) => Ok(rate),
+        _ => Err(anyhow!("expected 8000, 16000, 22050, or 24000")),
+    }
+}
+
+/// Parse the symbols an MBROLA voice has for phonemes, like `ɹ:r,ɑ:A`.
+fn parse_symbols(src: &str) -> Result<Vec<(phone::Phoneme, String)>, anyhow::Error> {
+    let mut out = Vec::new();
+    for pair in src.split(',') {
+        let invalid = || anyhow!("invalid symbol \"{pair}\": expected `phoneme:symbol`");
+        let (phoneme, symbol) = pair.split_once(':').ok_or_else(invalid)?;
+        let mut chars = phoneme.trim().chars();
+        let (Some(c), None, false) = (chars.next(), chars.next(), symbol.trim().is_empty()) else {
+            return Err(invalid());
+        };
+        out.push((phone::Phoneme::try_from(c)?, symbol.trim().into()));
+    }
+    Ok(out)
+}
+
+/// The path to the MBROLA voice database `voice`, which is a path already, or the name of a voice installed where
+/// packages put them.
+fn mbrola_voice(voice: &str) -> std::path::PathBuf {
+    let path = std::path::Path::new(voice);
+    if path.components().count() > 1 || path.exists() {
+        return path.into();
+    }
+    std::path::Path::new("/usr/share/mbrola")
+        .join(voice)
+        .join(voice)
+}
+
+/// The speech backend of the `--tts` flag, speaking as the rest of the speech flags say.
+async fn speech_backend() -> std::sync::Arc<dyn speech::TtsBackend> {
+    let voice = VOICE.get().expect("the voice is set before speaking");
+    let prosody = speech::Prosody {
+        rate: voice.rate.clone(),
+        pitch: voice.pitch.clone(),
+        volume: voice.volume.clone(),
+        // Syllables are joined into words with their own gap
+        pause: voice
+            .word_gap
+            .filter(|_| !voice.by_syllable)
+            .map(std::time::Duration::from_millis),
+    };
+    let backend: Box<dyn speech::TtsBackend> = match voice.tts {
+        #[cfg(feature = "aws")]
+        Tts::Polly => {
+            let client = aws_sdk_polly::Client::new(&aws_config::from_env().load().await);
+            let config = speech::PollyConfig {
+                voice: voice.voice.clone(),
+                engine: match voice.engine {
+                    SpeechEngine::Neural => speech::Engine::Neural,
+                    SpeechEngine::Standard => speech::Engine::Standard,
+                },
+                // Joining syllables and measuring loudness need the samples
+                format: match voice.audio_format {
+                    AudioFormat::Ogg if !voice.by_syllable && voice.normalize.is_none() => {
+                        speech::Format::OggVorbis
+                    }
+                    _ => speech::Format::Wav,
+                },
+                sample_rate: voice.sample_rate,
+                language_code: voice.language_code.clone(),
+            };
+            if config.format == speech::Format::Wav && voice.sample_rate > Some(16000) {
+                ErrorKind::Usage.exit("Polly only makes WAV at 8000 or 16000 samples a second");
+            }
+            Box::new(speech::Polly::new(client, config).with_prosody(prosody))
+        }
+        #[cfg(not(feature = "aws"))]
+        Tts::Polly => ErrorKind::Usage
+            .exit("conlang was built without Polly: build it with the `aws` feature"),
+        Tts::Espeak => {
+            let language = voice.language_code.as_deref().unwrap_or("en");
+            Box::new(speech::Espeak::new(language.to_lowercase()).with_prosody(prosody))
+        }
+        Tts::Mbrola => {
+            let Some(name) = &voice.mbrola_voice else {
+                ErrorKind::Usage.exit("--tts mbrola needs an --mbrola-voice, like en1");
+            };
+            let mut mbrola = speech::Mbrola::new(mbrola_voice(name))
+                .with_pitch(voice.base_pitch.unwrap_or(110.0))
+                .with_prosody(prosody);
+            for (phoneme, symbol) in voice.mbrola_symbols.iter().flatten() {
+                mbrola = mbrola.with_symbol(*phoneme, symbol.clone());
+            }
+            Box::new(mbrola)
+        }
+        Tts::Formant => Box::new(
+            synth::Formant::new()
+                .with_pitch(voice.base_pitch.unwrap_or(110.0))
+                .with_prosody(prosody),
+        ),
+    };
+    // Inside the cache, so only the try which worked is kept
+    let backend: Box<dyn speech::TtsBackend> = Box::new(speech::Retry::new(backend));
+    let dir = voice.cache_dir.clone().or_else(config::cache_dir);
+    let backend: Box<dyn speech::TtsBackend> = match dir.filter(|_| !voice.no_cache) {
+        Some(dir) => Box::new(speech::Cached::new(backend, dir)),
+        None => backend,
+    };
+    // The cache is inside, so each syllable is only synthesized once
+    let backend: Box<dyn speech::TtsBackend> = if voice.by_syllable {
+        Box::new(
+            speech::Assembled::new(backend)
+                .with_gap(std::time::Duration::from_millis(voice.syllable_gap))
+                .with_crossfade(std::time::Duration::from_millis(voice.crossfade))
+                .with_pause(std::time::Duration::from_millis(
+                    voice.word_gap.unwrap_or(150),
+                )),
+        )
+    } else {
+        backend
+    };
+    // Outside everything, so whole words are measured
+    let loudness = match voice.normalize {
+        Some(Normalize::Peak) => speech::Loudness::Peak(voice.loudness.unwrap_or(-1.0)),
+        Some(Normalize::Lufs) => speech::Loudness::Lufs(voice.loudness.unwrap_or(-18.0)),
+        None => return backend.into(),
+    };
+    std::sync::Arc::new(speech::Normalized::new(backend, loudness))
+}
+
+/// The name of the voice of the speech flags, like `polly:Joanna` or `espeak:en`.
+fn voice_name() -> String {
+    let voice = VOICE.get().expect("the voice is set before speaking");
+    match voice.tts {
+        Tts::Polly => format!("polly:{}", voice.voice),
+        Tts::Espeak => format!(
+            "espeak:{}",
+            voice
+                .language_code
+                .as_deref()
+                .unwrap_or("en")
+                .to_lowercase()
+        ),
+        Tts::Mbrola => format!(
+            "mbrola:{}",
+            voice.mbrola_voice.as_deref().unwrap_or_default()
+        ),
+        Tts::Formant => "formant".into(),
+    }
+}
+
+/// Speaks words with the speech backend through the speakers.
+struct SpeakerBox {
+    backend: std::sync::Arc<dyn speech::TtsBackend>,
+    player: std::sync::Mutex<Player>,
+}
+
+/// The speakers, and the last word played through them, which can be paused or played again while the interactive
+/// modes go on reading keys.
+struct Player {
+    soloud: soloud::Soloud,
+    /// The audio of the last word, which has to be kept while it plays, and its voice.
+    last: Option<(soloud::audio::Wav, soloud::Handle)>,
+}
+
+impl SpeakerBox {
+    pub async fn new() -> Result<Self, anyhow::Error> {
+        let soloud = soloud::Soloud::default()?;
+        Ok(Self {
+            backend: speech_backend().await,
+            player: std::sync::Mutex::new(Player { soloud, last: None }),
+        })
+    }
+
+    /// Speak `ipa`, returning once it has been spoken.
+    pub async fn speak(&self, ipa: &str) -> Result<(), anyhow::Error> {
+        self.start_speaking(ipa).await?;
+        self.finished().await;
+        Ok(())
+    }
+
+    /// Start speaking `ipa`, returning once it starts playing, so an interactive mode can go on while it plays. Any
+    /// word still playing is stopped.
+    pub async fn start_speaking(&self, ipa: &str) -> Result<(), anyhow::Error> {
+        let audio = self.backend.synthesize(ipa).await?;
+        self.start_audio(&audio)
+    }
+
+    /// A queue speaking words in order through these speakers, synthesizing up to `ahead` words while the one before
+    /// them plays.
+    pub fn queue(&self, ahead: usize) -> SpeechQueue<'_> {
+        SpeechQueue {
+            speaker: self,
+            ahead,
+            pending: std::collections::VecDeque::new(),
+        }
+    }
+
+    /// Speak words in order as they are sent to the stream, in a task of their own, so whatever sends them can go on
+    /// without waiting for them to be spoken. Up to `ahead` words are synthesized while the one before them plays.
+    pub fn stream(self, ahead: usize) -> SpeechStream {
+        let (words, mut rx) = tokio::sync::mpsc::channel::<String>(SPEECH_BACKLOG);
+        let playing = tokio::spawn(async move {
+            let mut queue = self.queue(ahead);
+            while let Some(ipa) = rx.recv().await {
+                queue.push(&ipa).await?;
+            }
+            queue.finish().await
+        });
+        SpeechStream { words, playing }
+    }
+
+    /// Check if the last word is still playing, which it is while paused.
+    pub fn is_playing(&self) -> bool {
+        let player = self.player.lock().unwrap();
+        player
+            .last
+            .as_ref()
+            .is_some_and(|(_, handle)| player.soloud.is_valid_voice_handle(*handle))
+    }
+
+    /// Pause the last word if it is playing, or carry on playing it if it is paused, returning whether it is paused.
+    pub fn toggle_pause(&self) -> bool {
+        let mut player = self.player.lock().unwrap();
+        let Some((_, handle)) = player.last else {
+            return false;
+        };
+        let paused = !player.soloud.pause(handle);
+        player.soloud.set_pause(handle, paused);
+        paused
+    }
+
+    /// Play the last word again from the start, returning whether there was one.
+    pub fn replay(&self) -> bool {
+        let mut player = self.player.lock().unwrap();
+        let Player { soloud, last } = &mut *player;
+        let Some((wav, handle)) = last else {
+            return false;
+        };
+        soloud.stop(*handle);
+        *handle = soloud.play(&*wav);
+        true
+    }
+
+    /// Stop the last word, if it is playing.
+    pub fn stop(&self) {
+        let mut player = self.player.lock().unwrap();
+        if let Some((_, handle)) = player.last {
+            player.soloud.stop(handle);
+        }
+    }
+
+    /// Change the volume of every word by `change`, where 1 is the volume the speech backend made them at, returning
+    /// the new volume, which is kept from silent to twice as loud.
+    pub fn change_volume(&self, change: f32) -> f32 {
+        let mut player = self.player.lock().unwrap();
+        let volume = (player.soloud.global_volume() + change).clamp(0.0, 2.0);
+        player.soloud.set_global_volume(volume);
+        volume
+    }
+
+    /// Handle `key` if it is one of the playback controls of the interactive modes, returning what it did: `z` pauses
+    /// or carries on, `r` replays, backspace stops, and `+` and `-` change the volume.
+    pub fn control(&self, key: ratatui::crossterm::event::KeyCode) -> Option<String> {
+        use ratatui::crossterm::event::KeyCode;
+
+        let volume = |volume: f32| format!("volume {:.0}%", volume * 100.0);
+        Some(match key {
+            KeyCode::Char('z') if !self.is_playing() => "nothing is playing".into(),
+            KeyCode::Char('z') if self.toggle_pause() => "paused".into(),
+            KeyCode::Char('z') => "playing".into(),
+            KeyCode::Char('r') if self.replay() => "playing again".into(),
+            KeyCode::Char('r') => "nothing has been spoken yet".into(),
+            KeyCode::Backspace => {
+                self.stop();
+                "stopped".into()
+            }
+            KeyCode::Char('+') | KeyCode::Char('=') => volume(self.change_volume(0.1)),
+            KeyCode::Char('-') => volume(self.change_volume(-0.1)),
+            _ => return None,
+        })
+    }
+
+    async fn play_audio(&self, src: &[u8]) -> Result<(), anyhow::Error> {
+        self.start_audio(src)?;
+        self.finished().await;
+        Ok(())
+    }
+
+    fn start_audio(&self, src: &[u8]) -> Result<(), anyhow::Error> {
+        let mut wav = soloud::audio::Wav::default();
+        wav.load_mem(src)?;
+        let mut player = self.player.lock().unwrap();
+        let handle = player.soloud.play(&wav);
+        // Dropping the audio of the word before stops it
+        player.last = Some((wav, handle));
+        Ok(())
+    }
+
+    /// Wait for the last word to finish playing, checking now and then without holding up other tasks.
+    async fn finished(&self) {
+        while self.is_playing() {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await
+        }
+    }
+}
+
+/// How many words a [`SpeechQueue`] synthesizes ahead of the one playing, which keeps the speakers busy without
+/// sending the speech backend more requests at once than it takes kindly to.
+const SPEECH_AHEAD: usize = 4;
+
+/// Speaks words in the order they are pushed, synthesizing the next ones while each plays, so speaking a long run of
+/// words is not mostly spent waiting on the speech backend between them.
+struct SpeechQueue<'a> {
+    speaker: &'a SpeakerBox,
+    ahead: usize,
+    pending: std::collections::VecDeque<
+        tokio::task::JoinHandle<Result<bytes::Bytes, speech::SpeechError>>,
+    >,
+}
+
+impl SpeechQueue<'_> {
+    /// Start synthesizing `ipa`, then speak the words before it until no more than `ahead` are waiting.
+    pub async fn push(&mut self, ipa: &str) -> Result<(), anyhow::Error> {
+        let backend = self.speaker.backend.clone();
+        let ipa = ipa.to_owned();
+        self.pending
+            .push_back(tokio::spawn(async move { backend.synthesize(&ipa).await }));
+        while self.pending.len() > self.ahead {
+            self.play_next().await?;
+        }
+        Ok(())
+    }
+
+    /// Speak every word still waiting.
+    pub async fn finish(mut self) -> Result<(), anyhow::Error> {
+        while !self.pending.is_empty() {
+            self.play_next().await?;
+        }
+        Ok(())
+    }
+
+    async fn play_next(&mut self) -> Result<(), anyhow::Error> {
+        if let Some(synthesis) = self.pending.pop_front() {
+            let audio = synthesis.await??;
+            self.speaker.play_audio(&audio).await?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for SpeechQueue<'_> {
+    /// Stop synthesizing words which will not be spoken, like after an error.
+    fn drop(&mut self) {
+        for synthesis in self.pending.iter() {
+            synthesis.abort();
+        }
+    }
+}
+
+/// How many words a [`SpeechStream`] holds waiting to be spoken before sending another waits for one to be spoken, so
+/// generating words without end does not fill up memory with words which will never be heard.
+const SPEECH_BACKLOG: usize = 1000;
+
+/// Words waiting to be spoken by a task playing them through the speakers, which [`SpeakerBox::stream`] starts.
+struct SpeechStream {
+    words: tokio::sync::mpsc::Sender<String>,
+    playing: tokio::task::JoinHandle<Result<(), anyhow::Error>>,
+}
+
+impl SpeechStream {
+    /// Send `ipa` to be spoken after the words sent before it. This only waits if the backlog is full.
+    pub async fn push(&mut self, ipa: impl Into<String>) -> Result<(), anyhow::Error> {
+        if self.words.send(ipa.into()).await.is_err() {
+            // The task only stops taking words when speaking one failed, so its error says why
+            return Err(match (&mut self.playing).await {
+                Ok(Err(e)) => e,
+                Ok(Ok(())) => anyhow::anyhow!("speech stopped before every word was spoken"),
+                Err(e) => e.into(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Wait for every word sent to be spoken.
+    pub async fn finish(self) -> Result<(), anyhow::Error> {
+        drop(self.words);
+        self.playing.await?
+    }
+}
+
+/// Run this command again, without `--watch`, each time one of the `files` changes. The files are checked twice a
+/// second, and the terminal is cleared before each run so only the latest words are on screen. A run which fails, like
+/// on a language file saved halfway through an edit, is reported and the next change is waited for as usual.
+fn watch(files: &[&Option<std::path::PathBuf>]) -> ! {
+    use std::io::IsTerminal;
+
+    let files: Vec<_> = files.iter().filter_map(|path| path.as_deref()).collect();
+    if files.is_empty() {
+        ErrorKind::Usage.exit("--watch needs a file to watch, like --language");
+    }
+    let modified = || -> Vec<_> {
+        files
+            .iter()
+            .map(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+            .collect()
+    };
+    let exe = match std::env::current_exe() {
+        Ok(x) => x,
+        Err(e) => ErrorKind::Other.exit(e),
+    };
+    let args: Vec<_> = std::env::args_os()
+        .skip(1)
+        .filter(|arg| arg != "--watch")
+        .collect();
+
+    let mut last = modified();
+    loop {
+        if std::io::stdout().is_terminal() {
+            print!("\x1b[2J\x1b[H");
+            let _ = std::io::Write::flush(&mut std::io::stdout());
+        }
+        match std::process::Command::new(&exe).args(&args).status() {
+            Ok(status) if !status.success() => eprintln!("error: generating failed with {status}"),
+            Ok(_) => {}
+            Err(e) => ErrorKind::Other.exit(e),
+        }
+        eprintln!(
+            "watching {} for changes",
+            files.iter().map(|path| path.display()).join(", ")
+        );
+        while modified() == last {
+            std::thread::sleep(std::time::Duration::from_millis(500));
+        }
+        last = modified();
+    }
+}
+
+/// Audition the `entries` in an interactive list, then print the ones which were starred and return how many there are.
+async fn audition_words(entries: Vec<audition::Entry>) -> usize {
+    let speaker = SpeakerBox::new().await;
+    match audition::run(entries, speaker).await {
+        Ok(starred) => {
+            for entry in starred.iter() {
+                output(format_args!("{}", entry.text));
+            }
+            starred.len()
+        }
+        Err(e) => ErrorKind::Speech.exit(e),
+    }
+}
+
+/// The exit statuses of [`ErrorKind`], for `--help`.
+const EXIT_STATUS_HELP: &str = "Exit status:
+  1  something else failed, like patterns which can not generate enough words
+  2  flags were invalid or do not work together
+  3  an input file or stdin could not be read or is not valid
+  4  a pattern could not be parsed
+  5  the output could not be written
+  6  the speech backend failed";
+
+/// Why the program stopped with an error. Each kind exits with its own status, so scripts can tell a mistake on the
+/// command line from a bad input file or a failure of the speech backend. Invalid flags are reported by clap, which
+/// also exits with 2.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ErrorKind {
+    /// Anything else, like patterns which can not generate enough words.
+    Other = 1,
+    /// Flags which do not work together, or which are missing something to work on.
+    Usage = 2,
+    /// A file or stdin could not be read, or is not valid: a language file, lexicon, word list, or rule file.
+    Input = 3,
+    /// A pattern could not be parsed.
+    Pattern = 4,
+    /// The output could not be written.
+    Output = 5,
+    /// The speech backend failed.
+    Speech = 6,
+}
+
+impl ErrorKind {
+    /// Print the error and exit with the status for this kind of failure.
+    fn exit(self, e: impl std::fmt::Display) -> ! {
+        eprintln!("error: {e}");
+        self.exit_quietly()
+    }
+
+    /// Exit with the status for this kind of failure, for when the error has already been reported.
+    fn exit_quietly(self) -> ! {
+        std::process::exit(self as i32)
+    }
+}
+
+/// Report a pattern parse error and exit.
+fn exit_parse_error(e: &gen::ParseError) -> ! {
+    report_parse_error(e);
+    ErrorKind::Pattern.exit_quietly()
+}
+
+/// Print a pattern parse error to stderr, pointing at where in the pattern it happened.
+fn report_parse_error(e: &gen::ParseError) {
+    eprint!("{}", parse_error_message(e));
+}
+
+/// The lines reporting a pattern parse error, with a caret under where in the pattern it happened.
+fn parse_error_message(e: &gen::ParseError) -> String {
+    let gen::ParseError::Pattern {
+        pattern,
+        position,
+        suggestion,
+        ..
+    } = e
+    else {
+        return format!("error: {e}\n");
+    };
+
+    // Combining diacritics, like the tilde of /ã/, take no room of their own in the terminal
+    let column = pattern
+        .chars()
+        .take(*position)
+        .filter(|c| !('\u{300}'..='\u{36f}').contains(c))
+        .count();
+    let mut out = format!("error: {}\n", e.kind());
+    writeln!(out, "  | {pattern}").unwrap();
+    writeln!(out, "  | {:>width$}", "^", width = column + 1).unwrap();
+    if let Some(suggestion) = suggestion {
+        writeln!(out, "  = help: {suggestion}").unwrap();
+    }
+    out
+}
+
+#[tokio::main]
+async fn main() {
+    let config = match config::Config::load() {
+        Ok(x) => x,
+        Err(e) => ErrorKind::Input.exit(e),
+    };
+    let command = Cli::command().after_long_help(format!("{}\n\n{EXIT_STATUS_HELP}", config::HELP));
+    let cli = match Cli::from_arg_matches(&config.apply(command).get_matches()) {
+        Ok(x) => x,
+        Err(e) => e.exit(),
+    };
+    BRACKET.set(cli.bracket).unwrap();
+    VOICE.set(cli.voice).unwrap();
+    match cli.command {
+        Command::GenerateSyllables(mut cmd) => {
+            if cmd.watch {
+                watch(&[&cmd.language, &cmd.rules, &cmd.pattern_file]);
+            }
+            let language = match Language::load(cmd.language.as_deref()) {
+                Ok(x) => x,
+                Err(e) => ErrorKind::Input.exit(e),
+            };
+            cmd.consonants = cmd.consonants.or(language.consonants);
+            cmd.vowels = cmd.vowels.or(language.vowels);
+            cmd.non_pulmonic = cmd.non_pulmonic.or(language.non_pulmonic);
+            cmd.romanization = cmd.romanization.or(language.romanization);
+            check_romanize(cmd.romanize, cmd.romanization.as_ref());
+            // Weights from the command line come last, so they replace the file's weights for the same phonemes
+            cmd.weights = Some(
+                language
+                    .weights
+                    .into_iter()
+                    .chain(cmd.weights.into_iter().flatten())
+                    .collect(),
+            );
+            cmd.filter = language.filters.into_iter().chain(cmd.filter).collect();
+            match length_filters(
+                (cmd.min_phonemes, cmd.max_phonemes),
+                (cmd.min_syllables, cmd.max_syllables),
+            ) {
+                Ok(filters) => cmd.filter.extend(filters),
+                Err(e) => ErrorKind::Usage.exit(e),
+            }
+            if let Some(counts) = &cmd.syllable_counts {
+                cmd.syllable_counts = match counts.within(cmd.min_syllables, cmd.max_syllables) {
+                    Some(counts) => Some(counts),
+                    None => ErrorKind::Usage.exit("none of the --syllable-counts are within --min-syllables and --max-syllables"),
+                };
+            }
+            // How often each pattern is picked, from weights like `CVC=3` on the command line or in the pattern file
+            let weighted: Result<Vec<gen::WeightedPattern>, _> = match cmd.load_pattern_file() {
+                Ok(Some(patterns)) => Ok(patterns),
+                Ok(None) => cmd.pattern.iter().map(|p| p.parse()).collect(),
+                Err(e) => ErrorKind::Input.exit(e),
+            };
+            let weighted = match weighted {
+                Ok(x) => x,
+                Err(e) => exit_parse_error(&e),
+            };
+            cmd.pattern = weighted.iter().map(|p| p.pattern().into()).collect();
+            let mut weights: Vec<_> = weighted.iter().map(gen::WeightedPattern::weight).collect();
+            if cmd.pattern.is_empty() {
+                cmd.pattern = language.patterns;
+            }
+            if cmd.pattern.is_empty() {
+                ErrorKind::Usage
+                    .exit("no patterns: use --pattern or add patterns to the language file");
+            }
+            weights.resize(cmd.pattern.len(), 1);
+
+            let inventory = inventory(&cmd.consonants, &cmd.vowels, &cmd.non_pulmonic);
+            let inventory = cmd
+                .weights
+                .iter()
+                .flatten()
+                .fold(inventory, |inventory, (phoneme, weight)| {
+                    inventory.with_weight(*phoneme, *weight)
+                });
+            let inventory = cmd
+                .stratum
+                .iter()
+                .fold(inventory, |inventory, (name, stratum)| {
+                    inventory.with_stratum(name.clone(), stratum.clone())
+                });
+
+            let speaker = if cmd.speak {
+                Some(
+                    SpeakerBox::new()
+                        .await
+                        .unwrap_or_else(|e| ErrorKind::Speech.exit(e)),
+                )
+            } else {
+                None
+            };
+            let mut speech = speaker.map(|speaker| speaker.stream(SPEECH_AHEAD));
+
+            let lexicon = match cmd.load_lexicon() {
+                Ok(lexicon) => lexicon.map(std::sync::Arc::new),
+                Err(e) => ErrorKind::Input.exit(e),
+            };
+
+            let rules = match cmd.load_rules() {
+                Ok(rules) => rules,
+                Err(e) => ErrorKind::Input.exit(e),
+            };
+
+            let (mut history, history_filter) = match cmd.history.as_deref().map(History::open) {
+                Some(Ok((history, lexicon))) => (
+                    Some(history),
+                    Some(gen::Filter::Lexicon(std::sync::Arc::new(lexicon))),
+                ),
+                Some(Err(e)) => ErrorKind::Input.exit(e),
+                None => (None, None),
+            };
+
+            let avoid = match load_wordlist(&cmd.avoid_wordlist) {
+                Ok(wordlist) => wordlist.map(|wordlist| {
+                    wordlist_filter(wordlist, cmd.romanization.clone().unwrap_or_default())
+                }),
+                Err(e) => ErrorKind::Input.exit(e),
+            };
+
+            let build = |p: &str| {
+                match rules.as_ref().filter(|rules| rules.rule(p).is_some()) {
+                    Some(rules) => rules.compile(p, &inventory),
+                    None => gen::WordGenerator::parse(p, &inventory),
+                }
+                .map(|gen| {
+                    cmd.filter
+                        .iter()
+                        .cloned()
+                        .fold(gen, gen::WordGenerator::with_filter)
+                })
+                .map(|gen| match &lexicon {
+                    Some(lexicon) => gen.with_filter(gen::Filter::Lexicon(lexicon.clone())),
+                    None => gen,
+                })
+                .map(|gen| match &avoid {
+                    Some(avoid) => gen.with_filter(avoid.clone()),
+                    None => gen,
+                })
+                .map(|gen| match &history_filter {
+                    Some(filter) => gen.with_filter(filter.clone()),
+                    None => gen,
+                })
+                .map(|gen| match cmd.syllable_counts.clone() {
+                    Some(counts) => gen.with_syllable_counts(counts),
+                    None => gen,
+                })
+                .map(|gen| match cmd.harmony.clone() {
+                    Some(harmony) => gen.with_harmony(harmony),
+                    None => gen,
+                })
+                .map(|gen| match cmd.positions.clone() {
+                    Some(positions) => gen.with_positions(positions),
+                    None => gen,
+                })
+                .map(|gen| match cmd.optional_probability {
+                    Some(probability) => gen.with_optional_probability(probability),
+                    None => gen,
+                })
+                .map(|gen| match cmd.max_attempts {
+                    Some(attempts) => gen.with_max_attempts(attempts.get()),
+                    None => gen,
+                })
+                .map(|gen| match cmd.reduplication {
+                    Some(reduplication) => gen.with_reduplication(reduplication),
+                    None => gen,
+                })
+                .map(|gen| match cmd.stress {
+                    Some(stress) => gen.with_stress(stress),
+                    None => gen,
+                })
+                .map(|gen| match cmd.tones.clone() {
+                    Some(tones) => gen.with_tones(tones),
+                    None => gen,
+                })
+                .map(|gen| match &cmd.style {
+                    Some(style) => gen.with_style(style),
+                    None => gen,
+                })
+            };
+
+            if let Some(path) = &cmd.output {
+                if let Err(e) = open_output(path, cmd.append) {
+                    ErrorKind::Output.exit(e);
+                }
+            }
+            if cmd.pattern == ["-"] {
+                let count = generate_batch(&cmd, build);
+                close_output(cmd.output.as_deref(), count, "words");
+                return;
+            }
+
+            let patterns: Result<Vec<_>, _> = cmd.pattern.iter().map(|p| build(p)).collect();
+            let patterns = match patterns {
+                Ok(ps) => ps,
+                Err(e) => exit_parse_error(&e),
+            };
+
+            // The number of words each pattern is expected to generate, going by its share of the weights
+            let total: u32 = weights.iter().sum();
+            let share = |idx: usize| (cmd.count * weights[idx] as usize).div_ceil(total as usize);
+            for (idx, pattern) in patterns.iter().enumerate() {
+                let lints = pattern.lint(&inventory).into_iter().chain(
+                    (!cmd.infinite && !cmd.explain)
+                        .then(|| pattern.lint_count(share(idx)))
+                        .flatten(),
+                );
+                for lint in lints {
+                    eprintln!("warning: {pattern}: {lint}");
+                }
+            }
+
+            if cmd.explain {
+                for (idx, pattern) in patterns.iter().enumerate() {
+                    if idx > 0 {
+                        output(format_args!(""));
+                    }
+                    show_pattern(pattern);
+                }
+                return;
+            }
+
+            if cmd.stats {
+                for pattern in patterns.iter() {
+                    eprintln!(
+                        "{pattern}: {} possible words, {:.1} bits of entropy, {:.1}% chance of a duplicate in {} words",
+                        pattern.possible_words(),
+                        pattern.entropy_bits(),
+                        pattern.collision_probability(cmd.count) * 100.0,
+                        cmd.count,
+                    );
+                }
+            }
+
+            let seed = cmd.seed.unwrap_or_else(rand::random);
+            let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+            let mut printer = Printer::new(cmd.format, seed);
+            if cmd.minimal_pairs {
+                let mut count = 0;
+                for (idx, pattern) in patterns.iter().enumerate() {
+                    for (a, b) in pattern.minimal_pairs(&mut rng, share(idx)) {
+                        output(format_args!(
+                            "{}\t{}",
+                            bracket(a.iter().join(" ")),
+                            bracket(b.iter().join(" "))
+                        ));
+                        count += 1;
+                    }
+                }
+                close_output(cmd.output.as_deref(), count, "pairs");
+                return;
+            }
+
+            let fail = |e: gen::GenerateError| -> ! { ErrorKind::Other.exit(e) };
+            // A known number of words which are not spoken as they come can be generated all at once, on every thread
+            // with the `parallel` feature
+            let batch = cmd.unique || !(cmd.infinite || cmd.speak);
+            // Unique words are generated all at once before any are printed, so there is no progress to show for them
+            let progress =
+                (!cmd.unique).then(|| Progress::new((!cmd.infinite).then_some(cmd.count)));
+            let choose = rand::distributions::WeightedIndex::new(&weights).unwrap();
+            // Unique words are drawn from all the patterns at once, so which one a word came from is only known when there
+            // is a single pattern
+            let words = if cmd.unique {
+                let words = gen::generate_unique(&patterns, &mut rng, cmd.count)
+                    .unwrap_or_else(|e| fail(e));
+                let pattern = (patterns.len() == 1).then_some(0);
+                let words: Vec<_> = words.into_iter().map(|word| (pattern, word)).collect();
+                itertools::Either::Left(words.into_iter())
+            } else if batch {
+                // Each word uses a pattern picked at random by weight, and each pattern generates its share in one
+                // batch
+                let picks: Vec<usize> = (0..cmd.count).map(|_| choose.sample(&mut rng)).collect();
+                let mut batches: Vec<_> = (0..patterns.len())
+                    .map(|idx| {
+                        let picked = picks.iter().filter(|&&pick| pick == idx).count();
+                        let record = |words, candidates| {
+                            if let Some(progress) = &progress {
+                                progress.record_words(words, candidates);
+                            }
+                        };
+                        patterns[idx]
+                            .generate_batch_with_progress(&mut rng, picked, record)
+                            .unwrap_or_else(|e| fail(e))
+                            .into_iter()
+                    })
+                    .collect();
+                let words: Vec<_> = picks
+                    .into_iter()
+                    .map(|idx| (Some(idx), batches[idx].next().unwrap()))
+                    .collect();
+                itertools::Either::Left(words.into_iter())
+            } else {
+                // Each word uses a pattern picked at random by weight, generated as it is needed so `--infinite` can
+                // stream and `--speak` can start speaking before the rest are generated
+                let random = std::iter::repeat_with(|| {
+                    let idx = choose.sample(&mut rng);
+                    let (word, candidates) = patterns[idx]
+                        .try_generate_counted(&mut rng)
+                        .unwrap_or_else(|e| fail(e));
+                    if let Some(progress) = &progress {
+                        progress.record(candidates);
+                    }
+                    (Some(idx), word)
+                });
+                itertools::Either::Right(random.take(limit(cmd.count, cmd.infinite)))
+            };
+
+            let mut entries = Vec::new();
+            let mut count = 0;
+            for (pattern, word) in words {
+                count += 1;
+                if let Some(history) = history.as_mut() {
+                    history.record(&word);
+                }
+                let ipa = word.iter().join(" ");
+                if let Some(printer) = printer.as_mut() {
+                    printer.print(
+                        &word,
+                        cmd.romanization.as_ref(),
+                        pattern.map(|idx| cmd.pattern[idx].as_str()),
+                    );
+                } else {
+                    let mut line = spell(
+                        ipa.clone(),
+                        &word,
+                        cmd.romanization.as_ref(),
+                        cmd.romanize.unwrap_or_default(),
+                    );
+                    for derived in gen::derive_family(&word, &cmd.derive) {
+                        write!(line, "\t{}", bracket(derived.iter().join(" "))).unwrap();
+                    }
+                    if cmd.audition {
+                        entries.push(audition::Entry { word, text: line });
+                    } else {
+                        output(format_args!("{line}"));
+                    }
+                }
+                if let Some(speech) = speech.as_mut() {
+                    if let Err(e) = speech.push(ipa).await {
+                        ErrorKind::Speech.exit(e);
+                    }
+                }
+            }
+            if let Some(progress) = progress {
+                progress.finish();
+            }
+            if let Some(speech) = speech {
+                if let Err(e) = speech.finish().await {
+                    ErrorKind::Speech.exit(e);
+                }
+            }
+            if cmd.audition {
+                count = audition_words(entries).await;
+            }
+            close_output(cmd.output.as_deref(), count, "words");
+        }
+        Command::GenerateWords(mut cmd) => {
+            if cmd.watch {
+                watch(&[&cmd.language]);
+            }
+            let language = match Language::load(cmd.language.as_deref()) {
+                Ok(x) => x,
+                Err(e) => ErrorKind::Input.exit(e),
+            };
+            cmd.consonants = cmd.consonants.or(language.consonants);
+            cmd.vowels = cmd.vowels.or(language.vowels);
+            cmd.non_pulmonic = cmd.non_pulmonic.or(language.non_pulmonic);
+            cmd.romanization = cmd.romanization.or(language.romanization);
+            check_romanize(cmd.romanize, cmd.romanization.as_ref());
+            if cmd.syllable.is_empty() {
+                cmd.syllable = language.patterns;
+            }
+            if cmd.syllable.is_empty() {
+                ErrorKind::Usage
+                    .exit("no syllables: use --syllable or add patterns to the language file");
+            }
+
+            let Some(syllable_counts) = cmd
+                .syllable_counts
+                .within(cmd.min_syllables, cmd.max_syllables)
+            else {
+                ErrorKind::Usage.exit(
+                    "none of the --syllable-counts are within --min-syllables and --max-syllables",
+                );
+            };
+            let filters = match length_filters(
+                (cmd.min_phonemes, cmd.max_phonemes),
+                (cmd.min_syllables, cmd.max_syllables),
+            ) {
+                Ok(filters) => filters,
+                Err(e) => ErrorKind::Usage.exit(e),
+            };
+
+            let inventory = inventory(&cmd.consonants, &cmd.vowels, &cmd.non_pulmonic);
+            let generator = match syllable_generator(
+                &cmd.syllable.join(" "),
+                inventory,
+                &language.weights,
+                &language.filters,
+            ) {
+                Ok(gen) => gen.with_syllable_counts(syllable_counts),
+                Err(e) => exit_parse_error(&e),
+            };
+            let generator = match cmd.stress {
+                Some(stress) => generator.with_stress(stress),
+                None => generator,
+            };
+            let generator = filters
+                .into_iter()
+                .fold(generator, gen::WordGenerator::with_filter);
+            let generator = match load_wordlist(&cmd.avoid_wordlist) {
+                Ok(Some(wordlist)) => generator.with_filter(wordlist_filter(
+                    wordlist,
+                    cmd.romanization.clone().unwrap_or_default(),
+                )),
+                Ok(None) => generator,
+                Err(e) => ErrorKind::Input.exit(e),
+            };
+            let (mut history, generator) = match cmd.history.as_deref().map(History::open) {
+                Some(Ok((history, lexicon))) => (
+                    Some(history),
+                    generator.with_filter(gen::Filter::Lexicon(std::sync::Arc::new(lexicon))),
+                ),
+                Some(Err(e)) => ErrorKind::Input.exit(e),
+                None => (None, generator),
+            };
+            if cmd.explain {
+                show_pattern(&generator);
+                return;
+            }
+
+            let speaker = if cmd.speak {
+                Some(
+                    SpeakerBox::new()
+                        .await
+                        .unwrap_or_else(|e| ErrorKind::Speech.exit(e)),
+                )
+            } else {
+                None
+            };
+            let mut speech = speaker.map(|speaker| speaker.stream(SPEECH_AHEAD));
+
+            if let Some(path) = &cmd.output {
+                if let Err(e) = open_output(path, cmd.append) {
+                    ErrorKind::Output.exit(e);
+                }
+            }
+            let seed = cmd.seed.unwrap_or_else(rand::random);
+            let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+            let mut printer = Printer::new(cmd.format, seed);
+            let pattern = cmd.syllable.join(" ");
+            let mut entries = Vec::new();
+            let mut count = 0;
+            let progress = Progress::new((!cmd.infinite).then_some(cmd.count));
+            for _ in 0..limit(cmd.count, cmd.infinite) {
+                let word = match generator.try_generate_counted(&mut rng) {
+                    Ok((word, candidates)) => {
+                        progress.record(candidates);
+                        word
+                    }
+                    Err(e) => ErrorKind::Other.exit(e),
+                };
+                if let Some(history) = history.as_mut() {
+                    history.record(&word);
+                }
+                if let Some(printer) = printer.as_mut() {
+                    printer.print(&word, cmd.romanization.as_ref(), Some(&pattern));
+                } else {
+                    let line = spell(
+                        word.to_string(),
+                        &word,
+                        cmd.romanization.as_ref(),
+                        cmd.romanize.unwrap_or_default(),
+                    );
+                    if cmd.audition {
+                        entries.push(audition::Entry { word, text: line });
+                        continue;
+                    }
+                    output(format_args!("{line}"));
+                }
+                count += 1;
+                if let Some(speech) = speech.as_mut() {
+                    if let Err(e) = speech.push(word.to_string()).await {
+                        ErrorKind::Speech.exit(e);
+                    }
+                }
+            }
+            progress.finish();
+            if let Some(speech) = speech {
+                if let Err(e) = speech.finish().await {
+                    ErrorKind::Speech.exit(e);
+                }
+            }
+            if cmd.audition {
+                count = audition_words(entries).await;
+            }
+            close_output(cmd.output.as_deref(), count, "words");
+        }
+        Command::GenerateNames(mut cmd) => {
+            let language = match Language::load(cmd.language.as_deref()) {
+                Ok(x) => x,
+                Err(e) => ErrorKind::Input.exit(e),
+            };
+            cmd.consonants = cmd.consonants.or(language.consonants);
+            cmd.vowels = cmd.vowels.or(language.vowels);
+            cmd.non_pulmonic = cmd.non_pulmonic.or(language.non_pulmonic);
+            let romanization = cmd
+                .romanization
+                .or(language.romanization)
+                .unwrap_or_default();
+            if cmd.syllable.is_empty() {
+                cmd.syllable = language.patterns;
+            }
+            if cmd.syllable.is_empty() {
+                ErrorKind::Usage
+                    .exit("no syllables: use --syllable or add patterns to the language file");
+            }
+
+            let inventory = inventory(&cmd.consonants, &cmd.vowels, &cmd.non_pulmonic);
+            let generator = match syllable_generator(
+                &cmd.syllable.join(" "),
+                inventory,
+                &language.weights,
+                &language.filters,
+            ) {
+                Ok(gen) => gen.with_syllable_counts(cmd.syllable_counts.clone()),
+                Err(e) => exit_parse_error(&e),
+            };
+            let generator = match cmd.stress {
+                Some(stress) => generator.with_stress(stress),
+                None => generator,
+            };
+            if cmd.explain {
+                show_pattern(&generator);
+                return;
+            }
+            let wordlist = match load_wordlist(&cmd.avoid_wordlist) {
+                Ok(wordlist) => wordlist,
+                Err(e) => ErrorKind::Input.exit(e),
+            };
+            let filters = match length_filters(
+                (cmd.min_phonemes, cmd.max_phonemes),
+                (cmd.min_syllables, cmd.max_syllables),
+            ) {
+                Ok(filters) => filters,
+                Err(e) => ErrorKind::Usage.exit(e),
+            };
+
+            if let Some(path) = &cmd.output {
+                if let Err(e) = open_output(path, cmd.append) {
+                    ErrorKind::Output.exit(e);
+                }
+            }
+            let seed = cmd.seed.unwrap_or_else(rand::random);
+            let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+            let mut generate = || {
+                let mut word = generator.generate(&mut rng);
+                if !cmd.prefix.is_empty() && rng.gen_bool(cmd.affix_probability) {
+                    let prefix = &cmd.prefix[rng.gen_range(0..cmd.prefix.len())];
+                    for (idx, syl) in prefix.iter().enumerate() {
+                        word.insert(idx, syl.clone());
+                    }
+                }
+                if !cmd.suffix.is_empty() && rng.gen_bool(cmd.affix_probability) {
+                    let suffix = &cmd.suffix[rng.gen_range(0..cmd.suffix.len())];
+                    for syl in suffix.iter() {
+                        word.push(syl.clone());
+                    }
+                }
+                let phonemes: Vec<_> = word.phonemes().collect();
+                let spelling = capitalize(&romanization.romanize(&phonemes));
+                (word, spelling)
+            };
+            let mut count = 0;
+            for _ in 0..limit(cmd.count, cmd.infinite) {
+                // Names are checked after the affixes are added, since a harmless name and ending can spell something
+                // unfortunate together, or be too long together
+                let name =
+                    (0..generator.max_attempts())
+                        .map(|_| generate())
+                        .find(|(word, spelling)| {
+                            wordlist.as_ref().is_none_or(|x| !x.resembles(spelling))
+                                && !filters.iter().any(|filter| filter.rejects(word))
+                        });
+                let Some((word, spelling)) = name else {
+                    ErrorKind::Other.exit(format_args!(
+                        "no name passed the wordlist and length limits in {} attempts",
+                        generator.max_attempts()
+                    ));
+                };
+                output(format_args!("{}\t{spelling}", bracket(word)));
+                count += 1;
+            }
+            close_output(cmd.output.as_deref(), count, "names");
+        }
+        Command::GenerateFromCorpus(mut cmd) => {
+            let language = match Language::load(cmd.language.as_deref()) {
+                Ok(x) => x,
+                Err(e) => ErrorKind::Input.exit(e),
+            };
+            cmd.romanization = cmd.romanization.or(language.romanization);
+            check_romanize(cmd.romanize, cmd.romanization.as_ref());
+            if cmd.order == 0 {
+                ErrorKind::Usage.exit("--order must be at least 1");
+            }
+            let generator = match cmd.load() {
+                Ok(x) => x,
+                Err(e) => ErrorKind::Input.exit(e),
+            };
+            let filters = match length_filters(
+                (cmd.min_phonemes, cmd.max_phonemes),
+                (cmd.min_syllables, cmd.max_syllables),
+            ) {
+                Ok(filters) => filters,
+                Err(e) => ErrorKind::Usage.exit(e),
+            };
+
+            let speaker = if cmd.speak {
+                Some(
+                    SpeakerBox::new()
+                        .await
+                        .unwrap_or_else(|e| ErrorKind::Speech.exit(e)),
+                )
+            } else {
+                None
+            };
+            let mut speech = speaker.map(|speaker| speaker.stream(SPEECH_AHEAD));
+
+            if let Some(path) = &cmd.output {
+                if let Err(e) = open_output(path, cmd.append) {
+                    ErrorKind::Output.exit(e);
+                }
+            }
+            let seed = cmd.seed.unwrap_or_else(rand::random);
+            let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+            let mut printer = Printer::new(cmd.format, seed);
+            let mut count = 0;
+            for _ in 0..limit(cmd.count, cmd.infinite) {
+                // The chain picks its own lengths, so words of the wrong length can only be rejected after the fact
+                let word = (0..CORPUS_LENGTH_ATTEMPTS)
+                    .filter_map(|_| generator.generate_novel(&mut rng))
+                    .find(|word| !filters.iter().any(|filter| filter.rejects(word)));
+                let Some(word) = word else {
+                    ErrorKind::Other.exit(
+                        "could not generate a word of the right length which is not in the corpus",
+                    );
+                };
+                let ipa = word.iter().join(" ");
+                match printer.as_mut() {
+                    Some(printer) => printer.print(&word, cmd.romanization.as_ref(), None),
+                    // The romanization is how the corpus is spelled, so words are only spelled with it if asked to
+                    None => output(format_args!(
+                        "{}",
+                        spell(
+                            ipa.clone(),
+                            &word,
+                            cmd.romanization.as_ref().filter(|_| cmd.romanize.is_some()),
+                            cmd.romanize.unwrap_or_default(),
+                        )
+                    )),
+                }
+                count += 1;
+                if let Some(speech) = speech.as_mut() {
+                    if let Err(e) = speech.push(ipa).await {
+                        ErrorKind::Speech.exit(e);
+                    }
+                }
+            }
+            if let Some(speech) = speech {
+                if let Err(e) = speech.finish().await {
+                    ErrorKind::Speech.exit(e);
+                }
+            }
+            close_output(cmd.output.as_deref(), count, "words");
+        }
+        Command::ShowInventory(cmd) => {
+            let language = match &cmd.preset {
+                Some(name) => {
+                    let preset = find_preset(name);
+                    Language::parse(preset.src, preset.name)
+                }
+                None => Language::load(cmd.language.as_deref()),
+            };
+            let language = match language {
+                Ok(x) => x,
+                Err(e) => ErrorKind::Input.exit(e),
+            };
+            let inventory = inventory(
+                &cmd.consonants.or(language.consonants),
+                &cmd.vowels.or(language.vowels),
+                &cmd.non_pulmonic.or(language.non_pulmonic),
+            );
+            let inventory = language
+                .weights
+                .into_iter()
+                .chain(cmd.weights.into_iter().flatten())
+                .fold(inventory, |inventory, (phoneme, weight)| {
+                    inventory.with_weight(phoneme, weight)
+                });
+            let tables = match cmd.format {
+                InventoryFormat::Table => Tables::Text,
+                InventoryFormat::Latex => Tables::Latex { tipa: cmd.tipa },
+            };
+            show_inventory(&inventory, tables);
+        }
+        Command::SoundChange(cmd) => {
+            let rules = match cmd.load_rules() {
+                Ok(x) => x,
+                Err(e) => ErrorKind::Input.exit(e),
+            };
+            let words = match read_ipa_words(cmd.input.as_deref()) {
+                Ok(x) => x,
+                Err(e) => ErrorKind::Input.exit(e),
+            };
+            for word in words.iter() {
+                output(format_args!("{}", bracket(rules.apply(word))));
+            }
+        }
+        Command::Analyze(cmd) => {
+            let words = match read_ipa_words(Some(&cmd.words)) {
+                Ok(words) => words,
+                Err(e) => ErrorKind::Input.exit(e),
+            };
+            if words.is_empty() {
+                ErrorKind::Input.exit(format_args!("{} has no words", cmd.words.display()));
+            }
+            show_analysis(&analysis::Analysis::new(&words), cmd.suggestions);
+        }
+        Command::Transliterate(mut cmd) => {
+            let language = match Language::load(cmd.language.as_deref()) {
+                Ok(x) => x,
+                Err(e) => ErrorKind::Input.exit(e),
+            };
+            let romanization = cmd.romanization.take().or(language.romanization);
+            let src = match &cmd.input {
+                Some(path) => std::fs::read_to_string(path)
+                    .map_err(|e| anyhow!("could not read {}: {e}", path.display())),
+                None => std::io::read_to_string(std::io::stdin()).map_err(Into::into),
+            };
+            let src = match src {
+                Ok(x) => x,
+                Err(e) => ErrorKind::Input.exit(e),
+            };
+            for (idx, line) in src.lines().enumerate() {
+                match cmd.convert(line, romanization.as_ref()) {
+                    Ok(converted) => output(format_args!("{converted}")),
+                    Err(e) => ErrorKind::Input.exit(format_args!("line {}: {e}", idx + 1)),
+                }
+            }
+        }
+        Command::ValidatePattern(mut cmd) => {
+            let language = match Language::load(cmd.language.as_deref()) {
+                Ok(x) => x,
+                Err(e) => ErrorKind::Input.exit(e),
+            };
+            if cmd.pattern.is_empty() {
+                cmd.pattern = language.patterns;
+            }
+            if cmd.pattern.is_empty() {
+                ErrorKind::Usage
+                    .exit("no patterns: use --pattern or add patterns to the language file");
+            }
+            let inventory = inventory(
+                &cmd.consonants.or(language.consonants),
+                &cmd.vowels.or(language.vowels),
+                &cmd.non_pulmonic.or(language.non_pulmonic),
+            );
+            let inventory = language
+                .weights
+                .into_iter()
+                .chain(cmd.weights.into_iter().flatten())
+                .fold(inventory, |inventory, (phoneme, weight)| {
+                    inventory.with_weight(phoneme, weight)
+                });
+            let inventory = cmd
+                .stratum
+                .iter()
+                .fold(inventory, |inventory, (name, stratum)| {
+                    inventory.with_stratum(name.clone(), stratum.clone())
+                });
+
+            // Check every pattern before failing, so all the errors are reported at once
+            let mut failed = false;
+            for (idx, src) in cmd.pattern.iter().enumerate() {
+                let pattern = match gen::WordGenerator::parse(src, &inventory) {
+                    Ok(gen) => match cmd.optional_probability {
+                        Some(probability) => gen.with_optional_probability(probability),
+                        None => gen,
+                    },
+                    Err(e) => {
+                        report_parse_error(&e);
+                        failed = true;
+                        continue;
+                    }
+                };
+                if idx > 0 {
+                    output(format_args!(""));
+                }
+                show_pattern(&pattern);
+                for lint in pattern.lint(&inventory) {
+                    eprintln!("warning: {pattern}: {lint}");
+                }
+            }
+            if failed {
+                ErrorKind::Pattern.exit_quietly();
+            }
+        }
+        Command::RandomInventory(cmd) => {
+            let seed = cmd.seed.unwrap_or_else(rand::random);
+            let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+            let inventory = gen::random_inventory(&mut rng, cmd.consonant_count, cmd.vowel_count);
+            output(format_args!(
+                "# conlang random-inventory --consonant-count {} --vowel-count {} --seed {seed}",
+                cmd.consonant_count, cmd.vowel_count
+            ));
+            output(format_args!(
+                "consonants = \"{}\"",
+                inventory.consonants().iter().join("")
+            ));
+            output(format_args!(
+                "vowels = \"{}\"",
+                inventory.vowels().iter().join("")
+            ));
+            output(format_args!("patterns = [\"CV\", \"CVC\", \"CV CV(C)\"]"));
+        }
+        Command::Export(cmd) => {
+            if cmd.media.is_some() && cmd.to != export::Target::Anki {
+                ErrorKind::Usage.exit("--media can only be used with --to anki");
+            }
+            let language = match Language::load(cmd.language.as_deref()) {
+                Ok(x) => x,
+                Err(e) => ErrorKind::Input.exit(e),
+            };
+            let lexicon = match &cmd.lexicon {
+                Some(path) => std::fs::read_to_string(path)
+                    .map_err(|e| anyhow!("could not read {}: {e}", path.display()))
+                    .and_then(|src| export::parse_lexicon(&path.display().to_string(), &src)),
+                None => Ok(Vec::new()),
+            };
+            let lexicon = match lexicon {
+                Ok(x) => x,
+                Err(e) => ErrorKind::Input.exit(e),
+            };
+            // Only export the phonemes the language has, rather than every phoneme like generation would use
+            let inventory = phone::Inventory::new(
+                language.consonants.unwrap_or_default(),
+                language.vowels.unwrap_or_default(),
+                language.non_pulmonic.unwrap_or_default(),
+            );
+            let romanization = cmd
+                .romanization
+                .or(language.romanization)
+                .unwrap_or_default();
+            let speech = match cmd.media {
+                Some(_) => Some(speech_backend().await),
+                None => None,
+            };
+            let export = export::Export {
+                inventory: &inventory,
+                romanization: &romanization,
+                patterns: &language.patterns,
+                lexicon: &lexicon,
+                sounds: speech.as_ref().map(|speech| speech.format()),
+            };
+            if let (Some(dir), Some(speech)) = (&cmd.media, &speech) {
+                match export.write_sounds(dir, speech).await {
+                    Ok(count) => eprintln!("wrote {count} sounds to {}", dir.display()),
+                    Err(e) => ErrorKind::Speech.exit(e),
+                }
+            }
+            // Like `output`, stop quietly if stdout is closed
+            if export.write(cmd.to, &mut std::io::stdout().lock()).is_err() {
+                std::process::exit(0);
+            }
+        }
+        Command::Import(cmd) => {
+            let src = match &cmd.input {
+                Some(path) => std::fs::read_to_string(path)
+                    .map_err(|e| anyhow!("could not read {}: {e}", path.display())),
+                None => std::io::read_to_string(std::io::stdin()).map_err(Into::into),
+            };
+            let src = match src {
+                Ok(x) => x,
+                Err(e) => ErrorKind::Input.exit(e),
+            };
+            let imported = match cmd.from {
+                ImportFormat::Awkwords => import::Import::awkwords(&src),
+                ImportFormat::Zompist => import::Import::zompist(&src),
+            };
+            let imported = match imported {
+                Ok(x) => x,
+                Err(e) => ErrorKind::Input.exit(e),
+            };
+
+            let inventory = imported.inventory();
+            let phonemes = inventory
+                .consonants()
+                .iter()
+                .map(|x| phone::Phoneme::from(*x))
+                .chain(inventory.vowels().iter().map(|x| (*x).into()))
+                .chain(
+                    inventory
+                        .non_pulmonic_consonants()
+                        .iter()
+                        .map(|x| (*x).into()),
+                );
+            let some = |s: String| (!s.is_empty()).then_some(s);
+            let file = LanguageFile {
+                consonants: some(inventory.consonants().iter().join("")),
+                vowels: some(inventory.vowels().iter().join("")),
+                non_pulmonic: some(inventory.non_pulmonic_consonants().iter().join("")),
+                weights: phonemes
+                    .filter(|ph| inventory.weight(*ph) != 1)
+                    .map(|ph| (ph.to_string(), inventory.weight(ph)))
+                    .collect(),
+                patterns: imported.patterns().to_vec(),
+                filters: imported.filters().iter().map(ToString::to_string).collect(),
+                romanization: None,
+            };
+            for note in imported.notes() {
+                output(format_args!("# {note}"));
+            }
+            output(format_args!(
+                "{}",
+                toml::to_string(&file).unwrap().trim_end()
+            ));
+        }
+        Command::Gloss(cmd) => {
+            let language = match Language::load(cmd.language.as_deref()) {
+                Ok(x) => x,
+                Err(e) => ErrorKind::Input.exit(e),
+            };
+            let romanization = cmd
+                .romanization
+                .or(language.romanization)
+                .unwrap_or_default();
+            let glossary: gloss::Glossary = match std::fs::read_to_string(&cmd.lexicon)
+                .map_err(|e| anyhow!("could not read {}: {e}", cmd.lexicon.display()))
+                .and_then(|src| {
+                    src.parse()
+                        .map_err(|e| anyhow!("{}: {e}", cmd.lexicon.display()))
+                }) {
+                Ok(x) => x,
+                Err(e) => ErrorKind::Input.exit(e),
+            };
+
+            let mut rows = vec![Vec::new(), Vec::new(), Vec::new()];
+            for word in cmd.sentence.split_whitespace() {
+                // Punctuation stays in the first line, but is not part of the word
+                let bare: String = word
+                    .trim_matches(|c: char| c.is_ascii_punctuation())
+                    .chars()
+                    .filter(|c| !matches!(c, '.' | 'ˈ' | 'ˌ' | '-'))
+                    .collect();
+                let segment = |src: &str| {
+                    romanization
+                        .read(src)
+                        .ok()
+                        .and_then(|phonemes| glossary.segment(&phonemes))
+                };
+                // The first word of a sentence is often capitalized
+                let morphemes = segment(&bare).or_else(|| {
+                    let mut chars = bare.chars();
+                    let first = chars.next()?.to_lowercase();
+                    segment(&first.chain(chars).collect::<String>())
+                });
+                let (segmented, glossed) = match morphemes {
+                    Some(morphemes) => (
+                        morphemes
+                            .iter()
+                            .map(|m| romanization.romanize(m.phonemes()))
+                            .join("-"),
+                        morphemes.iter().map(|m| m.gloss()).join("-"),
+                    ),
+                    None => {
+                        eprintln!("warning: \"{bare}\" is not made of morphemes in the lexicon");
+                        (bare, "?".into())
+                    }
+                };
+                rows[0].push(word.to_string());
+                rows[1].push(segmented);
+                rows[2].push(glossed);
+            }
+            print_table(&rows);
+            if let Some(translation) = &cmd.translation {
+                output(format_args!("‘{translation}’"));
+            }
+        }
+        Command::Pronounce(cmd) => {
+            use std::io::{BufRead, IsTerminal};
+
+            let reading = cmd.ipa.is_none();
+            let lines: Box<dyn Iterator<Item = String>> = match cmd.ipa {
+                Some(ipa) => Box::new(std::iter::once(ipa)),
+                None => Box::new(std::io::stdin().lock().lines().map(|line| match line {
+                    Ok(x) => x,
+                    Err(e) => ErrorKind::Input.exit(e),
+                })),
+            };
+            let speaker = SpeakerBox::new()
+                .await
+                .unwrap_or_else(|e| ErrorKind::Speech.exit(e));
+            // Someone typing at the terminal wants to hear each line as soon as it is entered
+            let ahead = if reading && std::io::stdin().is_terminal() {
+                0
+            } else {
+                SPEECH_AHEAD
+            };
+            let mut queue = speaker.queue(ahead);
+            for line in lines {
+                let ipa = line.trim();
+                if ipa.is_empty() {
+                    continue;
+                }
+                // The speech backend reads more IPA than the phoneme parser knows, so a mistake is only a warning
+                if let Err(e) = ipa.parse::<phone::Word>() {
+                    eprintln!("warning: {ipa}: {e}");
+                }
+                if reading {
+                    output(format_args!("{ipa}"));
+                }
+                if let Err(e) = queue.push(ipa).await {
+                    ErrorKind::Speech.exit(e);
+                }
+            }
+            if let Err(e) = queue.finish().await {
+                ErrorKind::Speech.exit(e);
+            }
+        }
+        Command::CompareInventories(cmd) => {
+            let inventories: Vec<_> = [&cmd.first, &cmd.second]
+                .into_iter()
+                .map(|path| match Language::load(Some(path)) {
+                    Ok(language) => inventory(
+                        &language.consonants,
+                        &language.vowels,
+                        &language.non_pulmonic,
+                    ),
+                    Err(e) => ErrorKind::Input.exit(e),
+                })
+                .collect();
+            show_comparison(
+                &analysis::Comparison::new(&inventories[0], &inventories[1]),
+                &cmd.first.display().to_string(),
+                &cmd.second.display().to_string(),
+            );
+        }
+        Command::Stats(cmd) => {
+            let words = match read_ipa_words(cmd.words.as_deref()) {
+                Ok(words) => words,
+                Err(e) => ErrorKind::Input.exit(e),
+            };
+            if words.is_empty() {
+                ErrorKind::Input.exit("no words to count");
+            }
+            let analysis = analysis::Analysis::new(&words);
+            match cmd.format {
+                StatsFormat::Table => show_stats(&analysis, Tables::Text),
+                StatsFormat::Latex => show_stats(&analysis, Tables::Latex { tipa: cmd.tipa }),
+                StatsFormat::Json => output(format_args!(
+                    "{}",
+                    serde_json::to_string_pretty(&Stats::new(&analysis)).unwrap()
+                )),
+            }
+        }
+        Command::Diff(cmd) => {
+            let [old, new] = [&cmd.old, &cmd.new].map(|path| match Language::load(Some(path)) {
+                Ok(language) => language,
+                Err(e) => ErrorKind::Input.exit(e),
+            });
+            show_language_diff(&old, &new);
+        }
+        Command::Quiz(cmd) => {
+            use rand::seq::SliceRandom;
+
+            let lexicon = std::fs::read_to_string(&cmd.lexicon)
+                .map_err(|e| anyhow!("could not read {}: {e}", cmd.lexicon.display()))
+                .and_then(|src| export::parse_lexicon(&cmd.lexicon.display().to_string(), &src));
+            let mut entries = match lexicon {
+                Ok(x) => x,
+                Err(e) => ErrorKind::Input.exit(e),
+            };
+            if cmd.mode == quiz::Mode::Meaning {
+                entries.retain(|entry| !entry.gloss.is_empty());
+            }
+            if entries.is_empty() {
+                ErrorKind::Input.exit(format_args!(
+                    "{} has no words to ask about",
+                    cmd.lexicon.display()
+                ));
+            }
+            let seed = cmd.seed.unwrap_or_else(rand::random);
+            entries.shuffle(&mut rand::rngs::StdRng::seed_from_u64(seed));
+            entries.truncate(cmd.count);
+
+            let speaker = match cmd.mode {
+                quiz::Mode::Listen => Some(
+                    SpeakerBox::new()
+                        .await
+                        .unwrap_or_else(|e| ErrorKind::Speech.exit(e)),
+                ),
+                quiz::Mode::Meaning => None,
+            };
+            match quiz::run(&entries, cmd.mode, speaker.as_ref()).await {
+                Ok(score) if score.asked > 0 => output(format_args!(
+                    "score: {}/{} ({:.0}%)",
+                    score.right,
+                    score.asked,
+                    score.right as f64 * 100.0 / score.asked as f64
+                )),
+                Ok(_) => {}
+                Err(e) => ErrorKind::Speech.exit(e),
+            }
+        }
+        Command::Trainer(cmd) => {
+            let language = match Language::load(cmd.language.as_deref()) {
+                Ok(x) => x,
+                Err(e) => ErrorKind::Input.exit(e),
+            };
+            let phonemes = cmd.phonemes.unwrap_or_else(|| {
+                let inventory = inventory(
+                    &language.consonants,
+                    &language.vowels,
+                    &language.non_pulmonic,
+                );
+                inventory
+                    .consonants()
+                    .iter()
+                    .map(|&c| phone::Phoneme::from(c))
+                    .chain(inventory.vowels().iter().map(|&v| v.into()))
+                    .chain(
+                        inventory
+                            .non_pulmonic_consonants()
+                            .iter()
+                            .map(|&c| c.into()),
+                    )
+                    .collect()
+            });
+            if phonemes.len() < 2 {
+                ErrorKind::Usage.exit("the trainer needs at least two phonemes to tell apart");
+            }
+            let speaker = SpeakerBox::new()
+                .await
+                .unwrap_or_else(|e| ErrorKind::Speech.exit(e));
+            let seed = cmd.seed.unwrap_or_else(rand::random);
+            let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+            match trainer::run(&phonemes, cmd.count, &mut rng, &speaker).await {
+                Ok(score) if score.asked > 0 => {
+                    output(format_args!(
+                        "score: {}/{} ({:.0}%)",
+                        score.right,
+                        score.asked,
+                        score.right as f64 * 100.0 / score.asked as f64
+                    ));
+                    for (played, answer, times) in score.most_confused().into_iter().take(5) {
+                        output(format_args!("heard /{played}/ as /{answer}/ {times}×"));
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => ErrorKind::Speech.exit(e),
+            }
+        }
+        Command::Serve(cmd) => {
+            let language = match Language::load(cmd.language.as_deref()) {
+                Ok(x) => x,
+                Err(e) => ErrorKind::Input.exit(e),
+            };
+            let (inventory, patterns) = language.compile();
+            let server = serve::Server {
+                inventory,
+                patterns,
+                filters: language.filters,
+                romanization: language.romanization,
+                speech: speech_backend().await,
+            };
+            if let Err(e) = serve::run(server, cmd.address).await {
+                ErrorKind::Other.exit(e);
+            }
+        }
+        Command::Preset(cmd) => match cmd.action {
+            PresetAction::List => {
+                let rows: Vec<_> = preset::PRESETS
+                    .iter()
+                    .map(|preset| vec![preset.name.to_string(), preset.description().to_string()])
+                    .collect();
+                print_table(&rows);
+            }
+            PresetAction::Show { name, output: path } => {
+                let preset = find_preset(&name);
+                let Some(path) = path else {
+                    output(format_args!("{}", preset.src.trim_end()));
+                    return;
+                };
+                let written = std::fs::OpenOptions::new()
+                    .write(true)
+                    .create_new(true)
+                    .open(&path)
+                    .and_then(|mut file| {
+                        std::io::Write::write_all(&mut file, preset.src.as_bytes())
+                    });
+                if let Err(e) = written {
+                    ErrorKind::Output.exit(format_args!("could not write {}: {e}", path.display()));
+                }
+                eprintln!("wrote {} to {}", preset.name, path.display());
+            }
+        },
+        Command::Paradigm(mut cmd) => {
+            if cmd.columns.is_empty() {
+                cmd.columns.push((String::new(), None));
+            }
+            let mark = |word: &phone::Word, process: &Option<gen::Process>| match process {
+                Some(process) => process.apply(word),
+                None => word.clone(),
+            };
+            let mut rows = vec![std::iter::once(String::new())
+                .chain(cmd.columns.iter().map(|(label, _)| label.clone()))
+                .collect::<Vec<_>>()];
+            for (label, row) in cmd.rows.iter() {
+                let forms = cmd
+                    .columns
+                    .iter()
+                    .map(|(_, column)| bracket(mark(&mark(&cmd.root, column), row)));
+                rows.push(std::iter::once(label.clone()).chain(forms).collect());
+            }
+            let tables = match cmd.format {
+                ParadigmFormat::Text => Tables::Text,
+                ParadigmFormat::Markdown => Tables::Markdown,
+                ParadigmFormat::Latex => Tables::Latex { tipa: cmd.tipa },
+            };
+            tables.begin();
+            // Labels are in the first row and column, and forms everywhere else
+            tables.print(&rows, |row, col| row > 0 && col > 0);
+        }
+        Command::FrequencyChart(cmd) => {
+            let (words, inventory) = match &cmd.language {
+                Some(path) => {
+                    let language = match Language::load(Some(path)) {
+                        Ok(x) => x,
+                        Err(e) => ErrorKind::Input.exit(e),
+                    };
+                    let (inventory, patterns) = language.compile();
+                    if patterns.is_empty() {
+                        ErrorKind::Usage.exit("no patterns: add patterns to the language file");
+                    }
+                    let seed = cmd.seed.unwrap_or_else(rand::random);
+                    let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+                    let words: Result<Vec<_>, _> = (0..cmd.count)
+                        .map(|_| {
+                            patterns[rng.gen_range(0..patterns.len())]
+                                .1
+                                .try_generate(&mut rng)
+                        })
+                        .collect();
+                    match words {
+                        Ok(words) => (words, Some(inventory)),
+                        Err(e) => ErrorKind::Other.exit(e),
+                    }
+                }
+                None => match read_ipa_words(cmd.words.as_deref()) {
+                    Ok(words) => (words, None),
+                    Err(e) => ErrorKind::Input.exit(e),
+                },
+            };
+            let analysis = analysis::Analysis::new(&words);
+            let total: usize = analysis.phonemes().iter().map(|(_, count)| count).sum();
+            let Some(most) = analysis.phonemes().first().map(|(_, count)| *count) else {
+                ErrorKind::Input.exit("no words to chart");
+            };
+            let rows: Vec<_> = analysis
+                .phonemes()
+                .iter()
+                .map(|(ph, count)| {
+                    let mut row = vec![
+                        ph.to_string(),
+                        format!("{:.1}%", *count as f64 * 100.0 / total as f64),
+                        unicode_bar(*count, most, cmd.width),
+                    ];
+                    if let Some(inventory) = &inventory {
+                        row.push(format!("weight {}", inventory.weight(*ph)));
+                    }
+                    row
+                })
+                .collect();
+            print_table(&rows);
+        }
+        Command::Swadesh(cmd) => {
+            // Give up on a concept after this many words which are already taken, so patterns which can only make a
+            // few words fail instead of looping forever
+            const MAX_ATTEMPTS: usize = 1000;
+
+            let language = match Language::load(Some(&cmd.language)) {
+                Ok(x) => x,
+                Err(e) => ErrorKind::Input.exit(e),
+            };
+            let (_, patterns) = language.compile();
+            if patterns.is_empty() {
+                ErrorKind::Usage.exit("no patterns: add patterns to the language file");
+            }
+            let seed = cmd.seed.unwrap_or_else(rand::random);
+            let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+            let mut taken = std::collections::HashSet::new();
+            let mut lexicon = Vec::new();
+            for concept in SWADESH.lines() {
+                let mut word = None;
+                for _ in 0..MAX_ATTEMPTS {
+                    let (_, gen) = &patterns[rng.gen_range(0..patterns.len())];
+                    let candidate = match gen.try_generate(&mut rng) {
+                        Ok(x) => x,
+                        Err(e) => ErrorKind::Other.exit(e),
+                    };
+                    if taken.insert(candidate.phonemes().collect::<Vec<_>>()) {
+                        word = Some(candidate);
+                        break;
+                    }
+                }
+                let Some(word) = word else {
+                    ErrorKind::Other.exit(format_args!(
+                        "the patterns ran out of new words after {} of the {} concepts",
+                        lexicon.len(),
+                        SWADESH.lines().count()
+                    ));
+                };
+                lexicon.push((word, concept));
+            }
+
+            if let Some(path) = &cmd.output {
+                if let Err(e) = open_output(path, false) {
+                    ErrorKind::Output.exit(e);
+                }
+            }
+            // Not every reader of lexicons skips comments, so the seed goes to stderr instead of a comment
+            eprintln!("seed {seed}");
+            for (word, concept) in lexicon.iter() {
+                output(format_args!("{word}\t{concept}"));
+            }
+            close_output(cmd.output.as_deref(), lexicon.len(), "words");
+        }
+        Command::Synthesize(cmd) => {
+            let lexicon = std::fs::read_to_string(&cmd.input)
+                .map_err(|e| anyhow!("could not read {}: {e}", cmd.input.display()))
+                .and_then(|src| export::parse_lexicon(&cmd.input.display().to_string(), &src));
+            let entries = match lexicon {
+                Ok(x) => x,
+                Err(e) => ErrorKind::Input.exit(e),
+            };
+            if cmd.concurrency == 0 {
+                ErrorKind::Usage.exit("--concurrency must be at least 1");
+            }
+            let language = match Language::load(cmd.language.as_deref()) {
+                Ok(x) => x,
+                Err(e) => ErrorKind::Input.exit(e),
+            };
+            let romanization = cmd.romanization.or(language.romanization);
+            let words: Vec<_> = entries.iter().map(|entry| entry.word.clone()).collect();
+            let speech = speech_backend().await;
+            let count = match export::synthesize_words(
+                &words,
+                &cmd.out_dir,
+                &speech,
+                cmd.concurrency,
+            )
+            .await
+            {
+                Ok(count) => count,
+                Err(e) => ErrorKind::Speech.exit(e),
+            };
+
+            if let Err(e) = export::write_manifest(
+                &cmd.out_dir,
+                &entries,
+                romanization.as_ref(),
+                &voice_name(),
+                speech.format(),
+                cmd.manifest,
+            ) {
+                ErrorKind::Output.exit(e);
+            }
+            eprintln!(
+                "wrote {count} sounds and a manifest of {} words to {}",
+                entries.len(),
+                cmd.out_dir.display()
+            );
+        }
+        Command::PickInventory(cmd) => {
+            let file = match cmd.language.exists() {
+                true => std::fs::read_to_string(&cmd.language)
+                    .map_err(|e| anyhow!("could not read {}: {e}", cmd.language.display()))
+                    .and_then(|src| {
+                        toml::from_str::<LanguageFile>(&src)
+                            .map_err(|e| anyhow!("{}: {e}", cmd.language.display()))
+                    }),
+                false => Ok(LanguageFile::default()),
+            };
+            let language = file.and_then(|file| {
+                Language::load(cmd.language.exists().then_some(&*cmd.language)).map(|x| (file, x))
+            });
+            let (mut file, language) = match language {
+                Ok(x) => x,
+                Err(e) => ErrorKind::Input.exit(e),
+            };
+            // Only the phonemes the file lists are picked, rather than every phoneme like generation would use
+            let inventory = phone::Inventory::new(
+                language.consonants.unwrap_or_default(),
+                language.vowels.unwrap_or_default(),
+                language.non_pulmonic.unwrap_or_default(),
+            );
+            let picked = match picker::run(&inventory, SpeakerBox::new().await).await {
+                Ok(Some(x)) => x,
+                Ok(None) => ErrorKind::Other.exit_quietly(),
+                Err(e) => ErrorKind::Other.exit(e),
+            };
+
+            file.consonants = Some(picked.consonants().iter().join(""));
+            file.vowels = Some(picked.vowels().iter().join(""));
+            file.non_pulmonic =
+                Some(picked.non_pulmonic_consonants().iter().join("")).filter(|x| !x.is_empty());
+            let written = toml::to_string(&file)
+                .map_err(anyhow::Error::from)
+                .and_then(|src| Ok(std::fs::write(&cmd.language, src)?));
+            if let Err(e) = written {
+                ErrorKind::Output.exit(format_args!("{}: {e}", cmd.language.display()));
+            }
+            eprintln!(
+                "wrote {} consonants and {} vowels to {}",
+                picked.consonants().len() + picked.non_pulmonic_consonants().len(),
+                picked.vowels().len(),
+                cmd.language.display()
+            );
+        }
+        Command::PhonemeInfo(cmd) => {
+            let phoneme = cmd.phoneme;
+            output(format_args!(
+                "{}: {}",
+                bracket(phoneme),
+                phoneme_name(phoneme)
+            ));
+            let features = [
+                gen::Feature::Sonorant,
+                gen::Feature::Obstruent,
+                gen::Feature::Labial,
+                gen::Feature::Coronal,
+                gen::Feature::Dorsal,
+                gen::Feature::Guttural,
+            ]
+            .into_iter()
+            .filter(|feature| feature.has(phoneme))
+            .map(|feature| feature.to_string());
+            let kind = match phoneme {
+                phone::Phoneme::Vowel(_) => "vowel",
+                _ => "consonant",
+            };
+            let voicing = if phoneme.is_voiced() {
+                "voiced"
+            } else {
+                "voiceless"
+            };
+            output(format_args!(
+                "features: {}",
+                std::iter::once(kind.to_string())
+                    .chain(features)
+                    .chain(std::iter::once(voicing.to_string()))
+                    .join(", ")
+            ));
+            output(format_args!("sonority: {} of 6", phoneme.sonority()));
+            match gen::frequency(phoneme) {
+                Some(frequency) => output(format_args!(
+                    "frequency: in about {frequency} of 100 languages"
+                )),
+                None => output(format_args!("frequency: rare")),
+            }
+            let examples = EXAMPLES.lines().find_map(|line| {
+                line.split_once('\t')
+                    .filter(|(code, _)| code.parse::<phone::Phoneme>().ok() == Some(phoneme))
+                    .map(|(_, examples)| examples)
+            });
+            if let Some(examples) = examples {
+                output(format_args!("examples: {examples}"));
+            }
+
+            if cmd.speak {
+                let speaker = SpeakerBox::new()
+                    .await
+                    .unwrap_or_else(|e| ErrorKind::Speech.exit(e));
+                if let Err(e) = speaker.speak(&picker::sample(phoneme)).await {
+                    ErrorKind::Speech.exit(e);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_error_caret() {
+        let inventory = phone::Inventory::with_everything();
+        let e = gen::WordGenerator::parse("[ʃ]V !", &inventory).unwrap_err();
+        assert_eq!(
+            "error: unrecognized character: '!'\n  | [ʃ]V !\n  |      ^\n",
+            parse_error_message(&e)
+        );
+
+        // The tilde takes no room of its own, so the caret is a column to the left of the number of characters
+        let e = gen::ParseError::Pattern {
+            pattern: "ʃa\u{303}!".into(),
+            position: 3,
+            source: Box::new(gen::ParseError::UnknownCharacter('!')),
+            suggestion: Some("leave it out".into()),
+        };
+        assert_eq!(
+            "error: unrecognized character: '!'\n  | ʃa\u{303}!\n  |   ^\n  = help: leave it out\n",
+            parse_error_message(&e)
+        );
+        assert_eq!(
+            "error: no input\n",
+            parse_error_message(&gen::ParseError::NoInput)
+        );
+    }
+
+    #[test]
+    fn exit_statuses() {
+        let kinds = [
+            (ErrorKind::Other, 1),
+            (ErrorKind::Usage, 2),
+            (ErrorKind::Input, 3),
+            (ErrorKind::Pattern, 4),
+            (ErrorKind::Output, 5),
+            (ErrorKind::Speech, 6),
+        ];
+        for (kind, status) in kinds {
+            assert_eq!(status, kind as i32, "{kind:?}");
+            assert!(
+                EXIT_STATUS_HELP.contains(&format!("\n  {status}  ")),
+                "{kind:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn flags() {
+        let weights = parse_weights("ʃ:3, a:1").unwrap();
+        assert_eq!(
+            vec![
+                (phone::Phoneme::try_from('ʃ').unwrap(), 3),
+                (phone::Phoneme::try_from('a').unwrap(), 1)
+            ],
+            weights
+        );
+        for bad in ["ʃa:3", "ʃ", "ʃ:x", "ʃ:300"] {
+            assert!(parse_weights(bad).is_err(), "{bad}");
+        }
+
+        assert_eq!("slow", parse_rate("slow").unwrap());
+        assert_eq!("50%", parse_rate("50%").unwrap());
+        assert!(parse_rate("10%").is_err());
+        assert_eq!("+10%", parse_pitch("+10%").unwrap());
+        assert!(parse_pitch("10%").is_err());
+        assert_eq!("-3dB", parse_volume("-3dB").unwrap());
+        assert!(parse_volume("3dB").is_err());
+
+        assert!(length_filters((None, None), (None, None))
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            1,
+            length_filters((Some(3), None), (None, None)).unwrap().len()
+        );
+        assert!(length_filters((None, None), (Some(3), Some(2))).is_err());
+    }
+
+    #[test]
+    fn text() {
+        assert_eq!("█", unicode_bar(4, 8, 2));
+        assert_eq!("▍", unicode_bar(3, 8, 1));
+        assert_eq!("███", unicode_bar(8, 8, 3));
+        assert_eq!("", unicode_bar(0, 0, 5));
+
+        assert_eq!(
+            vec!["ka", "  ", "ta", "\t", "i"],
+            split_whitespace_kept("ka  ta\ti")
+        );
+        assert!(split_whitespace_kept("").is_empty());
+    }
+
+    #[test]
+    fn language() {
+        assert!(Language::load(None).unwrap().patterns.is_empty());
+
+        let dir = std::env::temp_dir().join(format!("conlang-language-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("good.toml");
+        std::fs::write(
+            &path,
+            concat!(
+                "consonants = \"ptk\"\nvowels = \"ai\"\n",
+                "patterns = [\"CV\"]\nfilters = [\"no-final a\"]\n",
+                "\n[weights]\np = 3\n",
+            ),
+        )
+        .unwrap();
+        let language = Language::load(Some(&path)).unwrap();
+        assert_eq!(
+            Some(vec![
+                phone::Consonant::P,
+                phone::Consonant::T,
+                phone::Consonant::K
+            ]),
+            language.consonants
+        );
+        assert_eq!(
+            Some(vec![phone::Vowel::A, phone::Vowel::I]),
+            language.vowels
+        );
+        assert_eq!(vec!["CV".to_string()], language.patterns);
+        assert_eq!(1, language.filters.len());
+        assert_eq!(
+            vec![(phone::Phoneme::try_from('p').unwrap(), 3)],
+            language.weights
+        );
+
+        // Errors say which file they are in
+        let path = dir.join("bad.toml");
+        std::fs::write(&path, "vowels = \"a!\"\n").unwrap();
+        let e = Language::load(Some(&path)).unwrap_err();
+        assert!(e.to_string().contains("bad.toml"), "{e}");
+        std::fs::remove_dir_all(dir).unwrap();
+
+        // Every preset is a valid language, as `show-inventory --preset` reads it
+        for preset in preset::PRESETS {
+            let language = Language::parse(preset.src, preset.name).unwrap();
+            assert!(language.vowels.is_some(), "{}", preset.name);
+        }
+    }
+
+    #[test]
+    fn syllable_generator_filters() {
+        let inventory = inventory(
+            &Some(vec![phone::Consonant::P, phone::Consonant::T]),
+            &Some(vec![phone::Vowel::A, phone::Vowel::I]),
+            &None,
+        );
+        let filters = vec!["no-final a".parse().unwrap()];
+        let generator = syllable_generator("CV", inventory, &[], &filters).unwrap();
+        let a = phone::Phoneme::try_from('a').unwrap();
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        for _ in 0..100 {
+            let word = generator.generate(&mut rng);
+            assert_ne!(Some(a), word.phonemes().last(), "{word}");
+        }
+    }
 }
@@ -0,0 +1,453 @@
+//! # Orthography
+//!
+//! Orthography is how a language is written. IPA is precise, but most people do not want to read or type /ʃ/ when
+//! "sh" is right there on the keyboard. This module maps between phonemes and the graphemes used to spell them.
+
+use crate::phone;
+use std::{fmt, str::FromStr};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ParseError {
+    #[error("no input")]
+    NoInput,
+    #[error("invalid mapping \"{0}\": expected `grapheme=phoneme`")]
+    InvalidMapping(String),
+    #[error("unknown phoneme '{0}'")]
+    UnknownPhoneme(char),
+    #[error("could not spell \"{0}\" with the romanization")]
+    Unspellable(String),
+}
+
+/// A mapping from graphemes like "sh" to the phonemes they spell like /ʃ/.
+///
+/// A romanization can be parsed from a list of `grapheme=phoneme` pairs separated by whitespace or commas, like
+/// `sh=ʃ ng=ŋ y=j`. Phonemes without an explicit grapheme are spelled with their IPA symbol.
+#[derive(Clone, Default, PartialEq)]
+pub struct Romanization {
+    entries: Vec<(String, phone::Phoneme)>,
+}
+
+impl Romanization {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Spell the `phoneme` with the `grapheme`, replacing any previous spelling for it.
+    pub fn insert(&mut self, grapheme: impl Into<String>, phoneme: phone::Phoneme) {
+        self.entries.retain(|(_, ph)| *ph != phoneme);
+        self.entries.push((grapheme.into(), phoneme));
+    }
+
+    pub fn entries(&self) -> &[(String, phone::Phoneme)] {
+        &self.entries
+    }
+
+    /// Get the grapheme used to spell the `phoneme`, if there is one.
+    pub fn grapheme(&self, phoneme: phone::Phoneme) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(_, ph)| *ph == phoneme)
+            .map(|(g, _)| g.as_str())
+    }
+
+    /// Write the `phonemes` with this romanization.
+    pub fn romanize(&self, phonemes: &[phone::Phoneme]) -> String {
+        let mut out = String::with_capacity(phonemes.len());
+        for ph in phonemes {
+            match self.grapheme(*ph) {
+                Some(g) => out.push_str(g),
+                None => out.push(ph.code()),
+            }
+        }
+        out
+    }
+
+    /// Read the phonemes spelled by `src`. Graphemes are matched greedily, longest first, so with `s=s h=h sh=ʃ`,
+    /// "shh" reads as /ʃh/. Characters which are not graphemes are read as IPA.
+    pub fn read(&self, src: &str) -> Result<Vec<phone::Phoneme>, ParseError> {
+        let mut out = Vec::with_capacity(src.len());
+        let mut rem = src;
+        while let Some(c) = rem.chars().next() {
+            let grapheme = self
+                .entries
+                .iter()
+                .filter(|(g, _)| !g.is_empty() && rem.starts_with(g.as_str()))
+                .max_by_key(|(g, _)| g.len());
+            if let Some((g, ph)) = grapheme {
+                out.push(*ph);
+                rem = &rem[g.len()..];
+            } else if let Ok(ph) = phone::Phoneme::try_from(c) {
+                out.push(ph);
+                rem = &rem[c.len_utf8()..];
+            } else {
+                return Err(ParseError::Unspellable(src.into()));
+            }
+        }
+        Ok(out)
+    }
+}
+
+impl FromStr for Romanization {
+    type Err = ParseError;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        let mut out = Self::new();
+        for pair in src.split(|c: char| c.is_whitespace() || c == ',') {
+            if pair.is_empty() {
+                continue;
+            }
+            let Some((grapheme, ipa)) = pair.split_once('=') else {
+                return Err(ParseError::InvalidMapping(pair.into()));
+            };
+            let mut chars = ipa.chars();
+            let phoneme = match (chars.next(), chars.next()) {
+                (Some(c), None) => {
+                    phone::Phoneme::try_from(c).map_err(|_| ParseError::UnknownPhoneme(c))?
+                }
+                _ => return Err(ParseError::InvalidMapping(pair.into())),
+            };
+            if grapheme.is_empty() {
+                return Err(ParseError::InvalidMapping(pair.into()));
+            }
+            out.insert(grapheme, phoneme);
+        }
+
+        if out.entries.is_empty() {
+            Err(ParseError::NoInput)
+        } else {
+            Ok(out)
+        }
+    }
+}
+
+impl fmt::Display for Romanization {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (idx, (grapheme, phoneme)) in self.entries.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{grapheme}={phoneme}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Romanization {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Romanization({self})")
+    }
+}
+
+/// Real words which made-up words should not be spelled like, such as profanity. A generated name which happens to
+/// spell something crude in English is hard to take seriously.
+///
+/// A wordlist is parsed from one entry per line, where blank lines and lines starting with `#` are skipped. A plain
+/// entry like `dog` matches spellings which are the same apart from case, or for entries of
+/// [`CLOSE_LENGTH`](Self::CLOSE_LENGTH) letters or more, one letter off. An entry between asterisks like `*shit*`
+/// matches anywhere in a spelling.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Wordlist {
+    /// Entries to match whole words.
+    words: Vec<Vec<char>>,
+    /// Entries to match anywhere in a word.
+    fragments: Vec<String>,
+}
+
+impl Wordlist {
+    /// Entries at least this long also match spellings one letter off, since short words are too easy to land near.
+    pub const CLOSE_LENGTH: usize = 5;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A small list of English profanity, insults, and common words, bundled with this crate.
+    pub fn english() -> Self {
+        include_str!("data/english.txt")
+            .parse()
+            .expect("the bundled wordlist is valid")
+    }
+
+    /// Add an entry in the syntax of the list, like `dog` or `*shit*`.
+    pub fn with_entry(mut self, entry: &str) -> Self {
+        let entry = entry.trim().to_lowercase();
+        match entry.strip_prefix('*').and_then(|x| x.strip_suffix('*')) {
+            Some(fragment) if !fragment.is_empty() => self.fragments.push(fragment.into()),
+            _ => self.words.push(entry.chars().collect()),
+        }
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len() + self.fragments.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Find the entry which the `spelling` matches or closely resembles, if there is one.
+    pub fn resemblance(&self, spelling: &str) -> Option<String> {
+        let spelling = spelling.to_lowercase();
+        if let Some(fragment) = self
+            .fragments
+            .iter()
+            .find(|x| spelling.contains(x.as_str()))
+        {
+            return Some(format!("*{fragment}*"));
+        }
+        let chars: Vec<char> = spelling.chars().collect();
+        self.words
+            .iter()
+            .find(|word| {
+                let max = usize::from(word.len() >= Self::CLOSE_LENGTH);
+                crate::gen::within_distance(word, &chars, max)
+            })
+            .map(|word| word.iter().collect())
+    }
+
+    /// Check if the `spelling` matches or closely resembles an entry.
+    pub fn resembles(&self, spelling: &str) -> bool {
+        self.resemblance(spelling).is_some()
+    }
+}
+
+impl FromStr for Wordlist {
+    type Err = ParseError;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        let out = src
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .fold(Self::new(), Self::with_entry);
+        if out.is_empty() {
+            Err(ParseError::NoInput)
+        } else {
+            Ok(out)
+        }
+    }
+}
+
+/// Pairs of IPA and [X-SAMPA](https://en.wikipedia.org/wiki/X-SAMPA), the ASCII transcription used by tools like
+/// eSpeak. /ⱱ/ has no X-SAMPA symbol, so it is left as IPA.
+const XSAMPA: &[(char, &str)] = &[
+    // Plosives
+    ('p', "p"),
+    ('b', "b"),
+    ('t', "t"),
+    ('d', "d"),
+    ('ʈ', "t`"),
+    ('ɖ', "d`"),
+    ('c', "c"),
+    ('ɟ', "J\\"),
+    ('k', "k"),
+    ('g', "g"),
+    ('q', "q"),
+    ('ɢ', "G\\"),
+    ('ʔ', "?"),
+    // Nasals
+    ('m', "m"),
+    ('ɱ', "F"),
+    ('n', "n"),
+    ('ɳ', "n`"),
+    ('ɲ', "J"),
+    ('ŋ', "N"),
+    ('ɴ', "N\\"),
+    // Trills, taps, and flaps
+    ('ʙ', "B\\"),
+    ('r', "r"),
+    ('ʀ', "R\\"),
+    ('ɾ', "4"),
+    ('ɽ', "r`"),
+    // Fricatives
+    ('ɸ', "p\\"),
+    ('β', "B"),
+    ('f', "f"),
+    ('v', "v"),
+    ('θ', "T"),
+    ('ð', "D"),
+    ('s', "s"),
+    ('z', "z"),
+    ('ʃ', "S"),
+    ('ʒ', "Z"),
+    ('ʂ', "s`"),
+    ('ʐ', "z`"),
+    ('ç', "C"),
+    ('ʝ', "j\\"),
+    ('x', "x"),
+    ('ɣ', "G"),
+    ('χ', "X"),
+    ('ʁ', "R"),
+    ('ħ', "X\\"),
+    ('ʕ', "?\\"),
+    ('h', "h"),
+    ('ɦ', "h\\"),
+    ('ɬ', "K"),
+    ('ɮ', "K\\"),
+    // Approximants
+    ('ʋ', "P"),
+    ('ɹ', "r\\"),
+    ('ɻ', "r\\`"),
+    ('j', "j"),
+    ('ɰ', "M\\"),
+    ('l', "l"),
+    ('ɭ', "l`"),
+    ('ʎ', "L"),
+    ('ʟ', "L\\"),
+    // Non-pulmonic consonants
+    ('ʘ', "O\\"),
+    ('ǀ', "|\\"),
+    ('ǃ', "!\\"),
+    ('ǂ', "=\\"),
+    ('ǁ', "|\\|\\"),
+    ('ɓ', "b_<"),
+    ('ɗ', "d_<"),
+    ('ʄ', "J\\_<"),
+    ('ɠ', "g_<"),
+    ('ʛ', "G\\_<"),
+    // Vowels
+    ('i', "i"),
+    ('y', "y"),
+    ('ɨ', "1"),
+    ('ʉ', "}"),
+    ('ɯ', "M"),
+    ('u', "u"),
+    ('ɪ', "I"),
+    ('ʏ', "Y"),
+    ('ʊ', "U"),
+    ('e', "e"),
+    ('ø', "2"),
+    ('ɘ', "@\\"),
+    ('ɵ', "8"),
+    ('ɤ', "7"),
+    ('o', "o"),
+    ('ə', "@"),
+    ('ɛ', "E"),
+    ('œ', "9"),
+    ('ɜ', "3"),
+    ('ɞ', "3\\"),
+    ('ʌ', "V"),
+    ('ɔ', "O"),
+    ('æ', "{"),
+    ('ɐ', "6"),
+    ('a', "a"),
+    ('ɶ', "&"),
+    ('ɑ', "A"),
+    ('ɒ', "Q"),
+    // Suprasegmentals
+    ('ˈ', "\""),
+    ('ˌ', "%"),
+    (phone::LENGTH_MARK, ":"),
+    ('˥', "_T"),
+    ('˦', "_H"),
+    ('˧', "_M"),
+    ('˨', "_L"),
+    ('˩', "_B"),
+];
+
+/// Convert IPA to X-SAMPA. Characters without an X-SAMPA symbol, like `.` and spaces, are kept as they are.
+pub fn to_xsampa(ipa: &str) -> String {
+    let mut out = String::with_capacity(ipa.len());
+    for c in ipa.chars() {
+        match XSAMPA.iter().find(|(x, _)| *x == c) {
+            Some((_, xsampa)) => out.push_str(xsampa),
+            None => out.push(c),
+        }
+    }
+    out
+}
+
+/// Convert X-SAMPA to IPA. Symbols are matched greedily, longest first, so `t`` is /ʈ/ rather than /t/ followed by a
+/// backtick. Characters which are not part of a symbol are kept as they are.
+pub fn from_xsampa(xsampa: &str) -> String {
+    let mut out = String::with_capacity(xsampa.len());
+    let mut rem = xsampa;
+    while let Some(c) = rem.chars().next() {
+        let symbol = XSAMPA
+            .iter()
+            .filter(|(_, x)| rem.starts_with(x))
+            .max_by_key(|(_, x)| x.len());
+        match symbol {
+            Some((ipa, x)) => {
+                out.push(*ipa);
+                rem = &rem[x.len()..];
+            }
+            None => {
+                out.push(c);
+                rem = &rem[c.len_utf8()..];
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let rom = Romanization::from_str("sh=ʃ, ng=ŋ y=j").unwrap();
+        let phonemes = rom.read("shayang").unwrap();
+        let ipa: String = phonemes.iter().map(phone::Phoneme::code).collect();
+        assert_eq!("ʃajaŋ", ipa);
+        assert_eq!("shayang", rom.romanize(&phonemes));
+
+        // Greedy matching prefers "sh" over "s"
+        let rom = Romanization::from_str("s=s h=h sh=ʃ").unwrap();
+        let ipa: String = rom
+            .read("shh")
+            .unwrap()
+            .iter()
+            .map(phone::Phoneme::code)
+            .collect();
+        assert_eq!("ʃh", ipa);
+
+        assert!(rom.read("s!").is_err());
+        assert!(Romanization::from_str("sh").is_err());
+        assert!(Romanization::from_str("sh=ʃʃ").is_err());
+    }
+
+    #[test]
+    fn xsampa() {
+        assert_eq!("\"tS{.r\\i", to_xsampa("ˈtʃæ.ɹi"));
+        assert_eq!("ˈtʃæ.ɹi", from_xsampa("\"tS{.r\\i"));
+        assert_eq!("ɻ ǁ ǀ ʄ", from_xsampa("r\\` |\\|\\ |\\ J\\_<"));
+
+        // Every phoneme survives a round trip
+        for ph in phone::Consonant::all()
+            .iter()
+            .map(|&x| phone::Phoneme::from(x))
+            .chain(phone::Vowel::all().iter().map(|&x| phone::Phoneme::from(x)))
+            .chain(
+                phone::NonPulmonicConsonant::all()
+                    .iter()
+                    .map(|&x| phone::Phoneme::from(x)),
+            )
+        {
+            let ipa = ph.code().to_string();
+            assert_eq!(ipa, from_xsampa(&to_xsampa(&ipa)));
+        }
+    }
+    #[test]
+    fn wordlist() {
+        let wordlist: Wordlist = "# comment\ndog\nhouse\n*shit*\n".parse().unwrap();
+        assert_eq!(3, wordlist.len());
+        assert_eq!(Some("dog".into()), wordlist.resemblance("Dog"));
+        // Short entries only match exactly, longer ones also one letter off
+        assert!(!wordlist.resembles("dag"));
+        assert_eq!(Some("house".into()), wordlist.resemblance("hause"));
+        assert!(!wordlist.resembles("hauso"));
+        assert_eq!(Some("*shit*".into()), wordlist.resemblance("Ashitaka"));
+        assert!(matches!(
+            "\n# nothing\n".parse::<Wordlist>(),
+            Err(ParseError::NoInput)
+        ));
+
+        let english = Wordlist::english();
+        assert!(english.resembles("the"));
+        assert!(!english.resembles("talamir"));
+    }
+}
@@ -1,14 +1,17 @@
 //! # Phonetics
 //!
 //! Phonetics form the basis of spoken language. This module contains `Phoneme`s as the basic building block of the
-//! language and `Syllable`s to tie them together.
+//! language, `Syllable`s to tie them together, and `Word`s to tie those together.
 
 use smallvec::{SmallVec, smallvec};
 use std::{
     error::Error,
     fmt::{self, Write},
+    hash::{Hash, Hasher},
     num::NonZeroU8,
+    ops::Deref,
     str::FromStr,
+    sync::Arc,
 };
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -365,6 +368,45 @@ impl Consonant {
             Self::LCap => Place::Velar,
         }
     }
+
+    /// The position of this consonant on the [sonority hierarchy](https://en.wikipedia.org/wiki/Sonority_hierarchy).
+    /// See [`Phoneme::sonority`].
+    pub fn sonority(&self) -> u8 {
+        match self.manner() {
+            Manner::Plosive => 1,
+            Manner::Fricative | Manner::LateralFricative => 2,
+            Manner::Nasal => 3,
+            Manner::Trill | Manner::Tap | Manner::LateralApproximant => 4,
+            Manner::Approximant => 5,
+        }
+    }
+
+    /// Check if the vocal folds vibrate for this consonant, like /b/ and /z/ but not /p/ and /s/. Sonorants like /m/ and
+    /// /l/ are always voiced.
+    pub fn is_voiced(&self) -> bool {
+        !matches!(
+            self,
+            Self::P
+                | Self::T
+                | Self::TRetroflex
+                | Self::C
+                | Self::K
+                | Self::Q
+                | Self::GlottalStop
+                | Self::Phi
+                | Self::F
+                | Self::Theta
+                | Self::S
+                | Self::Esh
+                | Self::Sh
+                | Self::Ch
+                | Self::X
+                | Self::Xh
+                | Self::HBar
+                | Self::H
+                | Self::LBelt
+        )
+    }
 }
 
 impl TryFrom<char> for Consonant {
@@ -586,6 +628,24 @@ impl Place {
     }
 }
 
+impl fmt::Display for Place {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Bilabial => "bilabial",
+            Self::Labiodental => "labiodental",
+            Self::Dental => "dental",
+            Self::Alveolar => "alveolar",
+            Self::PostAlveolar => "post-alveolar",
+            Self::Retroflex => "retroflex",
+            Self::Palatal => "palatal",
+            Self::Velar => "velar",
+            Self::Uvular => "uvular",
+            Self::Pharyngeal => "pharyngeal",
+            Self::Glottal => "glottal",
+        })
+    }
+}
+
 /// The [manner of articulation](https://en.wikipedia.org/wiki/Manner_of_articulation) is the interaction of the speech
 /// organs used to make the sound. A plosive like "t" is a full stop of air, a nasal sound like "n" is made by allowing
 /// air to escape through the nose, and a lateral approximate sound like "l" allows air to escape around the sides of
@@ -615,6 +675,21 @@ impl Manner {
     }
 }
 
+impl fmt::Display for Manner {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Plosive => "plosive",
+            Self::Nasal => "nasal",
+            Self::Trill => "trill",
+            Self::Tap => "tap",
+            Self::Fricative => "fricative",
+            Self::LateralFricative => "lateral fricative",
+            Self::Approximant => "approximant",
+            Self::LateralApproximant => "lateral approximant",
+        })
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Vowel {
     I,                     // <V- i
@@ -783,6 +858,26 @@ impl Vowel {
         };
         Frontness::new(value)
     }
+
+    /// Check if the lips are rounded for this vowel, like /y/ and /u/ but not /i/ and /ɯ/.
+    pub fn is_rounded(&self) -> bool {
+        matches!(
+            self,
+            Self::Y
+                | Self::UBar
+                | Self::U
+                | Self::YCap
+                | Self::OmegaFlip
+                | Self::OCross
+                | Self::OBar
+                | Self::O
+                | Self::Oe
+                | Self::EpsilonClosedReversed
+                | Self::OOpen
+                | Self::OeSmall
+                | Self::AScriptFlip
+        )
+    }
 }
 
 impl fmt::Display for Vowel {
@@ -901,6 +996,16 @@ impl Frontness {
     pub fn value(&self) -> u8 {
         self.value.get()
     }
+
+    /// The column of the vowel chart this frontness falls in. Like /a/ counting as front, the near-open /ɐ/ at 3 counts
+    /// as back.
+    pub fn backness(&self) -> Backness {
+        match self.value() {
+            6.. => Backness::Front,
+            4..=5 => Backness::Central,
+            _ => Backness::Back,
+        }
+    }
 }
 
 impl fmt::Display for Frontness {
@@ -909,6 +1014,25 @@ impl fmt::Display for Frontness {
     }
 }
 
+/// The columns of the vowel chart, which group [`Frontness`] values for vowel classes like `Vf` and for front-back
+/// harmony.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Backness {
+    Front,
+    Central,
+    Back,
+}
+
+impl fmt::Display for Backness {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Front => "front",
+            Self::Central => "central",
+            Self::Back => "back",
+        })
+    }
+}
+
 /// Represents one of the phoneme types.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Phoneme {
@@ -943,6 +1067,79 @@ impl Phoneme {
             Self::NonPulmonicConsonant(c) => c.code(),
         }
     }
+
+    /// The position of this phoneme on the [sonority hierarchy](https://en.wikipedia.org/wiki/Sonority_hierarchy), from
+    /// least to most sonorous:
+    ///
+    /// | Sonority | Class                 | Examples  |
+    /// |:--------:|:----------------------|:---------:|
+    /// | 1        | Plosives, clicks      | p t ʔ ǀ   |
+    /// | 2        | Fricatives            | f s h ɬ   |
+    /// | 3        | Nasals                | m n ŋ     |
+    /// | 4        | Liquids               | l r ɾ     |
+    /// | 5        | Glides                | j ɰ ɹ     |
+    /// | 6        | Vowels                | i a u     |
+    ///
+    /// Syllables tend to rise in sonority toward the nucleus and fall after it, so /pla/ is a much more natural syllable
+    /// than /lpa/.
+    pub fn sonority(&self) -> u8 {
+        match self {
+            Self::Consonant(c) => c.sonority(),
+            Self::Vowel(_) => 6,
+            Self::NonPulmonicConsonant(_) => 1,
+        }
+    }
+
+    /// Check if the vocal folds vibrate for this phoneme. Vowels and implosives like /ɓ/ are voiced, while clicks like
+    /// /ǀ/ are not.
+    pub fn is_voiced(&self) -> bool {
+        match self {
+            Self::Consonant(c) => c.is_voiced(),
+            Self::Vowel(_) => true,
+            Self::NonPulmonicConsonant(c) => !matches!(
+                c,
+                NonPulmonicConsonant::BilabialClick
+                    | NonPulmonicConsonant::DentalClick
+                    | NonPulmonicConsonant::Postalveoalar
+                    | NonPulmonicConsonant::Palatoalveolar
+                    | NonPulmonicConsonant::AlveolarLateral
+            ),
+        }
+    }
+
+    /// How different this phoneme sounds from `other`, from 0 for the same phoneme to 1 for a consonant and a vowel.
+    /// Consonants are compared by place and manner of articulation, so /t/ is closest to /d/ with the same place and
+    /// manner, then to other plosives like /k/ and /q/ going back in the mouth, and further from /s/. Vowels are compared
+    /// by height, frontness, and rounding.
+    pub fn distance(&self, other: &Phoneme) -> f64 {
+        if self == other {
+            return 0.0;
+        }
+        match (self, other) {
+            (Self::Consonant(a), Self::Consonant(b)) => {
+                let places = (a.place() as u8).abs_diff(b.place() as u8);
+                let place =
+                    f64::from(places) / (Place::Glottal as u8 - Place::Bilabial as u8) as f64;
+                let manner = if a.manner() == b.manner() { 0.0 } else { 1.0 };
+                0.2 + 0.4 * place + 0.4 * manner
+            }
+            (Self::Vowel(a), Self::Vowel(b)) => {
+                let height = f64::from(a.height().value().abs_diff(b.height().value())) / 8.0;
+                let frontness =
+                    f64::from(a.frontness().value().abs_diff(b.frontness().value())) / 8.0;
+                let rounding = if a.is_rounded() == b.is_rounded() {
+                    0.0
+                } else {
+                    1.0
+                };
+                // Vowels can differ in all three at once, but distinct vowels are never the same sound
+                (0.4 * height + 0.4 * frontness + 0.2 * rounding).max(0.1)
+            }
+            (Self::NonPulmonicConsonant(_), Self::NonPulmonicConsonant(_)) => 0.5,
+            (Self::Vowel(_), _) | (_, Self::Vowel(_)) => 1.0,
+            _ => 0.8,
+        }
+    }
 }
 
 impl fmt::Display for Phoneme {
@@ -962,25 +1159,188 @@ impl TryFrom<char> for Phoneme {
     }
 }
 
+impl FromStr for Phoneme {
+    type Err = ParseError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.chars().count() {
+            0 => Err(ParseError::NoInput),
+            1 => Self::try_from(value.chars().nth(0).unwrap()),
+            _ => Err(ParseError::TooManyCharacters),
+        }
+    }
+}
+
+/// How much emphasis a syllable gets when spoken.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Stress {
+    #[default]
+    Unstressed,
+    Secondary,
+    Primary,
+}
+
+impl Stress {
+    /// The IPA mark written before a syllable with this stress, if any.
+    pub fn mark(&self) -> Option<char> {
+        match self {
+            Self::Unstressed => None,
+            Self::Secondary => Some('ˌ'),
+            Self::Primary => Some('ˈ'),
+        }
+    }
+}
+
+/// A tone contour written in [Chao tone letters](https://en.wikipedia.org/wiki/Tone_letter), with levels from 1
+/// (lowest, ˩) to 5 (highest, ˥). A level tone like the high ˥ has one level; a contour like the high-falling ˥˩ or the
+/// dipping ˨˩˦ has two or three.
+///
+/// Tones can be parsed from tone letters or Chao numerals, so `˥˩` and `51` are the same tone.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Tone {
+    levels: [u8; 3],
+    len: u8,
+}
+
+impl Tone {
+    /// Create a tone from its levels. Returns `None` if there are no levels, more than 3, or a level outside 1 to 5.
+    pub fn new(levels: &[u8]) -> Option<Self> {
+        if levels.is_empty() || levels.len() > 3 || levels.iter().any(|x| !(1..=5).contains(x)) {
+            return None;
+        }
+        let mut out = Self {
+            levels: [0; 3],
+            len: levels.len() as u8,
+        };
+        out.levels[..levels.len()].copy_from_slice(levels);
+        Some(out)
+    }
+
+    pub fn levels(&self) -> &[u8] {
+        &self.levels[..usize::from(self.len)]
+    }
+
+    /// Get the level of a tone letter like ˥.
+    pub fn level_of(c: char) -> Option<u8> {
+        match c {
+            '˥' => Some(5),
+            '˦' => Some(4),
+            '˧' => Some(3),
+            '˨' => Some(2),
+            '˩' => Some(1),
+            _ => None,
+        }
+    }
+
+    fn letter(level: u8) -> char {
+        ['˩', '˨', '˧', '˦', '˥'][usize::from(level - 1)]
+    }
+}
+
+impl FromStr for Tone {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut levels = SmallVec::<[u8; 3]>::new();
+        for c in s.trim().chars() {
+            let level = Self::level_of(c)
+                .or_else(|| c.to_digit(10).map(|x| x as u8))
+                .filter(|x| (1..=5).contains(x))
+                .ok_or(ParseError::UnknownCharacter(c))?;
+            levels.push(level);
+        }
+        match levels.len() {
+            0 => Err(ParseError::NoInput),
+            1..=3 => Ok(Self::new(&levels).unwrap()),
+            _ => Err(ParseError::TooManyCharacters),
+        }
+    }
+}
+
+impl fmt::Display for Tone {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for level in self.levels() {
+            f.write_char(Self::letter(*level))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Tone {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Tone({self})")
+    }
+}
+
+/// The IPA length mark, written after a long phoneme like /tː/.
+pub const LENGTH_MARK: char = 'ː';
+
 #[derive(Clone)]
 pub struct Syllable {
     inner: smallvec::SmallVec<[Phoneme; 8]>,
+    stress: Stress,
+    tone: Option<Tone>,
 }
 
 impl Syllable {
     pub fn new(seq: &[Phoneme]) -> Self {
         let inner = smallvec::SmallVec::from(seq);
-        Self { inner }
+        Self {
+            inner,
+            stress: Stress::Unstressed,
+            tone: None,
+        }
     }
 
     pub fn parts(&self) -> &[Phoneme] {
         self.inner.as_slice()
     }
+
+    pub fn stress(&self) -> Stress {
+        self.stress
+    }
+
+    pub fn set_stress(&mut self, stress: Stress) {
+        self.stress = stress;
+    }
+
+    pub fn with_stress(mut self, stress: Stress) -> Self {
+        self.stress = stress;
+        self
+    }
+
+    pub fn tone(&self) -> Option<Tone> {
+        self.tone
+    }
+
+    pub fn set_tone(&mut self, tone: Option<Tone>) {
+        self.tone = tone;
+    }
+
+    pub fn with_tone(mut self, tone: Tone) -> Self {
+        self.tone = Some(tone);
+        self
+    }
+
+    /// Check if this is a [heavy syllable](https://en.wikipedia.org/wiki/Syllable_weight): one with a coda (/pat/) or
+    /// a nucleus of more than one vowel (/pai/). Many languages prefer to put stress on heavy syllables.
+    pub fn is_heavy(&self) -> bool {
+        let is_vowel = |ph: &Phoneme| matches!(ph, Phoneme::Vowel(_));
+        match (
+            self.parts().iter().position(is_vowel),
+            self.parts().iter().rposition(is_vowel),
+        ) {
+            (Some(first), Some(last)) => first != last || last + 1 < self.parts().len(),
+            _ => false,
+        }
+    }
 }
 
 impl PartialEq for Syllable {
     fn eq(&self, other: &Self) -> bool {
-        self.parts().len() == other.parts().len()
+        self.stress == other.stress
+            && self.tone == other.tone
+            && self.parts().len() == other.parts().len()
             && self
                 .parts()
                 .iter()
@@ -991,10 +1351,29 @@ impl PartialEq for Syllable {
 
 impl Eq for Syllable {}
 
+impl Hash for Syllable {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.parts().hash(state);
+        self.stress.hash(state);
+        self.tone.hash(state);
+    }
+}
+
 impl fmt::Display for Syllable {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        for p in self.parts() {
+        if let Some(mark) = self.stress.mark() {
+            f.write_char(mark)?;
+        }
+        // A doubled phoneme is long, so it is written once with a length mark
+        let mut parts = self.parts().iter().peekable();
+        while let Some(p) = parts.next() {
             write!(f, "{p}")?;
+            if parts.next_if_eq(&p).is_some() {
+                f.write_char(LENGTH_MARK)?;
+            }
+        }
+        if let Some(tone) = self.tone {
+            write!(f, "{tone}")?;
         }
         Ok(())
     }
@@ -1010,11 +1389,165 @@ impl FromStr for Syllable {
     type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (stress, s) = if let Some(rest) = s.strip_prefix('ˈ') {
+            (Stress::Primary, rest)
+        } else if let Some(rest) = s.strip_prefix('ˌ') {
+            (Stress::Secondary, rest)
+        } else {
+            (Stress::Unstressed, s)
+        };
+
+        let (s, tone) = match s.find(|c| Tone::level_of(c).is_some()) {
+            Some(idx) => (&s[..idx], Some(s[idx..].parse()?)),
+            None => (s, None),
+        };
+
         let mut ps = Vec::with_capacity(s.len() * 2);
         for c in s.chars() {
-            ps.push(Phoneme::try_from(c)?);
+            match (c, ps.last()) {
+                (LENGTH_MARK, Some(prev)) => ps.push(*prev),
+                _ => ps.push(Phoneme::try_from(c)?),
+            }
+        }
+        let mut out = Self::new(&ps).with_stress(stress);
+        out.set_tone(tone);
+        Ok(out)
+    }
+}
+
+/// A sequence of syllables. A word dereferences to its syllables, so it can be used anywhere a `&[Syllable]` is
+/// expected.
+///
+/// Generated words remember the pattern which produced each of their syllables, like `CVC`. The patterns are not part
+/// of the word's identity: two words with the same syllables are equal, no matter where they came from.
+#[derive(Clone, Default)]
+pub struct Word {
+    syllables: SmallVec<[Syllable; 4]>,
+    patterns: SmallVec<[Option<Arc<str>>; 4]>,
+}
+
+impl Word {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn syllables(&self) -> &[Syllable] {
+        &self.syllables
+    }
+
+    pub fn syllables_mut(&mut self) -> &mut [Syllable] {
+        &mut self.syllables
+    }
+
+    pub fn push(&mut self, syllable: Syllable) {
+        self.syllables.push(syllable);
+        self.patterns.push(None);
+    }
+
+    /// Insert a syllable at `idx`, shifting the syllables after it to the right.
+    pub fn insert(&mut self, idx: usize, syllable: Syllable) {
+        self.syllables.insert(idx, syllable);
+        self.patterns.insert(idx, None);
+    }
+
+    /// Add a syllable which was generated from the `pattern`.
+    pub fn push_generated(&mut self, syllable: Syllable, pattern: Arc<str>) {
+        self.syllables.push(syllable);
+        self.patterns.push(Some(pattern));
+    }
+
+    /// Get the pattern which produced the syllable at `idx`, if it was generated.
+    pub fn pattern(&self, idx: usize) -> Option<&str> {
+        self.patterns.get(idx)?.as_deref()
+    }
+
+    /// Iterate over the phonemes of every syllable, ignoring syllable boundaries.
+    pub fn phonemes(&self) -> impl Iterator<Item = Phoneme> + '_ {
+        self.syllables
+            .iter()
+            .flat_map(|syl| syl.parts().iter().copied())
+    }
+}
+
+impl Deref for Word {
+    type Target = [Syllable];
+
+    fn deref(&self) -> &Self::Target {
+        &self.syllables
+    }
+}
+
+impl FromIterator<Syllable> for Word {
+    fn from_iter<T: IntoIterator<Item = Syllable>>(iter: T) -> Self {
+        let syllables: SmallVec<_> = iter.into_iter().collect();
+        let patterns = smallvec![None; syllables.len()];
+        Self {
+            syllables,
+            patterns,
+        }
+    }
+}
+
+impl PartialEq for Word {
+    fn eq(&self, other: &Self) -> bool {
+        self.syllables == other.syllables
+    }
+}
+
+impl Eq for Word {}
+
+impl Hash for Word {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.syllables.hash(state)
+    }
+}
+
+/// Words are written in IPA with `.` between syllables, like `ˈpa.ta`.
+impl fmt::Display for Word {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (idx, syl) in self.syllables.iter().enumerate() {
+            // A stress mark already separates the syllables
+            if idx > 0 && syl.stress() == Stress::Unstressed {
+                f.write_char('.')?;
+            }
+            write!(f, "{syl}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Word {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Word({self})")
+    }
+}
+
+impl FromStr for Word {
+    type Err = ParseError;
+
+    /// Parse a word with syllables separated by `.`, whitespace, or stress marks.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut out = Self::new();
+        let mut start = 0;
+        for (idx, c) in s.char_indices().chain(std::iter::once((s.len(), '.'))) {
+            if matches!(c, '.' | 'ˈ' | 'ˌ') || c.is_whitespace() {
+                let syl = s[start..idx].trim_matches(|c: char| c == '.' || c.is_whitespace());
+                if !syl.is_empty() {
+                    out.push(syl.parse()?);
+                }
+                start = if c == '.' || c.is_whitespace() {
+                    idx + c.len_utf8()
+                } else {
+                    idx
+                };
+            }
+        }
+
+        if out.is_empty() {
+            Err(ParseError::NoInput)
+        } else {
+            Ok(out)
         }
-        Ok(Self::new(&ps))
     }
 }
 
@@ -1023,6 +1556,10 @@ pub struct Inventory {
     consonants: Vec<Consonant>,
     vowels: Vec<Vowel>,
     non_pulmonic_consonants: Vec<NonPulmonicConsonant>,
+    /// How common each phoneme is relative to the others. Phonemes without a weight have a weight of 1.
+    weights: Vec<(Phoneme, u8)>,
+    /// Named inventories for layers of the lexicon with their own sounds, like loanwords.
+    strata: Vec<(String, Inventory)>,
 }
 
 impl Inventory {
@@ -1035,6 +1572,8 @@ impl Inventory {
             consonants: consonants.into(),
             vowels: vowels.into(),
             non_pulmonic_consonants: non_pulmonic_consonants.into(),
+            weights: Vec::new(),
+            strata: Vec::new(),
         }
     }
 
@@ -1053,6 +1592,65 @@ impl Inventory {
     pub fn non_pulmonic_consonants(&self) -> &[NonPulmonicConsonant] {
         &self.non_pulmonic_consonants
     }
+
+    /// Make the `phoneme` more or less common than the others. When classes like `C` are drawn from an inventory with
+    /// weights, each phoneme is picked in proportion to its weight; phonemes without a weight have a weight of 1.
+    pub fn with_weight(mut self, phoneme: impl Into<Phoneme>, weight: u8) -> Self {
+        let phoneme = phoneme.into();
+        self.weights.retain(|(ph, _)| *ph != phoneme);
+        self.weights.push((phoneme, weight));
+        self
+    }
+
+    /// Remove all weights, so every phoneme is equally common.
+    pub fn without_weights(mut self) -> Self {
+        self.weights.clear();
+        self
+    }
+
+    /// Check if any phoneme has been given a weight.
+    pub fn is_weighted(&self) -> bool {
+        !self.weights.is_empty()
+    }
+
+    pub fn weight(&self, phoneme: Phoneme) -> u8 {
+        self.weights
+            .iter()
+            .find(|(ph, _)| *ph == phoneme)
+            .map_or(1, |(_, weight)| *weight)
+    }
+
+    /// Add an alternate inventory called `name`, replacing any stratum with the same name. A slot of a pattern like
+    /// `C@loan` draws from the stratum instead of this inventory, so loanwords or onomatopoeia can use sounds the rest
+    /// of the language does not. Patterns can only refer to names made of lowercase ASCII letters, digits, `-`, and
+    /// `_`.
+    pub fn with_stratum(mut self, name: impl Into<String>, stratum: Inventory) -> Self {
+        let name = name.into();
+        self.strata.retain(|(x, _)| *x != name);
+        self.strata.push((name, stratum));
+        self
+    }
+
+    pub fn stratum(&self, name: &str) -> Option<&Inventory> {
+        self.strata
+            .iter()
+            .find(|(x, _)| x == name)
+            .map(|(_, stratum)| stratum)
+    }
+
+    pub fn strata(&self) -> impl Iterator<Item = (&str, &Inventory)> {
+        self.strata
+            .iter()
+            .map(|(name, stratum)| (name.as_str(), stratum))
+    }
+
+    pub fn contains(&self, phoneme: Phoneme) -> bool {
+        match phoneme {
+            Phoneme::Consonant(x) => self.consonants.contains(&x),
+            Phoneme::Vowel(x) => self.vowels.contains(&x),
+            Phoneme::NonPulmonicConsonant(x) => self.non_pulmonic_consonants.contains(&x),
+        }
+    }
 }
 
 impl fmt::Display for Inventory {
@@ -1111,4 +1709,93 @@ mod tests {
         // There should not be any codes shared between phonic classes
         assert_eq!(vec.len(), set.len());
     }
+
+    #[test]
+    fn backness() {
+        let backness = |v: Vowel| v.frontness().backness();
+        assert_eq!(Backness::Front, backness(Vowel::I));
+        assert_eq!(Backness::Front, backness(Vowel::A));
+        assert_eq!(Backness::Central, backness(Vowel::Schwa));
+        assert_eq!(Backness::Back, backness(Vowel::AFlip));
+        assert_eq!(Backness::Back, backness(Vowel::U));
+        assert_eq!("central", Backness::Central.to_string());
+    }
+
+    #[test]
+    fn syllable_stress() {
+        let syl = Syllable::from_str("ˈpat").unwrap();
+        assert_eq!(Stress::Primary, syl.stress());
+        assert_eq!("ˈpat", syl.to_string());
+        assert_ne!(syl, Syllable::from_str("pat").unwrap());
+        assert!(syl.is_heavy());
+        assert!(Syllable::from_str("pai").unwrap().is_heavy());
+        assert!(!Syllable::from_str("pa").unwrap().is_heavy());
+    }
+
+    #[test]
+    fn word_parsing() {
+        let word = Word::from_str("ˈpa.ta ˌki").unwrap();
+        assert_eq!(3, word.len());
+        assert_eq!(Stress::Primary, word[0].stress());
+        assert_eq!(Stress::Secondary, word[2].stress());
+        assert_eq!("ˈpa.taˌki", word.to_string());
+        assert_eq!(word, Word::from_str("ˈpa.taˌki").unwrap());
+        let word = Word::from_str("pa.tak").unwrap();
+        assert_eq!(
+            "patak",
+            word.phonemes().map(|p| p.code()).collect::<String>()
+        );
+        assert_eq!(None, word.pattern(0));
+        assert!(Word::from_str(" . ").is_err());
+    }
+
+    #[test]
+    fn syllable_tone() {
+        let tone = Tone::from_str("51").unwrap();
+        assert_eq!(&[5, 1], tone.levels());
+        assert_eq!("˥˩", tone.to_string());
+        assert_eq!(tone, Tone::from_str("˥˩").unwrap());
+        assert!(Tone::from_str("6").is_err());
+        assert!(Tone::from_str("2143").is_err());
+
+        let syl = Syllable::from_str("ˈma˨˩˦").unwrap();
+        assert_eq!(Tone::new(&[2, 1, 4]), syl.tone());
+        assert_eq!("ˈma˨˩˦", syl.to_string());
+        assert_ne!(syl, Syllable::from_str("ˈma").unwrap());
+    }
+
+    #[test]
+    fn syllable_length() {
+        let syl = Syllable::from_str("matːa").unwrap();
+        assert_eq!(5, syl.parts().len());
+        assert_eq!("matːa", syl.to_string());
+        assert_eq!(syl, Syllable::from_str("matta").unwrap());
+        assert_eq!("tːt", Syllable::from_str("ttt").unwrap().to_string());
+        assert!(Syllable::from_str("ːa").is_err());
+    }
+
+    #[test]
+    fn distance() {
+        let ph = |c| Phoneme::try_from(c).unwrap();
+        assert_eq!(0.0, ph('t').distance(&ph('t')));
+        assert!(ph('t').distance(&ph('d')) < ph('t').distance(&ph('k')));
+        assert!(ph('t').distance(&ph('k')) < ph('t').distance(&ph('q')));
+        assert!(ph('t').distance(&ph('q')) < ph('t').distance(&ph('s')));
+        assert!(ph('i').distance(&ph('e')) < ph('i').distance(&ph('a')));
+        assert!(ph('i').distance(&ph('y')) > 0.0);
+        assert_eq!(1.0, ph('t').distance(&ph('a')));
+        assert_eq!(ph('a').distance(&ph('t')), ph('t').distance(&ph('a')));
+    }
+
+    #[test]
+    fn voicing() {
+        let ph = |src: &str| Phoneme::from_str(src).unwrap();
+        assert!(!ph("ʃ").is_voiced());
+        assert!(ph("ʒ").is_voiced());
+        assert!(ph("m").is_voiced());
+        assert!(ph("a").is_voiced());
+        assert!(ph("ɓ").is_voiced());
+        assert!(!ph("ǀ").is_voiced());
+        assert!(Phoneme::from_str("ʃa").is_err());
+    }
 }
@@ -0,0 +1,336 @@
+//! # Inventory picker
+//!
+//! Typing out an inventory means knowing the IPA for every sound and how to type it. The picker shows the IPA charts
+//! in the terminal instead, with every phoneme the program knows, and each one can be switched on or off and listened
+//! to before deciding. The charts are drawn on stderr, like the audition.
+
+use crate::{height_name, SpeakerBox};
+use conlang::phone::{self, Phoneme};
+use itertools::Itertools;
+use ratatui::{
+    backend::CrosstermBackend,
+    crossterm::{
+        event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+        execute, terminal,
+    },
+    layout::{Constraint, Layout},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Paragraph, Row, Table},
+    Terminal,
+};
+use std::collections::HashSet;
+
+const HELP: &str =
+    "←↑↓→ move  space: toggle  enter: speak  q: save and quit  esc: quit without saving  \
+    z: pause  r: replay  ⌫: stop  +/-: volume";
+
+/// One of the charts: consonants, vowels, or non-pulmonic consonants.
+struct Chart {
+    title: &'static str,
+    columns: Vec<String>,
+    rows: Vec<ChartRow>,
+}
+
+/// A row of a chart, like the plosives, with the phonemes of each column.
+struct ChartRow {
+    label: String,
+    cells: Vec<Vec<Phoneme>>,
+}
+
+impl ChartRow {
+    /// The phonemes of the row from left to right, with the column each is in.
+    fn phonemes(&self) -> Vec<(usize, Phoneme)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .flat_map(|(col, cell)| cell.iter().map(move |ph| (col, *ph)))
+            .collect()
+    }
+}
+
+/// Every phoneme, laid out like the IPA charts.
+fn charts() -> Vec<Chart> {
+    let places: Vec<_> = phone::Consonant::all()
+        .iter()
+        .map(phone::Consonant::place)
+        .sorted()
+        .dedup()
+        .collect();
+    let manners = phone::Consonant::all()
+        .iter()
+        .map(phone::Consonant::manner)
+        .sorted()
+        .dedup();
+    let consonants = Chart {
+        title: "consonants",
+        columns: places.iter().map(ToString::to_string).collect(),
+        rows: manners
+            .map(|manner| ChartRow {
+                label: manner.to_string(),
+                cells: places
+                    .iter()
+                    .map(|place| {
+                        phone::Consonant::all()
+                            .iter()
+                            .filter(|c| c.place() == *place && c.manner() == manner)
+                            .map(|&c| Phoneme::from(c))
+                            .collect()
+                    })
+                    .collect(),
+            })
+            .collect(),
+    };
+
+    let columns = [
+        phone::Backness::Front,
+        phone::Backness::Central,
+        phone::Backness::Back,
+    ];
+    let vowels = Chart {
+        title: "vowels",
+        columns: columns.iter().map(ToString::to_string).collect(),
+        rows: phone::Vowel::all()
+            .iter()
+            .map(phone::Vowel::height)
+            .sorted_by_key(|height| std::cmp::Reverse(*height))
+            .dedup()
+            .map(|height| ChartRow {
+                label: height_name(height),
+                // Unrounded vowels come before rounded ones, like in the IPA chart
+                cells: columns
+                    .iter()
+                    .map(|column| {
+                        phone::Vowel::all()
+                            .iter()
+                            .filter(|v| v.height() == height && v.frontness().backness() == *column)
+                            .sorted_by_key(|v| (std::cmp::Reverse(v.frontness()), v.is_rounded()))
+                            .map(|&v| Phoneme::from(v))
+                            .collect()
+                    })
+                    .collect(),
+            })
+            .collect(),
+    };
+
+    let non_pulmonic = Chart {
+        title: "non-pulmonic consonants",
+        columns: vec![String::new()],
+        rows: vec![ChartRow {
+            label: String::new(),
+            cells: vec![phone::NonPulmonicConsonant::all()
+                .iter()
+                .map(|&c| Phoneme::from(c))
+                .collect()],
+        }],
+    };
+    vec![consonants, vowels, non_pulmonic]
+}
+
+/// How a phoneme is spoken on its own. Consonants are spoken between vowels, since many of them, like stops, can not be
+/// heard alone.
+pub fn sample(phoneme: Phoneme) -> String {
+    match phoneme {
+        Phoneme::Vowel(v) => v.to_string(),
+        consonant => format!("a{consonant}a"),
+    }
+}
+
+/// Show the charts with the phonemes of the `inventory` switched on until the user quits, returning the inventory they
+/// picked, or nothing if they quit without saving. Phonemes are spoken with the `speaker`; if it could not be created,
+/// the error is shown when speaking is tried.
+pub async fn run(
+    inventory: &phone::Inventory,
+    speaker: Result<SpeakerBox, anyhow::Error>,
+) -> Result<Option<phone::Inventory>, anyhow::Error> {
+    let charts = charts();
+    let mut picked: HashSet<Phoneme> = charts
+        .iter()
+        .flat_map(|chart| chart.rows.iter().flat_map(ChartRow::phonemes))
+        .map(|(_, ph)| ph)
+        .filter(|ph| inventory.contains(*ph))
+        .collect();
+
+    terminal::enable_raw_mode()?;
+    execute!(std::io::stderr(), terminal::EnterAlternateScreen)?;
+    let result = match Terminal::new(CrosstermBackend::new(std::io::stderr())) {
+        Ok(mut term) => pick(&mut term, &charts, &mut picked, speaker.as_ref()).await,
+        Err(e) => Err(e.into()),
+    };
+    // Put the terminal back the way it was before reporting any error, so the message is readable
+    terminal::disable_raw_mode()?;
+    execute!(std::io::stderr(), terminal::LeaveAlternateScreen)?;
+
+    if !result? {
+        return Ok(None);
+    }
+    // Phonemes are listed in the order of the charts, which keeps related sounds together in the language file
+    let mut consonants = Vec::new();
+    let mut vowels = Vec::new();
+    let mut non_pulmonic = Vec::new();
+    for chart in charts.iter() {
+        for (_, phoneme) in chart.rows.iter().flat_map(ChartRow::phonemes) {
+            if !picked.contains(&phoneme) {
+                continue;
+            }
+            match phoneme {
+                Phoneme::Consonant(c) => consonants.push(c),
+                Phoneme::Vowel(v) => vowels.push(v),
+                Phoneme::NonPulmonicConsonant(c) => non_pulmonic.push(c),
+            }
+        }
+    }
+    Ok(Some(phone::Inventory::new(
+        consonants,
+        vowels,
+        non_pulmonic,
+    )))
+}
+
+/// Run the event loop, returning whether to save the picked phonemes.
+async fn pick(
+    term: &mut Terminal<CrosstermBackend<std::io::Stderr>>,
+    charts: &[Chart],
+    picked: &mut HashSet<Phoneme>,
+    speaker: Result<&SpeakerBox, &anyhow::Error>,
+) -> Result<bool, anyhow::Error> {
+    // The cursor moves over the rows of every chart as if they were one long chart
+    let rows: Vec<Vec<(usize, Phoneme)>> = charts
+        .iter()
+        .flat_map(|chart| chart.rows.iter().map(ChartRow::phonemes))
+        .collect();
+    let (mut row, mut pos) = (0, 0);
+    let mut status = String::from(HELP);
+    let mut speaking = None;
+    loop {
+        let cursor = rows[row][pos].1;
+        term.draw(|frame| {
+            let heights = charts
+                .iter()
+                .map(|chart| Constraint::Length(chart.rows.len() as u16 + 3));
+            let areas = Layout::vertical(
+                heights
+                    .chain([Constraint::Min(0), Constraint::Length(1)])
+                    .collect::<Vec<_>>(),
+            )
+            .split(frame.area());
+            for (chart, area) in charts.iter().zip(areas.iter()) {
+                let count = chart
+                    .rows
+                    .iter()
+                    .flat_map(ChartRow::phonemes)
+                    .filter(|(_, ph)| picked.contains(ph))
+                    .count();
+                let table = chart_table(chart, picked, cursor)
+                    .block(Block::bordered().title(format!(" {count} {} ", chart.title)));
+                frame.render_widget(table, *area);
+            }
+            frame.render_widget(Paragraph::new(status.as_str()), areas[areas.len() - 1]);
+        })?;
+
+        // While a word plays, wake up now and then to notice it has finished
+        if let Some(ipa) = speaking.take_if(|_| speaker.is_ok_and(|speaker| !speaker.is_playing()))
+        {
+            // Unless something else has been said since
+            if status == format!("speaking /{ipa}/") {
+                status = format!("spoke /{ipa}/");
+            }
+            continue;
+        }
+        if speaking.is_some() && !event::poll(std::time::Duration::from_millis(50))? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        status = String::from(HELP);
+        match key.code {
+            KeyCode::Left | KeyCode::Char('h') => pos = pos.saturating_sub(1),
+            KeyCode::Right | KeyCode::Char('l') => pos = (pos + 1).min(rows[row].len() - 1),
+            KeyCode::Up | KeyCode::Char('k') if row > 0 => {
+                pos = nearest(&rows[row - 1], rows[row][pos].0);
+                row -= 1;
+            }
+            KeyCode::Down | KeyCode::Char('j') if row + 1 < rows.len() => {
+                pos = nearest(&rows[row + 1], rows[row][pos].0);
+                row += 1;
+            }
+            // A picked phoneme is unpicked by removing it, and any other is picked
+            KeyCode::Char(' ') | KeyCode::Char('x') if !picked.remove(&cursor) => {
+                picked.insert(cursor);
+            }
+            KeyCode::Enter | KeyCode::Char('p') => {
+                let ipa = sample(cursor);
+                status = match speaker {
+                    Ok(speaker) => match speaker.start_speaking(&ipa).await {
+                        Ok(()) => {
+                            speaking = Some(ipa.clone());
+                            format!("speaking /{ipa}/")
+                        }
+                        Err(e) => format!("could not speak /{ipa}/: {e}"),
+                    },
+                    Err(e) => format!("speech is not available: {e}"),
+                };
+            }
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Ok(false)
+            }
+            KeyCode::Esc => return Ok(false),
+            KeyCode::Char('q') => return Ok(true),
+            code => {
+                if let Some(done) = speaker.ok().and_then(|speaker| speaker.control(code)) {
+                    status = done;
+                }
+            }
+        }
+    }
+}
+
+/// The position in `row` of the phoneme in the column closest to `column`.
+fn nearest(row: &[(usize, Phoneme)], column: usize) -> usize {
+    row.iter()
+        .position_min_by_key(|(col, _)| col.abs_diff(column))
+        .unwrap_or(0)
+}
+
+/// Draw a chart with the picked phonemes in bold, the others dimmed, and the cursor reversed.
+fn chart_table<'a>(chart: &'a Chart, picked: &HashSet<Phoneme>, cursor: Phoneme) -> Table<'a> {
+    let header = Row::new(
+        std::iter::once(String::new())
+            .chain(chart.columns.iter().cloned())
+            .collect::<Vec<_>>(),
+    )
+    .style(Style::new().add_modifier(Modifier::UNDERLINED));
+    let rows = chart.rows.iter().map(|row| {
+        let cells = row.cells.iter().map(|cell| {
+            let spans = cell.iter().map(|ph| {
+                let mut style = if picked.contains(ph) {
+                    Style::new().add_modifier(Modifier::BOLD)
+                } else {
+                    Style::new().add_modifier(Modifier::DIM)
+                };
+                if *ph == cursor {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
+                Span::styled(ph.to_string(), style)
+            });
+            Line::from(Itertools::intersperse(spans, Span::raw(" ")).collect::<Vec<_>>())
+        });
+        Row::new(std::iter::once(Line::from(row.label.clone())).chain(cells))
+    });
+    // Each column is as wide as its name or its widest cell, whichever is wider
+    let widths = std::iter::once(chart.rows.iter().map(|row| row.label.chars().count()).max())
+        .chain(chart.columns.iter().enumerate().map(|(col, name)| {
+            chart
+                .rows
+                .iter()
+                .map(|row| row.cells[col].len() * 2)
+                .chain([name.chars().count()])
+                .max()
+        }))
+        .map(|width| Constraint::Length(width.unwrap_or_default() as u16));
+    Table::new(rows, widths).header(header)
+}
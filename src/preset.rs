@@ -0,0 +1,61 @@
+//! # Presets
+//!
+//! Starting from nothing is hard, and starting from a language people already know makes it easy to hear what a change
+//! does. Presets are language files for the sound systems of a few natural languages, bundled with the program. They
+//! are simplified: the phoneme model has no affricates or doubly articulated consonants, so sounds like /tʃ/ and /w/
+//! are left out, and the patterns only give the rough shape of words. Write one out with `conlang preset show` and edit
+//! it from there.
+
+/// A language file bundled with the program.
+pub struct Preset {
+    pub name: &'static str,
+    /// The TOML source of the language file, which starts with a comment describing it.
+    pub src: &'static str,
+}
+
+impl Preset {
+    /// The first line of the file, without its `#`.
+    pub fn description(&self) -> &'static str {
+        self.src
+            .lines()
+            .next()
+            .and_then(|line| line.strip_prefix('#'))
+            .unwrap_or_default()
+            .trim()
+    }
+}
+
+/// Every preset, in alphabetical order.
+pub const PRESETS: &[Preset] = &[
+    Preset {
+        name: "arabic",
+        src: include_str!("data/presets/arabic.toml"),
+    },
+    Preset {
+        name: "english",
+        src: include_str!("data/presets/english.toml"),
+    },
+    Preset {
+        name: "finnish",
+        src: include_str!("data/presets/finnish.toml"),
+    },
+    Preset {
+        name: "hawaiian",
+        src: include_str!("data/presets/hawaiian.toml"),
+    },
+    Preset {
+        name: "japanese",
+        src: include_str!("data/presets/japanese.toml"),
+    },
+    Preset {
+        name: "spanish",
+        src: include_str!("data/presets/spanish.toml"),
+    },
+];
+
+/// Find the preset called `name`, ignoring case.
+pub fn find(name: &str) -> Option<&'static Preset> {
+    PRESETS
+        .iter()
+        .find(|preset| preset.name.eq_ignore_ascii_case(name))
+}
@@ -0,0 +1,106 @@
+//! # Quiz
+//!
+//! Making up a language is one thing; learning it is another. A quiz goes through words of a lexicon in random order,
+//! either speaking each one and asking for its IPA or showing its IPA and asking what it means, and keeps score. It
+//! reads answers a line at a time, so it works in any terminal.
+
+use crate::{export::Entry, SpeakerBox};
+use conlang::phone;
+use std::io::{BufRead, Write};
+
+/// What a quiz asks for.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Mode {
+    /// Hear each word and type its IPA. Syllable breaks and stress marks are not checked.
+    #[default]
+    Listen,
+    /// Read each word in IPA and type its gloss.
+    Meaning,
+}
+
+/// How a quiz went.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Score {
+    pub right: usize,
+    pub asked: usize,
+}
+
+/// Ask about each of the `entries` in order, reading answers from stdin. In listen mode words are spoken with the
+/// `speaker` while the answer is typed, an empty answer speaks the word again, and `+` or `-` make words louder or
+/// softer. The quiz stops early at the end of input, so only the words which were answered count towards the score.
+pub async fn run(
+    entries: &[Entry],
+    mode: Mode,
+    speaker: Option<&SpeakerBox>,
+) -> Result<Score, anyhow::Error> {
+    let mut lines = std::io::stdin().lock().lines();
+    let mut score = Score::default();
+    for (idx, entry) in entries.iter().enumerate() {
+        let progress = format!("{}/{}", idx + 1, entries.len());
+        let prompt = match mode {
+            Mode::Listen => {
+                if let Some(speaker) = speaker {
+                    speaker.start_speaking(&entry.word.to_string()).await?;
+                }
+                format!("{progress} ipa> ")
+            }
+            Mode::Meaning => format!("{progress} /{}/ means> ", entry.word),
+        };
+
+        let right = loop {
+            print!("{prompt}");
+            std::io::stdout().flush()?;
+            let Some(line) = lines.next() else {
+                println!();
+                return Ok(score);
+            };
+            let line = line?;
+            let answer = line.trim();
+            if let (Mode::Listen, Some(speaker)) = (mode, speaker) {
+                match answer {
+                    "" => {
+                        speaker.replay();
+                        continue;
+                    }
+                    "+" | "-" => {
+                        let change = if answer == "+" { 0.1 } else { -0.1 };
+                        println!("  volume {:.0}%", speaker.change_volume(change) * 100.0);
+                        continue;
+                    }
+                    _ => {}
+                }
+            }
+            if answer.is_empty() {
+                continue;
+            }
+            match mode {
+                Mode::Listen => match answer.parse::<phone::Word>() {
+                    Ok(word) => break word.phonemes().eq(entry.word.phonemes()),
+                    // A typo in the IPA is not a wrong answer, so it is asked again
+                    Err(e) => println!("  could not read \"{answer}\": {e}"),
+                },
+                Mode::Meaning => break same_meaning(answer, &entry.gloss),
+            }
+        };
+
+        score.asked += 1;
+        if right {
+            score.right += 1;
+            println!("  right");
+        } else if entry.gloss.is_empty() {
+            println!("  wrong: /{}/", entry.word);
+        } else {
+            println!("  wrong: /{}/ means {}", entry.word, entry.gloss);
+        }
+    }
+    Ok(score)
+}
+
+/// Check if the `answer` is one of the meanings in the `gloss`, which can list several separated by `,` or `;`, ignoring
+/// case.
+fn same_meaning(answer: &str, gloss: &str) -> bool {
+    let answer = answer.to_lowercase();
+    gloss
+        .split([',', ';'])
+        .any(|meaning| meaning.trim().to_lowercase() == answer)
+}
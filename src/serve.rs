@@ -0,0 +1,192 @@
+//! # Server
+//!
+//! Web front-ends and chat bots can not shell out to a command line tool for every word. `conlang serve` puts
+//! generation, syllabification, and speech behind a small HTTP API which takes query parameters and answers in JSON:
+//!
+//! - `GET /generate?pattern=CV(C)&count=10&seed=1` generates words from the `pattern`, or from the patterns of the
+//!   language the server was started with. Words are objects like those of `generate-syllables --format json`.
+//! - `GET /syllabify?word=tanka` splits a word written in IPA into syllables.
+//! - `GET /speak?ipa=ˈka.ta` speaks IPA, answering with Ogg Vorbis audio, or WAV with `--tts espeak`.
+//!
+//! A bad request is answered with a status of 400 and an object with an `error` message. Speech which fails is answered
+//! the same way with a status of 503 if it might work if asked again later, or 500 if not.
+
+use crate::Record;
+use axum::{
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use conlang::{analysis, gen, ortho, phone, speech};
+use rand::{Rng, SeedableRng};
+use std::{net::SocketAddr, sync::Arc};
+
+/// The most words one request can ask for, so a single request can not keep the server busy.
+const MAX_COUNT: usize = 1000;
+
+/// How many candidates a `pattern` given in a request may generate to find each word that passes the filters, far
+/// fewer than on the command line, so an over-filtered pattern fails quickly instead of keeping the server busy.
+const MAX_ATTEMPTS: usize = 1000;
+
+/// What every request can use: the language the server was started with, and a backend for speech.
+pub struct Server {
+    pub inventory: phone::Inventory,
+    /// The patterns of the language, compiled, with the text they were compiled from.
+    pub patterns: Vec<(String, gen::WordGenerator)>,
+    /// Filters applied to words generated from a `pattern` given in a request.
+    pub filters: Vec<gen::Filter>,
+    pub romanization: Option<ortho::Romanization>,
+    pub speech: Arc<dyn speech::TtsBackend>,
+}
+
+/// Serve the API on `address` until the process is stopped.
+pub async fn run(server: Server, address: SocketAddr) -> Result<(), anyhow::Error> {
+    let app = Router::new()
+        .route("/generate", get(generate))
+        .route("/syllabify", get(syllabify))
+        .route("/speak", get(speak))
+        .with_state(Arc::new(server));
+    let listener = tokio::net::TcpListener::bind(address).await?;
+    eprintln!("listening on http://{}", listener.local_addr()?);
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+/// A request which could not be answered, sent back with the status and the message. Errors converted with `?` are
+/// the fault of the request, so they are a 400 Bad Request.
+struct Failure(StatusCode, String);
+
+impl Failure {
+    /// The failure for speech the backend could not make, which is not the fault of the request.
+    fn speech(e: speech::SpeechError) -> Self {
+        let status = if e.is_transient() {
+            StatusCode::SERVICE_UNAVAILABLE
+        } else {
+            StatusCode::INTERNAL_SERVER_ERROR
+        };
+        Self(status, e.to_string())
+    }
+}
+
+impl IntoResponse for Failure {
+    fn into_response(self) -> Response {
+        let body = serde_json::json!({ "error": self.1 });
+        (self.0, Json(body)).into_response()
+    }
+}
+
+impl<E: Into<anyhow::Error>> From<E> for Failure {
+    fn from(e: E) -> Self {
+        Self(StatusCode::BAD_REQUEST, e.into().to_string())
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct GenerateQuery {
+    pattern: Option<String>,
+    #[serde(default = "default_count")]
+    count: usize,
+    seed: Option<u64>,
+}
+
+fn default_count() -> usize {
+    10
+}
+
+async fn generate(
+    State(server): State<Arc<Server>>,
+    Query(query): Query<GenerateQuery>,
+) -> Result<Json<serde_json::Value>, Failure> {
+    if query.count > MAX_COUNT {
+        return Err(Failure(
+            StatusCode::BAD_REQUEST,
+            format!(
+                "count {} is more than the most words a request can ask for, {MAX_COUNT}",
+                query.count
+            ),
+        ));
+    }
+    let requested = match &query.pattern {
+        Some(pattern) => {
+            let gen = gen::WordGenerator::parse(pattern, &server.inventory)?;
+            let gen = server
+                .filters
+                .iter()
+                .cloned()
+                .fold(gen, gen::WordGenerator::with_filter)
+                .with_max_attempts(MAX_ATTEMPTS);
+            Some([(pattern.clone(), gen)])
+        }
+        None if server.patterns.is_empty() => {
+            return Err(Failure(
+                StatusCode::BAD_REQUEST,
+                "no pattern: ask with ?pattern= or start the server with a --language which has patterns".into(),
+            ))
+        }
+        None => None,
+    };
+
+    // Generating is slow with filters which reject most candidates, so it is kept off the threads answering requests
+    let words = tokio::task::spawn_blocking(move || {
+        let patterns = match &requested {
+            Some(requested) => &requested[..],
+            None => &server.patterns[..],
+        };
+        let seed = query.seed.unwrap_or_else(rand::random);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+        let mut records = Vec::with_capacity(query.count);
+        for _ in 0..query.count {
+            let (pattern, gen) = &patterns[rng.gen_range(0..patterns.len())];
+            let word = gen.try_generate(&mut rng)?;
+            records.push(Record {
+                ipa: word.to_string(),
+                romanization: server
+                    .romanization
+                    .as_ref()
+                    .map(|r| r.romanize(&word.phonemes().collect::<Vec<_>>())),
+                syllables: word.iter().map(ToString::to_string).collect(),
+                pattern: Some(pattern),
+                seed,
+            });
+        }
+        Ok::<_, Failure>(serde_json::to_value(records)?)
+    })
+    .await
+    .map_err(|e| Failure(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))??;
+    Ok(Json(words))
+}
+
+#[derive(serde::Deserialize)]
+struct SyllabifyQuery {
+    word: String,
+}
+
+async fn syllabify(
+    Query(query): Query<SyllabifyQuery>,
+) -> Result<Json<serde_json::Value>, Failure> {
+    let word = analysis::syllabify_word(&query.word.parse()?);
+    Ok(Json(serde_json::json!({
+        "ipa": word.to_string(),
+        "syllables": word.iter().map(ToString::to_string).collect::<Vec<_>>(),
+    })))
+}
+
+#[derive(serde::Deserialize)]
+struct SpeakQuery {
+    ipa: String,
+}
+
+async fn speak(
+    State(server): State<Arc<Server>>,
+    Query(query): Query<SpeakQuery>,
+) -> Result<Response, Failure> {
+    let audio = server
+        .speech
+        .synthesize(&query.ipa)
+        .await
+        .map_err(Failure::speech)?;
+    let content_type = server.speech.format().content_type();
+    Ok(([(header::CONTENT_TYPE, content_type)], audio).into_response())
+}
@@ -0,0 +1,642 @@
+//! # Speech
+//!
+//! Reading a word in IPA only goes so far; hearing it is how to tell whether a language sounds right. A [`TtsBackend`]
+//! turns IPA into audio, which can be played or written to a file. `Polly` speaks with Amazon Polly, with the `aws`
+//! feature, [`Espeak`] speaks offline with eSpeak NG, [`Mbrola`] speaks offline with MBROLA diphone voices, which
+//! follow length, stress, and tone exactly, and the [formant synthesizer](crate::synth::Formant) needs nothing at all.
+//! [`Cached`] keeps the audio of any backend on disk, so a word is only synthesized once, [`Retry`] tries again when a
+//! backend is busy or can not be reached, [`Assembled`] speaks words a syllable at a time, [`Normalized`] makes every
+//! word as loud as the rest, and [`Null`] makes silence, for testing code which speaks without a backend to speak with.
+//!
+//! This needs the `speech` feature.
+
+use bytes::Bytes;
+use rand::Rng;
+use std::{future::Future, path::PathBuf, pin::Pin, time::Duration};
+use thiserror::Error;
+
+mod audio;
+mod espeak;
+mod mbrola;
+#[cfg(feature = "aws")]
+mod polly;
+
+pub use audio::{duration, Clip};
+pub use espeak::Espeak;
+pub use mbrola::Mbrola;
+#[cfg(feature = "aws")]
+pub use polly::{Engine, Polly, PollyConfig};
+
+#[derive(Debug, Error)]
+pub enum SpeechError {
+    #[error("Polly could not speak: {0}")]
+    Polly(String),
+    /// The backend is getting more requests than it allows, and asked for fewer.
+    #[error("the speech backend is busy: {0}")]
+    Throttled(String),
+    /// The backend could not be reached, or failed in a way which might not happen again.
+    #[error("the speech backend is unavailable: {0}")]
+    Unavailable(String),
+    #[error("espeak-ng could not speak: {0}")]
+    Espeak(String),
+    #[error("mbrola could not speak: {0}")]
+    Mbrola(String),
+    #[error("{0}")]
+    Audio(String),
+    #[error("could not cache speech in {}: {source}", path.display())]
+    Cache {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+}
+
+impl SpeechError {
+    /// Whether asking again, after a while, might work.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::Throttled(_) | Self::Unavailable(_))
+    }
+}
+
+/// The format of the audio a backend makes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    OggVorbis,
+    Wav,
+}
+
+impl Format {
+    /// The extension of files of this format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::OggVorbis => "ogg",
+            Self::Wav => "wav",
+        }
+    }
+
+    /// The MIME type of this format.
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::OggVorbis => "audio/ogg",
+            Self::Wav => "audio/wav",
+        }
+    }
+}
+
+/// How to speak, like SSML `<prosody>`, for making long passages easier to follow or hinting at stress and length. A
+/// setting of `None` is left to the backend. Backends which do not read SSML get as close as they can.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Prosody {
+    /// `x-slow`, `slow`, `medium`, `fast`, `x-fast`, or a percentage of the normal rate like `80%`.
+    pub rate: Option<String>,
+    /// `x-low`, `low`, `medium`, `high`, `x-high`, or a change like `+10%` or `-20%`.
+    pub pitch: Option<String>,
+    /// `silent`, `x-soft`, `soft`, `medium`, `loud`, `x-loud`, or a change in decibels like `+6dB` or `-3dB`.
+    pub volume: Option<String>,
+    /// Silence between words.
+    pub pause: Option<Duration>,
+}
+
+impl Prosody {
+    /// Each setting of this, or of `under` where this has none.
+    pub fn or(&self, under: &Prosody) -> Prosody {
+        Prosody {
+            rate: self.rate.clone().or_else(|| under.rate.clone()),
+            pitch: self.pitch.clone().or_else(|| under.pitch.clone()),
+            volume: self.volume.clone().or_else(|| under.volume.clone()),
+            pause: self.pause.or(under.pause),
+        }
+    }
+
+    /// The rate as a multiple of the normal rate, like 0.8 for `80%`.
+    pub fn speed(&self) -> f64 {
+        match self.rate.as_deref() {
+            Some("x-slow") => 0.5,
+            Some("slow") => 0.75,
+            Some("fast") => 1.25,
+            Some("x-fast") => 1.5,
+            Some(percent) => percent
+                .strip_suffix('%')
+                .and_then(|x| x.parse::<f64>().ok())
+                .map_or(1.0, |x| x / 100.0),
+            None => 1.0,
+        }
+    }
+
+    /// The pitch as a multiple of the normal pitch, like 1.1 for `+10%`.
+    pub fn pitch_scale(&self) -> f64 {
+        match self.pitch.as_deref() {
+            Some("x-low") => 0.7,
+            Some("low") => 0.85,
+            Some("high") => 1.15,
+            Some("x-high") => 1.3,
+            Some(change) => change
+                .strip_suffix('%')
+                .and_then(|x| x.parse::<f64>().ok())
+                .map_or(1.0, |x| (1.0 + x / 100.0).max(0.0)),
+            None => 1.0,
+        }
+    }
+
+    /// The volume as a multiple of the normal amplitude, like about 2 for `+6dB`.
+    pub fn gain(&self) -> f64 {
+        let decibels = match self.volume.as_deref() {
+            Some("silent") => return 0.0,
+            Some("x-soft") => -12.0,
+            Some("soft") => -6.0,
+            Some("loud") => 4.0,
+            Some("x-loud") => 8.0,
+            Some(change) => change
+                .strip_suffix("dB")
+                .and_then(|x| x.parse::<f64>().ok())
+                .unwrap_or(0.0),
+            None => 0.0,
+        };
+        10f64.powf(decibels / 20.0)
+    }
+
+    /// The settings, for telling apart audio spoken with different prosody.
+    pub(crate) fn key(&self) -> String {
+        format!(
+            "{}-{}-{}-{}",
+            self.rate.as_deref().unwrap_or_default(),
+            self.pitch.as_deref().unwrap_or_default(),
+            self.volume.as_deref().unwrap_or_default(),
+            self.pause
+                .map(|pause| pause.as_millis().to_string())
+                .unwrap_or_default(),
+        )
+    }
+}
+
+/// The prosody of [`TtsBackend::synthesize`], which leaves everything to the backend.
+static NORMAL: Prosody = Prosody {
+    rate: None,
+    pitch: None,
+    volume: None,
+    pause: None,
+};
+
+/// The audio of a [`TtsBackend::synthesize`] call, once it is done.
+pub type Synthesis<'a> = Pin<Box<dyn Future<Output = Result<Bytes, SpeechError>> + Send + 'a>>;
+
+/// Something which speaks IPA.
+pub trait TtsBackend: Send + Sync {
+    /// Speak the IPA in `ipa`, like `ˈka.ta`, returning the audio in this backend's [`format`](Self::format).
+    fn synthesize<'a>(&'a self, ipa: &'a str) -> Synthesis<'a> {
+        self.synthesize_with(ipa, &NORMAL)
+    }
+
+    /// Speak the IPA in `ipa` like [`synthesize`](Self::synthesize), with the settings of `prosody` over the backend's
+    /// own.
+    fn synthesize_with<'a>(&'a self, ipa: &'a str, prosody: &'a Prosody) -> Synthesis<'a>;
+
+    /// The format of the audio this backend makes.
+    fn format(&self) -> Format;
+
+    /// Everything about this backend which changes how it sounds, like the voice, for telling apart audio of the same
+    /// IPA spoken by different backends.
+    fn key(&self) -> String;
+}
+
+/// A backend which makes a moment of silence for every word, as WAV.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Null;
+
+impl TtsBackend for Null {
+    fn synthesize_with<'a>(&'a self, _ipa: &'a str, _prosody: &'a Prosody) -> Synthesis<'a> {
+        Box::pin(async { Ok(Clip::silence(16000, Duration::from_millis(100)).to_wav()) })
+    }
+
+    fn format(&self) -> Format {
+        Format::Wav
+    }
+
+    fn key(&self) -> String {
+        "null".into()
+    }
+}
+
+/// A backend which keeps the audio it makes in a folder, so a word spoken again is read from the folder instead of
+/// being synthesized again, even by another run. Files are named after the [`key`](TtsBackend::key) of the backend and
+/// the IPA, and any [`Prosody`] it is spoken with, so changing the voice does not play audio of the old one.
+pub struct Cached<B> {
+    backend: B,
+    dir: PathBuf,
+}
+
+impl<B: TtsBackend> Cached<B> {
+    /// Cache the audio of the `backend` in `dir`, which is created when the first audio is written to it.
+    pub fn new(backend: B, dir: impl Into<PathBuf>) -> Self {
+        Self {
+            backend,
+            dir: dir.into(),
+        }
+    }
+
+    /// Where the audio of `ipa` spoken with `prosody` is kept, keeping only characters which are safe in a file name.
+    fn path(&self, ipa: &str, prosody: &Prosody) -> PathBuf {
+        let key = if prosody == &NORMAL {
+            self.backend.key()
+        } else {
+            format!("{}-{}", self.backend.key(), prosody.key())
+        };
+        let name: String = format!("{key}-{ipa}")
+            .chars()
+            .filter(|c| c.is_alphanumeric() || *c == '-')
+            .collect();
+        self.dir
+            .join(format!("{name}.{}", self.backend.format().extension()))
+    }
+}
+
+impl<B: TtsBackend> TtsBackend for Cached<B> {
+    fn synthesize_with<'a>(&'a self, ipa: &'a str, prosody: &'a Prosody) -> Synthesis<'a> {
+        Box::pin(async move {
+            let path = self.path(ipa, prosody);
+            if let Ok(audio) = tokio::fs::read(&path).await {
+                return Ok(audio.into());
+            }
+            let audio = self.backend.synthesize_with(ipa, prosody).await?;
+            let cache = |source| SpeechError::Cache {
+                path: self.dir.clone(),
+                source,
+            };
+            tokio::fs::create_dir_all(&self.dir).await.map_err(cache)?;
+            tokio::fs::write(&path, &audio).await.map_err(cache)?;
+            Ok(audio)
+        })
+    }
+
+    fn format(&self) -> Format {
+        self.backend.format()
+    }
+
+    fn key(&self) -> String {
+        self.backend.key()
+    }
+}
+
+impl<B: TtsBackend + ?Sized> TtsBackend for Box<B> {
+    fn synthesize_with<'a>(&'a self, ipa: &'a str, prosody: &'a Prosody) -> Synthesis<'a> {
+        (**self).synthesize_with(ipa, prosody)
+    }
+
+    fn format(&self) -> Format {
+        (**self).format()
+    }
+
+    fn key(&self) -> String {
+        (**self).key()
+    }
+}
+
+/// A backend which asks its backend again when it fails with a [transient](SpeechError::is_transient) error, waiting
+/// twice as long before each try, so one busy moment in a long run of words does not fail the whole run. Each wait is
+/// shortened by a random amount of up to half, so requests which failed together do not all try again together.
+pub struct Retry<B> {
+    backend: B,
+    tries: u32,
+    delay: Duration,
+}
+
+impl<B: TtsBackend> Retry<B> {
+    /// Try `backend` up to 5 times, waiting 200 milliseconds after the first try.
+    pub fn new(backend: B) -> Self {
+        Self {
+            backend,
+            tries: 5,
+            delay: Duration::from_millis(200),
+        }
+    }
+
+    /// Try up to `tries` times in all, at least once.
+    pub fn with_tries(mut self, tries: u32) -> Self {
+        self.tries = tries.max(1);
+        self
+    }
+
+    /// Wait `delay` after the first try, and twice as long after each one after it.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+impl<B: TtsBackend> TtsBackend for Retry<B> {
+    fn synthesize_with<'a>(&'a self, ipa: &'a str, prosody: &'a Prosody) -> Synthesis<'a> {
+        Box::pin(async move {
+            let mut delay = self.delay;
+            for _ in 1..self.tries {
+                match self.backend.synthesize_with(ipa, prosody).await {
+                    Err(e) if e.is_transient() => {
+                        let jitter = rand::thread_rng().gen_range(0.5..=1.0);
+                        tokio::time::sleep(delay.mul_f64(jitter)).await;
+                        delay *= 2;
+                    }
+                    done => return done,
+                }
+            }
+            self.backend.synthesize_with(ipa, prosody).await
+        })
+    }
+
+    fn format(&self) -> Format {
+        self.backend.format()
+    }
+
+    fn key(&self) -> String {
+        self.backend.key()
+    }
+}
+
+/// A backend which speaks each syllable on its own and joins them into words, so long texts flow evenly. Syllables
+/// repeat far more than words do, so with a [`Cached`] backend inside, a text needs far fewer calls to the backend.
+/// Syllables are split at `.` and before stress marks, and words at whitespace. The [`Prosody`] of a call is passed on
+/// to the backend for each syllable, except for its pause, which goes between words. The backend has to make WAV
+/// audio.
+pub struct Assembled<B> {
+    backend: B,
+    gap: Duration,
+    crossfade: Duration,
+    pause: Duration,
+}
+
+impl<B: TtsBackend> Assembled<B> {
+    /// Join the syllables spoken by `backend` with no gap and a crossfade of 10 milliseconds, pausing for 150
+    /// milliseconds between words.
+    pub fn new(backend: B) -> Self {
+        Self {
+            backend,
+            gap: Duration::ZERO,
+            crossfade: Duration::from_millis(10),
+            pause: Duration::from_millis(150),
+        }
+    }
+
+    /// Leave `gap` of silence between the syllables of a word.
+    pub fn with_gap(mut self, gap: Duration) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    /// Fade syllables into each other for `crossfade`. See [`Clip::join`].
+    pub fn with_crossfade(mut self, crossfade: Duration) -> Self {
+        self.crossfade = crossfade;
+        self
+    }
+
+    /// Leave `pause` of silence between words, unless the [`Prosody`] of a call has its own.
+    pub fn with_pause(mut self, pause: Duration) -> Self {
+        self.pause = pause;
+        self
+    }
+}
+
+impl<B: TtsBackend> TtsBackend for Assembled<B> {
+    fn synthesize_with<'a>(&'a self, ipa: &'a str, prosody: &'a Prosody) -> Synthesis<'a> {
+        Box::pin(async move {
+            // Syllables are never more than one word, so leaving out the pause lets them share the cache
+            let syllable_prosody = Prosody {
+                pause: None,
+                ..prosody.clone()
+            };
+            let pause = prosody.pause.unwrap_or(self.pause);
+            let mut out = Clip::new(Vec::new(), 0);
+            for (idx, word) in ipa.split_whitespace().enumerate() {
+                for (syl_idx, syllable) in syllables(word).into_iter().enumerate() {
+                    let audio = self
+                        .backend
+                        .synthesize_with(syllable, &syllable_prosody)
+                        .await?;
+                    let gap = match syl_idx {
+                        0 if idx > 0 => pause,
+                        _ => self.gap,
+                    };
+                    out.join(&Clip::from_wav(&audio)?, gap, self.crossfade)?;
+                }
+            }
+            Ok(out.to_wav())
+        })
+    }
+
+    fn format(&self) -> Format {
+        Format::Wav
+    }
+
+    fn key(&self) -> String {
+        format!(
+            "{}-{}-{}-{}",
+            self.backend.key(),
+            self.gap.as_millis(),
+            self.crossfade.as_millis(),
+            self.pause.as_millis()
+        )
+    }
+}
+
+/// How loud [`Normalized`] makes speech.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Loudness {
+    /// The loudest sample at this many decibels below full scale, like `-1.0`. Quick, but a word with one loud burst
+    /// ends up quieter than one without.
+    Peak(f64),
+    /// Sounding this loud, in LUFS, like `-18.0`, which is how loud people hear it to be.
+    Lufs(f64),
+}
+
+/// A backend which makes everything the backend inside it says as loud as the others, so words from different
+/// backends, voices, or syllables sound even when played one after another. Speech is never made louder than full
+/// scale, so a quiet word with a loud burst may end up quieter than asked. The backend has to make WAV audio.
+pub struct Normalized<B> {
+    backend: B,
+    loudness: Loudness,
+}
+
+impl<B: TtsBackend> Normalized<B> {
+    pub fn new(backend: B, loudness: Loudness) -> Self {
+        Self { backend, loudness }
+    }
+}
+
+impl<B: TtsBackend> TtsBackend for Normalized<B> {
+    fn synthesize_with<'a>(&'a self, ipa: &'a str, prosody: &'a Prosody) -> Synthesis<'a> {
+        Box::pin(async move {
+            let mut clip = Clip::from_wav(&self.backend.synthesize_with(ipa, prosody).await?)?;
+            let peak = clip.peak();
+            let change = match self.loudness {
+                Loudness::Peak(level) => level - peak,
+                Loudness::Lufs(level) => (level - clip.loudness()).min(-peak),
+            };
+            // Silence stays silent
+            if change.is_finite() {
+                clip.amplify(change);
+            }
+            Ok(clip.to_wav())
+        })
+    }
+
+    fn format(&self) -> Format {
+        Format::Wav
+    }
+
+    fn key(&self) -> String {
+        match self.loudness {
+            Loudness::Peak(level) => format!("{}-peak{level}", self.backend.key()),
+            Loudness::Lufs(level) => format!("{}-lufs{level}", self.backend.key()),
+        }
+    }
+}
+
+/// Split a word in IPA into syllables at `.` and before stress marks, keeping the marks.
+fn syllables(word: &str) -> Vec<&str> {
+    let mut out = Vec::new();
+    let mut start = 0;
+    for (idx, c) in word.char_indices() {
+        match c {
+            '.' => {
+                out.push(&word[start..idx]);
+                start = idx + 1;
+            }
+            'ˈ' | 'ˌ' => {
+                out.push(&word[start..idx]);
+                start = idx;
+            }
+            _ => {}
+        }
+    }
+    out.push(&word[start..]);
+    out.retain(|syllable| !syllable.is_empty());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn null() {
+        let audio = Null.synthesize("ˈka.ta").await.unwrap();
+        assert_eq!(b"RIFF", &audio[..4]);
+        assert_eq!(44 + 3200, audio.len());
+    }
+
+    #[tokio::test]
+    async fn cached() {
+        let dir = std::env::temp_dir().join(format!("conlang-speech-{}", std::process::id()));
+        let cached = Cached::new(Null, &dir);
+        let path = cached.path("ˈka.ta", &NORMAL);
+        assert_eq!(
+            Some("null-ˈkata.wav"),
+            path.file_name().and_then(|x| x.to_str())
+        );
+        let slow = Prosody {
+            rate: Some("80%".into()),
+            ..Prosody::default()
+        };
+        assert_eq!(
+            Some("null-80----ˈkata.wav"),
+            cached
+                .path("ˈka.ta", &slow)
+                .file_name()
+                .and_then(|x| x.to_str())
+        );
+        let audio = cached.synthesize("ˈka.ta").await.unwrap();
+        assert_eq!(audio, std::fs::read(&path).unwrap());
+        assert_eq!(audio, cached.synthesize("ˈka.ta").await.unwrap());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Fails with `error` until it has been asked `failures` times.
+    struct Flaky {
+        failures: u32,
+        error: fn() -> SpeechError,
+        calls: std::sync::atomic::AtomicU32,
+    }
+
+    impl TtsBackend for Flaky {
+        fn synthesize_with<'a>(&'a self, ipa: &'a str, prosody: &'a Prosody) -> Synthesis<'a> {
+            let calls = self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            if calls < self.failures {
+                Box::pin(async { Err((self.error)()) })
+            } else {
+                Null.synthesize_with(ipa, prosody)
+            }
+        }
+
+        fn format(&self) -> Format {
+            Format::Wav
+        }
+
+        fn key(&self) -> String {
+            "flaky".into()
+        }
+    }
+
+    #[tokio::test]
+    async fn retry() {
+        let flaky = |failures, error: fn() -> SpeechError| Flaky {
+            failures,
+            error,
+            calls: Default::default(),
+        };
+        let retry = |backend| Retry::new(backend).with_delay(Duration::from_millis(1));
+
+        let busy = retry(flaky(4, || SpeechError::Throttled("slow down".into())));
+        assert!(busy.synthesize("ka").await.is_ok());
+        assert_eq!(5, busy.backend.calls.into_inner());
+
+        let busy = retry(flaky(5, || SpeechError::Unavailable("timed out".into())));
+        assert!(matches!(
+            busy.synthesize("ka").await,
+            Err(SpeechError::Unavailable(_))
+        ));
+        assert_eq!(5, busy.backend.calls.into_inner());
+
+        let broken = retry(flaky(1, || SpeechError::Polly("no such voice".into())));
+        assert!(broken.synthesize("ka").await.is_err());
+        assert_eq!(1, broken.backend.calls.into_inner());
+    }
+
+    #[tokio::test]
+    async fn assembled() {
+        assert_eq!(vec!["ˈka", "ta", "ˌki"], syllables("ˈka.taˌki"));
+        assert_eq!(vec!["pa"], syllables(".pa."));
+
+        let assembled = Assembled::new(Null)
+            .with_gap(Duration::from_millis(10))
+            .with_pause(Duration::from_millis(50));
+        let audio = assembled.synthesize("ˈka.ta ki").await.unwrap();
+        let clip = Clip::from_wav(&audio).unwrap();
+        // Three syllables of 100ms, with a gap and a pause
+        assert_eq!(Duration::from_millis(360), clip.duration());
+
+        let prosody = Prosody {
+            pause: Some(Duration::from_millis(200)),
+            ..Prosody::default()
+        };
+        let audio = assembled
+            .synthesize_with("ˈka.ta ki", &prosody)
+            .await
+            .unwrap();
+        let clip = Clip::from_wav(&audio).unwrap();
+        assert_eq!(Duration::from_millis(510), clip.duration());
+    }
+
+    #[tokio::test]
+    async fn normalized() {
+        let level = |loudness| async move {
+            let normalized = Normalized::new(crate::synth::Formant::new(), loudness);
+            Clip::from_wav(&normalized.synthesize("ˈka.ta").await.unwrap()).unwrap()
+        };
+        assert!((level(Loudness::Peak(-1.0)).await.peak() + 1.0).abs() < 0.01);
+        assert!((level(Loudness::Lufs(-30.0)).await.loudness() + 30.0).abs() < 0.1);
+        // Never louder than full scale
+        assert!(level(Loudness::Lufs(0.0)).await.peak() <= 0.0);
+
+        let silence = Normalized::new(Null, Loudness::Peak(-1.0));
+        assert_eq!(
+            Null.synthesize("ka").await.unwrap(),
+            silence.synthesize("ka").await.unwrap()
+        );
+    }
+}
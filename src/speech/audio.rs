@@ -0,0 +1,395 @@
+//! Audio as samples, for working on what backends make: reading and writing WAV, and joining clips together.
+
+use super::{Format, SpeechError};
+use bytes::Bytes;
+use std::time::Duration;
+
+/// Mono 16-bit audio.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Clip {
+    samples: Vec<i16>,
+    rate: u32,
+}
+
+impl Clip {
+    /// Audio of the `samples`, played at `rate` samples a second.
+    pub fn new(samples: Vec<i16>, rate: u32) -> Self {
+        Self { samples, rate }
+    }
+
+    /// Silence lasting `duration`.
+    pub fn silence(rate: u32, duration: Duration) -> Self {
+        Self::new(vec![0; samples_in(rate, duration)], rate)
+    }
+
+    /// Read a WAV file of 16-bit PCM. Stereo audio is mixed down to mono.
+    pub fn from_wav(src: &[u8]) -> Result<Self, SpeechError> {
+        let invalid = |msg: &str| SpeechError::Audio(format!("not a 16-bit PCM WAV file: {msg}"));
+        if src.len() < 12 || &src[..4] != b"RIFF" || &src[8..12] != b"WAVE" {
+            return Err(invalid("no RIFF header"));
+        }
+        let mut format = None;
+        let mut rest = &src[12..];
+        while rest.len() >= 8 {
+            let id = &rest[..4];
+            let size = u32::from_le_bytes(rest[4..8].try_into().unwrap()) as usize;
+            // Programs which write to a pipe, like espeak-ng, can not go back to fill in the size of the data, so a
+            // size past the end means the rest of the file
+            let body = &rest[8..rest.len().min(8 + size)];
+            match id {
+                b"fmt " if body.len() >= 16 => {
+                    let field = |at: usize| u16::from_le_bytes([body[at], body[at + 1]]);
+                    let rate = u32::from_le_bytes(body[4..8].try_into().unwrap());
+                    format = Some((field(0), field(2), rate, field(14)));
+                }
+                b"data" => {
+                    let Some((1, channels @ (1 | 2), rate, 16)) = format else {
+                        return Err(invalid("not mono or stereo 16-bit PCM"));
+                    };
+                    let samples = body
+                        .chunks_exact(2 * channels as usize)
+                        .map(|frame| {
+                            let sum: i32 = frame
+                                .chunks_exact(2)
+                                .map(|x| i16::from_le_bytes([x[0], x[1]]) as i32)
+                                .sum();
+                            (sum / channels as i32) as i16
+                        })
+                        .collect();
+                    return Ok(Self::new(samples, rate));
+                }
+                _ => {}
+            }
+            // Chunks are padded to an even size
+            rest = &rest[(8 + size + size % 2).min(rest.len())..];
+        }
+        Err(invalid("no data"))
+    }
+
+    /// Write this clip as a WAV file.
+    pub fn to_wav(&self) -> Bytes {
+        let data = self.samples.len() as u32 * 2;
+        let mut out = Vec::with_capacity(44 + data as usize);
+        out.extend_from_slice(b"RIFF");
+        out.extend_from_slice(&(36 + data).to_le_bytes());
+        out.extend_from_slice(b"WAVEfmt ");
+        out.extend_from_slice(&16u32.to_le_bytes());
+        // PCM, with one channel
+        out.extend_from_slice(&1u16.to_le_bytes());
+        out.extend_from_slice(&1u16.to_le_bytes());
+        out.extend_from_slice(&self.rate.to_le_bytes());
+        out.extend_from_slice(&(self.rate * 2).to_le_bytes());
+        out.extend_from_slice(&2u16.to_le_bytes());
+        out.extend_from_slice(&16u16.to_le_bytes());
+        out.extend_from_slice(b"data");
+        out.extend_from_slice(&data.to_le_bytes());
+        for sample in self.samples.iter() {
+            out.extend_from_slice(&sample.to_le_bytes());
+        }
+        out.into()
+    }
+
+    pub fn samples(&self) -> &[i16] {
+        &self.samples
+    }
+
+    /// How many samples are played a second.
+    pub fn rate(&self) -> u32 {
+        self.rate
+    }
+
+    /// How long the clip plays for.
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f64(self.samples.len() as f64 / self.rate as f64)
+    }
+
+    /// The level of the loudest sample, in decibels below full scale, which is negative infinity for silence.
+    pub fn peak(&self) -> f64 {
+        let peak = self
+            .samples
+            .iter()
+            .map(|x| (*x as f64 / FULL_SCALE).abs())
+            .fold(0.0, f64::max);
+        20.0 * peak.log10()
+    }
+
+    /// How loud the clip sounds, in LUFS, measured as [ITU-R BS.1770](https://www.itu.int/rec/R-REC-BS.1770) does: the
+    /// samples are weighted to hear like people do, then the power of overlapping 400 millisecond blocks is averaged,
+    /// leaving out silent blocks and those far quieter than the rest. A clip shorter than a block is one block. Silence
+    /// is negative infinity.
+    pub fn loudness(&self) -> f64 {
+        if self.samples.is_empty() {
+            return f64::NEG_INFINITY;
+        }
+        let weighted = k_weighting(&self.samples, self.rate as f64);
+        // Blocks of 400 milliseconds, starting every 100
+        let len = samples_in(self.rate, Duration::from_millis(400)).clamp(1, weighted.len().max(1));
+        let step = (len / 4).max(1);
+        let powers: Vec<f64> = (0..=weighted.len().saturating_sub(len))
+            .step_by(step)
+            .map(|start| {
+                weighted[start..start + len]
+                    .iter()
+                    .map(|x| x * x)
+                    .sum::<f64>()
+                    / len as f64
+            })
+            .collect();
+        let lufs = |power: f64| -0.691 + 10.0 * power.log10();
+        let gated = |gate: f64| {
+            let loud: Vec<_> = powers.iter().filter(|x| lufs(**x) > gate).collect();
+            match loud.len() {
+                0 => 0.0,
+                count => loud.into_iter().sum::<f64>() / count as f64,
+            }
+        };
+        let relative = lufs(gated(-70.0)) - 10.0;
+        lufs(gated(relative.max(-70.0)))
+    }
+
+    /// Make the clip `db` decibels louder, or quieter if it is negative. Samples which would be louder than full scale
+    /// are clipped.
+    pub fn amplify(&mut self, db: f64) {
+        let scale = 10f64.powf(db / 20.0);
+        for sample in self.samples.iter_mut() {
+            *sample = (*sample as f64 * scale)
+                .round()
+                .clamp(i16::MIN as f64, i16::MAX as f64) as i16;
+        }
+    }
+
+    /// Add `next` to the end of this clip, after `gap` of silence. Without a gap, the clips overlap for `crossfade`,
+    /// with this one fading out as `next` fades in, so the join is smooth; with one, each side of the gap fades for
+    /// `crossfade` instead, so the audio does not click as it stops and starts. The clips must have the same rate,
+    /// unless this one is empty.
+    pub fn join(
+        &mut self,
+        next: &Clip,
+        gap: Duration,
+        crossfade: Duration,
+    ) -> Result<(), SpeechError> {
+        if self.samples.is_empty() {
+            *self = next.clone();
+            return Ok(());
+        }
+        if next.rate != self.rate {
+            return Err(SpeechError::Audio(format!(
+                "can not join audio at {} and {} samples a second",
+                self.rate, next.rate
+            )));
+        }
+        let gap = samples_in(self.rate, gap);
+        let fade = samples_in(self.rate, crossfade);
+        let scale = |sample: i16, idx: usize, len: usize| {
+            (sample as f64 * (idx as f64 + 0.5) / len as f64) as i16
+        };
+        if gap > 0 {
+            let len = fade.min(self.samples.len());
+            let start = self.samples.len() - len;
+            for (idx, sample) in self.samples[start..].iter_mut().enumerate() {
+                *sample = scale(*sample, len - idx - 1, len);
+            }
+            self.samples.resize(self.samples.len() + gap, 0);
+            let len = fade.min(next.samples.len());
+            self.samples
+                .extend(next.samples.iter().enumerate().map(|(idx, sample)| {
+                    if idx < len {
+                        scale(*sample, idx, len)
+                    } else {
+                        *sample
+                    }
+                }));
+        } else {
+            let len = fade.min(self.samples.len()).min(next.samples.len());
+            let start = self.samples.len() - len;
+            for (idx, sample) in self.samples[start..].iter_mut().enumerate() {
+                let out = scale(*sample, len - idx - 1, len) as i32;
+                let into = scale(next.samples[idx], idx, len) as i32;
+                *sample = (out + into).clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+            }
+            self.samples.extend_from_slice(&next.samples[len..]);
+        }
+        Ok(())
+    }
+}
+
+/// The level of a sample at full scale, where 0 dB is.
+const FULL_SCALE: f64 = 32768.0;
+
+/// Weight the `samples`, at `rate` samples a second, to hear like people do, as BS.1770 does: a high shelf for how the
+/// head boosts high frequencies, then a high pass for how little low ones are heard. The filters are the ones in the
+/// standard, worked out for any rate.
+fn k_weighting(samples: &[i16], rate: f64) -> Vec<f64> {
+    let biquad = |b: [f64; 3], a: [f64; 2], input: &mut Vec<f64>| {
+        let (mut x1, mut x2, mut y1, mut y2) = (0.0, 0.0, 0.0, 0.0);
+        for sample in input.iter_mut() {
+            let x = *sample;
+            let y = b[0] * x + b[1] * x1 + b[2] * x2 - a[0] * y1 - a[1] * y2;
+            (x2, x1, y2, y1) = (x1, x, y1, y);
+            *sample = y;
+        }
+    };
+    let mut out: Vec<f64> = samples.iter().map(|x| *x as f64 / FULL_SCALE).collect();
+
+    let (f0, gain, q) = (1681.974450955533, 3.999843853973347, 0.7071752369554196);
+    let k = (std::f64::consts::PI * f0 / rate).tan();
+    let vh = 10f64.powf(gain / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let a0 = 1.0 + k / q + k * k;
+    let b = [
+        (vh + vb * k / q + k * k) / a0,
+        2.0 * (k * k - vh) / a0,
+        (vh - vb * k / q + k * k) / a0,
+    ];
+    let a = [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0];
+    biquad(b, a, &mut out);
+
+    let (f0, q) = (38.13547087602444, 0.5003270373238773);
+    let k = (std::f64::consts::PI * f0 / rate).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let a = [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0];
+    biquad([1.0, -2.0, 1.0], a, &mut out);
+    out
+}
+
+/// How long the `audio`, in the `format`, plays for, without decoding it.
+pub fn duration(audio: &[u8], format: Format) -> Result<Duration, SpeechError> {
+    match format {
+        Format::Wav => Ok(Clip::from_wav(audio)?.duration()),
+        Format::OggVorbis => ogg_duration(audio),
+    }
+}
+
+/// How long Ogg Vorbis audio plays for: the position of the last page, in samples, at the rate in the identification
+/// header which starts the first.
+fn ogg_duration(src: &[u8]) -> Result<Duration, SpeechError> {
+    let invalid = |msg: &str| SpeechError::Audio(format!("not an Ogg Vorbis file: {msg}"));
+    // A page is `OggS`, a version, flags, the position, then more fields up to the number of segments at 26, each of
+    // which has its length in the table after it
+    if src.len() < 27 || &src[..4] != b"OggS" {
+        return Err(invalid("no Ogg page"));
+    }
+    let packet = &src[(27 + src[26] as usize).min(src.len())..];
+    if packet.len() < 16 || &packet[..7] != b"\x01vorbis" {
+        return Err(invalid("no identification header"));
+    }
+    let rate = u32::from_le_bytes(packet[12..16].try_into().unwrap());
+    if rate == 0 {
+        return Err(invalid("no sample rate"));
+    }
+    let last = src
+        .windows(4)
+        .rposition(|x| x == b"OggS")
+        .filter(|at| at + 14 <= src.len())
+        .ok_or_else(|| invalid("no last page"))?;
+    let samples = u64::from_le_bytes(src[last + 6..last + 14].try_into().unwrap());
+    Ok(Duration::from_secs_f64(samples as f64 / rate as f64))
+}
+
+/// How many samples at `rate` last for `duration`.
+fn samples_in(rate: u32, duration: Duration) -> usize {
+    (rate as f64 * duration.as_secs_f64()).round() as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wav() {
+        let clip = Clip::new(vec![0, 1000, -1000, i16::MAX], 16000);
+        let wav = clip.to_wav();
+        assert_eq!(44 + 8, wav.len());
+        assert_eq!(clip, Clip::from_wav(&wav).unwrap());
+        assert!(Clip::from_wav(b"OggS").is_err());
+
+        // A data size past the end, like a WAV file written to a pipe
+        let mut piped = wav.to_vec();
+        piped[40..44].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(clip, Clip::from_wav(&piped).unwrap());
+    }
+
+    #[test]
+    fn loudness() {
+        // A full-scale sine at 1 kHz is -3.01 LUFS, as the standard says
+        let sine = |amplitude: f64| {
+            let samples = (0..48000)
+                .map(|idx| {
+                    let at = idx as f64 / 48000.0;
+                    (amplitude * (2.0 * std::f64::consts::PI * 1000.0 * at).sin()) as i16
+                })
+                .collect();
+            Clip::new(samples, 48000)
+        };
+        let full = sine(i16::MAX as f64);
+        assert!((full.loudness() + 3.01).abs() < 0.05, "{}", full.loudness());
+        assert!(full.peak().abs() < 0.01);
+
+        let mut quiet = sine(i16::MAX as f64 / 10.0);
+        assert!((quiet.loudness() + 23.01).abs() < 0.05);
+        quiet.amplify(20.0);
+        assert!((quiet.loudness() - full.loudness()).abs() < 0.05);
+        assert_eq!(
+            f64::NEG_INFINITY,
+            Clip::silence(16000, Duration::from_secs(1)).loudness()
+        );
+        assert_eq!(f64::NEG_INFINITY, Clip::new(Vec::new(), 16000).loudness());
+        assert_eq!(f64::NEG_INFINITY, Clip::new(Vec::new(), 16000).peak());
+    }
+
+    #[test]
+    fn durations() {
+        let clip = Clip::new(vec![0; 8000], 16000);
+        assert_eq!(
+            Duration::from_millis(500),
+            duration(&clip.to_wav(), Format::Wav).unwrap()
+        );
+
+        // The identification header on the first page, then the last page at a quarter of a second
+        let page = |position: u64, packet: &[u8]| {
+            let mut page = b"OggS\0\0".to_vec();
+            page.extend_from_slice(&position.to_le_bytes());
+            page.resize(26, 0);
+            page.push(1);
+            page.push(packet.len() as u8);
+            page.extend_from_slice(packet);
+            page
+        };
+        let mut header = b"\x01vorbis\0\0\0\0\x01".to_vec();
+        header.extend_from_slice(&16000u32.to_le_bytes());
+        header.resize(30, 0);
+        let mut ogg = page(0, &header);
+        ogg.extend(page(4000, &[0; 10]));
+        assert_eq!(
+            Duration::from_millis(250),
+            duration(&ogg, Format::OggVorbis).unwrap()
+        );
+        assert!(duration(&clip.to_wav(), Format::OggVorbis).is_err());
+    }
+
+    #[test]
+    fn join() {
+        let ms = Duration::from_millis;
+        let tone = Clip::new(vec![1000; 100], 1000);
+
+        let mut clip = tone.clone();
+        clip.join(&tone, ms(20), ms(10)).unwrap();
+        assert_eq!(220, clip.samples().len());
+        assert_eq!(&[0i16; 20], &clip.samples()[100..120]);
+        assert!(clip.samples()[99] < 100);
+        assert_eq!(1000, clip.samples()[50]);
+
+        let mut clip = tone.clone();
+        clip.join(&tone, ms(0), ms(10)).unwrap();
+        assert_eq!(190, clip.samples().len());
+        // The fades add up to the full level across the overlap
+        assert!(clip.samples()[90..100]
+            .iter()
+            .all(|x| (999..=1000).contains(x)));
+
+        let mut clip = Clip::new(Vec::new(), 8000);
+        clip.join(&tone, ms(20), ms(10)).unwrap();
+        assert_eq!(tone, clip);
+        assert!(clip.join(&Clip::new(vec![0], 8000), ms(0), ms(0)).is_err());
+    }
+}
@@ -0,0 +1,279 @@
+//! Speech with [eSpeak NG](https://github.com/espeak-ng/espeak-ng), which runs on this computer instead of needing AWS
+//! credentials and a network connection like Polly. It sounds more robotic, but it is free and works offline. The
+//! `espeak-ng` program has to be installed and on the `PATH`.
+//!
+//! eSpeak does not read IPA, so words are translated to its phoneme mnemonics, which are based on the Kirshenbaum
+//! ASCII-IPA, like /ˈʃi.pa/ to `[['Sipa]]`. Phonemes eSpeak has no mnemonic for, like clicks, are spoken as the nearest
+//! phoneme it has.
+
+use super::{Format, Prosody, SpeechError, Synthesis, TtsBackend};
+use crate::phone;
+
+/// The speaking rate of eSpeak, in words per minute, when no rate is given.
+const WORDS_PER_MINUTE: u32 = 175;
+
+/// Speak with eSpeak NG, as WAV. eSpeak does not read SSML, so [`Prosody`] is translated to its own settings.
+pub struct Espeak {
+    language: String,
+    prosody: Prosody,
+}
+
+impl Espeak {
+    /// Speak with the eSpeak voice for `language`, like `en` or `fi`.
+    pub fn new(language: impl Into<String>) -> Self {
+        Self {
+            language: language.into(),
+            prosody: Prosody::default(),
+        }
+    }
+
+    /// Speak with `prosody` unless a call says otherwise.
+    pub fn with_prosody(mut self, prosody: Prosody) -> Self {
+        self.prosody = prosody;
+        self
+    }
+
+    async fn speak(&self, ipa: &str, prosody: &Prosody) -> Result<bytes::Bytes, SpeechError> {
+        let prosody = prosody.or(&self.prosody);
+        let args = [
+            "--stdout".to_string(),
+            "-v".to_string(),
+            self.language.clone(),
+            "-s".to_string(),
+            words_per_minute(prosody.rate.as_deref()).to_string(),
+            "-p".to_string(),
+            pitch(prosody.pitch.as_deref()).to_string(),
+            "-a".to_string(),
+            amplitude(prosody.volume.as_deref()).to_string(),
+            // In units of 10 milliseconds
+            "-g".to_string(),
+            prosody
+                .pause
+                .map_or(0, |pause| pause.as_millis() / 10)
+                .to_string(),
+            format!("[[{}]]", mnemonics(ipa)),
+        ];
+        let out = tokio::task::spawn_blocking(move || {
+            std::process::Command::new("espeak-ng").args(&args).output()
+        })
+        .await
+        .map_err(|e| SpeechError::Espeak(e.to_string()))?
+        .map_err(|e| SpeechError::Espeak(format!("could not run it, is it installed? {e}")))?;
+        if !out.status.success() {
+            return Err(SpeechError::Espeak(format!(
+                "it failed with {}: {}",
+                out.status,
+                String::from_utf8_lossy(&out.stderr).trim()
+            )));
+        }
+        Ok(out.stdout.into())
+    }
+}
+
+impl TtsBackend for Espeak {
+    fn synthesize_with<'a>(&'a self, ipa: &'a str, prosody: &'a Prosody) -> Synthesis<'a> {
+        Box::pin(self.speak(ipa, prosody))
+    }
+
+    fn format(&self) -> Format {
+        Format::Wav
+    }
+
+    fn key(&self) -> String {
+        format!("espeak-{}-{}", self.language, self.prosody.key())
+    }
+}
+
+/// The `rate`, which is an SSML keyword or a percentage of the normal rate, in words per minute.
+fn words_per_minute(rate: Option<&str>) -> u32 {
+    match rate {
+        Some("x-slow") => WORDS_PER_MINUTE / 2,
+        Some("slow") => WORDS_PER_MINUTE * 3 / 4,
+        Some("fast") => WORDS_PER_MINUTE * 5 / 4,
+        Some("x-fast") => WORDS_PER_MINUTE * 3 / 2,
+        Some(percent) => percent
+            .strip_suffix('%')
+            .and_then(|x| x.parse::<u32>().ok())
+            .map_or(WORDS_PER_MINUTE, |x| WORDS_PER_MINUTE * x / 100),
+        None => WORDS_PER_MINUTE,
+    }
+}
+
+/// The `pitch`, which is an SSML keyword or a percentage change like `+10%`, as an eSpeak pitch from 0 to 99.
+fn pitch(pitch: Option<&str>) -> u32 {
+    match pitch {
+        Some("x-low") => 10,
+        Some("low") => 30,
+        Some("high") => 70,
+        Some("x-high") => 90,
+        Some(change) => change
+            .strip_suffix('%')
+            .and_then(|x| x.parse::<i32>().ok())
+            .map_or(50, |x| (50 + x / 2).clamp(0, 99) as u32),
+        None => 50,
+    }
+}
+
+/// The `volume`, which is an SSML keyword or a change in decibels like `+6dB`, as an eSpeak amplitude from 0 to 200.
+fn amplitude(volume: Option<&str>) -> u32 {
+    match volume {
+        Some("silent") => 0,
+        Some("x-soft") => 25,
+        Some("soft") => 50,
+        Some("loud") => 150,
+        Some("x-loud") => 200,
+        Some(change) => change
+            .strip_suffix("dB")
+            .and_then(|x| x.parse::<f64>().ok())
+            .map_or(100, |x| {
+                (100.0 * 10f64.powf(x / 20.0)).clamp(0.0, 200.0) as u32
+            }),
+        None => 100,
+    }
+}
+
+/// Translate the IPA in `src` to eSpeak phoneme mnemonics. Stress and length are kept, while syllable breaks, tones, and
+/// characters which are not phonemes are left out.
+fn mnemonics(src: &str) -> String {
+    let mut out = String::with_capacity(src.len());
+    for c in src.chars() {
+        match c {
+            'ˈ' => out.push('\''),
+            'ˌ' => out.push(','),
+            'ː' => out.push(':'),
+            c if c.is_whitespace() => out.push(' '),
+            c => {
+                if let Ok(phoneme) = phone::Phoneme::try_from(c) {
+                    out.push_str(mnemonic(phoneme));
+                }
+            }
+        }
+    }
+    out
+}
+
+/// The eSpeak mnemonic for the `phoneme`.
+fn mnemonic(phoneme: phone::Phoneme) -> &'static str {
+    use phone::{Consonant as C, NonPulmonicConsonant as N, Vowel as V};
+
+    match phoneme {
+        phone::Phoneme::Consonant(c) => match c {
+            C::P => "p",
+            C::B => "b",
+            C::T => "t",
+            C::D => "d",
+            C::TRetroflex => "t.",
+            C::DRetroflex => "d.",
+            C::C => "c",
+            C::JPalatal => "J",
+            C::K => "k",
+            C::G => "g",
+            C::Q => "q",
+            C::GCap => "G",
+            C::GlottalStop => "?",
+            C::M => "m",
+            C::MHook => "M",
+            C::N => "n",
+            C::NRetroflex => "n.",
+            C::NPalatal => "n^",
+            C::NVelar => "N",
+            C::NUvular => "n\"",
+            C::BCap => "b",
+            C::Rrr => "r",
+            C::RCap => "r\"",
+            C::VTap => "v",
+            C::RTap => "*",
+            C::RFlap => "*.",
+            C::Phi => "F",
+            C::Beta => "B",
+            C::F => "f",
+            C::V => "v",
+            C::Theta => "T",
+            C::Del => "D",
+            C::S => "s",
+            C::Z => "z",
+            C::Esh => "S",
+            C::Ezh => "Z",
+            C::Sh => "s.",
+            C::Zh => "z.",
+            C::Ch => "C",
+            C::JCurl => "j",
+            C::X => "x",
+            C::Gamma => "Q",
+            C::Xh => "X",
+            C::Yr => "g\"",
+            C::HBar => "H",
+            C::Crook => "H",
+            C::H => "h",
+            C::HCurl => "h",
+            C::LBelt => "l#",
+            C::Lezh => "l",
+            C::VHook => "v#",
+            C::RTilt => "r",
+            C::RTiltHook => "r.",
+            C::J => "j",
+            C::MTiltTail => "Q",
+            C::L => "l",
+            C::Ll => "l.",
+            C::Lambda => "l^",
+            C::LCap => "L",
+        },
+        phone::Phoneme::Vowel(v) => match v {
+            V::I => "i",
+            V::Y => "y",
+            V::IBar => "i\"",
+            V::UBar => "u\"",
+            V::Uu => "u-",
+            V::U => "u",
+            V::Ii => "I",
+            V::YCap => "I.",
+            V::OmegaFlip => "U",
+            V::E => "e",
+            V::OCross => "Y",
+            V::EReverse => "@",
+            V::OBar => "@.",
+            V::RamsHorns => "o-",
+            V::O => "o",
+            V::Schwa => "@",
+            V::EOpen => "E",
+            V::Oe => "W",
+            V::Ze => "3",
+            V::EpsilonClosedReversed => "O\"",
+            V::VFlip => "V",
+            V::OOpen => "O",
+            V::Ae => "&",
+            V::AFlip => "a#",
+            V::A => "a",
+            V::OeSmall => "&.",
+            V::AScript => "A",
+            V::AScriptFlip => "A.",
+        },
+        // eSpeak has no clicks or implosives, so they are spoken as the plosive at the same place
+        phone::Phoneme::NonPulmonicConsonant(c) => match c {
+            N::BilabialClick => "p",
+            N::DentalClick | N::Postalveoalar | N::AlveolarLateral => "t",
+            N::Palatoalveolar => "c",
+            N::BilabialImplosive => "b",
+            N::DentalImplosive => "d",
+            N::Palatal => "J",
+            N::Velar => "g",
+            N::Uvular => "G",
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translation() {
+        assert_eq!("'Sipa", mnemonics("ˈʃi.pa"));
+        assert_eq!("ta:n,&", mnemonics("taːn ˌæ˥").replace(' ', ""));
+        assert_eq!(WORDS_PER_MINUTE * 4 / 5, words_per_minute(Some("80%")));
+        assert_eq!(55, pitch(Some("+10%")));
+        assert_eq!(0, pitch(Some("-200%")));
+        assert_eq!(199, amplitude(Some("+6dB")));
+        assert_eq!(100, amplitude(Some("medium")));
+    }
+}
@@ -0,0 +1,295 @@
+//! Speech with [MBROLA](https://github.com/numediart/MBROLA), which joins recordings of diphones from a voice database
+//! on this computer. Unlike a text-to-speech voice, it is told exactly which phonemes to speak, for how long, and at
+//! what pitch, so length, stress, and tone come out as written. The `mbrola` program and a voice, like `en1`, have to
+//! be installed.
+//!
+//! Voices name their phonemes in SAMPA, which differs a little from voice to voice, so phonemes are given in X-SAMPA
+//! unless [`Mbrola::with_symbol`] says otherwise. Phonemes the voice does not have are left out.
+
+use super::{Format, Prosody, SpeechError, Synthesis, TtsBackend};
+use crate::{
+    phone::{self, Phoneme},
+    synth::{self, Sound},
+};
+use std::{collections::HashMap, fmt::Write, path::PathBuf};
+
+/// Speak with MBROLA, as WAV.
+pub struct Mbrola {
+    voice: PathBuf,
+    pitch: f64,
+    symbols: HashMap<Phoneme, String>,
+    prosody: Prosody,
+}
+
+impl Mbrola {
+    /// Speak with the voice database at `voice`, like `/usr/share/mbrola/en1/en1`, at 110 Hz.
+    pub fn new(voice: impl Into<PathBuf>) -> Self {
+        Self {
+            voice: voice.into(),
+            pitch: 110.0,
+            symbols: HashMap::new(),
+            prosody: Prosody::default(),
+        }
+    }
+
+    /// Speak at `pitch` Hz, which should be near the pitch of the voice.
+    pub fn with_pitch(mut self, pitch: f64) -> Self {
+        self.pitch = pitch;
+        self
+    }
+
+    /// Give the voice `symbol` for the `phoneme`, for voices which do not name it in X-SAMPA.
+    pub fn with_symbol(mut self, phoneme: Phoneme, symbol: impl Into<String>) -> Self {
+        self.symbols.insert(phoneme, symbol.into());
+        self
+    }
+
+    /// Speak with `prosody` unless a call says otherwise.
+    pub fn with_prosody(mut self, prosody: Prosody) -> Self {
+        self.prosody = prosody;
+        self
+    }
+
+    /// The MBROLA input for speaking `ipa` with `prosody`: a line for each phoneme with its symbol and milliseconds,
+    /// then the pitch at its start and end for voiced phonemes, between short silences.
+    fn pho(&self, ipa: &str, prosody: &Prosody) -> String {
+        let pause = prosody.pause.map_or(0.1, |pause| pause.as_secs_f64());
+        let mut out = String::from("_ 50\n");
+        for segment in synth::segments(ipa, pause) {
+            let ms = (segment.seconds / prosody.speed().max(0.1) * 1000.0).round();
+            let phoneme = match segment.sound {
+                Sound::Silence => {
+                    writeln!(out, "_ {ms}").unwrap();
+                    continue;
+                }
+                Sound::Glide(v) => {
+                    write!(out, "{} {ms}", glide(v)).unwrap();
+                    Phoneme::from(v)
+                }
+                Sound::Phoneme(phoneme) => {
+                    let symbol = self.symbols.get(&phoneme).map(String::as_str);
+                    write!(out, "{} {ms}", symbol.unwrap_or(sampa(phoneme))).unwrap();
+                    phoneme
+                }
+            };
+            if phoneme.is_voiced() {
+                let pitch = |at| self.pitch * prosody.pitch_scale() * segment.pitch_scale(at);
+                write!(out, " 0 {:.0} 100 {:.0}", pitch(0.0), pitch(1.0)).unwrap();
+            }
+            out.push('\n');
+        }
+        out.push_str("_ 50\n");
+        out
+    }
+
+    async fn speak(&self, ipa: &str, prosody: &Prosody) -> Result<bytes::Bytes, SpeechError> {
+        use std::io::Write;
+
+        let prosody = prosody.or(&self.prosody);
+        let pho = self.pho(ipa, &prosody);
+        let args = [
+            // Leave out phonemes the voice does not have instead of failing
+            "-e".into(),
+            "-v".into(),
+            prosody.gain().to_string(),
+            self.voice.display().to_string(),
+            "-".into(),
+            "-.wav".into(),
+        ];
+        let out = tokio::task::spawn_blocking(move || {
+            let mut child = std::process::Command::new("mbrola")
+                .args(&args)
+                .stdin(std::process::Stdio::piped())
+                .stdout(std::process::Stdio::piped())
+                .stderr(std::process::Stdio::piped())
+                .spawn()?;
+            // Dropping stdin closes it, so MBROLA knows the input is done
+            child.stdin.take().unwrap().write_all(pho.as_bytes())?;
+            child.wait_with_output()
+        })
+        .await
+        .map_err(|e| SpeechError::Mbrola(e.to_string()))?
+        .map_err(|e| SpeechError::Mbrola(format!("could not run it, is it installed? {e}")))?;
+        if !out.status.success() {
+            return Err(SpeechError::Mbrola(format!(
+                "it failed with {}: {}",
+                out.status,
+                String::from_utf8_lossy(&out.stderr).trim()
+            )));
+        }
+        Ok(out.stdout.into())
+    }
+}
+
+impl TtsBackend for Mbrola {
+    fn synthesize_with<'a>(&'a self, ipa: &'a str, prosody: &'a Prosody) -> Synthesis<'a> {
+        Box::pin(self.speak(ipa, prosody))
+    }
+
+    fn format(&self) -> Format {
+        Format::Wav
+    }
+
+    fn key(&self) -> String {
+        let mut symbols: Vec<_> = self.symbols.iter().collect();
+        symbols.sort();
+        let symbols: String = symbols
+            .into_iter()
+            .map(|(phoneme, symbol)| format!("{phoneme}{symbol}"))
+            .collect();
+        format!(
+            "mbrola-{}-{}-{symbols}-{}",
+            self.voice.display(),
+            self.pitch,
+            self.prosody.key()
+        )
+    }
+}
+
+/// The X-SAMPA for a vowel which moves straight on to the next sound, like /w/ for /u/.
+fn glide(v: phone::Vowel) -> &'static str {
+    match v {
+        phone::Vowel::U => "w",
+        phone::Vowel::Y => "H",
+        v => sampa(v.into()),
+    }
+}
+
+/// The X-SAMPA for the `phoneme`.
+fn sampa(phoneme: Phoneme) -> &'static str {
+    use phone::{Consonant as C, NonPulmonicConsonant as N, Vowel as V};
+
+    match phoneme {
+        Phoneme::Consonant(c) => match c {
+            C::P => "p",
+            C::B => "b",
+            C::T => "t",
+            C::D => "d",
+            C::TRetroflex => "t`",
+            C::DRetroflex => "d`",
+            C::C => "c",
+            C::JPalatal => "J\\",
+            C::K => "k",
+            C::G => "g",
+            C::Q => "q",
+            C::GCap => "G\\",
+            C::GlottalStop => "?",
+            C::M => "m",
+            C::MHook => "F",
+            C::N => "n",
+            C::NRetroflex => "n`",
+            C::NPalatal => "J",
+            C::NVelar => "N",
+            C::NUvular => "N\\",
+            C::BCap => "B\\",
+            C::Rrr => "r",
+            C::RCap => "R\\",
+            // X-SAMPA has no labiodental flap, so it is spoken as /v/
+            C::VTap => "v",
+            C::RTap => "4",
+            C::RFlap => "r`",
+            C::Phi => "p\\",
+            C::Beta => "B",
+            C::F => "f",
+            C::V => "v",
+            C::Theta => "T",
+            C::Del => "D",
+            C::S => "s",
+            C::Z => "z",
+            C::Esh => "S",
+            C::Ezh => "Z",
+            C::Sh => "s`",
+            C::Zh => "z`",
+            C::Ch => "C",
+            C::JCurl => "j\\",
+            C::X => "x",
+            C::Gamma => "G",
+            C::Xh => "X",
+            C::Yr => "R",
+            C::HBar => "X\\",
+            C::Crook => "?\\",
+            C::H => "h",
+            C::HCurl => "h\\",
+            C::LBelt => "K",
+            C::Lezh => "K\\",
+            C::VHook => "P",
+            C::RTilt => "r\\",
+            C::RTiltHook => "r\\`",
+            C::J => "j",
+            C::MTiltTail => "M\\",
+            C::L => "l",
+            C::Ll => "l`",
+            C::Lambda => "L",
+            C::LCap => "L\\",
+        },
+        Phoneme::Vowel(v) => match v {
+            V::I => "i",
+            V::Y => "y",
+            V::IBar => "1",
+            V::UBar => "}",
+            V::Uu => "M",
+            V::U => "u",
+            V::Ii => "I",
+            V::YCap => "Y",
+            V::OmegaFlip => "U",
+            V::E => "e",
+            V::OCross => "2",
+            V::EReverse => "@\\",
+            V::OBar => "8",
+            V::RamsHorns => "7",
+            V::O => "o",
+            V::Schwa => "@",
+            V::EOpen => "E",
+            V::Oe => "9",
+            V::Ze => "3",
+            V::EpsilonClosedReversed => "3\\",
+            V::VFlip => "V",
+            V::OOpen => "O",
+            V::Ae => "{",
+            V::AFlip => "6",
+            V::A => "a",
+            V::OeSmall => "&",
+            V::AScript => "A",
+            V::AScriptFlip => "Q",
+        },
+        Phoneme::NonPulmonicConsonant(c) => match c {
+            N::BilabialClick => "O\\",
+            N::DentalClick => "|\\",
+            N::Postalveoalar => "!\\",
+            N::Palatoalveolar => "=\\",
+            N::AlveolarLateral => "|\\|\\",
+            N::BilabialImplosive => "b_<",
+            N::DentalImplosive => "d_<",
+            N::Palatal => "J\\_<",
+            N::Velar => "g_<",
+            N::Uvular => "G\\_<",
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pho() {
+        let mbrola = Mbrola::new("en1").with_symbol(Phoneme::try_from('ʃ').unwrap(), "sh");
+        let pho = mbrola.pho("ˈʃaː.ta˥", &Prosody::default());
+        let lines: Vec<_> = pho.lines().collect();
+        assert_eq!(6, lines.len());
+        assert_eq!("sh 110", lines[1]);
+        // Stressed and long, and higher for the stress
+        assert_eq!("a 287 0 126 100 126", lines[2]);
+        assert_eq!("t 90", lines[3]);
+        // High tone
+        assert_eq!("a 130 0 138 100 138", lines[4]);
+
+        let slow = Prosody {
+            rate: Some("50%".into()),
+            ..Prosody::default()
+        };
+        assert!(mbrola.pho("ta", &slow).contains("t 180\n"));
+        assert!(mbrola.pho("wa ɓa", &slow).starts_with("_ 50\nw "));
+        assert!(mbrola.pho("wa ɓa", &slow).contains("\nb_< "));
+    }
+}
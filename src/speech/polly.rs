@@ -0,0 +1,246 @@
+//! Speech with [Amazon Polly](https://aws.amazon.com/polly/), which reads IPA through SSML `<phoneme>` tags.
+
+use super::{Clip, Format, Prosody, SpeechError, Synthesis, TtsBackend};
+use itertools::Itertools;
+
+/// The Polly engine to speak with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Engine {
+    /// More natural, but not every voice has it.
+    #[default]
+    Neural,
+    Standard,
+}
+
+/// Everything about how Polly speaks but what it says.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PollyConfig {
+    /// The voice to speak with, like `Joanna`, `Matthew`, or `Lupe`.
+    pub voice: String,
+    pub engine: Engine,
+    /// The format of the audio. Polly makes WAV as raw 16-bit PCM, which is given a header here.
+    pub format: Format,
+    /// Samples a second, or Polly's default for the format and engine. Ogg Vorbis can be 8000, 16000, 22050, or 24000,
+    /// and WAV 8000 or 16000.
+    pub sample_rate: Option<u32>,
+    /// The language to speak with, like `en-IN`, for voices which speak more than one.
+    pub language_code: Option<String>,
+}
+
+impl Default for PollyConfig {
+    /// Joanna, with the neural engine, as Ogg Vorbis.
+    fn default() -> Self {
+        Self {
+            voice: "Joanna".into(),
+            engine: Engine::default(),
+            format: Format::OggVorbis,
+            sample_rate: None,
+            language_code: None,
+        }
+    }
+}
+
+impl PollyConfig {
+    /// The samples a second of WAV audio, which Polly does not say, so has to be known to write the header.
+    fn pcm_rate(&self) -> u32 {
+        self.sample_rate.unwrap_or(16000)
+    }
+}
+
+/// Speak with Amazon Polly.
+pub struct Polly {
+    client: aws_sdk_polly::Client,
+    config: PollyConfig,
+    prosody: Prosody,
+}
+
+impl Polly {
+    pub fn new(client: aws_sdk_polly::Client, config: PollyConfig) -> Self {
+        Self {
+            client,
+            config,
+            prosody: Prosody::default(),
+        }
+    }
+
+    pub fn config(&self) -> &PollyConfig {
+        &self.config
+    }
+
+    /// Speak with `prosody` unless a call says otherwise. Neural voices can not change their pitch.
+    pub fn with_prosody(mut self, prosody: Prosody) -> Self {
+        self.prosody = prosody;
+        self
+    }
+
+    async fn speak(&self, ipa: &str, prosody: &Prosody) -> Result<bytes::Bytes, SpeechError> {
+        let text = ssml(ipa, &prosody.or(&self.prosody));
+        let resp = self
+            .client
+            .synthesize_speech()
+            .output_format(match self.config.format {
+                Format::OggVorbis => aws_sdk_polly::types::OutputFormat::OggVorbis,
+                Format::Wav => aws_sdk_polly::types::OutputFormat::Pcm,
+            })
+            .set_sample_rate(self.config.sample_rate.map(|rate| rate.to_string()))
+            .text_type(aws_sdk_polly::types::TextType::Ssml)
+            .text(text)
+            .voice_id(aws_sdk_polly::types::VoiceId::from(
+                self.config.voice.as_str(),
+            ))
+            .engine(match self.config.engine {
+                Engine::Neural => aws_sdk_polly::types::Engine::Neural,
+                Engine::Standard => aws_sdk_polly::types::Engine::Standard,
+            })
+            .set_language_code(
+                self.config
+                    .language_code
+                    .as_deref()
+                    .map(aws_sdk_polly::types::LanguageCode::from),
+            )
+            .send()
+            .await
+            .map_err(error)?;
+        let blob = resp
+            .audio_stream
+            .collect()
+            .await
+            .map_err(|e| SpeechError::Unavailable(e.to_string()))?;
+        match self.config.format {
+            Format::OggVorbis => Ok(blob.into_bytes()),
+            Format::Wav => {
+                let samples = blob
+                    .as_ref()
+                    .chunks_exact(2)
+                    .map(|x| i16::from_le_bytes([x[0], x[1]]))
+                    .collect();
+                Ok(Clip::new(samples, self.config.pcm_rate()).to_wav())
+            }
+        }
+    }
+}
+
+impl TtsBackend for Polly {
+    fn synthesize_with<'a>(&'a self, ipa: &'a str, prosody: &'a Prosody) -> Synthesis<'a> {
+        Box::pin(self.speak(ipa, prosody))
+    }
+
+    fn format(&self) -> Format {
+        self.config.format
+    }
+
+    fn key(&self) -> String {
+        format!(
+            "{}-{:?}-{}-{}-{}",
+            self.config.voice,
+            self.config.engine,
+            self.config.language_code.as_deref().unwrap_or_default(),
+            self.config
+                .sample_rate
+                .map(|rate| rate.to_string())
+                .unwrap_or_default(),
+            self.prosody.key(),
+        )
+    }
+}
+
+/// The error for a failed request, telling throttling and failures which may not happen again from the rest.
+fn error(
+    e: aws_sdk_polly::error::SdkError<
+        aws_sdk_polly::operation::synthesize_speech::SynthesizeSpeechError,
+    >,
+) -> SpeechError {
+    use aws_sdk_polly::error::{DisplayErrorContext, ProvideErrorMetadata, SdkError};
+
+    let message = DisplayErrorContext(&e).to_string();
+    match &e {
+        SdkError::TimeoutError(_) | SdkError::DispatchFailure(_) | SdkError::ResponseError(_) => {
+            SpeechError::Unavailable(message)
+        }
+        SdkError::ServiceError(service) => {
+            let status = service.raw().http().status().as_u16();
+            if status == 429 || service.err().code() == Some("ThrottlingException") {
+                SpeechError::Throttled(message)
+            } else if status >= 500 {
+                SpeechError::Unavailable(message)
+            } else {
+                SpeechError::Polly(message)
+            }
+        }
+        _ => SpeechError::Polly(message),
+    }
+}
+
+/// The SSML for speaking `ipa` with `prosody`, with each word in its own `<phoneme>` if there is a pause between them.
+fn ssml(ipa: &str, prosody: &Prosody) -> String {
+    let phoneme = |ipa: &str| {
+        format!(
+            r#"<phoneme alphabet="ipa" ph="{}"></phoneme>"#,
+            xml_escape(ipa)
+        )
+    };
+    let mut text = match prosody.pause {
+        Some(pause) => ipa
+            .split_whitespace()
+            .map(phoneme)
+            .join(&format!(r#"<break time="{}ms"/>"#, pause.as_millis())),
+        None => phoneme(ipa),
+    };
+    let settings = [
+        ("rate", &prosody.rate),
+        ("pitch", &prosody.pitch),
+        ("volume", &prosody.volume),
+    ];
+    let attrs: String = settings
+        .into_iter()
+        .filter_map(|(name, value)| Some(format!(r#" {name}="{}""#, xml_escape(value.as_ref()?))))
+        .collect();
+    if !attrs.is_empty() {
+        text = format!("<prosody{attrs}>{text}</prosody>");
+    }
+    format!("<speak>{text}</speak>")
+}
+
+/// Escape the characters which are special in XML, so `src` can go in an attribute of the SSML sent for speech.
+fn xml_escape(src: &str) -> String {
+    let mut out = String::with_capacity(src.len());
+    for c in src.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn prosody() {
+        assert_eq!(
+            r#"<speak><phoneme alphabet="ipa" ph="ˈka.ta ki"></phoneme></speak>"#,
+            ssml("ˈka.ta ki", &Prosody::default())
+        );
+        let prosody = Prosody {
+            rate: Some("slow".into()),
+            volume: Some("+6dB".into()),
+            pause: Some(Duration::from_millis(300)),
+            ..Prosody::default()
+        };
+        assert_eq!(
+            concat!(
+                r#"<speak><prosody rate="slow" volume="+6dB">"#,
+                r#"<phoneme alphabet="ipa" ph="ˈka.ta"></phoneme><break time="300ms"/>"#,
+                r#"<phoneme alphabet="ipa" ph="ki"></phoneme></prosody></speak>"#,
+            ),
+            ssml("ˈka.ta ki", &prosody)
+        );
+    }
+}
@@ -0,0 +1,654 @@
+//! # Synthesis
+//!
+//! A small [formant synthesizer](https://en.wikipedia.org/wiki/Formant_synthesis) in the style of Dennis Klatt's, which
+//! speaks IPA with nothing installed, no network connection, and no credentials. Instead of recordings of a voice, each
+//! phoneme is built from its articulatory features: the height, frontness, and rounding of a vowel set its formants,
+//! while the place and manner of a consonant set where its noise is and how it starts and stops. It sounds robotic,
+//! but every [`Phoneme`] gets a sound of its own, including the ones voices of natural languages mangle, like clicks.
+//!
+//! A buzz like the vocal folds and a hiss like breath go through a chain of resonators, one for each formant, like
+//! sound through the vocal tract. The noise of a narrow gap, like /s/, goes through a resonator of its own. The
+//! settings of the resonators move smoothly from one phoneme to the next, which is most of what makes the phonemes
+//! sound like speech instead of a row of beeps.
+//!
+//! This needs the `speech` feature.
+
+use crate::{
+    phone::{Manner, NonPulmonicConsonant, Phoneme, Place, Tone, Vowel},
+    speech::{Clip, Format, Prosody, Synthesis, TtsBackend},
+};
+use std::f64::consts::PI;
+
+/// Samples a second of the audio.
+const RATE: u32 = 16000;
+
+/// Settings change once a frame, which is 5 milliseconds.
+const FRAME: usize = 80;
+
+/// Speak with the formant synthesizer, as WAV.
+#[derive(Clone, Debug)]
+pub struct Formant {
+    pitch: f64,
+    prosody: Prosody,
+}
+
+impl Default for Formant {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Formant {
+    /// Speak at 110 Hz, around the pitch of an adult man.
+    pub fn new() -> Self {
+        Self {
+            pitch: 110.0,
+            prosody: Prosody::default(),
+        }
+    }
+
+    /// Speak at `pitch` Hz, like 200 for around the pitch of an adult woman.
+    pub fn with_pitch(mut self, pitch: f64) -> Self {
+        self.pitch = pitch;
+        self
+    }
+
+    /// Speak with `prosody` unless a call says otherwise.
+    pub fn with_prosody(mut self, prosody: Prosody) -> Self {
+        self.prosody = prosody;
+        self
+    }
+
+    /// Speak the IPA in `ipa` with `prosody` over this synthesizer's own. Characters which are not phonemes, stress
+    /// marks, length marks, or tone letters are left out.
+    pub fn speak(&self, ipa: &str, prosody: &Prosody) -> Clip {
+        let prosody = prosody.or(&self.prosody);
+        let pause = prosody.pause.map_or(0.1, |pause| pause.as_secs_f64());
+        let segments = segments(ipa, pause);
+
+        let mut frames = Vec::new();
+        for segment in segments.iter() {
+            let seconds = segment.seconds / prosody.speed().max(0.1);
+            let count = ((seconds * RATE as f64) / FRAME as f64).round().max(1.0) as usize;
+            for idx in 0..count {
+                let at = idx as f64 / count as f64;
+                let mut frame = frame(segment.sound, at, seconds);
+                frame.pitch = self.pitch * prosody.pitch_scale() * segment.pitch_scale(at);
+                frames.push(frame);
+            }
+        }
+        // Speech falls in pitch as it goes on
+        let len = frames.len() as f64;
+        for (idx, frame) in frames.iter_mut().enumerate() {
+            frame.pitch *= 1.0 - 0.1 * idx as f64 / len;
+        }
+
+        let mut samples = render(&frames);
+        let peak = samples.iter().fold(0.0f64, |peak, x| peak.max(x.abs()));
+        let scale = if peak > 0.0 {
+            0.5 * prosody.gain() / peak
+        } else {
+            0.0
+        };
+        for sample in samples.iter_mut() {
+            *sample = (*sample * scale).clamp(-1.0, 1.0);
+        }
+        Clip::new(
+            samples
+                .into_iter()
+                .map(|x| (x * i16::MAX as f64) as i16)
+                .collect(),
+            RATE,
+        )
+    }
+}
+
+impl TtsBackend for Formant {
+    fn synthesize_with<'a>(&'a self, ipa: &'a str, prosody: &'a Prosody) -> Synthesis<'a> {
+        Box::pin(async move { Ok(self.speak(ipa, prosody).to_wav()) })
+    }
+
+    fn format(&self) -> Format {
+        Format::Wav
+    }
+
+    fn key(&self) -> String {
+        format!("formant-{}-{}", self.pitch, self.prosody.key())
+    }
+}
+
+/// Something to make a sound for.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Sound {
+    Phoneme(Phoneme),
+    /// A vowel which moves straight on to the next sound, like /w/, which is a short /u/.
+    Glide(Vowel),
+    Silence,
+}
+
+/// A sound, for how long, and how.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Segment {
+    pub sound: Sound,
+    pub seconds: f64,
+    pub stressed: bool,
+    /// The tone levels at the start and end of the sound, from 1 for low to 5 for high.
+    pub tone: Option<(u8, u8)>,
+}
+
+impl Segment {
+    /// How much higher or lower than normal the pitch is `at` a point from 0 to 1 through the sound, which is higher
+    /// for stress, and follows the tone from about three quarters of normal for the lowest to a quarter more for the
+    /// highest.
+    pub fn pitch_scale(&self, at: f64) -> f64 {
+        let tone = self.tone.map_or(1.0, |(start, end)| {
+            let level = start as f64 + (end as f64 - start as f64) * at;
+            0.75 + (level - 1.0) / 8.0
+        });
+        let stress = if self.stressed { 1.15 } else { 1.0 };
+        tone * stress
+    }
+}
+
+/// Split the IPA in `src` into the sounds to make, with `pause` seconds of silence between words. Stress lasts until
+/// the end of the syllable, and a tone goes on every sound of the syllable it ends.
+pub(crate) fn segments(src: &str, pause: f64) -> Vec<Segment> {
+    let mut out: Vec<Segment> = Vec::new();
+    let mut stressed = false;
+    let mut syllable = 0;
+    for c in src.chars() {
+        let sound = match c {
+            'ˈ' | 'ˌ' => {
+                syllable = out.len();
+                stressed = true;
+                continue;
+            }
+            '.' => {
+                syllable = out.len();
+                stressed = false;
+                continue;
+            }
+            'ː' | 'ˑ' => {
+                if let Some(last) = out.last_mut() {
+                    last.seconds *= if c == 'ː' { 1.7 } else { 1.3 };
+                }
+                continue;
+            }
+            'w' => Sound::Glide(Vowel::U),
+            'ɥ' => Sound::Glide(Vowel::Y),
+            c if c.is_whitespace() => {
+                syllable = out.len() + 1;
+                stressed = false;
+                Sound::Silence
+            }
+            c => {
+                if let Some(level) = Tone::level_of(c) {
+                    for segment in out[syllable..].iter_mut() {
+                        segment.tone = Some(match segment.tone {
+                            Some((start, _)) => (start, level),
+                            None => (level, level),
+                        });
+                    }
+                    continue;
+                }
+                match Phoneme::try_from(c) {
+                    Ok(phoneme) => Sound::Phoneme(phoneme),
+                    Err(_) => continue,
+                }
+            }
+        };
+        let seconds = match sound {
+            Sound::Silence => pause,
+            Sound::Phoneme(Phoneme::Vowel(_)) if stressed => duration(sound) * 1.3,
+            _ => duration(sound),
+        };
+        out.push(Segment {
+            sound,
+            seconds,
+            stressed,
+            tone: None,
+        });
+    }
+    out
+}
+
+/// How many seconds the `sound` lasts, before stress, length, and rate.
+fn duration(sound: Sound) -> f64 {
+    match sound {
+        Sound::Phoneme(Phoneme::Vowel(_)) => 0.13,
+        Sound::Phoneme(Phoneme::Consonant(c)) => match c.manner() {
+            Manner::Plosive => 0.09,
+            Manner::Nasal => 0.07,
+            Manner::Trill => 0.09,
+            Manner::Tap => 0.03,
+            Manner::Fricative | Manner::LateralFricative => 0.11,
+            Manner::Approximant | Manner::LateralApproximant => 0.07,
+        },
+        Sound::Phoneme(Phoneme::NonPulmonicConsonant(c)) => match click(c) {
+            Some(_) => 0.05,
+            None => 0.09,
+        },
+        Sound::Glide(_) => 0.06,
+        Sound::Silence => 0.1,
+    }
+}
+
+/// The settings of the synthesizer for one frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Frame {
+    /// The frequency and bandwidth of the first three formants, in Hz.
+    formants: [(f64, f64); 3],
+    /// How loud the buzz of the vocal folds is, from 0 to 1.
+    voice: f64,
+    /// How loud breath through the vocal tract is, like /h/.
+    breath: f64,
+    /// How loud the noise of a narrow gap is, like /s/, with the frequency and bandwidth it is loudest over.
+    noise: (f64, f64, f64),
+    /// The pitch of the voice, in Hz.
+    pitch: f64,
+}
+
+/// A vocal tract at rest, like /ə/.
+const NEUTRAL: [f64; 3] = [500.0, 1500.0, 2500.0];
+
+impl Frame {
+    fn new(formants: [f64; 3]) -> Self {
+        Self {
+            formants: [
+                (formants[0], 80.0),
+                (formants[1], 100.0),
+                (formants[2], 150.0),
+            ],
+            voice: 0.0,
+            breath: 0.0,
+            noise: (0.0, 1000.0, 1000.0),
+            pitch: 0.0,
+        }
+    }
+
+    fn voiced(mut self, voice: f64) -> Self {
+        self.voice = voice;
+        self
+    }
+
+    fn with_noise(mut self, (freq, bandwidth, loudness): (f64, f64, f64), scale: f64) -> Self {
+        self.noise = (loudness * scale, freq, bandwidth);
+        self
+    }
+}
+
+/// The settings for `sound`, `at` a point from 0 to 1 through it, which lasts `seconds`.
+fn frame(sound: Sound, at: f64, seconds: f64) -> Frame {
+    let c = match sound {
+        Sound::Silence => return Frame::new(NEUTRAL),
+        Sound::Glide(v) => return Frame::new(vowel(v)).voiced(0.8),
+        Sound::Phoneme(Phoneme::Vowel(v)) => return Frame::new(vowel(v)).voiced(1.0),
+        Sound::Phoneme(Phoneme::NonPulmonicConsonant(c)) => match click(c) {
+            // A sharp pop, then silence
+            Some(place) => {
+                let frame = Frame::new(locus(place));
+                return if at < 0.25 {
+                    frame.with_noise(noise(place), 2.0)
+                } else {
+                    frame
+                };
+            }
+            // Air is drawn in instead of pushed out, so the voice goes on through the closure and the release is soft
+            None => {
+                let place = implosive(c);
+                let frame = Frame::new(locus(place));
+                return match at {
+                    at if at < 0.65 => frame.voiced(0.35),
+                    at if at < 0.75 => frame.voiced(0.5).with_noise(noise(place), 0.3),
+                    _ => frame.voiced(0.6),
+                };
+            }
+        },
+        Sound::Phoneme(Phoneme::Consonant(c)) => c,
+    };
+    let (place, voiced) = (c.place(), c.is_voiced());
+    match c.manner() {
+        Manner::Plosive => stop(place, voiced, at),
+        Manner::Nasal => {
+            let mut frame = Frame::new([250.0, nasal(place), 2300.0]).voiced(0.5);
+            frame.formants[0].1 = 150.0;
+            frame.formants[1].1 = 300.0;
+            frame
+        }
+        Manner::Trill => {
+            // The tongue or lips flap against the air about 25 times a second
+            let flap = (2.0 * PI * 25.0 * at * seconds).cos();
+            let formants = match place {
+                Place::Alveolar => approximant(place, false),
+                _ => locus(place),
+            };
+            Frame::new(formants).voiced(0.45 + 0.25 * flap)
+        }
+        Manner::Tap => {
+            let dip = 1.0 - (PI * at).sin();
+            Frame::new(locus(place)).voiced(0.2 + 0.5 * dip)
+        }
+        Manner::Fricative | Manner::LateralFricative => {
+            let noise = match c.manner() {
+                Manner::LateralFricative => (4000.0, 2500.0, 0.6),
+                _ => noise(place),
+            };
+            match place {
+                // Breath through the vocal tract, shaped like the sounds around it
+                Place::Glottal => Frame {
+                    breath: 0.7,
+                    ..Frame::new(NEUTRAL).voiced(if voiced { 0.3 } else { 0.0 })
+                },
+                _ if voiced => Frame::new(locus(place)).voiced(0.4).with_noise(noise, 0.5),
+                _ => Frame::new(locus(place)).with_noise(noise, 1.0),
+            }
+        }
+        Manner::Approximant | Manner::LateralApproximant => {
+            Frame::new(approximant(place, c.manner() == Manner::LateralApproximant)).voiced(0.7)
+        }
+    }
+}
+
+/// The settings for a stop at `place`, `at` a point from 0 to 1 through it: silence while the air is held, or a quiet
+/// buzz if it is `voiced`, then a burst of noise as it is let go, then breath if it is not voiced, like English /p/.
+fn stop(place: Place, voiced: bool, at: f64) -> Frame {
+    let frame = Frame::new(locus(place));
+    match at {
+        _ if place == Place::Glottal => frame,
+        at if at < 0.65 => frame.voiced(if voiced { 0.1 } else { 0.0 }),
+        at if at < 0.75 => frame.with_noise(noise(place), 1.0),
+        _ if voiced => frame.voiced(0.6),
+        _ => Frame {
+            breath: 0.4,
+            ..frame
+        },
+    }
+}
+
+/// The place of the click `c`, or nothing if it is not a click.
+fn click(c: NonPulmonicConsonant) -> Option<Place> {
+    match c {
+        NonPulmonicConsonant::BilabialClick => Some(Place::Bilabial),
+        NonPulmonicConsonant::DentalClick => Some(Place::Dental),
+        NonPulmonicConsonant::Postalveoalar => Some(Place::PostAlveolar),
+        NonPulmonicConsonant::Palatoalveolar => Some(Place::Palatal),
+        NonPulmonicConsonant::AlveolarLateral => Some(Place::Alveolar),
+        _ => None,
+    }
+}
+
+/// The place of the implosive `c`, which is spoken like a voiced plosive.
+fn implosive(c: NonPulmonicConsonant) -> Place {
+    match c {
+        NonPulmonicConsonant::BilabialImplosive => Place::Bilabial,
+        NonPulmonicConsonant::DentalImplosive => Place::Alveolar,
+        NonPulmonicConsonant::Palatal => Place::Palatal,
+        NonPulmonicConsonant::Velar => Place::Velar,
+        _ => Place::Uvular,
+    }
+}
+
+/// The formants of the vowel `v`. The first formant rises as the vowel opens, the second as it goes forward, and
+/// rounding lowers both the second and third.
+fn vowel(v: Vowel) -> [f64; 3] {
+    let height = v.height().value() as f64;
+    let front = v.frontness().value() as f64;
+    let f1 = 250.0 + (9.0 - height) * 75.0;
+    let mut f2 = 850.0 + (front - 1.0) * 180.0;
+    let mut f3 = 2500.0 + (front - 1.0) * 50.0;
+    // Open vowels have less room to move forward and back in
+    f2 -= (f2 - 1400.0) * (9.0 - height) / 8.0 * 0.3;
+    if v.is_rounded() {
+        f2 *= 0.85;
+        f3 -= 250.0;
+    }
+    [f1, f2, f3]
+}
+
+/// The formants the vocal tract moves toward when it is closed at `place`, which are what tell /b/, /d/, and /g/
+/// apart.
+fn locus(place: Place) -> [f64; 3] {
+    let f2 = match place {
+        Place::Bilabial => 800.0,
+        Place::Labiodental => 1000.0,
+        Place::Dental => 1500.0,
+        Place::Alveolar => 1700.0,
+        Place::PostAlveolar => 1900.0,
+        Place::Retroflex => 1500.0,
+        Place::Palatal => 2200.0,
+        Place::Velar => 1800.0,
+        Place::Uvular => 1200.0,
+        Place::Pharyngeal => 1100.0,
+        Place::Glottal => return NEUTRAL,
+    };
+    let f3 = if place == Place::Retroflex {
+        1800.0
+    } else {
+        2500.0
+    };
+    [300.0, f2, f3]
+}
+
+/// The second formant of a nasal at `place`.
+fn nasal(place: Place) -> f64 {
+    match place {
+        Place::Bilabial => 1000.0,
+        Place::Labiodental => 1100.0,
+        Place::Dental | Place::Alveolar => 1500.0,
+        Place::PostAlveolar => 1700.0,
+        Place::Retroflex => 1400.0,
+        Place::Palatal => 2100.0,
+        Place::Velar => 1900.0,
+        _ => 1300.0,
+    }
+}
+
+/// The formants of an approximant at `place`, which is `lateral` like /l/, or not like /j/.
+fn approximant(place: Place, lateral: bool) -> [f64; 3] {
+    match (place, lateral) {
+        (Place::Labiodental, false) => [350.0, 1400.0, 2400.0],
+        (Place::Alveolar | Place::Dental, false) => [350.0, 1300.0, 1700.0],
+        (Place::Retroflex, false) => [350.0, 1300.0, 1500.0],
+        (Place::Palatal, false) => [280.0, 2200.0, 2900.0],
+        (Place::Velar, false) => [320.0, 1000.0, 2400.0],
+        (Place::Retroflex, true) => [380.0, 1100.0, 2200.0],
+        (Place::Palatal, true) => [300.0, 1900.0, 2700.0],
+        (Place::Velar, true) => [350.0, 900.0, 2500.0],
+        (_, true) => [380.0, 1100.0, 2600.0],
+        (_, false) => [350.0, 1400.0, 2500.0],
+    }
+}
+
+/// The frequency, bandwidth, and loudness of the noise of air through a narrow gap at `place`. The further forward the
+/// gap, the higher the noise, and /s/ and /ʃ/ are much louder than /f/ and /θ/, since the air hits the teeth.
+fn noise(place: Place) -> (f64, f64, f64) {
+    match place {
+        Place::Bilabial => (1200.0, 2000.0, 0.3),
+        Place::Labiodental => (5000.0, 3000.0, 0.35),
+        Place::Dental => (6000.0, 3000.0, 0.35),
+        Place::Alveolar => (5500.0, 1500.0, 1.0),
+        Place::PostAlveolar => (3000.0, 1200.0, 1.0),
+        Place::Retroflex => (2500.0, 1000.0, 0.9),
+        Place::Palatal => (3500.0, 1500.0, 0.8),
+        Place::Velar => (2000.0, 1200.0, 0.6),
+        Place::Uvular => (1200.0, 800.0, 0.5),
+        Place::Pharyngeal => (900.0, 800.0, 0.4),
+        Place::Glottal => (1500.0, 3000.0, 0.3),
+    }
+}
+
+/// A two-pole filter which lets through frequencies near its own.
+#[derive(Clone, Copy, Debug, Default)]
+struct Resonator {
+    a: f64,
+    b: f64,
+    c: f64,
+    last: (f64, f64),
+}
+
+impl Resonator {
+    /// Resonate at `freq` with `bandwidth`, passing low frequencies through unchanged, like the resonators of Klatt's
+    /// cascade.
+    fn set(&mut self, freq: f64, bandwidth: f64) {
+        let t = 1.0 / RATE as f64;
+        self.c = -(-2.0 * PI * bandwidth * t).exp();
+        self.b = 2.0 * (-PI * bandwidth * t).exp() * (2.0 * PI * freq * t).cos();
+        self.a = 1.0 - self.b - self.c;
+    }
+
+    /// Resonate at `freq` with `bandwidth`, passing that frequency through unchanged, for noise which has to be the
+    /// same loudness wherever it is.
+    fn set_peak(&mut self, freq: f64, bandwidth: f64) {
+        self.set(freq, bandwidth);
+        let t = 1.0 / RATE as f64;
+        let r = (-PI * bandwidth * t).exp();
+        self.a = (1.0 - r) * (1.0 - 2.0 * r * (4.0 * PI * freq * t).cos() + r * r).sqrt();
+    }
+
+    fn next(&mut self, x: f64) -> f64 {
+        let y = self.a * x + self.b * self.last.0 + self.c * self.last.1;
+        self.last = (y, self.last.0);
+        y
+    }
+}
+
+/// The shape of one pulse of air through the vocal folds, `at` a point from 0 to 1 through it: opening slowly, closing
+/// quickly, then staying closed.
+fn glottal(at: f64) -> f64 {
+    const OPENING: f64 = 0.4;
+    const CLOSING: f64 = 0.16;
+    if at < OPENING {
+        0.5 * (1.0 - (PI * at / OPENING).cos())
+    } else if at < OPENING + CLOSING {
+        (PI / 2.0 * (at - OPENING) / CLOSING).cos()
+    } else {
+        0.0
+    }
+}
+
+/// Make the audio of `frames`, from -1 to 1 but not yet at any particular loudness. Settings move toward those of each
+/// frame instead of jumping to them, so the formants glide from one sound to the next.
+fn render(frames: &[Frame]) -> Vec<f64> {
+    let Some(first) = frames.first() else {
+        return Vec::new();
+    };
+    let mut at = *first;
+    let mut cascade = [Resonator::default(); 4];
+    cascade[3].set(3500.0, 250.0);
+    let mut gap = Resonator::default();
+    // A fixed seed, so the same IPA always sounds the same
+    let mut seed = 0x2545_f491_u32;
+    let mut noise = || {
+        seed ^= seed << 13;
+        seed ^= seed >> 17;
+        seed ^= seed << 5;
+        seed as f64 / u32::MAX as f64 * 2.0 - 1.0
+    };
+    let (mut phase, mut pulse) = (0.0, 0.0);
+
+    let mut out = Vec::with_capacity(frames.len() * FRAME);
+    for frame in frames {
+        let glide = |from: f64, to: f64, speed: f64| from + (to - from) * speed;
+        for (idx, formant) in at.formants.iter_mut().enumerate() {
+            formant.0 = glide(formant.0, frame.formants[idx].0, 0.35);
+            formant.1 = glide(formant.1, frame.formants[idx].1, 0.35);
+            cascade[idx].set(formant.0, formant.1);
+        }
+        at.voice = glide(at.voice, frame.voice, 0.6);
+        at.breath = glide(at.breath, frame.breath, 0.6);
+        at.noise.0 = glide(at.noise.0, frame.noise.0, 0.6);
+        // The noise jumps to where it is, since it is too short to glide far
+        (at.noise.1, at.noise.2) = (frame.noise.1, frame.noise.2);
+        gap.set_peak(at.noise.1, at.noise.2);
+        at.pitch = if at.pitch > 0.0 {
+            glide(at.pitch, frame.pitch, 0.3)
+        } else {
+            frame.pitch
+        };
+
+        for _ in 0..FRAME {
+            phase = (phase + at.pitch / RATE as f64).fract();
+            let next = glottal(phase);
+            // The lips let through the change in the flow of air, not the flow itself
+            let voice = (next - pulse) * 20.0;
+            pulse = next;
+            let mut sample = at.voice * voice + at.breath * noise() * 0.25;
+            for resonator in cascade.iter_mut() {
+                sample = resonator.next(sample);
+            }
+            out.push(sample + gap.next(at.noise.0 * noise()));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::phone::Consonant;
+
+    #[test]
+    fn parse() {
+        let parsed = segments("ˈka.ta ma˥˩", 0.2);
+        let sounds: Vec<_> = parsed.iter().map(|segment| segment.sound).collect();
+        let phoneme = |c| Sound::Phoneme(Phoneme::try_from(c).unwrap());
+        assert_eq!(
+            vec![
+                phoneme('k'),
+                phoneme('a'),
+                phoneme('t'),
+                phoneme('a'),
+                Sound::Silence,
+                phoneme('m'),
+                phoneme('a'),
+            ],
+            sounds
+        );
+        assert!(parsed[1].stressed && !parsed[3].stressed);
+        assert!(parsed[1].seconds > parsed[3].seconds);
+        assert_eq!(0.2, parsed[4].seconds);
+        assert_eq!(None, parsed[3].tone);
+        assert_eq!(Some((5, 1)), parsed[6].tone);
+
+        let long = segments("aːw", 0.1);
+        assert_eq!(Sound::Glide(Vowel::U), long[1].sound);
+        assert!(long[0].seconds > duration(long[0].sound));
+    }
+
+    #[test]
+    fn every_phoneme() {
+        let phonemes = Consonant::all()
+            .iter()
+            .map(|&c| Phoneme::from(c))
+            .chain(Vowel::all().iter().map(|&v| Phoneme::from(v)))
+            .chain(
+                NonPulmonicConsonant::all()
+                    .iter()
+                    .map(|&c| Phoneme::from(c)),
+            );
+        let formant = Formant::new();
+        let plain = formant.speak("aa", &Prosody::default());
+        let mut heard = std::collections::HashSet::new();
+        for phoneme in phonemes {
+            let clip = formant.speak(&format!("a{phoneme}a"), &Prosody::default());
+            assert!(clip.duration() > plain.duration(), "{phoneme}");
+            assert!(
+                heard.insert(clip.samples().to_vec()),
+                "{phoneme} sounds like another phoneme"
+            );
+        }
+    }
+
+    #[test]
+    fn prosody() {
+        let formant = Formant::new();
+        let normal = formant.speak("ˈka.ta", &Prosody::default());
+        let slow = Prosody {
+            rate: Some("50%".into()),
+            ..Prosody::default()
+        };
+        let slow = formant.speak("ˈka.ta", &slow);
+        assert!(slow.duration() > normal.duration() * 19 / 10);
+        assert!(formant.speak("", &Prosody::default()).samples().is_empty());
+    }
+}
@@ -0,0 +1,104 @@
+//! # Trainer
+//!
+//! A language with /s/, /ʃ/, and /ɕ/ only works for someone who can hear the difference, and that takes practice.
+//! The trainer plays the reference sound of a phoneme, the same one the inventory picker plays, and asks which phoneme
+//! it was. A wrong answer plays the right phoneme and the answer one after the other, so the two can be compared. Like
+//! the quiz, it reads answers a line at a time, so it works in any terminal.
+
+use crate::{picker, SpeakerBox};
+use conlang::phone::Phoneme;
+use itertools::Itertools;
+use rand::{seq::SliceRandom, Rng};
+use std::{
+    collections::HashMap,
+    io::{BufRead, Write},
+};
+
+/// How training went.
+#[derive(Clone, Debug, Default)]
+pub struct Score {
+    pub right: usize,
+    pub asked: usize,
+    /// How many times each phoneme played was taken for another, by the phoneme played and the answer.
+    pub confusions: HashMap<(Phoneme, Phoneme), usize>,
+}
+
+impl Score {
+    /// The phonemes most often taken for one another, most often first, as the phoneme played, the answer, and how
+    /// many times.
+    pub fn most_confused(&self) -> Vec<(Phoneme, Phoneme, usize)> {
+        self.confusions
+            .iter()
+            .map(|(&(played, answer), &times)| (played, answer, times))
+            .sorted_by_key(|&(played, answer, times)| (std::cmp::Reverse(times), played, answer))
+            .collect()
+    }
+}
+
+/// Play `count` phonemes picked at random from the `phonemes` with the `speaker`, reading which one each was from
+/// stdin. An empty answer plays the phoneme again, `?` lists the phonemes it could be, and `+` or `-` make it louder or
+/// softer. Training stops early at the end of input, so only the phonemes which were answered count towards the score.
+pub async fn run(
+    phonemes: &[Phoneme],
+    count: usize,
+    rng: &mut impl Rng,
+    speaker: &SpeakerBox,
+) -> Result<Score, anyhow::Error> {
+    let choices = phonemes.iter().join(" ");
+    println!("phonemes: {choices}");
+    let mut lines = std::io::stdin().lock().lines();
+    let mut score = Score::default();
+    for idx in 0..count {
+        let Some(&played) = phonemes.choose(rng) else {
+            break;
+        };
+        speaker.start_speaking(&picker::sample(played)).await?;
+
+        let answer = loop {
+            print!("{}/{count} which phoneme> ", idx + 1);
+            std::io::stdout().flush()?;
+            let Some(line) = lines.next() else {
+                println!();
+                return Ok(score);
+            };
+            let line = line?;
+            // Phonemes are often written between slashes or brackets
+            let answer = line.trim().trim_matches(['/', '[', ']']);
+            match answer {
+                "" => {
+                    speaker.replay();
+                    continue;
+                }
+                "?" => {
+                    println!("  one of {choices}");
+                    continue;
+                }
+                "+" | "-" => {
+                    let change = if answer == "+" { 0.1 } else { -0.1 };
+                    println!("  volume {:.0}%", speaker.change_volume(change) * 100.0);
+                    continue;
+                }
+                _ => {}
+            }
+            match answer.parse::<Phoneme>() {
+                Ok(phoneme) if phonemes.contains(&phoneme) => break phoneme,
+                // A phoneme which is not being trained is not a wrong answer, so it is asked again
+                Ok(phoneme) => println!("  /{phoneme}/ is not one of {choices}"),
+                Err(e) => println!("  could not read \"{answer}\": {e}"),
+            }
+        };
+
+        score.asked += 1;
+        if answer == played {
+            score.right += 1;
+            println!("  right");
+            continue;
+        }
+        *score.confusions.entry((played, answer)).or_default() += 1;
+        println!("  wrong: it was /{played}/, which sounds like this, and /{answer}/ like this");
+        speaker.speak(&picker::sample(played)).await?;
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        speaker.speak(&picker::sample(answer)).await?;
+    }
+    Ok(score)
+}